@@ -0,0 +1,274 @@
+//! Bant genişliği sınırlama
+//!
+//! Aktarım yolu bir token bucket ile yavaşlatılır: her çerçeve boyutu kadar
+//! token harcar, token biterse borç kapanana kadar beklenir. İstemcide tüm
+//! akışlar aynı kovayı paylaşır; sunucuda her istemci oturumunun kendi
+//! kovası vardır. Zamanlama ile sınır günün saatine göre değişebilir
+//! (örn. 20:00-07:00 sınırsız, diğer saatlerde 5 MB/s).
+
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::time::Duration;
+use chrono::Timelike;
+use tokio::time::Instant;
+use crate::chunk::format_size;
+
+/// Kovada birikebilecek en fazla token, saniye cinsinden
+const BURST_SECS: f64 = 1.0;
+
+/// Saniyede bayt cinsinden hız sınırı
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Bandwidth {
+    #[default]
+    Unlimited,
+    Limited(u64),
+}
+
+impl FromStr for Bandwidth {
+    type Err = String;
+
+    /// `unlimited`, `0`, `500K`, `5M`, `5MB/s`, `1.5M`, `1G` biçimlerini kabul eder
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let upper = value.trim().to_ascii_uppercase();
+        if upper == "UNLIMITED" || upper == "0" {
+            return Ok(Bandwidth::Unlimited);
+        }
+        let digits = upper.trim_end_matches("/S").trim_end_matches('B');
+        let (number, multiplier) = match digits.chars().last() {
+            Some('K') => (&digits[..digits.len() - 1], 1024.0),
+            Some('M') => (&digits[..digits.len() - 1], 1024.0 * 1024.0),
+            Some('G') => (&digits[..digits.len() - 1], 1024.0 * 1024.0 * 1024.0),
+            _ => (digits, 1.0),
+        };
+        match number.trim().parse::<f64>() {
+            Ok(n) if n.is_finite() && n * multiplier >= 1.0 => Ok(Bandwidth::Limited((n * multiplier) as u64)),
+            _ => Err(format!("Geçersiz bant genişliği: '{}' (örn. 500K, 5M, unlimited)", value)),
+        }
+    }
+}
+
+impl fmt::Display for Bandwidth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Bandwidth::Unlimited => write!(f, "sınırsız"),
+            Bandwidth::Limited(rate) => write!(f, "{}/s", format_size(*rate as usize)),
+        }
+    }
+}
+
+/// Günün bir aralığında geçerli sınır
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Window {
+    /// Gece yarısından itibaren dakika
+    start: u16,
+    end: u16,
+    limit: Bandwidth,
+}
+
+impl Window {
+    /// Başlangıç dahil, bitiş hariç; gece yarısını aşan aralıklar desteklenir
+    fn contains(&self, minute: u16) -> bool {
+        if self.start <= self.end {
+            (self.start..self.end).contains(&minute)
+        } else {
+            minute >= self.start || minute < self.end
+        }
+    }
+}
+
+/// Saatlere göre bant genişliği zamanlaması. Hiçbir aralığa düşmeyen
+/// saatlerde varsayılan sınır geçerlidir.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Schedule {
+    windows: Vec<Window>,
+}
+
+impl Schedule {
+    /// Gece yarısından itibaren verilen dakikada geçerli sınır; ilk eşleşen
+    /// aralık kazanır
+    pub fn limit_at(&self, minute: u16, default: Bandwidth) -> Bandwidth {
+        self.windows.iter()
+            .find(|window| window.contains(minute))
+            .map_or(default, |window| window.limit)
+    }
+}
+
+impl FromStr for Schedule {
+    type Err = String;
+
+    /// `20:00-07:00=unlimited,09:00-18:00=2M` biçimini kabul eder
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let windows = value.split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .map(|entry| {
+                let (range, limit) = entry.split_once('=')
+                    .ok_or_else(|| format!("Zamanlama girdisi 'SS:DD-SS:DD=sınır' biçiminde olmalı: '{}'", entry))?;
+                let (start, end) = range.split_once('-')
+                    .ok_or_else(|| format!("Geçersiz saat aralığı: '{}'", range))?;
+                Ok(Window {
+                    start: parse_clock(start)?,
+                    end: parse_clock(end)?,
+                    limit: limit.parse()?,
+                })
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        if windows.is_empty() {
+            return Err("Zamanlama boş".to_string());
+        }
+        Ok(Schedule { windows })
+    }
+}
+
+/// `SS:DD` saatini gece yarısından itibaren dakikaya çevirir
+fn parse_clock(value: &str) -> Result<u16, String> {
+    let invalid = || format!("Geçersiz saat: '{}' (SS:DD)", value);
+    let (hour, minute) = value.trim().split_once(':').ok_or_else(invalid)?;
+    let hour: u16 = hour.parse().map_err(|_| invalid())?;
+    let minute: u16 = minute.parse().map_err(|_| invalid())?;
+    if hour > 23 || minute > 59 {
+        return Err(invalid());
+    }
+    Ok(hour * 60 + minute)
+}
+
+/// Paylaşılan token bucket
+pub struct RateLimiter {
+    limit: Bandwidth,
+    schedule: Option<Schedule>,
+    bucket: Mutex<Bucket>,
+}
+
+struct Bucket {
+    /// Eksi değer, beklenerek kapatılacak borçtur
+    tokens: f64,
+    last: Instant,
+}
+
+impl RateLimiter {
+    pub fn new(limit: Bandwidth) -> Self {
+        Self::with_schedule(limit, None)
+    }
+
+    /// Zamanlamaya uymayan saatlerde `limit` geçerlidir
+    pub fn with_schedule(limit: Bandwidth, schedule: Option<Schedule>) -> Self {
+        Self {
+            limit,
+            schedule,
+            bucket: Mutex::new(Bucket { tokens: 0.0, last: Instant::now() }),
+        }
+    }
+
+    /// Hiçbir zaman sınır uygulanmayacaksa `true`
+    pub fn is_unlimited(&self) -> bool {
+        self.limit == Bandwidth::Unlimited
+            && self.schedule.as_ref().is_none_or(|schedule| schedule.windows.iter().all(|w| w.limit == Bandwidth::Unlimited))
+    }
+
+    /// Şu an geçerli sınır (yerel saate göre)
+    pub fn current_limit(&self) -> Bandwidth {
+        match &self.schedule {
+            Some(schedule) => {
+                let now = chrono::Local::now();
+                schedule.limit_at((now.hour() * 60 + now.minute()) as u16, self.limit)
+            }
+            None => self.limit,
+        }
+    }
+
+    /// `bytes` kadar token harcar; sınır aşıldıysa borç kapanana kadar bekler
+    pub async fn acquire(&self, bytes: usize) {
+        let Bandwidth::Limited(rate) = self.current_limit() else { return };
+        let wait = {
+            let mut bucket = self.bucket.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+            bucket.take(bytes as f64, rate as f64, Instant::now())
+        };
+        if let Some(wait) = wait {
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+impl Bucket {
+    /// Token ekler ve harcar; borç varsa kapanması için gereken süreyi döner
+    fn take(&mut self, bytes: f64, rate: f64, now: Instant) -> Option<Duration> {
+        let elapsed = now.duration_since(self.last).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * rate).min(rate * BURST_SECS);
+        self.last = now;
+        self.tokens -= bytes;
+        (self.tokens < 0.0).then(|| Duration::from_secs_f64(-self.tokens / rate))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_bandwidth() {
+        assert_eq!("unlimited".parse::<Bandwidth>().unwrap(), Bandwidth::Unlimited);
+        assert_eq!("0".parse::<Bandwidth>().unwrap(), Bandwidth::Unlimited);
+        assert_eq!("500K".parse::<Bandwidth>().unwrap(), Bandwidth::Limited(500 * 1024));
+        assert_eq!("5MB/s".parse::<Bandwidth>().unwrap(), Bandwidth::Limited(5 * 1024 * 1024));
+        assert_eq!("1.5m".parse::<Bandwidth>().unwrap(), Bandwidth::Limited(1536 * 1024));
+        assert_eq!("1G".parse::<Bandwidth>().unwrap(), Bandwidth::Limited(1024 * 1024 * 1024));
+        assert_eq!("1000".parse::<Bandwidth>().unwrap(), Bandwidth::Limited(1000));
+        assert!("hızlı".parse::<Bandwidth>().is_err());
+        assert!("-5M".parse::<Bandwidth>().is_err());
+        assert_eq!(Bandwidth::Limited(5 * 1024 * 1024).to_string(), "5 MB/s");
+    }
+
+    #[test]
+    fn test_schedule_windows() {
+        let schedule: Schedule = "20:00-07:00=unlimited, 12:00-13:00=10M".parse().unwrap();
+        let office = Bandwidth::Limited(5 * 1024 * 1024);
+        let at = |h: u16, m: u16| schedule.limit_at(h * 60 + m, office);
+
+        // Gece yarısını aşan aralık
+        assert_eq!(at(20, 0), Bandwidth::Unlimited);
+        assert_eq!(at(23, 59), Bandwidth::Unlimited);
+        assert_eq!(at(3, 0), Bandwidth::Unlimited);
+        assert_eq!(at(7, 0), office);
+        assert_eq!(at(19, 59), office);
+        assert_eq!(at(12, 30), Bandwidth::Limited(10 * 1024 * 1024));
+
+        assert!("".parse::<Schedule>().is_err());
+        assert!("25:00-07:00=1M".parse::<Schedule>().is_err());
+        assert!("20:00=1M".parse::<Schedule>().is_err());
+        assert!("20:00-07:00".parse::<Schedule>().is_err());
+    }
+
+    #[test]
+    fn test_bucket_accumulates_debt() {
+        let start = Instant::now();
+        let mut bucket = Bucket { tokens: 0.0, last: start };
+
+        // Saniyede 1000 bayt: 500 bayt yarım saniye borç bırakır
+        assert_eq!(bucket.take(500.0, 1000.0, start), Some(Duration::from_millis(500)));
+        assert_eq!(bucket.take(500.0, 1000.0, start), Some(Duration::from_secs(1)));
+
+        // Boşta geçen süre en fazla bir saniyelik token biriktirir
+        let later = start + Duration::from_secs(10);
+        assert_eq!(bucket.take(1000.0, 1000.0, later), None);
+        assert!(bucket.take(1.0, 1000.0, later).is_some());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_limiter_paces_transfer() {
+        let limiter = RateLimiter::new(Bandwidth::Limited(1024 * 1024));
+        let start = Instant::now();
+        for _ in 0..12 {
+            limiter.acquire(256 * 1024).await;
+        }
+        // 3 MB, saniyede 1 MB ile ~3 saniye sürer
+        let elapsed = start.elapsed();
+        assert!(elapsed >= Duration::from_millis(2990) && elapsed <= Duration::from_millis(3100), "{:?}", elapsed);
+
+        let unlimited = RateLimiter::new(Bandwidth::Unlimited);
+        assert!(unlimited.is_unlimited());
+        let start = Instant::now();
+        unlimited.acquire(usize::MAX).await;
+        assert_eq!(start.elapsed(), Duration::ZERO);
+    }
+}
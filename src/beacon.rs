@@ -0,0 +1,330 @@
+//! UDP broadcast beacon
+//!
+//! Multicast'in filtrelendiği ağlarda (mDNS çalışmadığında) sunucu, periyodik
+//! olarak küçük ve imzalı bir UDP broadcast paketi yayınlar. İstemci bu
+//! paketleri dinleyerek sunucunun adını, portunu ve sunucu kimliğini öğrenir.
+//!
+//! Paket formatı: `DSB2` + JSON gövde + 32 baytlık BLAKE3 MAC.
+//! MAC, paylaşılan anahtardan türetilen alt anahtar ile hesaplanır; anahtarı
+//! bilen istemci sahte beacon'ları ayıklayabilir. İmzalı gövde sunucunun
+//! yayın yaptığı adresleri de içerir; başka bir makineden yeniden yayınlanan
+//! beacon kaynak adresi eşleşmediği için reddedilir. Sunucu her başladığında
+//! rastgele bir kimlik seçer ve her beacon'da sıra numarasını artırır; aynı
+//! beacon ikinci kez kabul edilmez. Kimlik anahtardan türetilmez; beacon'ı
+//! dinleyen biri anahtar (veya parola) tahminini yalnızca MAC'e karşı
+//! deneyebilir, herkese açık bir parmak izine karşı değil.
+
+use anyhow::{Result, Context};
+use serde::{Serialize, Deserialize};
+use socket2::{Domain, Protocol, Socket, Type};
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, UdpSocket};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use crate::crypto::derive_subkey;
+use crate::net;
+use crate::protocol::PROTOCOL_VERSION;
+
+/// Beacon'ların gönderildiği UDP portu
+pub const BEACON_PORT: u16 = 12399;
+
+/// Beacon gönderim aralığı
+pub const BEACON_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Paket başındaki sihirli bayt dizisi (format sürümü dahil)
+const BEACON_MAGIC: &[u8; 4] = b"DSB2";
+
+/// MAC uzunluğu (BLAKE3 çıktısı)
+const MAC_SIZE: usize = 32;
+
+/// En büyük kabul edilen beacon paketi
+const MAX_BEACON_SIZE: usize = 1024;
+
+/// İmzalı beacon'ın kabul edileceği en büyük saat farkı (tekrar oynatmaya karşı)
+const MAX_CLOCK_SKEW_SECS: u64 = 30;
+
+/// MAC alt anahtarı için BLAKE3 bağlamı
+const BEACON_MAC_CONTEXT: &str = "deltasafe 2024 beacon mac";
+
+/// Beacon içeriği
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Beacon {
+    pub name: String,
+    pub port: u16,
+    /// Sunucu her başladığında seçilen rastgele kimlik (mDNS kaydındakiyle aynı)
+    pub server_id: String,
+    /// Sunucunun beacon'ı yayınladığı IPv4 adresleri
+    pub addresses: Vec<IpAddr>,
+    /// Aynı `server_id` içinde her beacon'da artan sıra numarası
+    pub sequence: u64,
+    pub timestamp: u64,
+    /// Sunucunun konuştuğu protokol sürümü
+    #[serde(default)]
+    pub protocol: u32,
+}
+
+impl Beacon {
+    pub fn new(name: &str, port: u16, server_id: &str, addresses: Vec<IpAddr>, sequence: u64) -> Self {
+        Self {
+            name: name.to_string(),
+            port,
+            server_id: server_id.to_string(),
+            addresses,
+            sequence,
+            timestamp: unix_now(),
+            protocol: PROTOCOL_VERSION,
+        }
+    }
+
+    /// Beacon'ı imzalayıp ağ paketine çevirir
+    pub fn encode(&self, key: &[u8; 32]) -> Result<Vec<u8>> {
+        let body = serde_json::to_vec(self)
+            .context("Beacon serialize edilemedi")?;
+
+        let mut packet = Vec::with_capacity(BEACON_MAGIC.len() + body.len() + MAC_SIZE);
+        packet.extend_from_slice(BEACON_MAGIC);
+        packet.extend_from_slice(&body);
+        let mac = blake3::keyed_hash(&derive_subkey(key, BEACON_MAC_CONTEXT), &packet);
+        packet.extend_from_slice(mac.as_bytes());
+        Ok(packet)
+    }
+
+    /// Paketi çözer. Anahtar verilirse MAC ve zaman damgası doğrulanır.
+    pub fn decode(packet: &[u8], key: Option<&[u8; 32]>) -> Result<Self> {
+        if packet.len() > MAX_BEACON_SIZE || packet.len() < BEACON_MAGIC.len() + MAC_SIZE {
+            anyhow::bail!("Geçersiz beacon boyutu: {} bayt", packet.len());
+        }
+        if !packet.starts_with(BEACON_MAGIC) {
+            anyhow::bail!("Deltasafe beacon'ı değil");
+        }
+
+        let (signed, mac) = packet.split_at(packet.len() - MAC_SIZE);
+        let beacon: Beacon = serde_json::from_slice(&signed[BEACON_MAGIC.len()..])
+            .context("Beacon deserialize edilemedi")?;
+
+        if let Some(key) = key {
+            let expected = blake3::keyed_hash(&derive_subkey(key, BEACON_MAC_CONTEXT), signed);
+            let mac: [u8; MAC_SIZE] = mac.try_into()
+                .context("Geçersiz beacon MAC uzunluğu")?;
+            // blake3::Hash karşılaştırması sabit zamanlıdır
+            if expected != blake3::Hash::from(mac) {
+                anyhow::bail!("Beacon imzası geçersiz");
+            }
+            if unix_now().abs_diff(beacon.timestamp) > MAX_CLOCK_SKEW_SECS {
+                anyhow::bail!("Beacon zaman damgası çok eski");
+            }
+        }
+
+        Ok(beacon)
+    }
+
+    /// Beacon'ın geldiği adres, beacon'da duyurulan adreslerden biri mi
+    pub fn sent_from(&self, source: IpAddr) -> bool {
+        self.addresses.contains(&net::canonical_addr(SocketAddr::new(source, 0)).ip())
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Sunucu tarafı: arka planda periyodik beacon yayınlayan tokio görevi başlatır.
+/// Yayın, dönen handle `abort()` edilene kadar sürer.
+pub fn spawn_beacon(name: String, port: u16, server_id: String, key: [u8; 32]) -> Result<tokio::task::JoinHandle<()>> {
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0))
+        .context("Beacon soketi oluşturulamadı")?;
+    socket.set_broadcast(true)
+        .context("Broadcast etkinleştirilemedi")?;
+    socket.set_nonblocking(true)
+        .context("Beacon soketi non-blocking yapılamadı")?;
+    let socket = tokio::net::UdpSocket::from_std(socket)
+        .context("Beacon soketi tokio'ya aktarılamadı")?;
+
+    let destination = SocketAddr::new(IpAddr::V4(Ipv4Addr::BROADCAST), BEACON_PORT);
+
+    let handle = tokio::spawn(async move {
+        let mut warned = false;
+        let mut interval = tokio::time::interval(BEACON_INTERVAL);
+        for sequence in 0.. {
+            interval.tick().await;
+            // Adresler DHCP ile değişebileceği için her beacon'da yeniden okunur
+            let packet = net::local_ipv4_addrs()
+                .and_then(|addresses| Beacon::new(&name, port, &server_id, addresses, sequence).encode(&key));
+            let sent = match packet {
+                Ok(packet) => socket.send_to(&packet, destination).await
+                    .map(|_| ())
+                    .context("Beacon gönderilemedi"),
+                Err(e) => Err(e),
+            };
+            if let Err(e) = sent
+                && !warned
+            {
+                // Ağ yoksa her 2 saniyede bir uyarı basma
+                log::warn!("[⚠️] {}", e);
+                warned = true;
+            }
+        }
+    });
+    Ok(handle)
+}
+
+/// Broadcast dinleme soketi oluşturur. Aynı makinede birden fazla dinleyici
+/// olabilmesi için SO_REUSEADDR (ve Unix'te SO_REUSEPORT) kullanılır.
+fn bind_beacon_socket(port: u16) -> Result<UdpSocket> {
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, Some(Protocol::UDP))
+        .context("UDP socket oluşturulamadı")?;
+    socket.set_reuse_address(true)
+        .context("SO_REUSEADDR ayarlanamadı")?;
+    #[cfg(unix)]
+    socket.set_reuse_port(true)
+        .context("SO_REUSEPORT ayarlanamadı")?;
+    socket.set_broadcast(true)
+        .context("Broadcast etkinleştirilemedi")?;
+    let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), port);
+    socket.bind(&addr.into())
+        .with_context(|| format!("Beacon portuna bağlanılamadı: {}", port))?;
+    Ok(socket.into())
+}
+
+/// İstemci tarafı: belirtilen süre boyunca beacon dinler.
+///
+/// Dönen listede her öğe, sunucunun TCP adresi ve beacon içeriğidir.
+/// Anahtar verilirse imzası doğrulanamayan beacon'lar atlanır.
+pub fn listen_for_beacons(timeout: Duration, key: Option<&[u8; 32]>) -> Result<Vec<(SocketAddr, Beacon)>> {
+    let socket = bind_beacon_socket(BEACON_PORT)?;
+    receive_beacons(&socket, timeout, key)
+}
+
+fn receive_beacons(socket: &UdpSocket, timeout: Duration, key: Option<&[u8; 32]>) -> Result<Vec<(SocketAddr, Beacon)>> {
+    let deadline = Instant::now() + timeout;
+    let mut found: Vec<(SocketAddr, Beacon)> = Vec::new();
+    let mut last_sequence: HashMap<String, u64> = HashMap::new();
+    let mut buffer = [0u8; MAX_BEACON_SIZE];
+
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            break;
+        }
+        socket.set_read_timeout(Some(remaining))
+            .context("Okuma zaman aşımı ayarlanamadı")?;
+
+        let (len, source) = match socket.recv_from(&mut buffer) {
+            Ok(received) => received,
+            Err(e) if matches!(e.kind(), std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut) => break,
+            Err(e) => return Err(e).context("Beacon alınamadı"),
+        };
+
+        match Beacon::decode(&buffer[..len], key) {
+            Ok(beacon) if key.is_some() && !beacon.sent_from(source.ip()) => {
+                log::warn!("[⚠️] {} kaynağından gelen beacon reddedildi: gönderen adres beacon'da yok", source.ip());
+            }
+            Ok(beacon) if key.is_some() && last_sequence.get(&beacon.server_id).is_some_and(|last| beacon.sequence <= *last) => {
+                // Tekrarlanan beacon; yeniden yayın ya da aynı paketin ikinci kopyası
+                continue;
+            }
+            Ok(beacon) => {
+                last_sequence.insert(beacon.server_id.clone(), beacon.sequence);
+                let address = SocketAddr::new(source.ip(), beacon.port);
+                if !found.iter().any(|(addr, _)| *addr == address) {
+                    found.push((address, beacon));
+                }
+            }
+            Err(e) => {
+                log::warn!("[⚠️] {} kaynağından gelen beacon reddedildi: {}", source.ip(), e);
+            }
+        }
+    }
+
+    Ok(found)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn beacon_from_loopback(port: u16, sequence: u64) -> Beacon {
+        Beacon::new("ofis-nas", port, "0011aabb", vec![IpAddr::V4(Ipv4Addr::LOCALHOST)], sequence)
+    }
+
+    #[test]
+    fn test_beacon_roundtrip() {
+        let key = [7u8; 32];
+        let beacon = beacon_from_loopback(12345, 0);
+        let packet = beacon.encode(&key).unwrap();
+
+        let decoded = Beacon::decode(&packet, Some(&key)).unwrap();
+        assert_eq!(decoded, beacon);
+
+        // Anahtar olmadan da okunabilir (doğrulanmadan)
+        assert_eq!(Beacon::decode(&packet, None).unwrap(), beacon);
+    }
+
+    #[test]
+    fn test_beacon_rejects_wrong_key_and_tampering() {
+        let key = [7u8; 32];
+        let packet = beacon_from_loopback(12345, 0).encode(&key).unwrap();
+
+        assert!(Beacon::decode(&packet, Some(&[8u8; 32])).is_err());
+
+        let mut tampered = packet.clone();
+        let port_pos = tampered.windows(5).position(|w| w == b"12345").unwrap();
+        tampered[port_pos] = b'2';
+        assert!(Beacon::decode(&tampered, Some(&key)).is_err());
+
+        assert!(Beacon::decode(b"DSB2", None).is_err());
+        assert!(Beacon::decode(&[0u8; 64], None).is_err());
+    }
+
+    #[test]
+    fn test_beacon_rejects_stale_timestamp() {
+        let key = [7u8; 32];
+        let mut beacon = beacon_from_loopback(12345, 0);
+        beacon.timestamp -= MAX_CLOCK_SKEW_SECS + 60;
+        let packet = beacon.encode(&key).unwrap();
+
+        assert!(Beacon::decode(&packet, Some(&key)).is_err());
+    }
+
+    #[test]
+    fn test_receive_beacons_over_loopback() {
+        let key = [7u8; 32];
+        let listener = bind_beacon_socket(0).unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let sender = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let packet = beacon_from_loopback(4000, 0).encode(&key).unwrap();
+        sender.send_to(&packet, ("127.0.0.1", port)).unwrap();
+        sender.send_to(b"baska bir protokol", ("127.0.0.1", port)).unwrap();
+
+        let found = receive_beacons(&listener, Duration::from_millis(300), Some(&key)).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].0, "127.0.0.1:4000".parse::<SocketAddr>().unwrap());
+        assert_eq!(found[0].1.name, "ofis-nas");
+    }
+
+    #[test]
+    fn test_receive_beacons_rejects_relayed_and_repeated() {
+        let key = [7u8; 32];
+        let listener = bind_beacon_socket(0).unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let sender = UdpSocket::bind("127.0.0.1:0").unwrap();
+
+        // Başka bir makinenin adresiyle imzalanmış beacon, geçerli MAC'e rağmen reddedilir
+        let relayed = Beacon::new("ofis-nas", 4000, "0011aabb", vec!["192.168.1.100".parse().unwrap()], 0);
+        sender.send_to(&relayed.encode(&key).unwrap(), ("127.0.0.1", port)).unwrap();
+
+        // Aynı beacon'ın tekrarı sayılmaz; eski sıra numarası yeniden kabul edilmez
+        let first = beacon_from_loopback(5000, 3).encode(&key).unwrap();
+        sender.send_to(&first, ("127.0.0.1", port)).unwrap();
+        sender.send_to(&first, ("127.0.0.1", port)).unwrap();
+        sender.send_to(&beacon_from_loopback(6000, 2).encode(&key).unwrap(), ("127.0.0.1", port)).unwrap();
+
+        let found = receive_beacons(&listener, Duration::from_millis(300), Some(&key)).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].0, "127.0.0.1:5000".parse::<SocketAddr>().unwrap());
+        assert_eq!(found[0].1.sequence, 3);
+    }
+}
@@ -0,0 +1,229 @@
+//! Chunk boyutu
+//!
+//! Chunk boyutu oturum başında pazarlık edilir: istemci kullanmak istediği
+//! en büyük boyutu `Hello` ile bildirir, sunucu kendi sınırına göre kırpıp
+//! `Welcome` ile döner. Otomatik modda istemci başlangıç boyutundan başlar
+//! ve gözlenen aktarım hızı arttıkça boyutu büyütür, düştüğünde küçültür.
+
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Varsayılan (ve otomatik modda başlangıç) chunk boyutu
+pub const CHUNK_SIZE: usize = 256 * 1024; // 256 KB
+
+/// Pazarlık edilebilen en küçük chunk boyutu
+pub const MIN_CHUNK_SIZE: usize = 64 * 1024; // 64 KB
+
+/// Pazarlık edilebilen en büyük chunk boyutu
+pub const MAX_CHUNK_SIZE: usize = 4 * 1024 * 1024; // 4 MB
+
+/// Hız ölçümü için örnekleme aralığı
+const SAMPLE_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Hız bu oranda artarsa chunk boyutu büyütülür
+const GROW_THRESHOLD: f64 = 1.05;
+
+/// Hız bu orana düşerse chunk boyutu küçültülür
+const SHRINK_THRESHOLD: f64 = 0.7;
+
+/// İstemcinin chunk boyutu tercihi
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ChunkSize {
+    /// Aktarım hızına göre otomatik ayarla
+    #[default]
+    Auto,
+    /// Sabit boyut (bayt)
+    Fixed(usize),
+}
+
+impl ChunkSize {
+    /// Sunucudan istenecek en büyük chunk boyutu
+    pub fn requested(self) -> usize {
+        match self {
+            ChunkSize::Auto => MAX_CHUNK_SIZE,
+            ChunkSize::Fixed(size) => size,
+        }
+    }
+}
+
+impl FromStr for ChunkSize {
+    type Err = String;
+
+    /// `auto`, `65536`, `64K`, `64KB`, `1M`, `4MB` biçimlerini kabul eder
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        if value.eq_ignore_ascii_case("auto") {
+            return Ok(ChunkSize::Auto);
+        }
+        parse_chunk_size(value).map(ChunkSize::Fixed)
+    }
+}
+
+/// Boyut metnini bayta çevirir ve izin verilen aralıkta olduğunu doğrular
+pub fn parse_chunk_size(value: &str) -> Result<usize, String> {
+    let upper = value.trim().to_ascii_uppercase();
+    let digits = upper.trim_end_matches('B');
+    let (number, multiplier) = match digits.chars().last() {
+        Some('K') => (&digits[..digits.len() - 1], 1024),
+        Some('M') => (&digits[..digits.len() - 1], 1024 * 1024),
+        _ => (upper.as_str(), 1),
+    };
+    let size = number.trim().parse::<usize>()
+        .ok()
+        .and_then(|n| n.checked_mul(multiplier))
+        .ok_or_else(|| format!("Geçersiz chunk boyutu: '{}'", value))?;
+
+    if !(MIN_CHUNK_SIZE..=MAX_CHUNK_SIZE).contains(&size) {
+        return Err(format!("Chunk boyutu {} ile {} arasında olmalı: '{}'", format_size(MIN_CHUNK_SIZE), format_size(MAX_CHUNK_SIZE), value));
+    }
+    Ok(size)
+}
+
+/// Boyutu okunabilir biçimde yazar (örn. `256 KB`, `4 MB`)
+pub fn format_size(size: usize) -> String {
+    if size >= 1024 * 1024 && size.is_multiple_of(1024 * 1024) {
+        format!("{} MB", size / (1024 * 1024))
+    } else if size >= 1024 && size.is_multiple_of(1024) {
+        format!("{} KB", size / 1024)
+    } else {
+        format!("{} bayt", size)
+    }
+}
+
+/// Oturum boyunca kullanılacak chunk boyutunu belirler. Tüm akışların
+/// işçileri aynı ayarlayıcıyı paylaşır; böylece toplam hız ölçülür.
+pub struct ChunkSizer {
+    adaptive: bool,
+    min: usize,
+    max: usize,
+    state: Mutex<SizerState>,
+}
+
+struct SizerState {
+    current: usize,
+    sample_bytes: u64,
+    sample_start: Instant,
+    best_rate: f64,
+}
+
+impl SizerState {
+    /// Bir örnekleme aralığındaki hıza göre boyutu ayarlar (bayt/saniye)
+    fn adjust(&mut self, rate: f64, min: usize, max: usize) {
+        if rate > self.best_rate * GROW_THRESHOLD {
+            self.best_rate = rate;
+            self.current = (self.current * 2).min(max);
+        } else if rate < self.best_rate * SHRINK_THRESHOLD {
+            self.best_rate = rate;
+            self.current = (self.current / 2).max(min);
+        }
+    }
+}
+
+impl ChunkSizer {
+    /// Tercih ve sunucunun izin verdiği üst sınırdan ayarlayıcı oluşturur
+    pub fn new(preference: ChunkSize, negotiated: usize) -> Self {
+        let (adaptive, start) = match preference {
+            ChunkSize::Auto => (true, CHUNK_SIZE),
+            ChunkSize::Fixed(size) => (false, size),
+        };
+        let max = negotiated.max(1);
+        Self {
+            adaptive,
+            min: MIN_CHUNK_SIZE.min(max),
+            max,
+            state: Mutex::new(SizerState {
+                current: start.min(max),
+                sample_bytes: 0,
+                sample_start: Instant::now(),
+                best_rate: 0.0,
+            }),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, SizerState> {
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Şu an kullanılacak chunk boyutu
+    pub fn current(&self) -> usize {
+        self.lock().current
+    }
+
+    /// Gönderilen baytları kaydeder; otomatik modda boyutu ayarlar
+    pub fn record(&self, bytes: usize) {
+        if !self.adaptive {
+            return;
+        }
+        let mut state = self.lock();
+        state.sample_bytes += bytes as u64;
+        let elapsed = state.sample_start.elapsed();
+        if elapsed >= SAMPLE_INTERVAL {
+            let rate = state.sample_bytes as f64 / elapsed.as_secs_f64();
+            state.adjust(rate, self.min, self.max);
+            state.sample_bytes = 0;
+            state.sample_start = Instant::now();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_chunk_size() {
+        assert_eq!("auto".parse::<ChunkSize>().unwrap(), ChunkSize::Auto);
+        assert_eq!("64K".parse::<ChunkSize>().unwrap(), ChunkSize::Fixed(64 * 1024));
+        assert_eq!("256kb".parse::<ChunkSize>().unwrap(), ChunkSize::Fixed(256 * 1024));
+        assert_eq!("1M".parse::<ChunkSize>().unwrap(), ChunkSize::Fixed(1024 * 1024));
+        assert_eq!("4MB".parse::<ChunkSize>().unwrap(), ChunkSize::Fixed(4 * 1024 * 1024));
+        assert_eq!("131072".parse::<ChunkSize>().unwrap(), ChunkSize::Fixed(128 * 1024));
+
+        // Aralık dışı ve bozuk değerler
+        assert!("4096".parse::<ChunkSize>().is_err());
+        assert!("8M".parse::<ChunkSize>().is_err());
+        assert!("birkaç".parse::<ChunkSize>().is_err());
+        assert!("".parse::<ChunkSize>().is_err());
+    }
+
+    #[test]
+    fn test_fixed_size_clamped_to_negotiated() {
+        let sizer = ChunkSizer::new(ChunkSize::Fixed(4 * 1024 * 1024), 1024 * 1024);
+        assert_eq!(sizer.current(), 1024 * 1024);
+        sizer.record(100 * 1024 * 1024);
+        assert_eq!(sizer.current(), 1024 * 1024);
+    }
+
+    #[test]
+    fn test_adaptive_grows_with_throughput_and_backs_off() {
+        let sizer = ChunkSizer::new(ChunkSize::Auto, MAX_CHUNK_SIZE);
+        let mut state = sizer.lock();
+        assert_eq!(state.current, CHUNK_SIZE);
+
+        // Hız arttıkça boyut iki katına çıkar, üst sınırda durur
+        for (rate, expected) in [(100.0, 512 * 1024), (200.0, 1024 * 1024), (400.0, 2 * 1024 * 1024), (800.0, MAX_CHUNK_SIZE), (1600.0, MAX_CHUNK_SIZE)] {
+            state.adjust(rate, sizer.min, sizer.max);
+            assert_eq!(state.current, expected);
+        }
+
+        // Hız sabitse boyut değişmez, belirgin düşüşte küçülür
+        state.adjust(1600.0, sizer.min, sizer.max);
+        assert_eq!(state.current, MAX_CHUNK_SIZE);
+        state.adjust(500.0, sizer.min, sizer.max);
+        assert_eq!(state.current, 2 * 1024 * 1024);
+
+        // Alt sınırın altına inmez
+        for _ in 0..10 {
+            let rate = state.best_rate * 0.5;
+            state.adjust(rate, sizer.min, sizer.max);
+        }
+        assert_eq!(state.current, MIN_CHUNK_SIZE);
+    }
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(64 * 1024), "64 KB");
+        assert_eq!(format_size(4 * 1024 * 1024), "4 MB");
+        assert_eq!(format_size(1000), "1000 bayt");
+    }
+}
@@ -1,4 +1,11 @@
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand};
+use crate::chunk::{parse_chunk_size, ChunkSize};
+use crate::bandwidth::{Bandwidth, Schedule};
+use crate::compress::Compression;
+use crate::metadata::Preserve;
+use crate::entry::SymlinkPolicy;
+use crate::xattrs::XattrFilter;
+use crate::sync::{DEFAULT_STREAMS, DEFAULT_WINDOW};
 
 #[derive(Parser)]
 #[command(name = "deltasafe")]
@@ -16,7 +23,7 @@ pub enum Commands {
         #[arg(short, long)]
         source: String,
 
-        /// Hedef IP adresi (opsiyonel, --auto ile otomatik keşif)
+        /// Hedef adres (IP:port, `quic://`, `unix:` veya `ssh://` biçiminde) veya adres defterindeki sunucu adı (opsiyonel, --auto ile otomatik keşif)
         #[arg(short, long, conflicts_with = "auto")]
         target: Option<String>,
 
@@ -28,6 +35,14 @@ pub enum Commands {
         #[arg(long, requires = "auto")]
         auto_select: bool,
 
+        /// Yalnızca bu isimle duyurulan sunucuları seç (--auto ile)
+        #[arg(long, requires = "auto")]
+        share: Option<String>,
+
+        /// Parmak izi anahtarla eşleşen tam olarak bir sunucu bulunmasını zorunlu kıl (--auto ile)
+        #[arg(long, requires = "auto")]
+        require_trusted: bool,
+
         /// AES anahtarı (hex formatında 32 bayt)
         #[arg(short, long, conflicts_with = "password")]
         key: Option<String>,
@@ -35,6 +50,58 @@ pub enum Commands {
         /// Şifre (otomatik olarak AES anahtarına dönüştürülür)
         #[arg(short, long, conflicts_with = "key")]
         password: Option<String>,
+
+        /// Sunucu onayı beklenmeden aynı anda gönderilen en fazla dosya
+        #[arg(long, default_value_t = DEFAULT_WINDOW)]
+        window: usize,
+
+        /// Sunucuya açılacak paralel akış sayısı (sunucu daha azına izin verebilir)
+        #[arg(long, default_value_t = DEFAULT_STREAMS)]
+        streams: usize,
+
+        /// Chunk boyutu: "auto" veya 64K ile 4M arası bir değer (örn. 256K, 1M)
+        #[arg(long, default_value = "auto")]
+        chunk_size: ChunkSize,
+
+        /// Şifrelemeden önce sıkıştırma: "none", "zstd" veya "zstd:<seviye>" (1-22)
+        #[arg(long, default_value = "none")]
+        compress: Compression,
+
+        /// Toplam gönderim hızı sınırı (örn. 500K, 5M; varsayılan: sınırsız)
+        #[arg(long, default_value = "unlimited")]
+        bwlimit: Bandwidth,
+
+        /// Saate göre sınır, örn. "20:00-07:00=unlimited,12:00-13:00=10M";
+        /// aralık dışındaki saatlerde --bwlimit geçerlidir
+        #[arg(long)]
+        schedule: Option<Schedule>,
+
+        /// Yalnızca bu gitignore desenine uyan dosyaları gönder (tekrarlanabilir)
+        #[arg(long, value_name = "DESEN")]
+        include: Vec<String>,
+
+        /// Bu gitignore desenine uyan dosya ve klasörleri gönderme (tekrarlanabilir)
+        #[arg(long, value_name = "DESEN")]
+        exclude: Vec<String>,
+
+        /// Kaynak ağacındaki .gitignore dosyalarını da uygula
+        #[arg(long)]
+        gitignore: bool,
+
+        /// Sembolik bağlantılar: "preserve" (bağlantı olarak), "follow" (hedefi gönder) veya "skip"
+        #[arg(long, default_value = "preserve")]
+        symlinks: SymlinkPolicy,
+
+        /// Aynı dosyaya bağlı hardlink'leri bir kez gönder, sunucuda yeniden bağla
+        #[arg(long)]
+        hardlinks: bool,
+
+        /// Şifrelenip gönderilecek xattr ad alanları, örn. "user,security,acl" veya "all"
+        #[arg(long, default_value = "none", value_name = "AD_ALANLARI")]
+        xattrs: XattrFilter,
+
+        #[command(flatten)]
+        tls: ClientTlsArgs,
     },
 
     /// LAN'daki Deltasafe sunucularını keşfet
@@ -42,6 +109,14 @@ pub enum Commands {
         /// Keşif timeout süresi (saniye)
         #[arg(short, long, default_value = "5")]
         timeout: u64,
+
+        /// Adres defterini JSON olarak yazdır
+        #[arg(long)]
+        json: bool,
+
+        /// Kayıtlı sonuçları kullanmak yerine ağı yeniden tara
+        #[arg(long)]
+        refresh: bool,
     },
 
     /// Peer cihazla bağlantı kur
@@ -58,12 +133,22 @@ pub enum Commands {
         folder: String,
     },
 
-    /// TCP sunucusunu başlat
+    /// Sunucuyu başlat
+    #[command(alias = "serve")]
     Server {
-        /// Sunucu adresi (IP ve port) - opsiyonel, varsayılan: otomatik
+        /// Sunucu adresi (IP ve port, `quic://IP:port` veya `unix:/yol/soket`) - opsiyonel, varsayılan: otomatik
         #[arg(short, long)]
         address: Option<String>,
 
+        /// Tek bir istemciye stdin/stdout üzerinden hizmet ver (`ssh host deltasafe serve --stdio`);
+        /// durum mesajları stderr'e yazılır
+        #[arg(long, conflicts_with_all = ["address", "name"])]
+        stdio: bool,
+
+        /// Keşifte duyurulacak sunucu/paylaşım adı (varsayılan: host adı)
+        #[arg(short, long)]
+        name: Option<String>,
+
         /// AES anahtarı (hex formatında 32 bayt)
         #[arg(short, long, conflicts_with = "password")]
         key: Option<String>,
@@ -71,5 +156,251 @@ pub enum Commands {
         /// Şifre (otomatik olarak AES anahtarına dönüştürülür)
         #[arg(short, long, conflicts_with = "key")]
         password: Option<String>,
+
+        /// Aynı anda hizmet verilecek en fazla istemci oturumu
+        #[arg(long, default_value_t = 64)]
+        max_sessions: usize,
+
+        /// Bağlantı başına okuma/yazma zaman aşımı (saniye)
+        #[arg(long, default_value_t = 30)]
+        timeout: u64,
+
+        /// Bir istemcinin açabileceği en fazla paralel akış
+        #[arg(long, default_value_t = 8)]
+        max_streams: usize,
+
+        /// İstemcilere izin verilen en büyük chunk boyutu (64K ile 4M arası)
+        #[arg(long, default_value = "4M", value_parser = parse_chunk_size)]
+        max_chunk_size: usize,
+
+        /// İstemcilerden sıkıştırılmış chunk kabul etme
+        #[arg(long)]
+        no_compression: bool,
+
+        /// İstemci başına alım hızı sınırı (örn. 5M; varsayılan: sınırsız)
+        #[arg(long, default_value = "unlimited")]
+        client_bwlimit: Bandwidth,
+
+        /// Alınan dosyalara uygulanacak öznitelikler: mode, times, owner, all veya none
+        #[arg(long, default_value = "mode,times")]
+        preserve: Preserve,
+
+        /// Uygulanacak xattr ad alanları, örn. "user,acl" (varsayılan: hiçbiri)
+        #[arg(long, default_value = "none", value_name = "AD_ALANLARI")]
+        xattrs: XattrFilter,
+
+        /// Üzerine yazılan her dosyanın en yeni N eski sürümünü sakla
+        #[arg(long, default_value_t = 0, value_name = "N")]
+        keep_versions: usize,
+
+        /// Son N gün için her günün en yeni eski sürümünü sakla
+        #[arg(long, default_value_t = 0, value_name = "GÜN")]
+        keep_daily: u32,
+
+        /// Dosya yerine istemcinin şifrelediği blob'ları sakla (`push` / `pull`)
+        #[arg(long)]
+        vault: bool,
+
+        /// Anahtar yerine yalnızca erişim anahtarını kullan (`access-key` çıktısı);
+        /// sunucu içeriği çözemez
+        #[arg(long, value_name = "HEX", requires = "vault", conflicts_with_all = ["key", "password"])]
+        access_key: Option<String>,
+
+        /// İçeriği S3 uyumlu depoya yaz (`http://host:port/kova/önek`).
+        /// Kimlik bilgileri AWS_ACCESS_KEY_ID / AWS_SECRET_ACCESS_KEY / AWS_REGION
+        /// ortam değişkenlerinden okunur
+        #[arg(long, value_name = "URL", conflicts_with_all = ["keep_versions", "keep_daily"])]
+        s3: Option<String>,
+
+        /// TLS 1.3 için sunucu sertifika zinciri (PEM)
+        #[arg(long, value_name = "PEM", requires = "tls_key")]
+        tls_cert: Option<String>,
+
+        /// Sunucu sertifikasının özel anahtarı (PEM)
+        #[arg(long, value_name = "PEM", requires = "tls_cert")]
+        tls_key: Option<String>,
+
+        /// Karşılıklı TLS: yalnızca bu CA'nın imzaladığı istemci sertifikaları kabul edilir
+        #[arg(long, value_name = "PEM", requires = "tls_cert")]
+        tls_client_ca: Option<String>,
     },
+
+    /// Sunucuda saklanan dosya sürümlerini listele
+    Versions {
+        /// Sunucu adresi (IP:port) veya adres defterindeki sunucu adı
+        #[arg(short, long)]
+        target: String,
+
+        /// Paylaşım köküne göreli dosya yolu
+        #[arg(long)]
+        path: String,
+
+        /// AES anahtarı (hex formatında 32 bayt)
+        #[arg(short, long, conflicts_with = "password")]
+        key: Option<String>,
+
+        /// Şifre (otomatik olarak AES anahtarına dönüştürülür)
+        #[arg(short, long, conflicts_with = "key")]
+        password: Option<String>,
+
+        #[command(flatten)]
+        tls: ClientTlsArgs,
+    },
+
+    /// Klasörü istemcide şifreleyip kasa modundaki sunucuya gönder
+    Push {
+        /// Kaynak dizin
+        #[arg(short, long)]
+        source: String,
+
+        /// Sunucu adresi (IP:port) veya adres defterindeki sunucu adı
+        #[arg(short, long)]
+        target: String,
+
+        /// AES anahtarı (hex formatında 32 bayt)
+        #[arg(short, long, conflicts_with = "password")]
+        key: Option<String>,
+
+        /// Şifre (otomatik olarak AES anahtarına dönüştürülür)
+        #[arg(short, long, conflicts_with = "key")]
+        password: Option<String>,
+
+        #[command(flatten)]
+        tls: ClientTlsArgs,
+    },
+
+    /// Kasadaki bir gönderimi indirip dosyaları yerelde yeniden kur
+    Pull {
+        /// Sunucu adresi (IP:port) veya adres defterindeki sunucu adı
+        #[arg(short, long)]
+        target: String,
+
+        /// Dosyaların yazılacağı klasör
+        #[arg(short, long, default_value = ".")]
+        output: String,
+
+        /// İndirilecek indeks (varsayılan: en yenisi)
+        #[arg(long)]
+        index: Option<String>,
+
+        /// İndirmek yerine kayıtlı indeksleri listele
+        #[arg(long)]
+        list: bool,
+
+        /// AES anahtarı (hex formatında 32 bayt)
+        #[arg(short, long, conflicts_with = "password")]
+        key: Option<String>,
+
+        /// Şifre (otomatik olarak AES anahtarına dönüştürülür)
+        #[arg(short, long, conflicts_with = "key")]
+        password: Option<String>,
+
+        #[command(flatten)]
+        tls: ClientTlsArgs,
+    },
+
+    /// Kasa sunucusuna verilecek erişim anahtarını yazdır
+    AccessKey {
+        /// AES anahtarı (hex formatında 32 bayt)
+        #[arg(short, long, conflicts_with = "password", required_unless_present = "password")]
+        key: Option<String>,
+
+        /// Şifre (otomatik olarak AES anahtarına dönüştürülür)
+        #[arg(short, long, conflicts_with = "key")]
+        password: Option<String>,
+    },
+
+    /// Sunucudaki paylaşımın anlık görüntülerini yönet
+    Snapshot {
+        #[command(subcommand)]
+        action: SnapshotAction,
+    },
+
+    /// Bir dosya sürümünü sunucudan geri yükle
+    Restore {
+        /// Sunucu adresi (IP:port) veya adres defterindeki sunucu adı
+        #[arg(short, long)]
+        target: String,
+
+        /// Paylaşım köküne göreli dosya yolu
+        #[arg(long)]
+        path: String,
+
+        /// Geri yüklenecek sürümün kimliği (`versions` çıktısından)
+        #[arg(long)]
+        version: String,
+
+        /// Yazılacak yerel dosya (varsayılan: bulunulan klasörde aynı ad)
+        #[arg(short, long)]
+        output: Option<String>,
+
+        /// AES anahtarı (hex formatında 32 bayt)
+        #[arg(short, long, conflicts_with = "password")]
+        key: Option<String>,
+
+        /// Şifre (otomatik olarak AES anahtarına dönüştürülür)
+        #[arg(short, long, conflicts_with = "key")]
+        password: Option<String>,
+
+        #[command(flatten)]
+        tls: ClientTlsArgs,
+    },
+}
+
+/// `snapshot` alt komutları; sunucunun çalıştığı makinede paylaşım kökü
+/// üzerinde çalışır
+#[derive(Subcommand)]
+pub enum SnapshotAction {
+    /// Paylaşımın şu anki halinin görüntüsünü al
+    Create {
+        /// Paylaşım kökü
+        #[arg(long, default_value = "received_files")]
+        root: String,
+
+        /// Görüntüye verilecek açıklama, örn. "v2.3 derlemesi"
+        #[arg(long)]
+        label: Option<String>,
+    },
+
+    /// Kayıtlı görüntüleri listele
+    List {
+        /// Paylaşım kökü
+        #[arg(long, default_value = "received_files")]
+        root: String,
+    },
+
+    /// Paylaşımı bir görüntüdeki hale geri getir
+    Restore {
+        /// Görüntü kimliği veya tek bir görüntüyle eşleşen başı (örn. 20240326)
+        id: String,
+
+        /// Paylaşım kökü
+        #[arg(long, default_value = "received_files")]
+        root: String,
+    },
+}
+
+/// İstemci komutlarının TLS seçenekleri. `--ca` veya `--pin` verilirse
+/// bağlantı TLS 1.3 ile sarılır.
+#[derive(Args, Debug, Clone, Default)]
+pub struct ClientTlsArgs {
+    /// Sunucu sertifikasını doğrulayan CA sertifikası (PEM)
+    #[arg(long, value_name = "PEM")]
+    pub ca: Option<String>,
+
+    /// Sunucu sertifikasının SHA-256 parmak izi (`sha256:<hex>`)
+    #[arg(long, value_name = "PARMAK_IZI")]
+    pub pin: Option<String>,
+
+    /// Karşılıklı TLS için istemci sertifikası (PEM)
+    #[arg(long = "tls-cert", value_name = "PEM", requires = "client_key")]
+    pub client_cert: Option<String>,
+
+    /// İstemci sertifikasının özel anahtarı (PEM)
+    #[arg(long = "tls-key", value_name = "PEM", requires = "client_cert")]
+    pub client_key: Option<String>,
+
+    /// Sertifikada doğrulanacak sunucu adı (varsayılan: hedefin host kısmı)
+    #[arg(long, value_name = "AD")]
+    pub tls_name: Option<String>,
 }
@@ -0,0 +1,215 @@
+//! Chunk sıkıştırma
+//!
+//! Sıkıştırma oturum başında pazarlık edilir: istemci `Hello` ile istediği
+//! algoritmayı bildirir, sunucu destekliyorsa `Welcome` ile kabul eder.
+//! Veri şifrelemeden önce sıkıştırılır. Zaten sıkıştırılmış dosya türleri
+//! ve küçülmeyen chunk'lar olduğu gibi gönderilir.
+
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Pazarlıkta kullanılan algoritma adı
+pub const ZSTD: &str = "zstd";
+
+/// `zstd` seviyesi verilmediğinde kullanılan seviye
+pub const DEFAULT_LEVEL: i32 = 3;
+
+/// Desteklenen en yüksek zstd seviyesi
+pub const MAX_LEVEL: i32 = 22;
+
+/// Art arda bu kadar chunk küçülmezse aralığın kalanı sıkıştırılmaz
+const INCOMPRESSIBLE_STREAK: usize = 4;
+
+/// Zaten sıkıştırılmış olduğu için yeniden sıkıştırılmayan uzantılar
+const PRECOMPRESSED_EXTENSIONS: &[&str] = &[
+    // Arşivler
+    "zip", "gz", "tgz", "bz2", "xz", "txz", "zst", "lz4", "lzma", "7z", "rar", "br",
+    // Görüntü, ses ve video
+    "jpg", "jpeg", "png", "gif", "webp", "heic", "avif", "mp3", "m4a", "aac", "ogg", "opus", "flac",
+    "mp4", "m4v", "mkv", "webm", "mov", "avi",
+    // Sıkıştırılmış paket ve belge biçimleri
+    "jar", "apk", "deb", "rpm", "whl", "docx", "xlsx", "pptx", "odt", "ods", "epub", "woff", "woff2",
+];
+
+/// İstemcinin sıkıştırma tercihi
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Compression {
+    /// Sıkıştırma yok
+    #[default]
+    None,
+    /// Verilen seviyede zstd
+    Zstd(i32),
+}
+
+impl Compression {
+    /// `Hello` ile istenecek algoritma
+    pub fn algorithm(self) -> Option<&'static str> {
+        match self {
+            Compression::None => None,
+            Compression::Zstd(_) => Some(ZSTD),
+        }
+    }
+}
+
+impl FromStr for Compression {
+    type Err = String;
+
+    /// `none`, `zstd` veya `zstd:<seviye>` (1-22) biçimlerini kabul eder
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim().to_ascii_lowercase();
+        let (name, level) = match value.split_once(':') {
+            Some((name, level)) => (name, Some(level)),
+            None => (value.as_str(), None),
+        };
+        match (name, level) {
+            ("none", None) => Ok(Compression::None),
+            ("zstd", None) => Ok(Compression::Zstd(DEFAULT_LEVEL)),
+            ("zstd", Some(level)) => match level.parse::<i32>() {
+                Ok(level) if (1..=MAX_LEVEL).contains(&level) => Ok(Compression::Zstd(level)),
+                _ => Err(format!("zstd seviyesi 1 ile {} arasında olmalı: '{}'", MAX_LEVEL, level)),
+            },
+            _ => Err(format!("Bilinmeyen sıkıştırma: '{}' (none, zstd veya zstd:<seviye>)", value)),
+        }
+    }
+}
+
+impl fmt::Display for Compression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Compression::None => write!(f, "none"),
+            Compression::Zstd(level) => write!(f, "zstd:{}", level),
+        }
+    }
+}
+
+/// Dosya uzantısına göre içeriğin zaten sıkıştırılmış olup olmadığı
+pub fn is_precompressed(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| PRECOMPRESSED_EXTENSIONS.iter().any(|known| ext.eq_ignore_ascii_case(known)))
+}
+
+/// Bir aralığın chunk'larını sıkıştırır. Art arda küçülmeyen chunk'lardan
+/// sonra aralığın kalanı için denemeyi bırakır.
+pub struct ChunkCompressor {
+    compressor: Option<zstd::bulk::Compressor<'static>>,
+    misses: usize,
+}
+
+impl ChunkCompressor {
+    /// `None` verilirse hiçbir chunk sıkıştırılmaz
+    pub fn new(level: Option<i32>) -> Self {
+        Self {
+            compressor: level.and_then(|level| zstd::bulk::Compressor::new(level).ok()),
+            misses: 0,
+        }
+    }
+
+    /// Chunk küçülüyorsa sıkıştırılmış halini döndürür
+    pub fn compress(&mut self, data: &[u8]) -> Option<Vec<u8>> {
+        if self.misses >= INCOMPRESSIBLE_STREAK {
+            return None;
+        }
+        let compressed = self.compressor.as_mut()?.compress(data).ok()
+            .filter(|compressed| compressed.len() < data.len());
+        match compressed {
+            Some(_) => self.misses = 0,
+            None => self.misses += 1,
+        }
+        compressed
+    }
+}
+
+/// Sıkıştırılmış chunk'ı açar; sonuç `limit` bayttan büyükse veya veri
+/// bozuksa `None` döner.
+pub fn decompress_chunk(data: &[u8], limit: usize) -> Option<Vec<u8>> {
+    zstd::bulk::decompress(data, limit).ok()
+}
+
+/// Gönderilen dosya verisinin ham ve tel üzerindeki (sıkıştırılmış, şifreleme
+/// öncesi) boyutları
+#[derive(Debug, Default)]
+pub struct CompressionStats {
+    raw: AtomicU64,
+    sent: AtomicU64,
+}
+
+impl CompressionStats {
+    pub fn record(&self, raw: usize, sent: usize) {
+        self.raw.fetch_add(raw as u64, Ordering::Relaxed);
+        self.sent.fetch_add(sent as u64, Ordering::Relaxed);
+    }
+
+    pub fn raw(&self) -> u64 {
+        self.raw.load(Ordering::Relaxed)
+    }
+
+    pub fn sent(&self) -> u64 {
+        self.sent.load(Ordering::Relaxed)
+    }
+
+    /// Ham boyutun gönderilen boyuta oranı (ör. 5.0 = beş kat küçüldü)
+    pub fn ratio(&self) -> f64 {
+        match self.sent() {
+            0 => 1.0,
+            sent => self.raw() as f64 / sent as f64,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_compression() {
+        assert_eq!("none".parse::<Compression>().unwrap(), Compression::None);
+        assert_eq!("zstd".parse::<Compression>().unwrap(), Compression::Zstd(DEFAULT_LEVEL));
+        assert_eq!("ZSTD:19".parse::<Compression>().unwrap(), Compression::Zstd(19));
+        assert!("zstd:0".parse::<Compression>().is_err());
+        assert!("zstd:23".parse::<Compression>().is_err());
+        assert!("gzip".parse::<Compression>().is_err());
+        assert_eq!(Compression::Zstd(7).to_string(), "zstd:7");
+    }
+
+    #[test]
+    fn test_precompressed_extensions() {
+        assert!(is_precompressed(Path::new("arsiv.tar.gz")));
+        assert!(is_precompressed(Path::new("foto.JPG")));
+        assert!(!is_precompressed(Path::new("kayit.log")));
+        assert!(!is_precompressed(Path::new("Makefile")));
+    }
+
+    #[test]
+    fn test_compress_roundtrip_and_bypass() {
+        let mut compressor = ChunkCompressor::new(Some(DEFAULT_LEVEL));
+        let text = "2024-01-01 INFO istek tamamlandı\n".repeat(2000);
+        let compressed = compressor.compress(text.as_bytes()).unwrap();
+        assert!(compressed.len() * 5 < text.len());
+        assert_eq!(decompress_chunk(&compressed, text.len()).unwrap(), text.as_bytes());
+
+        // Sınırı aşan açılım reddedilir
+        assert!(decompress_chunk(&compressed, text.len() - 1).is_none());
+
+        // Rastgele veri küçülmez; art arda başarısızlıktan sonra denenmez
+        let noise: Vec<u8> = (0..64 * 1024).map(|_| rand::random::<u8>()).collect();
+        for _ in 0..INCOMPRESSIBLE_STREAK {
+            assert!(compressor.compress(&noise).is_none());
+        }
+        assert!(compressor.compress(text.as_bytes()).is_none());
+
+        assert!(ChunkCompressor::new(None).compress(text.as_bytes()).is_none());
+    }
+
+    #[test]
+    fn test_stats_ratio() {
+        let stats = CompressionStats::default();
+        assert_eq!(stats.ratio(), 1.0);
+        stats.record(1000, 200);
+        stats.record(500, 100);
+        assert_eq!((stats.raw(), stats.sent()), (1500, 300));
+        assert_eq!(stats.ratio(), 5.0);
+    }
+}
@@ -1,11 +1,21 @@
 //! Kriptografik yardımcı fonksiyonlar
 //! 
-//! Bu modül şifreleme anahtarı türetme ve doğrulama işlemlerini içerir.
+//! Bu modül şifreleme anahtarı türetme ve doğrulama işlemlerini, chunk'lar ile
+//! şifreli metadata'nın ortak AES-256-CBC biçimini içerir.
 
+use aes::Aes256;
 use anyhow::{Result, Context};
+use cipher::{block_padding::Pkcs7, BlockDecryptMut, BlockEncryptMut, KeyIvInit};
 use pbkdf2::pbkdf2_hmac;
+use rand::Rng;
 use sha2::Sha256;
 
+type Aes256CbcEnc = cbc::Encryptor<Aes256>;
+type Aes256CbcDec = cbc::Decryptor<Aes256>;
+
+/// IV (16 bayt) uzunluğu
+pub const IV_SIZE: usize = 16;
+
 /// PBKDF2 iterasyon sayısı (güvenlik için yeterli)
 const PBKDF2_ITERATIONS: u32 = 100_000;
 
@@ -68,7 +78,7 @@ pub fn validate_password_strength(password: &str) -> Result<()> {
     let has_digit = password.chars().any(|c| c.is_numeric());
     
     if !has_letter || !has_digit {
-        println!("⚠️  Güvenlik önerisi: Şifrenizde hem harf hem rakam bulunması önerilir");
+        log::warn!("⚠️  Güvenlik önerisi: Şifrenizde hem harf hem rakam bulunması önerilir");
     }
     
     Ok(())
@@ -82,6 +92,64 @@ pub fn generate_random_hex_key() -> String {
     hex::encode(key)
 }
 
+/// El sıkışma ve oturum kimlikleri için rastgele 16 baytlık hex değer üretir
+pub fn random_nonce() -> String {
+    use rand::Rng;
+    let mut nonce = [0u8; 16];
+    rand::thread_rng().fill(&mut nonce);
+    hex::encode(nonce)
+}
+
+/// Erişim anahtarı için BLAKE3 bağlamı
+const ACCESS_CONTEXT: &str = "deltasafe 2024 stream auth";
+
+/// El sıkışmada kullanılan erişim anahtarını türetir. Erişim anahtarı
+/// istemcileri doğrulamaya yeter ama içeriği çözmeye yetmez; kasa modundaki
+/// sunucuya yalnızca bu anahtar verilebilir.
+pub fn access_key(key: &[u8; 32]) -> [u8; 32] {
+    derive_subkey(key, ACCESS_CONTEXT)
+}
+
+/// Anahtardan belirli bir amaç için alt anahtar türetir (örn. beacon imzası)
+pub fn derive_subkey(key: &[u8; 32], context: &str) -> [u8; 32] {
+    blake3::derive_key(context, key)
+}
+
+/// Veriyi rastgele IV ile şifreler; IV + şifreli veri döner
+pub fn seal(data: &[u8], key: &[u8; 32]) -> Vec<u8> {
+    let mut iv = [0u8; IV_SIZE];
+    rand::thread_rng().fill(&mut iv);
+    seal_with_iv(data, key, &iv)
+}
+
+/// Veriyi verilen IV ile şifreler. Aynı IV yalnızca aynı veri için
+/// kullanılmalıdır (örn. içerikten türetilen IV); aksi halde [`seal`] kullanın.
+pub fn seal_with_iv(data: &[u8], key: &[u8; 32], iv: &[u8; IV_SIZE]) -> Vec<u8> {
+    // IV başta, ardından padding için bir blok fazlasıyla şifreli veri
+    let mut sealed = Vec::with_capacity(IV_SIZE + data.len() + 16);
+    sealed.extend_from_slice(iv);
+    sealed.extend_from_slice(data);
+    sealed.resize(IV_SIZE + data.len() + 16, 0);
+    let cipher = Aes256CbcEnc::new(key.into(), iv.into());
+    let len = cipher.encrypt_padded_mut::<Pkcs7>(&mut sealed[IV_SIZE..], data.len())
+        .expect("Şifreleme hatası")
+        .len();
+    sealed.truncate(IV_SIZE + len);
+    sealed
+}
+
+/// [`seal`] çıktısını çözer; anahtar yanlışsa veya veri bozuksa `None` döner
+pub fn open(sealed: &[u8], key: &[u8; 32]) -> Option<Vec<u8>> {
+    if sealed.len() <= IV_SIZE {
+        return None;
+    }
+    let (iv, ciphertext) = sealed.split_at(IV_SIZE);
+    let mut buf = ciphertext.to_vec();
+    let cipher = Aes256CbcDec::new(key.into(), iv.into());
+    let decrypted = cipher.decrypt_padded_mut::<Pkcs7>(&mut buf).ok()?;
+    Some(decrypted.to_vec())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -144,4 +212,20 @@ mod tests {
         // Üretilen anahtarın parse edilebilir olduğunu kontrol et
         assert!(parse_hex_key(&key1).is_ok());
     }
+
+    #[test]
+    fn test_seal_and_open() {
+        let key = [5u8; 32];
+        for len in [0, 1, 15, 16, 17, 1000] {
+            let data: Vec<u8> = (0..len).map(|i| i as u8).collect();
+            let sealed = seal(&data, &key);
+            assert_eq!(sealed.len(), IV_SIZE + (len / 16 + 1) * 16);
+            assert_eq!(open(&sealed, &key).unwrap(), data);
+        }
+
+        // Aynı veri her seferinde farklı IV ile şifrelenir
+        assert_ne!(seal(b"abc", &key), seal(b"abc", &key));
+        assert!(open(&seal(b"abc", &key), &[6u8; 32]).is_none_or(|data| data != b"abc"));
+        assert!(open(&[0u8; IV_SIZE], &key).is_none());
+    }
 }
\ No newline at end of file
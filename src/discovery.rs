@@ -1,16 +1,36 @@
 //! Ağ keşif modülü
 //! 
 //! Bu modül LAN üzerindeki Deltasafe sunucularını otomatik olarak keşfetmek için
-//! mDNS (Bonjour/Zeroconf) ve basit port tarama yöntemlerini kullanır.
+//! mDNS (Bonjour/Zeroconf, IPv4 ve IPv6 multicast), UDP broadcast beacon ve
+//! basit port tarama yöntemlerini kullanır.
 
 use anyhow::{Result, Context};
-use std::net::{IpAddr, Ipv4Addr, SocketAddr, TcpStream};
-use std::time::Duration;
+use mdns_sd::{IfKind, ServiceDaemon, ServiceEvent, ServiceInfo};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, SocketAddrV6};
+use std::time::{Duration, Instant};
 use std::collections::HashMap;
+use crate::beacon::{self, Beacon};
+use crate::error::Error;
+use crate::net;
+use crate::protocol::{is_compatible, PROTOCOL_VERSION};
+use crate::sync;
+use crate::transport::Connection;
 
 /// Deltasafe servisi için mDNS service type
 const DELTASAFE_SERVICE_TYPE: &str = "_deltasafe._tcp.local.";
 
+/// mDNS TXT kaydında sunucu kimliğinin tutulduğu alan
+const SERVER_ID_TXT_KEY: &str = "id";
+
+/// mDNS TXT kaydında protokol sürümünün tutulduğu alan
+const PROTOCOL_TXT_KEY: &str = "proto";
+
+/// RTT ölçümünde bağlantı için beklenecek en uzun süre
+const RTT_PROBE_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Sunucunun el sıkışma kanıtı için beklenecek en uzun süre
+const PROOF_PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+
 /// Varsayılan port aralığı tarama için
 const DEFAULT_PORT_RANGE: std::ops::Range<u16> = 12340..12350;
 
@@ -20,74 +40,375 @@ pub struct DiscoveredServer {
     pub address: SocketAddr,
     pub name: Option<String>,
     pub discovery_method: DiscoveryMethod,
+    /// Sunucunun her başlatmada rastgele seçtiği kimlik (mDNS TXT kaydı
+    /// veya beacon'dan); anahtardan türetilmez
+    pub server_id: Option<String>,
+    /// Kimliğin nereden geldiği
+    pub server_id_source: Option<ServerIdSource>,
+    /// Ölçülen bağlantı gecikmesi (TCP bağlantı kurma süresi)
+    pub rtt: Option<Duration>,
+    /// Sunucunun duyurduğu protokol sürümü (port taramasında bilinmez)
+    pub protocol_version: Option<u32>,
+    /// Bu adresteki sunucu el sıkışmada anahtarımızı bildiğini kanıtladı mı
+    /// (anahtar verilmediyse veya sınama sonuçsuz kaldıysa `None`)
+    pub proven: Option<bool>,
 }
 
 #[derive(Debug, Clone)]
 pub enum DiscoveryMethod {
     MDns,
+    Broadcast,
     PortScan,
 }
 
-/// LAN'daki Deltasafe sunucularını keşfeder
-pub async fn discover_servers(timeout_secs: u64) -> Result<Vec<DiscoveredServer>> {
-    println!("[🔍] LAN'da Deltasafe sunucuları aranıyor...");
+/// Sunucu kimliğinin kaynağı. mDNS kaydındaki kimlik kopyalanabildiği için
+/// aynı adresten gelen doğrulanmış beacon'daki kimlik tercih edilir.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ServerIdSource {
+    /// mDNS TXT kaydı; LAN'daki herkes aynı değeri kopyalayıp duyurabilir
+    MDns,
+    /// İmzası kontrol edilmeden (anahtarsız) alınan beacon
+    Beacon,
+    /// MAC'i anahtarımızla doğrulanan beacon
+    VerifiedBeacon,
+}
+
+impl ServerIdSource {
+    pub fn is_verified(self) -> bool {
+        self == ServerIdSource::VerifiedBeacon
+    }
+}
+
+impl DiscoveryMethod {
+    /// Aynı adres birden fazla yöntemle bulunduğunda hangisinin gösterileceği
+    fn preference(&self) -> u8 {
+        match self {
+            DiscoveryMethod::MDns => 2,
+            DiscoveryMethod::Broadcast => 1,
+            DiscoveryMethod::PortScan => 0,
+        }
+    }
+}
+
+impl DiscoveredServer {
+    /// Aynı adresteki başka bir keşif sonucunun bilgilerini bu kayda ekler
+    fn merge(&mut self, other: DiscoveredServer) {
+        if other.discovery_method.preference() > self.discovery_method.preference() {
+            self.discovery_method = other.discovery_method;
+            self.name = other.name.or(self.name.take());
+        } else {
+            self.name = self.name.take().or(other.name);
+        }
+        // Daha güçlü kaynaktan gelen kimlik kazanır
+        if other.server_id.is_some() && other.server_id_source > self.server_id_source {
+            self.server_id = other.server_id;
+            self.server_id_source = other.server_id_source;
+        }
+        self.rtt = self.rtt.or(other.rtt);
+        self.protocol_version = self.protocol_version.or(other.protocol_version);
+        self.proven = self.proven.or(other.proven);
+    }
+}
+
+/// LAN'daki Deltasafe sunucularını keşfeder.
+///
+/// Anahtar verilirse imzası doğrulanamayan broadcast beacon'ları atlanır.
+pub async fn discover_servers(timeout_secs: u64, key: Option<&[u8; 32]>) -> Result<Vec<DiscoveredServer>> {
+    run_discovery(timeout_secs, key, true).await
+}
+
+/// Ekrana çıktı basmadan keşif yapar (örn. adres defterinin arka planda
+/// yenilenmesi veya JSON çıktısı için).
+pub async fn discover_servers_quietly(timeout_secs: u64, key: Option<&[u8; 32]>) -> Result<Vec<DiscoveredServer>> {
+    run_discovery(timeout_secs, key, false).await
+}
+
+async fn run_discovery(timeout_secs: u64, key: Option<&[u8; 32]>, verbose: bool) -> Result<Vec<DiscoveredServer>> {
+    let report = |message: String| {
+        if verbose {
+            log::info!("{}", message);
+        }
+    };
+
+    report("[🔍] LAN'da Deltasafe sunucuları aranıyor...".to_string());
     
     let mut servers = Vec::new();
     
-    // 1. mDNS ile keşif dene
-    match discover_via_mdns(timeout_secs).await {
+    // 1. mDNS ve broadcast beacon dinlemesi aynı anda çalışır
+    report("[📡] mDNS keşfi deneniyor...".to_string());
+    report(format!("[📣] Broadcast beacon'ları dinleniyor (UDP {})...", beacon::BEACON_PORT));
+    let (mdns_result, broadcast_result) = tokio::join!(
+        discover_via_mdns(timeout_secs),
+        discover_via_broadcast(timeout_secs, key.copied()),
+    );
+
+    match mdns_result {
         Ok(mut mdns_servers) => {
-            println!("[📡] mDNS ile {} sunucu bulundu", mdns_servers.len());
+            report(format!("[📡] mDNS ile {} sunucu bulundu", mdns_servers.len()));
             servers.append(&mut mdns_servers);
         },
         Err(e) => {
-            println!("[⚠️] mDNS keşfi başarısız: {}", e);
+            report(format!("[⚠️] mDNS keşfi başarısız: {}", e));
+        }
+    }
+
+    match broadcast_result {
+        Ok(mut broadcast_servers) => {
+            report(format!("[📣] Broadcast beacon ile {} sunucu bulundu", broadcast_servers.len()));
+            servers.append(&mut broadcast_servers);
+        },
+        Err(e) => {
+            report(format!("[⚠️] Broadcast keşfi başarısız: {}", e));
         }
     }
     
     // 2. Port tarama ile keşif
+    report("[🔎] Yerel ağda port taraması yapılıyor...".to_string());
     match discover_via_port_scan().await {
-        Ok(mut scan_servers) => {
-            println!("[🔎] Port tarama ile {} sunucu bulundu", scan_servers.len());
+        Ok(Some(mut scan_servers)) => {
+            report(format!("[🔎] Port tarama ile {} sunucu bulundu", scan_servers.len()));
             servers.append(&mut scan_servers);
         },
+        Ok(None) => {
+            // IPv6 alt ağları taranamayacak kadar büyük, mDNS'e güveniyoruz
+            report("[ℹ️] Yalnızca IPv6 ağı bulundu, port tarama atlanıyor (mDNS kullanılıyor)".to_string());
+        },
         Err(e) => {
-            println!("[⚠️] Port tarama başarısız: {}", e);
+            report(format!("[⚠️] Port tarama başarısız: {}", e));
         }
     }
     
-    // Duplikatları temizle
+    // Duplikatları temizle, gecikmeleri ölç ve sunucuları sına
     servers = deduplicate_servers(servers);
+    probe_servers(&mut servers, key).await;
     
     if servers.is_empty() {
-        println!("[ℹ️] Hiç sunucu bulunamadı. Manuel IP:port belirtmeyi deneyin.");
+        report("[ℹ️] Hiç sunucu bulunamadı. Manuel IP:port belirtmeyi deneyin.".to_string());
     } else {
-        println!("[✅] Toplam {} benzersiz sunucu keşfedildi", servers.len());
+        report(format!("[✅] Toplam {} benzersiz sunucu keşfedildi", servers.len()));
         for (i, server) in servers.iter().enumerate() {
-            println!("  {}. {} ({:?})", i + 1, server.address, server.discovery_method);
+            report(format!("  {}. {} ({:?})", i + 1, server.address, server.discovery_method));
         }
     }
     
     Ok(servers)
 }
 
-/// mDNS kullanarak sunucu keşfi
+/// Sunucuya TCP bağlantı kurma süresini ölçer (yaklaşık RTT). Anahtar
+/// verilirse aynı bağlantıda sunucunun anahtarı bildiğini el sıkışma
+/// kanıtıyla sınar: kanıt uyuşmazsa `Some(false)`, sınama sonuçsuz kalırsa
+/// (örn. TLS bekleyen sunucu) `None` döner.
+pub async fn probe_address(addr: SocketAddr, key: Option<&[u8; 32]>) -> (Option<Duration>, Option<bool>) {
+    let started = Instant::now();
+    let stream = match tokio::time::timeout(RTT_PROBE_TIMEOUT, tokio::net::TcpStream::connect(addr)).await {
+        Ok(Ok(stream)) => stream,
+        _ => return (None, None),
+    };
+    let rtt = started.elapsed();
+
+    let Some(key) = key else {
+        return (Some(rtt), None);
+    };
+    let proven = match tokio::time::timeout(PROOF_PROBE_TIMEOUT, sync::probe_server(Connection::new(stream), key)).await {
+        Ok(Ok(())) => Some(true),
+        Ok(Err(e)) if matches!(e.downcast_ref::<Error>(), Some(Error::Auth(_))) => Some(false),
+        _ => None,
+    };
+    (Some(rtt), proven)
+}
+
+/// Sunucuları paralel olarak sınar. RTT'si bilinmeyenler için ölçüm yapılır;
+/// anahtar verilmişse her adres el sıkışma kanıtıyla sınanır.
+async fn probe_servers(servers: &mut [DiscoveredServer], key: Option<&[u8; 32]>) {
+    let key = key.copied();
+    let probes: Vec<_> = servers.iter()
+        .map(|server| (server.rtt.is_none() || key.is_some(), server.address))
+        .map(|(needed, addr)| tokio::spawn(async move {
+            if needed { probe_address(addr, key.as_ref()).await } else { (None, None) }
+        }))
+        .collect();
+
+    for (server, probe) in servers.iter_mut().zip(probes) {
+        if let Ok((rtt, proven)) = probe.await {
+            server.rtt = server.rtt.or(rtt);
+            server.proven = proven;
+        }
+    }
+}
+
+/// mDNS kullanarak sunucu keşfi (IPv4 224.0.0.251 ve IPv6 ff02::fb)
 async fn discover_via_mdns(timeout_secs: u64) -> Result<Vec<DiscoveredServer>> {
-    // mDNS şimdilik basit implementasyon - gerçek mDNS karmaşık
-    println!("[📡] mDNS keşfi deneniyor... (basit implementasyon)");
-    
-    // Şimdilik boş liste döndür, gelecekte gerçek mDNS eklenecek
-    tokio::time::sleep(Duration::from_millis(100)).await;
-    
-    Ok(Vec::new())
+    let timeout = Duration::from_secs(timeout_secs);
+    tokio::task::spawn_blocking(move || browse_mdns(timeout))
+        .await
+        .context("mDNS görevi tamamlanamadı")?
 }
 
-/// Port tarama ile sunucu keşfi
-async fn discover_via_port_scan() -> Result<Vec<DiscoveredServer>> {
-    let local_network = get_local_network_range()?;
+/// Link-local IPv6 adresleri yalnızca geldikleri arayüzde geçerlidir ve
+/// mDNS kütüphanesi kaydın hangi arayüzden geldiğini bildirmez. Bu yüzden
+/// her arayüz ayrı bir daemon ile taranır.
+fn browse_mdns(timeout: Duration) -> Result<Vec<DiscoveredServer>> {
+    let interfaces = net::network_interfaces()?;
+    if interfaces.is_empty() {
+        return browse_mdns_on(None, timeout);
+    }
+
+    let results: Vec<Result<Vec<DiscoveredServer>>> = std::thread::scope(|scope| {
+        let handles: Vec<_> = interfaces.iter()
+            .map(|interface| scope.spawn(move || browse_mdns_on(Some(interface), timeout)))
+            .collect();
+        handles.into_iter()
+            .map(|handle| handle.join().unwrap_or_else(|_| Err(anyhow::anyhow!("mDNS taraması çöktü"))))
+            .collect()
+    });
+
+    // Bir arayüzde tarama başlatılamaması diğerlerinin sonuçlarını düşürmez
+    let mut servers = Vec::new();
+    let mut first_error = None;
+    let mut browsed = false;
+    for result in results {
+        match result {
+            Ok(mut found) => {
+                browsed = true;
+                servers.append(&mut found);
+            }
+            Err(e) => {
+                first_error.get_or_insert(e);
+            }
+        }
+    }
+    match (browsed, first_error) {
+        (false, Some(e)) => Err(e),
+        _ => Ok(servers),
+    }
+}
+
+/// Tek bir arayüzde (`None` ise tüm arayüzlerde) mDNS taraması yapar
+fn browse_mdns_on(interface: Option<&(String, u32)>, timeout: Duration) -> Result<Vec<DiscoveredServer>> {
+    let daemon = ServiceDaemon::new().context("mDNS daemon başlatılamadı")?;
+    if let Some((name, _)) = interface {
+        daemon.disable_interface(IfKind::All)
+            .and_then(|()| daemon.enable_interface(IfKind::Name(name.clone())))
+            .with_context(|| format!("mDNS taraması {} arayüzüyle sınırlanamadı", name))?;
+    }
+    let receiver = daemon.browse(DELTASAFE_SERVICE_TYPE)
+        .context("mDNS taraması başlatılamadı")?;
+
+    let scope_id = interface.map_or(0, |(_, index)| *index);
+    let mut servers = Vec::new();
+    let deadline = Instant::now() + timeout;
+
+    while let Ok(event) = receiver.recv_deadline(deadline) {
+        if let ServiceEvent::ServiceResolved(info) = event {
+            servers.extend(servers_from_service_info(&info, scope_id));
+        }
+    }
+
+    let _ = daemon.shutdown();
+    Ok(servers)
+}
+
+/// Çözümlenen mDNS kaydını (her adres için bir tane) sunucu listesine
+/// çevirir. `scope_id`, kaydın geldiği arayüzün indeksidir (bilinmiyorsa 0).
+fn servers_from_service_info(info: &ServiceInfo, scope_id: u32) -> Vec<DiscoveredServer> {
+    let server_id = info.get_property_val_str(SERVER_ID_TXT_KEY)
+        .map(str::to_string);
+    let protocol_version = info.get_property_val_str(PROTOCOL_TXT_KEY)
+        .and_then(|v| v.parse::<u32>().ok());
+    let name = info.get_fullname()
+        .strip_suffix(DELTASAFE_SERVICE_TYPE)
+        .map(|n| n.trim_end_matches('.').to_string())
+        .unwrap_or_else(|| info.get_fullname().to_string());
+
+    info.get_addresses()
+        .iter()
+        .filter_map(|ip| mdns_socket_addr(*ip, info.get_port(), scope_id))
+        .map(|address| DiscoveredServer {
+            address,
+            name: Some(name.clone()),
+            discovery_method: DiscoveryMethod::MDns,
+            server_id: server_id.clone(),
+            server_id_source: server_id.as_ref().map(|_| ServerIdSource::MDns),
+            rtt: None,
+            protocol_version,
+            proven: None,
+        })
+        .collect()
+}
+
+/// mDNS adresini soket adresine çevirir. Link-local IPv6 adreslerine
+/// kaydın geldiği arayüzün scope id'si eklenir; arayüz bilinmiyorsa bu
+/// adresler kullanılamayacağı için atlanır.
+fn mdns_socket_addr(ip: IpAddr, port: u16, scope_id: u32) -> Option<SocketAddr> {
+    match ip {
+        IpAddr::V6(v6) if v6.is_unicast_link_local() => match scope_id {
+            0 => None,
+            _ => Some(SocketAddr::V6(SocketAddrV6::new(v6, port, 0, scope_id))),
+        },
+        IpAddr::V6(v6) => Some(SocketAddr::V6(SocketAddrV6::new(v6, port, 0, 0))),
+        IpAddr::V4(_) => Some(SocketAddr::new(ip, port)),
+    }
+}
+
+/// Sunucuyu mDNS ile duyurur. Duyuru, dönen daemon yaşadığı sürece devam eder;
+/// adresler (IPv4 ve IPv6) arayüzlerden otomatik toplanır.
+pub fn advertise_service(instance_name: &str, port: u16, server_id: &str) -> Result<ServiceDaemon> {
+    let daemon = ServiceDaemon::new().context("mDNS daemon başlatılamadı")?;
+
+    let host_name = format!("{}.local.", net::local_hostname());
+    let properties = [
+        (SERVER_ID_TXT_KEY, server_id.to_string()),
+        (PROTOCOL_TXT_KEY, PROTOCOL_VERSION.to_string()),
+    ];
+    let info = ServiceInfo::new(
+        DELTASAFE_SERVICE_TYPE,
+        instance_name,
+        &host_name,
+        "",
+        port,
+        &properties[..],
+    )
+    .context("mDNS servis kaydı oluşturulamadı")?
+    .enable_addr_auto();
+
+    daemon.register(info).context("mDNS servisi kaydedilemedi")?;
+    Ok(daemon)
+}
+
+/// UDP broadcast beacon'ları dinleyerek sunucu keşfi
+async fn discover_via_broadcast(timeout_secs: u64, key: Option<[u8; 32]>) -> Result<Vec<DiscoveredServer>> {
+    let timeout = Duration::from_secs(timeout_secs);
+    let beacons = tokio::task::spawn_blocking(move || beacon::listen_for_beacons(timeout, key.as_ref()))
+        .await
+        .context("Beacon görevi tamamlanamadı")??;
+
+    Ok(beacons.into_iter()
+        .map(|(address, beacon)| server_from_beacon(address, beacon, key.is_some()))
+        .collect())
+}
+
+/// `verified`, beacon'ın MAC'inin anahtarımızla doğrulanıp doğrulanmadığıdır
+fn server_from_beacon(address: SocketAddr, beacon: Beacon, verified: bool) -> DiscoveredServer {
+    DiscoveredServer {
+        address: net::canonical_addr(address),
+        name: Some(beacon.name),
+        discovery_method: DiscoveryMethod::Broadcast,
+        server_id: Some(beacon.server_id),
+        server_id_source: Some(if verified { ServerIdSource::VerifiedBeacon } else { ServerIdSource::Beacon }),
+        rtt: None,
+        protocol_version: Some(beacon.protocol),
+        proven: None,
+    }
+}
+
+/// Port tarama ile sunucu keşfi. Yalnızca IPv6 ağı varsa tarama atlanır
+/// ve `None` döner.
+async fn discover_via_port_scan() -> Result<Option<Vec<DiscoveredServer>>> {
+    let local_network = match get_local_network_range()? {
+        Some(range) => range,
+        None => return Ok(None),
+    };
     let mut servers = Vec::new();
-    
-    println!("[🔎] Yerel ağda port taraması yapılıyor...");
     
     // Paralel port tarama (sadece birkaç IP test et, çok fazla olmasın)
     let mut tasks = Vec::new();
@@ -110,12 +431,13 @@ async fn discover_via_port_scan() -> Result<Vec<DiscoveredServer>> {
         }
     }
     
-    Ok(servers)
+    Ok(Some(servers))
 }
 
 /// Belirli bir adreste Deltasafe sunucusu olup olmadığını kontrol eder
 async fn check_deltasafe_server(addr: SocketAddr) -> Option<DiscoveredServer> {
     // Tokio TcpStream kullan
+    let started = Instant::now();
     match tokio::time::timeout(Duration::from_millis(100), tokio::net::TcpStream::connect(addr)).await {
         Ok(Ok(_)) => {
             // Bağlantı başarılı, muhtemelen bir sunucu var
@@ -123,30 +445,29 @@ async fn check_deltasafe_server(addr: SocketAddr) -> Option<DiscoveredServer> {
                 address: addr,
                 name: None,
                 discovery_method: DiscoveryMethod::PortScan,
+                server_id: None,
+                server_id_source: None,
+                rtt: Some(started.elapsed()),
+                protocol_version: None,
+                proven: None,
             })
         },
         _ => None,
     }
 }
 
-/// Yerel ağ IP aralığını bulur
-fn get_local_network_range() -> Result<NetworkRange> {
-    use std::net::UdpSocket;
-    
-    // Yerel IP'yi bul
-    let socket = UdpSocket::bind("0.0.0.0:0").context("UDP socket oluşturulamadı")?;
-    socket.connect("8.8.8.8:80").context("Test bağlantısı kurulamadı")?;
-    let local_addr = socket.local_addr().context("Yerel adres alınamadı")?;
-    
-    if let IpAddr::V4(local_ip) = local_addr.ip() {
-        // /24 subnet varsay (255.255.255.0)
-        let octets = local_ip.octets();
-        let network_base = Ipv4Addr::new(octets[0], octets[1], octets[2], 1);
-        let network_end = Ipv4Addr::new(octets[0], octets[1], octets[2], 254);
-        
-        Ok(NetworkRange::new(network_base, network_end))
-    } else {
-        anyhow::bail!("IPv6 henüz desteklenmiyor")
+/// Yerel ağ IP aralığını bulur. Yalnızca IPv6 adresi varsa `None` döner.
+fn get_local_network_range() -> Result<Option<NetworkRange>> {
+    match net::local_ip()? {
+        IpAddr::V4(local_ip) => {
+            // /24 subnet varsay (255.255.255.0)
+            let octets = local_ip.octets();
+            let network_base = Ipv4Addr::new(octets[0], octets[1], octets[2], 1);
+            let network_end = Ipv4Addr::new(octets[0], octets[1], octets[2], 254);
+
+            Ok(Some(NetworkRange::new(network_base, network_end)))
+        }
+        IpAddr::V6(_) => Ok(None),
     }
 }
 
@@ -191,88 +512,187 @@ impl Iterator for NetworkRangeIter {
     }
 }
 
-/// Duplikat sunucuları temizler
+/// Duplikat sunucuları temizler. Aynı adresteki kayıtlar birleştirilir:
+/// mDNS > broadcast > port tarama sırasıyla tercih edilir, isim ve sunucu
+/// kimliği hangi yöntemden gelirse gelsin korunur.
 fn deduplicate_servers(servers: Vec<DiscoveredServer>) -> Vec<DiscoveredServer> {
-    let mut unique_servers = HashMap::new();
+    let mut unique_servers: HashMap<SocketAddr, DiscoveredServer> = HashMap::new();
     
     for server in servers {
-        // Aynı adresteki sunucuları birleştir, mDNS'i tercih et
-        match unique_servers.get(&server.address) {
-            Some(_existing) => {
-                if matches!(server.discovery_method, DiscoveryMethod::MDns) {
-                    unique_servers.insert(server.address, server);
-                }
-            },
+        match unique_servers.get_mut(&server.address) {
+            Some(existing) => existing.merge(server),
             None => {
                 unique_servers.insert(server.address, server);
             }
         }
     }
     
-    unique_servers.into_values().collect()
+    // HashMap sırası rastgele; çıktının her çalıştırmada aynı olması için sırala
+    let mut servers: Vec<DiscoveredServer> = unique_servers.into_values().collect();
+    servers.sort_by_key(|s| s.address);
+    servers
+}
+
+/// Otomatik sunucu seçiminde kullanılan ölçütler
+#[derive(Debug, Clone, Default)]
+pub struct SelectionCriteria {
+    /// Adres defterine göre daha önce senkronizasyon yapılmış adresler
+    pub known_addresses: Vec<SocketAddr>,
+    /// Yalnızca bu isimle duyurulan sunucular seçilir
+    pub share_name: Option<String>,
+    /// Tam olarak bir güvenilir sunucu bulunmasını zorunlu kıl
+    pub require_trusted: bool,
+}
+
+/// Sunucunun anahtarımızla ilişkisi
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Trust {
+    /// Adres sınanamadı; duyurular (mDNS, beacon) tek başına adresteki
+    /// sunucunun anahtarı bildiğini göstermez
+    Unverified,
+    /// Bu adresteki sunucu el sıkışmada anahtarımızı bildiğini kanıtladı
+    Trusted,
+}
+
+/// Otomatik seçim sonucu ve seçimin gerekçeleri
+#[derive(Debug)]
+pub struct Selection<'a> {
+    pub server: &'a DiscoveredServer,
+    pub reasons: Vec<String>,
+}
+
+/// Sıralamaya giren aday ve sıralama anahtarı
+struct Candidate<'a> {
+    server: &'a DiscoveredServer,
+    trust: Trust,
+    known: bool,
+    protocol_known: bool,
 }
 
-/// Kullanıcıya sunucu seçimi yaptırır
-pub fn select_server_interactive(servers: &[DiscoveredServer]) -> Option<&DiscoveredServer> {
+impl Candidate<'_> {
+    /// Büyük olan tercih edilir: güven > daha önce senkronize edilmiş >
+    /// protokol sürümü biliniyor > keşif yöntemi > düşük RTT
+    fn rank(&self) -> (Trust, bool, bool, u8, std::cmp::Reverse<Duration>) {
+        (
+            self.trust,
+            self.known,
+            self.protocol_known,
+            self.server.discovery_method.preference(),
+            std::cmp::Reverse(self.server.rtt.unwrap_or(Duration::MAX)),
+        )
+    }
+}
+
+/// Otomatik sunucu seçer (kullanıcı etkileşimi olmadan).
+///
+/// Protokolü uyumsuz, el sıkışma kanıtı anahtarımızla uyuşmayan veya
+/// paylaşım adı eşleşmeyen sunucular elenir; kalanlar güven, geçmiş senkronizasyon ve
+/// ölçülen RTT'ye göre sıralanır. Seçimin gerekçeleri de döndürülür.
+pub fn select_best_server_auto<'a>(servers: &'a [DiscoveredServer], criteria: &SelectionCriteria) -> Result<Selection<'a>> {
     if servers.is_empty() {
-        return None;
+        anyhow::bail!("Seçilebilecek sunucu yok");
     }
-    
-    if servers.len() == 1 {
-        println!("[✅] Tek sunucu bulundu: {}", servers[0].address);
-        return Some(&servers[0]);
+
+    let mut candidates = Vec::new();
+    for server in servers {
+        match evaluate_candidate(server, criteria) {
+            Ok(candidate) => candidates.push(candidate),
+            Err(reason) => log::warn!("[🚫] {} elendi: {}", server.address, reason),
+        }
     }
-    
-    // Birden fazla sunucu varsa kullanıcıya sor
-    println!("[🔍] {} sunucu bulundu. Lütfen birini seçin:", servers.len());
-    for (i, server) in servers.iter().enumerate() {
-        println!("  {}. {} ({:?})", i + 1, server.address, server.discovery_method);
-        if let Some(name) = &server.name {
-            println!("     Servis adı: {}", name);
+
+    if criteria.require_trusted {
+        candidates.retain(|c| c.trust == Trust::Trusted);
+        // Aynı sunucu birden fazla adresle (IPv4/IPv6) bulunabilir; isimle
+        // say. İsimsiz sunucular adresleriyle ayrı ayrı sayılır.
+        let mut trusted: Vec<Result<&str, SocketAddr>> = candidates.iter()
+            .map(|c| c.server.name.as_deref().ok_or(c.server.address))
+            .collect();
+        trusted.sort();
+        trusted.dedup();
+        if trusted.len() != 1 {
+            anyhow::bail!("Tam olarak bir güvenilir sunucu bekleniyordu, {} bulundu", trusted.len());
         }
     }
-    
-    // Kullanıcı girişi al
-    loop {
-        print!("Seçiminiz (1-{}): ", servers.len());
-        use std::io::{self, Write};
-        io::stdout().flush().unwrap();
-        
-        let mut input = String::new();
-        match io::stdin().read_line(&mut input) {
-            Ok(_) => {
-                if let Ok(choice) = input.trim().parse::<usize>() {
-                    if choice >= 1 && choice <= servers.len() {
-                        println!("[✅] {} seçildi", servers[choice - 1].address);
-                        return Some(&servers[choice - 1]);
-                    }
-                }
-                println!("[⚠️] Geçersiz seçim. 1-{} arası bir sayı girin.", servers.len());
-            }
-            Err(_) => {
-                println!("[⚠️] Giriş hatası. Tekrar deneyin.");
-            }
+
+    // Eşit sıralamada adres sırası belirleyici olsun (servers adrese göre sıralı)
+    let best = candidates.iter()
+        .rev()
+        .max_by_key(|c| c.rank())
+        .context("Ölçütlere uyan sunucu bulunamadı")?;
+
+    let reasons = explain_selection(best, &candidates, criteria);
+
+    if servers.len() > 1 {
+        log::info!("[ℹ️] {} sunucu bulundu, otomatik olarak {} seçildi", servers.len(), best.server.address);
+        log::info!("[💡] Tüm sunucuları görmek için 'deltasafe discover' komutunu kullanın");
+    }
+
+    Ok(Selection { server: best.server, reasons })
+}
+
+/// Sunucunun aday olup olamayacağını değerlendirir; elenirse gerekçeyi döndürür
+fn evaluate_candidate<'a>(server: &'a DiscoveredServer, criteria: &SelectionCriteria) -> Result<Candidate<'a>, String> {
+    if let Some(version) = server.protocol_version
+        && !is_compatible(version)
+    {
+        return Err(format!("protokol sürümü {} uyumsuz (beklenen {})", version, PROTOCOL_VERSION));
+    }
+
+    if let Some(share) = &criteria.share_name {
+        let matches = server.name.as_deref()
+            .is_some_and(|name| name.eq_ignore_ascii_case(share));
+        if !matches {
+            return Err(format!("paylaşım adı '{}' ile eşleşmiyor", share));
         }
     }
+
+    // Güven, seçilecek adresin kendisine yapılan el sıkışma sınamasından gelir
+    let trust = match server.proven {
+        Some(true) => Trust::Trusted,
+        Some(false) => return Err("el sıkışma kanıtı anahtarınızla uyuşmuyor (farklı anahtar)".to_string()),
+        None => Trust::Unverified,
+    };
+
+    let known = criteria.known_addresses.contains(&server.address);
+
+    Ok(Candidate {
+        server,
+        trust,
+        known,
+        protocol_known: server.protocol_version.is_some(),
+    })
 }
 
-/// Otomatik sunucu seçer (kullanıcı etkileşimi olmadan)
-pub fn select_best_server_auto(servers: &[DiscoveredServer]) -> Option<&DiscoveredServer> {
-    if servers.is_empty() {
-        return None;
+/// Seçilen sunucunun neden tercih edildiğini açıklar
+fn explain_selection(best: &Candidate, candidates: &[Candidate], criteria: &SelectionCriteria) -> Vec<String> {
+    let mut reasons = Vec::new();
+
+    if let Some(share) = &criteria.share_name {
+        reasons.push(format!("paylaşım adı '{}' ile eşleşiyor", share));
     }
-    
-    // mDNS ile bulunanları tercih et, yoksa ilkini al
-    let selected = servers.iter()
-        .find(|s| matches!(s.discovery_method, DiscoveryMethod::MDns))
-        .or_else(|| servers.first())?;
-    
-    if servers.len() > 1 {
-        println!("[ℹ️] {} sunucu bulundu, otomatik olarak {} seçildi", servers.len(), selected.address);
-        println!("[💡] Tüm sunucuları görmek için 'deltasafe discover' komutunu kullanın");
+    match best.trust {
+        Trust::Trusted => reasons.push("el sıkışmada anahtarınızı bildiğini kanıtladı".to_string()),
+        Trust::Unverified => reasons.push("sunucu doğrulanamadı (güvenilir sunucu bulunamadı)".to_string()),
     }
-    
-    Some(selected)
+    if best.known {
+        reasons.push("daha önce senkronizasyon yapıldı".to_string());
+    }
+    if let Some(version) = best.server.protocol_version {
+        reasons.push(format!("protokol sürümü {} uyumlu", version));
+    }
+    if let Some(rtt) = best.server.rtt {
+        let fastest = candidates.iter()
+            .filter_map(|c| c.server.rtt)
+            .min()
+            .is_some_and(|min| rtt <= min);
+        if fastest && candidates.len() > 1 {
+            reasons.push(format!("en düşük RTT ({:.1} ms)", rtt.as_secs_f64() * 1000.0));
+        } else {
+            reasons.push(format!("RTT {:.1} ms", rtt.as_secs_f64() * 1000.0));
+        }
+    }
+    reasons
 }
 
 #[cfg(test)]
@@ -291,6 +711,23 @@ mod tests {
         assert_eq!(ips[2], Ipv4Addr::new(192, 168, 1, 3));
     }
     
+    #[test]
+    fn test_mdns_socket_addr() {
+        let v4 = IpAddr::V4(Ipv4Addr::new(192, 168, 1, 100));
+        assert_eq!(mdns_socket_addr(v4, 12345, 0), Some(SocketAddr::new(v4, 12345)));
+
+        let global: IpAddr = "2001:db8::10".parse().unwrap();
+        assert_eq!(mdns_socket_addr(global, 12345, 3), Some(SocketAddr::new(global, 12345)));
+
+        let link_local: IpAddr = "fe80::10".parse().unwrap();
+        assert_eq!(mdns_socket_addr(link_local, 12345, 0), None);
+
+        // Link-local adres, geldiği arayüzün scope id'siyle kullanılabilir
+        let scoped = mdns_socket_addr(link_local, 12345, 3).unwrap();
+        assert_eq!(scoped, "[fe80::10%3]:12345".parse().unwrap());
+        assert_eq!(crate::net::resolve_target(&scoped.to_string()).unwrap(), vec![scoped]);
+    }
+
     #[test]
     fn test_deduplicate_servers() {
         let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(192, 168, 1, 100)), 12345);
@@ -300,11 +737,21 @@ mod tests {
                 address: addr,
                 name: None,
                 discovery_method: DiscoveryMethod::PortScan,
+                server_id: None,
+                server_id_source: None,
+                rtt: None,
+                protocol_version: None,
+                proven: None,
             },
             DiscoveredServer {
                 address: addr,
                 name: Some("test".to_string()),
                 discovery_method: DiscoveryMethod::MDns,
+                server_id: None,
+                server_id_source: None,
+                rtt: None,
+                protocol_version: None,
+                proven: None,
             },
         ];
         
@@ -312,4 +759,186 @@ mod tests {
         assert_eq!(unique.len(), 1);
         assert!(matches!(unique[0].discovery_method, DiscoveryMethod::MDns));
     }
-}
\ No newline at end of file
+
+    fn candidate(address: &str, server_id: Option<&str>, rtt_ms: Option<u64>) -> DiscoveredServer {
+        DiscoveredServer {
+            address: address.parse().unwrap(),
+            name: Some("ofis-nas".to_string()),
+            discovery_method: DiscoveryMethod::Broadcast,
+            server_id: server_id.map(str::to_string),
+            server_id_source: server_id.map(|_| ServerIdSource::VerifiedBeacon),
+            rtt: rtt_ms.map(Duration::from_millis),
+            protocol_version: Some(PROTOCOL_VERSION),
+            proven: server_id.map(|_| true),
+        }
+    }
+
+    #[test]
+    fn test_selection_prefers_trusted_then_rtt() {
+        let mut other_key = candidate("192.168.1.13:12345", Some("bbbb"), Some(1));
+        other_key.proven = Some(false);
+        let servers = vec![
+            candidate("192.168.1.10:12345", None, Some(1)),
+            candidate("192.168.1.11:12345", Some("aaaa"), Some(9)),
+            candidate("192.168.1.12:12345", Some("aaaa"), Some(4)),
+            other_key,
+        ];
+
+        let selection = select_best_server_auto(&servers, &SelectionCriteria::default()).unwrap();
+        assert_eq!(selection.server.address, servers[2].address);
+        assert!(selection.reasons.iter().any(|r| r.contains("RTT")));
+    }
+
+    #[test]
+    fn test_selection_excludes_incompatible_and_other_shares() {
+        let mut old = candidate("192.168.1.10:12345", Some("aaaa"), Some(1));
+        old.protocol_version = Some(PROTOCOL_VERSION + 1);
+        let mut other_share = candidate("192.168.1.11:12345", Some("aaaa"), Some(2));
+        other_share.name = Some("yedek".to_string());
+        let servers = vec![old, other_share, candidate("192.168.1.12:12345", Some("aaaa"), Some(8))];
+
+        let mut criteria = SelectionCriteria {
+            share_name: Some("OFIS-NAS".to_string()),
+            ..SelectionCriteria::default()
+        };
+        let selection = select_best_server_auto(&servers, &criteria).unwrap();
+        assert_eq!(selection.server.address, servers[2].address);
+
+        criteria.share_name = Some("yok".to_string());
+        assert!(select_best_server_auto(&servers, &criteria).is_err());
+    }
+
+    #[test]
+    fn test_selection_prefers_known_peer() {
+        let servers = vec![
+            candidate("192.168.1.10:12345", None, Some(1)),
+            candidate("192.168.1.11:12345", None, Some(5)),
+        ];
+
+        let mut criteria = SelectionCriteria::default();
+        // Güven eşit, RTT belirleyici
+        assert_eq!(select_best_server_auto(&servers, &criteria).unwrap().server.address, servers[0].address);
+
+        criteria.known_addresses = vec![servers[1].address];
+        // Daha önce senkronizasyon yapılan adres, RTT'si yüksek olsa da seçilir
+        let selection = select_best_server_auto(&servers, &criteria).unwrap();
+        assert_eq!(selection.server.address, servers[1].address);
+        assert!(selection.reasons.iter().any(|r| r.contains("daha önce")));
+    }
+
+    #[test]
+    fn test_selection_require_single_trusted() {
+        let mut backup = candidate("192.168.1.11:12345", Some("aaaa"), Some(5));
+        backup.name = Some("yedek".to_string());
+        let servers = vec![
+            candidate("192.168.1.10:12345", Some("aaaa"), Some(1)),
+            backup,
+            candidate("192.168.1.12:12345", None, Some(1)),
+        ];
+        let criteria = SelectionCriteria {
+            require_trusted: true,
+            ..SelectionCriteria::default()
+        };
+        assert!(select_best_server_auto(&servers, &criteria).is_err());
+
+        let selection = select_best_server_auto(&servers[1..], &criteria).unwrap();
+        assert_eq!(selection.server.address, servers[1].address);
+
+        // Aynı sunucunun IPv4 ve IPv6 adresleri tek sunucu sayılır
+        let dual_stack = vec![
+            candidate("192.168.1.10:12345", Some("aaaa"), Some(3)),
+            candidate("[2001:db8::10]:12345", Some("aaaa"), Some(1)),
+        ];
+        let selection = select_best_server_auto(&dual_stack, &criteria).unwrap();
+        assert_eq!(selection.server.address, dual_stack[1].address);
+
+        assert!(select_best_server_auto(&servers[2..], &criteria).is_err());
+
+        // İsimsiz iki farklı sunucu tek sunucu sayılmaz
+        let mut unnamed = vec![
+            candidate("192.168.1.10:12345", Some("aaaa"), Some(1)),
+            candidate("192.168.1.11:12345", Some("bbbb"), Some(2)),
+        ];
+        unnamed.iter_mut().for_each(|server| server.name = None);
+        assert!(select_best_server_auto(&unnamed, &criteria).is_err());
+        assert!(select_best_server_auto(&unnamed[..1], &criteria).is_ok());
+    }
+
+    #[test]
+    fn test_selection_ignores_spoofed_announcements() {
+        // Sahte sunucu gerçek sunucunun mDNS kaydını ve beacon'ını kopyalar
+        // ve daha hızlıdır; ancak el sıkışma sınamasını geçemez
+        let mut spoofed = candidate("192.168.1.66:12345", Some("aaaa"), Some(1));
+        spoofed.discovery_method = DiscoveryMethod::MDns;
+        spoofed.proven = None;
+        let servers = vec![candidate("192.168.1.10:12345", Some("aaaa"), Some(9)), spoofed];
+
+        let mut criteria = SelectionCriteria::default();
+        let selection = select_best_server_auto(&servers, &criteria).unwrap();
+        assert_eq!(selection.server.address, servers[0].address);
+        assert!(selection.reasons.iter().any(|r| r.contains("kanıtladı")));
+
+        // Yalnızca sahte kayıt varsa güvenilir sayılmaz
+        let selection = select_best_server_auto(&servers[1..], &criteria).unwrap();
+        assert!(selection.reasons.iter().any(|r| r.contains("doğrulanamadı")));
+        assert!(!selection.reasons.iter().any(|r| r.contains("daha önce")));
+        criteria.require_trusted = true;
+        assert!(select_best_server_auto(&servers[1..], &criteria).is_err());
+    }
+
+    #[test]
+    fn test_deduplicate_merges_broadcast_details() {
+        let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(192, 168, 1, 100)), 12345);
+        let beacon = Beacon::new("ofis-nas", 12345, "0011aabb", vec![addr.ip()], 0);
+
+        let servers = vec![
+            DiscoveredServer {
+                address: addr,
+                name: None,
+                discovery_method: DiscoveryMethod::PortScan,
+                server_id: None,
+                server_id_source: None,
+                rtt: None,
+                protocol_version: None,
+                proven: None,
+            },
+            server_from_beacon(addr, beacon, true),
+        ];
+
+        let unique = deduplicate_servers(servers);
+        assert_eq!(unique.len(), 1);
+        assert!(matches!(unique[0].discovery_method, DiscoveryMethod::Broadcast));
+        assert_eq!(unique[0].name.as_deref(), Some("ofis-nas"));
+        assert_eq!(unique[0].server_id.as_deref(), Some("0011aabb"));
+
+        // mDNS sonradan gelse de beacon'dan gelen kimlik korunur
+        let mut merged = unique[0].clone();
+        merged.merge(DiscoveredServer {
+            address: addr,
+            name: Some("ofis-nas-mdns".to_string()),
+            discovery_method: DiscoveryMethod::MDns,
+            server_id: None,
+            server_id_source: None,
+            rtt: None,
+            protocol_version: None,
+            proven: None,
+        });
+        assert!(matches!(merged.discovery_method, DiscoveryMethod::MDns));
+        assert_eq!(merged.name.as_deref(), Some("ofis-nas-mdns"));
+        assert!(merged.server_id.is_some());
+
+        // Aynı adresten gelen mDNS kimliği doğrulanmış olanın yerine geçmez
+        merged.merge(DiscoveredServer {
+            address: addr,
+            name: None,
+            discovery_method: DiscoveryMethod::MDns,
+            server_id: Some("sahte".to_string()),
+            server_id_source: Some(ServerIdSource::MDns),
+            rtt: None,
+            protocol_version: None,
+            proven: None,
+        });
+        assert_eq!(merged.server_id.as_deref(), Some("0011aabb"));
+        assert_eq!(merged.server_id_source, Some(ServerIdSource::VerifiedBeacon));
+    }
+}
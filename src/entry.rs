@@ -0,0 +1,170 @@
+//! Dosya dışı girdiler
+//!
+//! Normal dosyalar `File` + `Chunk` çerçeveleriyle gider. Klasörler,
+//! sembolik bağlantılar ve hardlink grupları içeriği olmayan `Entry`
+//! çerçeveleriyle gönderilir:
+//!
+//! - Klasörler boş olsalar da sunucuda oluşturulur; izin ve zamanları en
+//!   sonda, içleri doldurulduktan sonra uygulanır
+//! - Sembolik bağlantılar politikaya göre bağlantı olarak korunur, izlenir
+//!   (hedefi gönderilir) veya atlanır. Hedef göreli olmalı ve paylaşım
+//!   kökünün dışına çıkmamalıdır; sunucu aynı denetimi yeniden yapar
+//! - Hardlink grubunun ilk üyesi normal dosya olarak, diğerleri o dosyaya
+//!   bağlantı olarak gider; bu girdiler tüm dosyalar onaylandıktan sonra
+//!   gönderilir
+
+use std::path::{Component, Path, PathBuf};
+use std::str::FromStr;
+use serde::{Deserialize, Serialize};
+use crate::metadata::FileMetadata;
+
+/// Sembolik bağlantılar için politika
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SymlinkPolicy {
+    /// Bağlantı olarak gönder
+    #[default]
+    Preserve,
+    /// Bağlantının gösterdiği dosya veya klasörü gönder
+    Follow,
+    /// Hiç gönderme
+    Skip,
+}
+
+impl FromStr for SymlinkPolicy {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "preserve" => Ok(SymlinkPolicy::Preserve),
+            "follow" => Ok(SymlinkPolicy::Follow),
+            "skip" => Ok(SymlinkPolicy::Skip),
+            other => Err(format!("Bilinmeyen symlink politikası: '{}' (preserve, follow, skip)", other)),
+        }
+    }
+}
+
+/// Girdinin türü
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum EntryKind {
+    Directory,
+    /// `target`: bağlantının kendi klasörüne göre hedefi
+    Symlink { target: PathBuf },
+    /// `target`: aynı gruptaki, daha önce gönderilmiş dosyanın kök göreli yolu
+    Hardlink { target: PathBuf },
+}
+
+/// `Entry` çerçevesinin gövdesi
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct EntryHeader {
+    pub relative_path: PathBuf,
+    #[serde(flatten)]
+    pub kind: EntryKind,
+    #[serde(default)]
+    pub metadata: FileMetadata,
+}
+
+/// Kök göreli yolun yalnızca normal bileşenlerden oluştuğunu doğrular
+pub fn is_safe_relative_path(path: &Path) -> bool {
+    path.components().next().is_some()
+        && path.components().all(|c| matches!(c, Component::Normal(_)))
+}
+
+/// `link` konumundaki (kök göreli) bağlantının `target` hedefinin kökte
+/// kaldığını doğrular. Hedef göreli olmalı; `..` bileşenleri yalnızca başta
+/// bulunabilir ve bağlantının klasör derinliğini aşamaz. Böylece hedef,
+/// yolun ortasındaki başka bir bağlantının üst klasörüne de çıkamaz.
+pub fn is_safe_symlink_target(link: &Path, target: &Path) -> bool {
+    let depth = link.parent().map_or(0, |parent| parent.components().count());
+    if target.as_os_str().is_empty() {
+        return false;
+    }
+    let mut ups = 0;
+    let mut descended = false;
+    for component in target.components() {
+        match component {
+            Component::ParentDir if !descended => ups += 1,
+            Component::Normal(_) => descended = true,
+            Component::CurDir => {}
+            _ => return false,
+        }
+    }
+    ups <= depth
+}
+
+/// Kökteki `target` dosyasını gösteren, `link` konumuna göre göreli yol
+pub fn relative_link_target(link: &Path, target: &Path) -> PathBuf {
+    let base: Vec<Component> = link.parent().map_or_else(Vec::new, |p| p.components().collect());
+    let target_components: Vec<Component> = target.components().collect();
+    let common = base.iter().zip(&target_components).take_while(|(a, b)| a == b).count();
+    let mut relative = PathBuf::new();
+    for _ in common..base.len() {
+        relative.push("..");
+    }
+    for component in &target_components[common..] {
+        relative.push(component);
+    }
+    if relative.as_os_str().is_empty() {
+        relative.push(".");
+    }
+    relative
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_symlink_target_safety() {
+        let ok = |link: &str, target: &str| is_safe_symlink_target(Path::new(link), Path::new(target));
+
+        assert!(ok("a", "b"));
+        assert!(ok("alt/a", "../b"));
+        assert!(ok("alt/ic/a", "../../lib/b.so"));
+        assert!(ok("alt/a", "./b"));
+        assert!(ok("alt/a", ".."));
+        assert!(ok("a", "."));
+
+        // Kökün dışına çıkan, mutlak veya ortasında `..` olan hedefler
+        assert!(!ok("a", "../b"));
+        assert!(!ok("alt/a", "../../b"));
+        assert!(!ok("a", "/etc/passwd"));
+        assert!(!ok("alt/a", "x/../../.."));
+        assert!(!ok("alt/a", "baglanti/.."));
+        assert!(!ok("a", ""));
+    }
+
+    #[test]
+    fn test_relative_link_target() {
+        let rel = |link: &str, target: &str| relative_link_target(Path::new(link), Path::new(target));
+        assert_eq!(rel("a", "b"), PathBuf::from("b"));
+        assert_eq!(rel("bin/app", "lib/app.so"), PathBuf::from("../lib/app.so"));
+        assert_eq!(rel("x/y/z", "x/q"), PathBuf::from("../q"));
+        assert_eq!(rel("x/y/z", "x/y"), PathBuf::from("."));
+        assert_eq!(rel("x/kok", ""), PathBuf::from(".."));
+        assert!(is_safe_symlink_target(Path::new("bin/app"), &rel("bin/app", "lib/app.so")));
+    }
+
+    #[test]
+    fn test_entry_header_json() {
+        let entry = EntryHeader {
+            relative_path: PathBuf::from("bin/python"),
+            kind: EntryKind::Symlink { target: PathBuf::from("python3") },
+            metadata: FileMetadata::default(),
+        };
+        let json = serde_json::to_string(&entry).unwrap();
+        assert!(json.contains(r#""type":"symlink""#));
+        assert_eq!(serde_json::from_str::<EntryHeader>(&json).unwrap(), entry);
+
+        let dir: EntryHeader = serde_json::from_str(r#"{"relative_path":"bos","type":"directory"}"#).unwrap();
+        assert_eq!(dir.kind, EntryKind::Directory);
+    }
+
+    #[test]
+    fn test_parse_symlink_policy() {
+        assert_eq!("preserve".parse::<SymlinkPolicy>().unwrap(), SymlinkPolicy::Preserve);
+        assert_eq!("FOLLOW".parse::<SymlinkPolicy>().unwrap(), SymlinkPolicy::Follow);
+        assert_eq!("skip".parse::<SymlinkPolicy>().unwrap(), SymlinkPolicy::Skip);
+        assert!("copy".parse::<SymlinkPolicy>().is_err());
+    }
+}
@@ -0,0 +1,190 @@
+use std::fmt;
+use std::io;
+
+/// Kütüphanenin hata türü. Sunucunun bir dosyayı veya oturumu neden
+/// reddettiği tel üzerinde [`ErrorCode`] ile taşınır; istemci aynı türü
+/// yeniden kurar.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// Anahtar, el sıkışma kanıtı veya TLS kimliği doğrulanamadı
+    Auth(String),
+    /// Karşı taraf protokole uymadı: uyumsuz sürüm, beklenmeyen veya
+    /// bozuk çerçeve, yanlış moddaki sunucuya istek
+    Protocol(String),
+    /// İçerik doğrulanamadı: hash uyuşmazlığı, çözülemeyen chunk veya blob
+    Integrity(String),
+    /// Dosya sistemi, depolama veya yerel yapılandırma hatası; başka bir
+    /// türe uymayan hatalar da buraya düşer
+    Io(String),
+    /// Bağlantı kurulamadı, koptu, zaman aşımına uğradı veya sunucu kapanıyor
+    Network(String),
+    /// İşleme izin verilmiyor: kökün dışına çıkan yol, sunucu ayarı veya
+    /// dosya sistemi izni
+    Permission(String),
+}
+
+/// Tel üzerindeki hata kodu
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorCode {
+    Auth = 1,
+    Protocol = 2,
+    Integrity = 3,
+    Io = 4,
+    Network = 5,
+    Permission = 6,
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+impl ErrorCode {
+    pub fn from_u8(code: u8) -> Option<Self> {
+        Some(match code {
+            1 => ErrorCode::Auth,
+            2 => ErrorCode::Protocol,
+            3 => ErrorCode::Integrity,
+            4 => ErrorCode::Io,
+            5 => ErrorCode::Network,
+            6 => ErrorCode::Permission,
+            _ => return None,
+        })
+    }
+
+    /// Makineler için sabit, dilden bağımsız ad
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ErrorCode::Auth => "auth",
+            ErrorCode::Protocol => "protocol",
+            ErrorCode::Integrity => "integrity",
+            ErrorCode::Io => "io",
+            ErrorCode::Network => "network",
+            ErrorCode::Permission => "permission",
+        }
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Error {
+    /// Tel üzerinden gelen kod ve mesajdan hatayı kurar
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        let message = message.into();
+        match code {
+            ErrorCode::Auth => Error::Auth(message),
+            ErrorCode::Protocol => Error::Protocol(message),
+            ErrorCode::Integrity => Error::Integrity(message),
+            ErrorCode::Io => Error::Io(message),
+            ErrorCode::Network => Error::Network(message),
+            ErrorCode::Permission => Error::Permission(message),
+        }
+    }
+
+    /// G/Ç hatasını türüne göre sınıflandırır; mesaj `context: hata` olur
+    pub fn io(context: &str, error: &io::Error) -> Self {
+        Error::new(io_code(error.kind()), format!("{}: {}", context, error))
+    }
+
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Error::Auth(_) => ErrorCode::Auth,
+            Error::Protocol(_) => ErrorCode::Protocol,
+            Error::Integrity(_) => ErrorCode::Integrity,
+            Error::Io(_) => ErrorCode::Io,
+            Error::Network(_) => ErrorCode::Network,
+            Error::Permission(_) => ErrorCode::Permission,
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            Error::Auth(message)
+            | Error::Protocol(message)
+            | Error::Integrity(message)
+            | Error::Io(message)
+            | Error::Network(message)
+            | Error::Permission(message) => message,
+        }
+    }
+
+    /// Aynı türde, mesajının önüne bağlam eklenmiş hata
+    pub fn context(&self, context: &str) -> Self {
+        Error::new(self.code(), format!("{}: {}", context, self.message()))
+    }
+}
+
+fn io_code(kind: io::ErrorKind) -> ErrorCode {
+    use io::ErrorKind::*;
+    match kind {
+        PermissionDenied | ReadOnlyFilesystem => ErrorCode::Permission,
+        ConnectionRefused | ConnectionReset | ConnectionAborted | NotConnected | BrokenPipe | TimedOut
+        | AddrInUse | AddrNotAvailable | UnexpectedEof | HostUnreachable | NetworkUnreachable | NetworkDown => ErrorCode::Network,
+        InvalidData => ErrorCode::Protocol,
+        _ => ErrorCode::Io,
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.message())
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Self {
+        Error::new(io_code(error.kind()), error.to_string())
+    }
+}
+
+/// İç katmanların `anyhow` hataları: zincirde bir [`Error`] varsa türü, yoksa
+/// G/Ç hatasının türü korunur, zaman aşımları ağ hatasıdır; mesaj tüm
+/// bağlamı içerir
+impl From<anyhow::Error> for Error {
+    fn from(error: anyhow::Error) -> Self {
+        let code = error.chain()
+            .find_map(|cause| cause.downcast_ref::<Error>().map(Error::code))
+            .or_else(|| error.chain().find_map(|cause| cause.downcast_ref::<io::Error>().map(|e| io_code(e.kind()))))
+            .or_else(|| error.chain().any(|cause| cause.is::<tokio::time::error::Elapsed>()).then_some(ErrorCode::Network))
+            .unwrap_or(ErrorCode::Io);
+        Error::new(code, format!("{:#}", error))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    #[test]
+    fn test_codes_roundtrip() {
+        for code in 1..=6 {
+            let code = ErrorCode::from_u8(code).unwrap();
+            let error = Error::new(code, "mesaj");
+            assert_eq!((error.code(), error.message()), (code, "mesaj"));
+            assert_eq!(ErrorCode::from_u8(code as u8), Some(code));
+        }
+        assert_eq!(ErrorCode::from_u8(0), None);
+        assert_eq!(ErrorCode::Integrity.to_string(), "integrity");
+    }
+
+    #[test]
+    fn test_classification() {
+        let denied = io::Error::from(io::ErrorKind::PermissionDenied);
+        assert_eq!(Error::io("Dosya oluşturulamadı", &denied).code(), ErrorCode::Permission);
+        assert_eq!(Error::from(io::Error::from(io::ErrorKind::ConnectionReset)).code(), ErrorCode::Network);
+
+        // Bağlam katmanlarının altındaki tür korunur
+        let wrapped = Err::<(), _>(Error::Auth("Kimlik doğrulaması başarısız".to_string()))
+            .context("Sunucu bağlantıyı reddetti")
+            .unwrap_err();
+        let error = Error::from(wrapped);
+        assert_eq!(error, Error::Auth("Sunucu bağlantıyı reddetti: Kimlik doğrulaması başarısız".to_string()));
+        let timeout = Err::<(), _>(io::Error::from(io::ErrorKind::TimedOut)).context("Yanıt yok").unwrap_err();
+        assert_eq!(Error::from(timeout).code(), ErrorCode::Network);
+        assert_eq!(Error::from(anyhow::anyhow!("bir klasör değil")).code(), ErrorCode::Io);
+    }
+}
@@ -0,0 +1,133 @@
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use crate::transport::Peer;
+
+/// İstemci veya sunucuda bir oturum sırasında olan bir şey. Sunucuda aynı
+/// anda birden fazla oturum olabildiği için her olay oturumun karşı
+/// tarafını taşır; istemcide bu, bağlanılan sunucudur.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Event {
+    pub peer: Peer,
+    pub kind: EventKind,
+}
+
+/// Olay türleri. Yollar paylaşımın köküne göredir.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EventKind {
+    /// Oturum kuruldu. İstemcide gönderilecek dosya sayısı ve toplam
+    /// boyutu; sunucuda bunlar henüz bilinmediği için 0
+    SessionStarted { files: usize, bytes: u64 },
+    /// Dosya aktarıma alındı
+    FileStarted { path: PathBuf, size: u64 },
+    /// Dosyanın bir bölgesi gönderildi (istemci) veya yazıldı (sunucu);
+    /// seyrek dosyalarda delik olarak bildirilen bölgeler de sayılır
+    ChunkSent { path: PathBuf, offset: u64, bytes: u64 },
+    /// Dosya veya girdi sunucuda doğrulanıp yerine yerleştirildi; girdiler
+    /// için `size` 0
+    FileVerified { path: PathBuf, size: u64 },
+    /// Dosya gönderilmedi veya alınmadı, ama bu bir hata değil (örn. özel
+    /// dosya, kapanan sunucu)
+    FileSkipped { path: PathBuf, reason: String },
+    /// Dosya reddedildi (`path` dolu) veya oturum bir hatayla bitti
+    Error { path: Option<PathBuf>, message: String },
+    /// Oturum bitti; doğrulanan ve reddedilen dosya sayısı ile doğrulanan
+    /// dosyaların toplam boyutu
+    SessionFinished { verified: usize, failed: usize, bytes: u64 },
+}
+
+/// Olayları alan taraf. Aktarım görevlerinden çağrıldığı için hızlı
+/// dönmeli; ağır işler bir kanal üzerinden başka bir göreve bırakılmalı.
+pub trait EventHandler: Send + Sync {
+    fn on_event(&self, event: &Event);
+}
+
+/// Alıcı kapandıysa olaylar sessizce düşer
+impl EventHandler for mpsc::UnboundedSender<Event> {
+    fn on_event(&self, event: &Event) {
+        let _ = self.send(event.clone());
+    }
+}
+
+impl EventHandler for std::sync::mpsc::Sender<Event> {
+    fn on_event(&self, event: &Event) {
+        let _ = self.send(event.clone());
+    }
+}
+
+/// Kapanışı olay işleyicisine çevirir
+struct FnHandler<F>(F);
+
+impl<F: Fn(&Event) + Send + Sync> EventHandler for FnHandler<F> {
+    fn on_event(&self, event: &Event) {
+        (self.0)(event)
+    }
+}
+
+/// Ayarlarda taşınan olay işleyicisi tutamağı; varsayılanı olayları atar
+#[derive(Clone, Default)]
+pub struct Events(Option<Arc<dyn EventHandler>>);
+
+impl Events {
+    pub fn new(handler: impl EventHandler + 'static) -> Self {
+        Self(Some(Arc::new(handler)))
+    }
+
+    /// Olayları bir kapanışa iletir
+    pub fn from_fn(handler: impl Fn(&Event) + Send + Sync + 'static) -> Self {
+        Self::new(FnHandler(handler))
+    }
+
+    /// Olayları sınırsız bir kanala iletir
+    pub fn channel() -> (Self, mpsc::UnboundedReceiver<Event>) {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        (Self::new(sender), receiver)
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.0.is_some()
+    }
+
+    pub fn emit(&self, peer: &Peer, kind: EventKind) {
+        if let Some(handler) = &self.0 {
+            handler.on_event(&Event { peer: peer.clone(), kind });
+        }
+    }
+}
+
+impl fmt::Debug for Events {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(if self.is_enabled() { "Events(açık)" } else { "Events(kapalı)" })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[test]
+    fn test_handlers_receive_events() {
+        let peer = Peer::Pipe("test".to_string());
+        let kind = EventKind::FileSkipped { path: PathBuf::from("a"), reason: "özel dosya".to_string() };
+
+        // Varsayılan tutamak olayları atar
+        let disabled = Events::default();
+        assert!(!disabled.is_enabled());
+        disabled.emit(&peer, kind.clone());
+        assert_eq!(format!("{:?}", disabled), "Events(kapalı)");
+
+        let (events, mut receiver) = Events::channel();
+        events.clone().emit(&peer, kind.clone());
+        assert_eq!(receiver.try_recv().unwrap(), Event { peer: peer.clone(), kind: kind.clone() });
+        drop(receiver);
+        events.emit(&peer, kind.clone());
+
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let sink = seen.clone();
+        let events = Events::from_fn(move |event| sink.lock().unwrap().push(event.kind.clone()));
+        events.emit(&peer, kind.clone());
+        assert_eq!(*seen.lock().unwrap(), vec![kind]);
+    }
+}
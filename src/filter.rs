@@ -0,0 +1,312 @@
+//! Dosya seçimi
+//!
+//! Kaynak klasör gitignore söz dizimiyle süzülerek taranır:
+//!
+//! - Ağacın herhangi bir seviyesindeki `.deltasafeignore` dosyaları, o
+//!   klasör ve altı için geçerlidir
+//! - İstenirse mevcut `.gitignore` dosyaları da uygulanır (`.git` klasörleri
+//!   bu durumda hiç gönderilmez)
+//! - `--include` / `--exclude` desenleri kaynak köküne göre yorumlanır ve
+//!   dosyalardaki kuralların önüne geçer. `--include` verilirse yalnızca
+//!   eşleşen dosyalar gönderilir; `--exclude` her zaman kazanır
+//!
+//! Süzme tarama sırasında yapılır; hariç tutulan klasörlere hiç girilmez.
+//! `--include` verildiğinde yalnızca gönderilen bir girdiyi içeren klasörler
+//! oluşturulur. FIFO, soket ve aygıt gibi özel dosyalar atlanır.
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use anyhow::{Context, Result};
+use ignore::overrides::OverrideBuilder;
+use ignore::WalkBuilder;
+use crate::entry::{is_safe_symlink_target, relative_link_target, SymlinkPolicy};
+
+/// Her seviyede okunan ignore dosyasının adı
+pub const IGNORE_FILE_NAME: &str = ".deltasafeignore";
+
+/// Tarama süzgeci ayarları
+#[derive(Debug, Clone, Default)]
+pub struct FilterOptions {
+    /// Yalnızca bu desenlerle eşleşen dosyalar gönderilir
+    pub include: Vec<String>,
+    /// Bu desenlerle eşleşen dosya ve klasörler gönderilmez
+    pub exclude: Vec<String>,
+    /// Mevcut `.gitignore` dosyalarını da uygula
+    pub gitignore: bool,
+    /// Sembolik bağlantıların nasıl gönderileceği
+    pub symlinks: SymlinkPolicy,
+}
+
+/// Taramada bulunan girdinin türü
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SourceKind {
+    File,
+    Directory,
+    /// Hedefi bağlantının klasörüne göre; kökün dışına çıkmadığı doğrulanmıştır
+    Symlink(PathBuf),
+}
+
+/// Taramada bulunan girdi
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceEntry {
+    pub path: PathBuf,
+    pub kind: SourceKind,
+}
+
+/// Kaynak klasörü süzerek tarar ve gönderilecek dosyaları döndürür
+pub fn collect_files(root: &Path, options: &FilterOptions) -> Result<Vec<PathBuf>> {
+    Ok(collect_entries(root, options)?
+        .into_iter()
+        .filter(|entry| entry.kind == SourceKind::File)
+        .map(|entry| entry.path)
+        .collect())
+}
+
+/// Kaynak klasörü süzerek tarar ve gönderilecek dosya, klasör ve sembolik
+/// bağlantıları döndürür. Kök klasörün kendisi listede yer almaz.
+pub fn collect_entries(root: &Path, options: &FilterOptions) -> Result<Vec<SourceEntry>> {
+    scan_entries(root, options, &mut Vec::new())
+}
+
+/// [`collect_entries`] ile aynı; desenlerle hariç tutulmadığı halde
+/// gönderilemeyen girdileri nedenleriyle `skipped` listesine ekler.
+pub fn scan_entries(root: &Path, options: &FilterOptions, skipped: &mut Vec<(PathBuf, String)>) -> Result<Vec<SourceEntry>> {
+    // Sonradan eklenen desen önceliklidir; hariç tutma dahil etmeyi ezer
+    let mut overrides = OverrideBuilder::new(root);
+    for pattern in &options.include {
+        overrides.add(pattern)
+            .with_context(|| format!("Geçersiz --include deseni: '{}'", pattern))?;
+    }
+    for pattern in &options.exclude {
+        overrides.add(&format!("!{}", pattern))
+            .with_context(|| format!("Geçersiz --exclude deseni: '{}'", pattern))?;
+    }
+    let overrides = overrides.build()
+        .context("Dahil etme/hariç tutma desenleri derlenemedi")?;
+
+    let gitignore = options.gitignore;
+    let walker = WalkBuilder::new(root)
+        .standard_filters(false)
+        .follow_links(options.symlinks == SymlinkPolicy::Follow)
+        .git_ignore(gitignore)
+        .require_git(false)
+        .parents(false)
+        .add_custom_ignore_filename(IGNORE_FILE_NAME)
+        .overrides(overrides)
+        .filter_entry(move |entry| {
+            !(gitignore && entry.file_name() == ".git" && entry.file_type().is_some_and(|t| t.is_dir()))
+        })
+        .sort_by_file_name(|a, b| a.cmp(b))
+        .build();
+
+    // Mutlak bağlantı hedefleri köke göre karşılaştırılır
+    let absolute_root = fs::canonicalize(root).unwrap_or_else(|_| root.to_path_buf());
+    let mut entries = Vec::new();
+    for entry in walker {
+        let entry = match entry {
+            Ok(entry) if entry.depth() > 0 => entry,
+            Ok(_) => continue,
+            Err(e) => {
+                log::warn!("[⚠️] Tarama uyarısı: {}", e);
+                continue;
+            }
+        };
+        let Some(file_type) = entry.file_type() else { continue };
+        // `follow` politikasında bağlantılar hedefin türüyle gelir
+        let kind = if file_type.is_symlink() {
+            if options.symlinks == SymlinkPolicy::Skip {
+                continue;
+            }
+            match link_target(root, &absolute_root, entry.path()) {
+                Some(target) => SourceKind::Symlink(target),
+                None => {
+                    log::warn!("[⚠️] Kökün dışını gösteren sembolik bağlantı atlandı: {}", entry.path().display());
+                    skipped.push((entry.into_path(), "Kökün dışını gösteren sembolik bağlantı".to_string()));
+                    continue;
+                }
+            }
+        } else if file_type.is_dir() {
+            SourceKind::Directory
+        } else if file_type.is_file() {
+            SourceKind::File
+        } else {
+            log::warn!("[⚠️] Özel dosya atlandı: {}", entry.path().display());
+            skipped.push((entry.into_path(), "Özel dosya".to_string()));
+            continue;
+        };
+        entries.push(SourceEntry { path: entry.into_path(), kind });
+    }
+
+    // Dahil etme desenleri klasörleri eşlemez; yalnızca içinde gönderilen
+    // girdi olan klasörler tutulur
+    if !options.include.is_empty() {
+        let used: HashSet<PathBuf> = entries.iter()
+            .filter(|entry| entry.kind != SourceKind::Directory)
+            .flat_map(|entry| entry.path.ancestors().skip(1).map(Path::to_path_buf).collect::<Vec<_>>())
+            .collect();
+        entries.retain(|entry| entry.kind != SourceKind::Directory || used.contains(&entry.path));
+    }
+    Ok(entries)
+}
+
+/// Sembolik bağlantının hedefini okur. Kökün içini gösteren mutlak hedefler
+/// göreliye çevrilir; kökün dışına çıkan hedefler için `None` döner.
+fn link_target(root: &Path, absolute_root: &Path, path: &Path) -> Option<PathBuf> {
+    let link = path.strip_prefix(root).ok()?;
+    let target = fs::read_link(path).ok()?;
+    let target = if target.is_absolute() {
+        let inside = target.strip_prefix(absolute_root)
+            .or_else(|_| target.strip_prefix(root))
+            .ok()?;
+        relative_link_target(link, inside)
+    } else {
+        target
+    };
+    is_safe_symlink_target(link, &target).then_some(target)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn tree(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("deltasafe-filter-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        for (path, content) in files {
+            let path = root.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+        root
+    }
+
+    fn relative(root: &Path, options: &FilterOptions) -> Vec<String> {
+        collect_files(root, options).unwrap()
+            .iter()
+            .map(|path| path.strip_prefix(root).unwrap().to_string_lossy().replace('\\', "/"))
+            .collect()
+    }
+
+    #[test]
+    fn test_ignore_files_at_any_level() {
+        let root = tree("levels", &[
+            (".deltasafeignore", "*.log\nbuild/\n"),
+            ("a.txt", ""),
+            ("debug.log", ""),
+            ("build/out.bin", ""),
+            ("src/main.rs", ""),
+            ("src/.deltasafeignore", "gen/\n!onemli.log\n"),
+            ("src/gen/x.rs", ""),
+            ("src/onemli.log", ""),
+            ("src/diger.log", ""),
+        ]);
+
+        assert_eq!(relative(&root, &FilterOptions::default()), vec![
+            ".deltasafeignore",
+            "a.txt",
+            "src/.deltasafeignore",
+            "src/main.rs",
+            "src/onemli.log",
+        ]);
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_gitignore_is_optional() {
+        let root = tree("git", &[
+            (".gitignore", "target/\n"),
+            (".git/HEAD", "ref"),
+            ("target/debug/app", ""),
+            ("node_modules/pkg/index.js", ""),
+            ("src/lib.rs", ""),
+        ]);
+
+        // Varsayılan olarak .gitignore dikkate alınmaz
+        let all = relative(&root, &FilterOptions::default());
+        assert!(all.contains(&"target/debug/app".to_string()));
+        assert!(all.contains(&".git/HEAD".to_string()));
+
+        let options = FilterOptions { gitignore: true, exclude: vec!["node_modules/".to_string()], ..FilterOptions::default() };
+        assert_eq!(relative(&root, &options), vec![".gitignore", "src/lib.rs"]);
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_include_and_exclude_patterns() {
+        let root = tree("patterns", &[
+            ("a.rs", ""),
+            ("b.txt", ""),
+            ("src/c.rs", ""),
+            ("src/vendor/d.rs", ""),
+            (".deltasafeignore", "a.rs\n"),
+        ]);
+
+        // --include ignore dosyalarını ezer, --exclude her şeyi
+        let options = FilterOptions {
+            include: vec!["*.rs".to_string()],
+            exclude: vec!["vendor/".to_string()],
+            ..FilterOptions::default()
+        };
+        assert_eq!(relative(&root, &options), vec!["a.rs", "src/c.rs"]);
+
+        // Köke bağlı desen yalnızca kökteki yolu eşler
+        let options = FilterOptions { exclude: vec!["/b.txt".to_string(), "src/*.rs".to_string()], ..FilterOptions::default() };
+        assert_eq!(relative(&root, &options), vec![".deltasafeignore", "src/vendor/d.rs"]);
+
+        let options = FilterOptions { include: vec!["[".to_string()], ..FilterOptions::default() };
+        assert!(collect_files(&root, &options).is_err());
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_entries_and_symlink_policies() {
+        use std::os::unix::fs::symlink;
+        let root = tree("entries", &[("lib/app.so", "elf"), ("docs/a.md", "")]);
+        fs::create_dir_all(root.join("bos")).unwrap();
+        symlink("../lib/app.so", root.join("docs/app")).unwrap();
+        symlink(root.join("lib/app.so"), root.join("mutlak")).unwrap();
+        symlink("lib", root.join("kutuphane")).unwrap();
+        symlink("/etc/passwd", root.join("kacak")).unwrap();
+        symlink("../../disari", root.join("docs/kacak")).unwrap();
+
+        let entries = |symlinks| {
+            let options = FilterOptions { symlinks, ..FilterOptions::default() };
+            collect_entries(&root, &options).unwrap()
+                .into_iter()
+                .map(|entry| (entry.path.strip_prefix(&root).unwrap().to_string_lossy().into_owned(), entry.kind))
+                .collect::<Vec<_>>()
+        };
+        let link = |target: &str| SourceKind::Symlink(PathBuf::from(target));
+
+        // Kaçan bağlantılar atlanır, mutlak hedef göreliye çevrilir
+        assert_eq!(entries(SymlinkPolicy::Preserve), vec![
+            ("bos".to_string(), SourceKind::Directory),
+            ("docs".to_string(), SourceKind::Directory),
+            ("docs/a.md".to_string(), SourceKind::File),
+            ("docs/app".to_string(), link("../lib/app.so")),
+            ("kutuphane".to_string(), link("lib")),
+            ("lib".to_string(), SourceKind::Directory),
+            ("lib/app.so".to_string(), SourceKind::File),
+            ("mutlak".to_string(), link("lib/app.so")),
+        ]);
+
+        let skipped = entries(SymlinkPolicy::Skip);
+        assert!(skipped.iter().all(|(_, kind)| !matches!(kind, SourceKind::Symlink(_))));
+        assert_eq!(skipped.len(), 5);
+
+        // İzlenen bağlantılar hedefin türüyle, klasörlere girilerek gelir
+        let followed = entries(SymlinkPolicy::Follow);
+        assert!(followed.contains(&("kutuphane/app.so".to_string(), SourceKind::File)));
+        assert!(followed.contains(&("kacak".to_string(), SourceKind::File)));
+        assert!(followed.iter().all(|(_, kind)| !matches!(kind, SourceKind::Symlink(_))));
+
+        // Dahil etme desenleri boş kalan klasörleri göndermez
+        let options = FilterOptions { include: vec!["*.md".to_string()], ..FilterOptions::default() };
+        let included: Vec<_> = collect_entries(&root, &options).unwrap().into_iter().map(|entry| entry.path).collect();
+        assert_eq!(included, vec![root.join("docs"), root.join("docs/a.md")]);
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
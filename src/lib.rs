@@ -14,7 +14,7 @@
 //! ## Kullanım
 //! 
 //! ```rust,no_run
-//! use deltasafe::sync::{start_sync, calculate_file_hash};
+//! use deltasafe::{calculate_file_hash, FileOutcome, SyncClient};
 //! use std::path::Path;
 //! 
 //! // Dosya hash'i hesaplama
@@ -22,16 +22,59 @@
 //! println!("Dosya hash'i: {}", hash);
 //! 
 //! // Senkronizasyon başlatma
+//! # async fn run() -> deltasafe::Result<()> {
 //! let key = [0u8; 32]; // 32 baytlık AES anahtarı
-//! start_sync("./source_folder", "192.168.1.100:12345", &key);
+//! let client = SyncClient::builder()
+//!     .source("./source_folder")
+//!     .target("192.168.1.100:12345")
+//!     .key(key)
+//!     .build()?;
+//! let report = client.sync().await?;
+//! for file in &report.files {
+//!     if let FileOutcome::Rejected(reason) = &file.outcome {
+//!         println!("{} reddedildi: {}", file.path.display(), reason);
+//!     }
+//! }
+//! # Ok(())
+//! # }
 //! ```
+//! 
+//! Kütüphane ekrana yazmaz; durum mesajları [`log`] ile kaydedilir.
+//! Hatalar [`Error`] türündedir; sunucunun ret nedeni tel üzerinden
+//! [`ErrorCode`] ile gelir.
 
 pub mod cli;
+pub mod error;
 pub mod sync;
 pub mod server;
 pub mod crypto;
 pub mod discovery;
+pub mod beacon;
+pub mod net;
+pub mod transport;
+pub mod events;
+pub mod quic;
+pub mod tls;
+pub mod peers;
+pub mod protocol;
+pub mod chunk;
+pub mod compress;
+pub mod bandwidth;
+pub mod filter;
+pub mod metadata;
+pub mod entry;
+pub mod xattrs;
+pub mod sparse;
+pub mod versions;
+pub mod restore;
+pub mod snapshot;
+pub mod vault;
+pub mod storage;
 
 pub use cli::{Cli, Commands};
-pub use sync::{start_sync, calculate_file_hash, FileHeader, CHUNK_SIZE};
-pub use server::start_server;
\ No newline at end of file
+pub use error::{Error, ErrorCode, Result};
+#[allow(deprecated)]
+pub use sync::{start_sync, sync_files, calculate_file_hash, FileHeader, FileOutcome, FileReport, SyncClient, SyncClientBuilder, SyncOptions, SyncReport, CHUNK_SIZE};
+#[allow(deprecated)]
+pub use server::{start_server, Server, ServerBuilder, ServerOptions};
+pub use events::{Event, EventHandler, EventKind, Events};
\ No newline at end of file
@@ -1,50 +1,169 @@
-mod cli;
-mod sync;
-mod server;
-mod crypto;
-mod discovery;
-
-use cli::{Cli, Commands};
+use deltasafe::cli::{Cli, ClientTlsArgs, Commands, SnapshotAction};
 use clap::Parser;
-use sync::start_sync;
-use server::start_server;
-use crypto::{derive_key_from_password, parse_hex_key, validate_password_strength, generate_random_hex_key};
-use discovery::{discover_servers, select_server_interactive, select_best_server_auto};
+use deltasafe::sync::{FileOutcome, SyncClient, SyncOptions};
+use deltasafe::filter::FilterOptions;
+use deltasafe::server::{serve_connection, shutdown_signal, Server, ServerOptions};
+use deltasafe::transport::{Connection, Stdio};
+use deltasafe::crypto::{access_key, derive_key_from_password, parse_hex_key, validate_password_strength, generate_random_hex_key};
+use deltasafe::discovery::{discover_servers, discover_servers_quietly, select_best_server_auto, DiscoveredServer, ServerIdSource, SelectionCriteria};
+use deltasafe::net::{self, DEFAULT_PORT, DUAL_STACK_ADDR};
+use deltasafe::peers::{AddressBook, PeerEntry};
+use deltasafe::restore::{list_versions, restore_version};
+use deltasafe::versions::Retention;
+use deltasafe::snapshot;
+use deltasafe::vault;
+use deltasafe::storage::{S3Storage, Storage};
+use deltasafe::tls::{ClientTls, ServerTls};
+use deltasafe::events::{EventKind, Events};
 use anyhow::{Result, Context};
+use indicatif::{ProgressBar, ProgressStyle};
+use std::net::{IpAddr, SocketAddr};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Eski adres defteri kaydı arka planda yenilenirken kullanılan keşif süresi
+const BACKGROUND_REFRESH_TIMEOUT_SECS: u64 = 3;
+
+
+/// Kütüphanenin durum mesajlarını olduğu gibi standart çıktıya yazar
+struct ConsoleLogger;
 
+impl log::Log for ConsoleLogger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.level() <= log::Level::Info && metadata.target().starts_with("deltasafe")
+    }
+
+    fn log(&self, record: &log::Record) {
+        if self.enabled(record.metadata()) {
+            println!("{}", record.args());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: ConsoleLogger = ConsoleLogger;
 
 #[tokio::main]
 async fn main() {
     let cli = Cli::parse();
+    if log::set_logger(&LOGGER).is_ok() {
+        log::set_max_level(log::LevelFilter::Info);
+    }
 
     if let Err(e) = run_command(&cli.command).await {
-        eprintln!("[❌] Hata: {}", e);
+        eprintln!("[❌] Hata: {:#}", e);
         std::process::exit(1);
     }
 }
 
 async fn run_command(command: &Commands) -> Result<()> {
     match command {
-        Commands::Sync { source, target, auto, auto_select, key, password } => {
-            let target_address = resolve_target_address(target.as_deref(), *auto, *auto_select).await?;
-            println!("Sync başlatılıyor: {} -> {}", source, target_address);
-            
+        Commands::Sync { source, target, auto, auto_select, share, require_trusted, key, password, window, streams, chunk_size, compress, bwlimit, schedule, include, exclude, gitignore, symlinks, hardlinks, xattrs, tls } => {
             let key_bytes = resolve_key(key.as_deref(), password.as_deref())?;
-            start_sync(source, &target_address, &key_bytes);
-        },
-        Commands::Discover { timeout } => {
-            let servers = discover_servers(*timeout).await?;
-            if servers.is_empty() {
-                println!("[ℹ️] Hiç sunucu bulunamadı.");
+            let tls = client_tls(tls)?;
+            let mut book = load_address_book();
+            let criteria = SelectionCriteria {
+                known_addresses: book.synced_addresses(),
+                share_name: share.clone(),
+                require_trusted: *require_trusted,
+            };
+            let target_address = resolve_target_address(target.as_deref(), *auto, *auto_select || *require_trusted, &key_bytes, &mut book, &criteria).await?;
+            println!("Sync başlatılıyor: {} -> {}", source, target_address);
+
+            // Eski adres defteri kaydı senkronizasyon sürerken arka planda yenilenir
+            let refresh = target.as_deref()
+                .and_then(|name| book.lookup(name))
+                .filter(|entry| entry.is_stale())
+                .map(|_| {
+                    println!("[🔄] Adres defteri kaydı eski, arka planda yenileniyor...");
+                    tokio::spawn(async move {
+                        discover_servers_quietly(BACKGROUND_REFRESH_TIMEOUT_SECS, Some(&key_bytes)).await
+                    })
+                });
+
+            let progress = ProgressBar::new(0);
+            progress.set_style(
+                ProgressStyle::default_bar()
+                    .template("[{elapsed_precise}] {bar:40.cyan/blue} {pos:>7}/{len:7} {msg}")
+                    .unwrap()
+                    .progress_chars("##-")
+            );
+            let options = SyncOptions {
+                window: *window,
+                streams: *streams,
+                chunk_size: *chunk_size,
+                compression: *compress,
+                bwlimit: *bwlimit,
+                schedule: schedule.clone(),
+                filter: FilterOptions {
+                    include: include.clone(),
+                    exclude: exclude.clone(),
+                    gitignore: *gitignore,
+                    symlinks: *symlinks,
+                },
+                hardlinks: *hardlinks,
+                xattrs: xattrs.clone(),
+                tls,
+                events: progress_events(&progress),
+            };
+            let client = SyncClient::builder()
+                .source(source)
+                .target(target_address.as_str())
+                .key(key_bytes)
+                .options(options)
+                .build()?;
+            let report = client.sync().await?;
+            if report.is_success() {
+                progress.finish_with_message("Tüm dosyalar başarıyla gönderildi!");
             } else {
-                println!("[✅] Bulunan sunucular:");
-                for (i, server) in servers.iter().enumerate() {
-                    println!("  {}. {} ({:?})", i + 1, server.address, server.discovery_method);
-                    if let Some(name) = &server.name {
-                        println!("     Servis adı: {}", name);
+                progress.abandon_with_message("Bazı dosyalar gönderilemedi!");
+                for file in report.rejected() {
+                    if let FileOutcome::Rejected(message) = &file.outcome {
+                        println!("[❌] {}: {}", Path::new(source).join(&file.path).display(), message);
                     }
                 }
             }
+            report.ensure_success()?;
+            println!("[🚀] Senkronizasyon tamamlandı.");
+
+            let mut book_changed = *auto;
+            if let Ok(addrs) = net::resolve_target(&target_address) {
+                book_changed |= book.record_sync(addrs[0]);
+            }
+            if let Some(refresh) = refresh
+                && let Ok(Ok(servers)) = refresh.await
+            {
+                book.record_discovered(&servers);
+                book_changed = true;
+            }
+            if book_changed {
+                save_address_book(&book);
+            }
+        },
+        Commands::Discover { timeout, json, refresh } => {
+            let mut book = AddressBook::load_default()?;
+
+            if *refresh || book.peers.is_empty() {
+                let servers = if *json {
+                    discover_servers_quietly(*timeout, None).await?
+                } else {
+                    discover_servers(*timeout, None).await?
+                };
+                book.record_discovered(&servers);
+                book.save()?;
+            } else if !*json {
+                println!("[💡] Kayıtlı sunucular gösteriliyor, yeniden taramak için --refresh kullanın");
+            }
+
+            if *json {
+                let output = serde_json::to_string_pretty(&book.peers)
+                    .context("Adres defteri JSON'a çevrilemedi")?;
+                println!("{}", output);
+            } else {
+                print_address_book(&book.peers);
+            }
         },
         Commands::Connect { ip } => {
             println!("Peer cihaza bağlanılıyor: {}", ip);
@@ -54,17 +173,197 @@ async fn run_command(command: &Commands) -> Result<()> {
             println!("Klasör izleniyor: {}", folder);
             println!("⚠️ Bu özellik henüz geliştirilme aşamasındadır.");
         },
-        Commands::Server { address, key, password } => {
-            let server_address = resolve_server_address(address.as_deref())?;
-            println!("Sunucu başlatılıyor: {}", server_address);
-            
+        Commands::Server { address, stdio, name, key, password, max_sessions, timeout, max_streams, max_chunk_size, no_compression, client_bwlimit, preserve, xattrs, keep_versions, keep_daily, vault, access_key, s3, tls_cert, tls_key, tls_client_ca } => {
+            // Protokol stdout'u kullanacağı için yönlendirme her çıktıdan önce yapılır
+            let stdio = match stdio {
+                true => Some(stdio_transport()?),
+                false => None,
+            };
+
+            let key_bytes = match access_key {
+                Some(access) => parse_hex_key(access).context("Geçersiz erişim anahtarı")?,
+                None => resolve_key(key.as_deref(), password.as_deref())?,
+            };
+            if *vault && access_key.is_none() {
+                println!("[⚠️] Kasa modu tam anahtarla çalışıyor; sunucunun içeriği çözememesi için --access-key kullanın.");
+            }
+            let storage: Option<Arc<dyn Storage>> = match s3 {
+                Some(url) => Some(Arc::new(S3Storage::from_env(url).context("S3 depolaması ayarlanamadı")?)),
+                None => None,
+            };
+            let tls = match (tls_cert, tls_key) {
+                (Some(cert), Some(key)) => Some(ServerTls::new(Path::new(cert), Path::new(key), tls_client_ca.as_deref().map(Path::new))?),
+                _ => None,
+            };
+            let options = ServerOptions {
+                name: name.clone().unwrap_or_else(net::local_hostname),
+                storage,
+                max_sessions: *max_sessions,
+                io_timeout: Duration::from_secs(*timeout),
+                max_streams: *max_streams,
+                max_chunk_size: *max_chunk_size,
+                compression: !*no_compression,
+                client_bwlimit: *client_bwlimit,
+                preserve: *preserve,
+                xattrs: xattrs.clone(),
+                retention: Retention { keep_last: *keep_versions, keep_daily: *keep_daily },
+                vault: *vault,
+                access_only: access_key.is_some(),
+                tls,
+                ..ServerOptions::default()
+            };
+            match stdio {
+                Some(transport) => serve_connection(Connection::new(transport), &key_bytes, &options, shutdown_signal()).await?,
+                None => {
+                    let server_address = resolve_server_address(address.as_deref())?;
+                    println!("Sunucu başlatılıyor: {}", server_address);
+                    Server::builder()
+                        .address(server_address)
+                        .key(key_bytes)
+                        .options(options)
+                        .announce(true)
+                        .bind()
+                        .await?
+                        .run(shutdown_signal())
+                        .await?;
+                }
+            }
+        },
+        Commands::Versions { target, path, key, password, tls } => {
             let key_bytes = resolve_key(key.as_deref(), password.as_deref())?;
-            start_server(&server_address, &key_bytes);
+            let tls = client_tls(tls)?;
+            let target_address = resolve_target_address(Some(target), false, false, &key_bytes, &mut load_address_book(), &SelectionCriteria::default()).await?;
+            let versions = list_versions(&target_address, &key_bytes, tls.as_ref(), Path::new(path)).await?;
+            if versions.is_empty() {
+                println!("[ℹ️] '{}' için saklanan sürüm yok.", path);
+            } else {
+                println!("[🗂️] '{}' için {} sürüm:", path, versions.len());
+                for version in &versions {
+                    let time = version.time()
+                        .map(|time| time.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M:%S").to_string())
+                        .unwrap_or_default();
+                    println!("  {}  {}  {} bayt", version.id, time, version.size);
+                }
+            }
+        },
+        Commands::Push { source, target, key, password, tls } => {
+            let key_bytes = resolve_key(key.as_deref(), password.as_deref())?;
+            let tls = client_tls(tls)?;
+            let target_address = resolve_target_address(Some(target), false, false, &key_bytes, &mut load_address_book(), &SelectionCriteria::default()).await?;
+            println!("[🔐] '{}' şifrelenip {} kasasına gönderiliyor...", source, target_address);
+            let pushed = vault::push(Path::new(source), &target_address, &key_bytes, tls.as_ref()).await?;
+            println!("[✅] {} indeksi kaydedildi: {} dosya, {} bayt ({} yeni blob)", pushed.index, pushed.files, pushed.bytes, pushed.uploaded);
+        },
+        Commands::Pull { target, output, index, list, key, password, tls } => {
+            let key_bytes = resolve_key(key.as_deref(), password.as_deref())?;
+            let tls = client_tls(tls)?;
+            let target_address = resolve_target_address(Some(target), false, false, &key_bytes, &mut load_address_book(), &SelectionCriteria::default()).await?;
+            if *list {
+                let indexes = vault::indexes(&target_address, &key_bytes, tls.as_ref()).await?;
+                if indexes.is_empty() {
+                    println!("[ℹ️] Kasada kayıtlı indeks yok.");
+                } else {
+                    println!("[🔐] {} indeks:", indexes.len());
+                    for id in &indexes {
+                        println!("  {}", id);
+                    }
+                }
+                return Ok(());
+            }
+            println!("[🔓] Kasadan {} indeksi indiriliyor -> {}", index.as_deref().unwrap_or("en yeni"), output);
+            let pulled = vault::pull(&target_address, &key_bytes, tls.as_ref(), index.as_deref(), Path::new(output)).await?;
+            println!("[✅] {} indeksi geri kuruldu: {} dosya, {} bayt, hash'ler doğrulandı.", pulled.index, pulled.files, pulled.bytes);
+        },
+        Commands::AccessKey { key, password } => {
+            let key_bytes = resolve_key(key.as_deref(), password.as_deref())?;
+            println!("{}", hex::encode(access_key(&key_bytes)));
+        },
+        Commands::Snapshot { action } => run_snapshot(action)?,
+        Commands::Restore { target, path, version, output, key, password, tls } => {
+            let key_bytes = resolve_key(key.as_deref(), password.as_deref())?;
+            let tls = client_tls(tls)?;
+            let target_address = resolve_target_address(Some(target), false, false, &key_bytes, &mut load_address_book(), &SelectionCriteria::default()).await?;
+            let output = match output {
+                Some(output) => PathBuf::from(output),
+                None => PathBuf::from(Path::new(path).file_name().context("Dosya yolu bir dosya adı içermiyor")?),
+            };
+            println!("[⏪] '{}' dosyasının {} sürümü geri yükleniyor -> {}", path, version, output.display());
+            let size = restore_version(&target_address, &key_bytes, tls.as_ref(), Path::new(path), version, &output).await?;
+            println!("[✅] {} bayt geri yüklendi ve hash doğrulandı.", size);
         },
     }
     Ok(())
 }
 
+/// İstemci TLS ayarlarını kurar; `--ca` ve `--pin` verilmezse bağlantı düz kalır
+fn client_tls(args: &ClientTlsArgs) -> Result<Option<ClientTls>> {
+    if args.ca.is_none() && args.pin.is_none() {
+        if args.client_cert.is_some() || args.tls_name.is_some() {
+            anyhow::bail!("TLS seçenekleri için sunucu sertifikası --ca veya --pin ile doğrulanmalı");
+        }
+        return Ok(None);
+    }
+    let identity = args.client_cert.as_deref()
+        .zip(args.client_key.as_deref())
+        .map(|(cert, key)| (Path::new(cert), Path::new(key)));
+    let tls = ClientTls::new(args.ca.as_deref().map(Path::new), args.pin.as_deref(), identity)?;
+    Ok(Some(match &args.tls_name {
+        Some(name) => tls.with_server_name(name),
+        None => tls,
+    }))
+}
+
+/// `snapshot` alt komutlarını çalıştırır
+fn run_snapshot(action: &SnapshotAction) -> Result<()> {
+    match action {
+        SnapshotAction::Create { root, label } => {
+            println!("[📸] '{}' paylaşımının görüntüsü alınıyor...", root);
+            let manifest = snapshot::create(Path::new(root), label.as_deref(), chrono::Utc::now())
+                .context("Görüntü alınamadı")?;
+            let (files, bytes) = manifest.totals();
+            println!("[✅] {} görüntüsü kaydedildi: {} dosya, {} bayt", manifest.id, files, bytes);
+        },
+        SnapshotAction::List { root } => {
+            let manifests = snapshot::list(Path::new(root))
+                .context("Görüntüler okunamadı")?;
+            if manifests.is_empty() {
+                println!("[ℹ️] '{}' için kayıtlı görüntü yok.", root);
+            } else {
+                println!("[📸] {} görüntü:", manifests.len());
+                for manifest in &manifests {
+                    let time = manifest.time()
+                        .map(|time| time.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M:%S").to_string())
+                        .unwrap_or_default();
+                    let (files, bytes) = manifest.totals();
+                    let label = manifest.label.as_deref().map(|label| format!("  ({})", label)).unwrap_or_default();
+                    println!("  {}  {}  {} dosya, {} bayt{}", manifest.id, time, files, bytes, label);
+                }
+            }
+        },
+        SnapshotAction::Restore { id, root } => {
+            let manifest = snapshot::find(Path::new(root), id)
+                .map_err(anyhow::Error::msg)?;
+            println!("[⏪] '{}' paylaşımı {} görüntüsüne geri getiriliyor...", root, manifest.id);
+            let restored = snapshot::restore(Path::new(root), &manifest, chrono::Utc::now())
+                .context("Görüntü geri yüklenemedi")?;
+            println!("[✅] {} dosya yazıldı, {} girdi silindi.", restored.written, restored.removed);
+            println!("[💡] Önceki hal {} görüntüsü olarak saklandı.", restored.safety);
+        },
+    }
+    Ok(())
+}
+
+/// Aktarım olaylarıyla ilerleme çubuğunu günceller
+fn progress_events(progress: &ProgressBar) -> Events {
+    let progress = progress.clone();
+    Events::from_fn(move |event| match &event.kind {
+        EventKind::SessionStarted { bytes, .. } => progress.set_length(*bytes),
+        EventKind::FileStarted { path, .. } => progress.set_message(format!("Gönderiliyor: {}", path.display())),
+        EventKind::ChunkSent { bytes, .. } => progress.inc(*bytes),
+        _ => {}
+    })
+}
+
 /// Anahtar veya şifreden AES anahtarı çözümler
 fn resolve_key(key: Option<&str>, password: Option<&str>) -> Result<[u8; 32]> {
     match (key, password) {
@@ -88,13 +387,41 @@ fn resolve_key(key: Option<&str>, password: Option<&str>) -> Result<[u8; 32]> {
     }
 }
 
-/// Hedef adresini çözümler (sync için)
-async fn resolve_target_address(target: Option<&str>, auto_discover: bool, auto_select: bool) -> Result<String> {
+/// `--stdio` için taşıma katmanı. Protokol stdout'un bir kopyasını kullanır;
+/// stdout stderr'e yönlendirilir, böylece durum mesajları protokolü bozmaz.
+#[cfg(unix)]
+fn stdio_transport() -> Result<Stdio> {
+    let output = Stdio::output().context("stdout kopyalanamadı")?;
+    if unsafe { libc::dup2(libc::STDERR_FILENO, libc::STDOUT_FILENO) } < 0 {
+        return Err(std::io::Error::last_os_error()).context("stdout yönlendirilemedi");
+    }
+    Ok(Stdio::with_output(output))
+}
+
+#[cfg(not(unix))]
+fn stdio_transport() -> Result<Stdio> {
+    anyhow::bail!("--stdio yalnızca Unix sistemlerde destekleniyor")
+}
+
+/// Hedef adresini çözümler (sync için).
+///
+/// Hedef, adres defterindeki bir sunucu adıysa kayıtlı son adres kullanılır;
+/// otomatik keşif sonuçları adres defterine işlenir.
+async fn resolve_target_address(target: Option<&str>, auto_discover: bool, auto_select: bool, key: &[u8; 32], book: &mut AddressBook, criteria: &SelectionCriteria) -> Result<String> {
     match (target, auto_discover) {
-        (Some(addr), false) => Ok(addr.to_string()),
+        (Some(addr), false) => {
+            match book.lookup(addr) {
+                Some(entry) if !entry.addresses.is_empty() => {
+                    println!("[📒] '{}' adres defterinden çözümlendi: {}", entry.name, entry.addresses[0]);
+                    Ok(entry.addresses[0].to_string())
+                },
+                _ => Ok(addr.to_string()),
+            }
+        },
         (None, true) => {
             println!("[🔍] Otomatik sunucu keşfi başlatılıyor...");
-            let servers = discover_servers(5).await?;
+            let servers = discover_servers(5, Some(key)).await?;
+            book.record_discovered(&servers);
             
             if servers.is_empty() {
                 anyhow::bail!("Hiç sunucu bulunamadı. Manuel IP:port belirtin veya önce sunucu başlatın.");
@@ -102,8 +429,10 @@ async fn resolve_target_address(target: Option<&str>, auto_discover: bool, auto_
             
             let selected_server = if auto_select {
                 // Otomatik seçim (kullanıcı etkileşimi olmadan)
-                select_best_server_auto(&servers)
-                    .context("Otomatik sunucu seçimi başarısız")?
+                let selection = select_best_server_auto(&servers, criteria)
+                    .context("Otomatik sunucu seçimi başarısız")?;
+                println!("[🧭] Seçim gerekçesi: {}", selection.reasons.join(", "));
+                selection.server
             } else {
                 // Kullanıcıya seçim yaptır
                 select_server_interactive(&servers)
@@ -122,33 +451,111 @@ async fn resolve_target_address(target: Option<&str>, auto_discover: bool, auto_
     }
 }
 
+/// Kullanıcıya sunucu seçimi yaptırır
+fn select_server_interactive(servers: &[DiscoveredServer]) -> Option<&DiscoveredServer> {
+    if servers.is_empty() {
+        return None;
+    }
+    
+    if servers.len() == 1 {
+        println!("[✅] Tek sunucu bulundu: {}", servers[0].address);
+        return Some(&servers[0]);
+    }
+    
+    // Birden fazla sunucu varsa kullanıcıya sor
+    println!("[🔍] {} sunucu bulundu. Lütfen birini seçin:", servers.len());
+    for (i, server) in servers.iter().enumerate() {
+        println!("  {}. {} ({:?})", i + 1, server.address, server.discovery_method);
+        if let Some(name) = &server.name {
+            println!("     Servis adı: {}", name);
+        }
+        if let Some(server_id) = &server.server_id {
+            let verified = server.server_id_source.is_some_and(ServerIdSource::is_verified);
+            println!("     Sunucu kimliği: {}{}", server_id, if verified { "" } else { " (doğrulanmadı)" });
+        }
+    }
+    
+    // Kullanıcı girişi al
+    loop {
+        print!("Seçiminiz (1-{}): ", servers.len());
+        use std::io::{self, Write};
+        io::stdout().flush().unwrap();
+        
+        let mut input = String::new();
+        match io::stdin().read_line(&mut input) {
+            Ok(_) => {
+                if let Ok(choice) = input.trim().parse::<usize>()
+                    && choice >= 1 && choice <= servers.len()
+                {
+                    println!("[✅] {} seçildi", servers[choice - 1].address);
+                    return Some(&servers[choice - 1]);
+                }
+                println!("[⚠️] Geçersiz seçim. 1-{} arası bir sayı girin.", servers.len());
+            }
+            Err(_) => {
+                println!("[⚠️] Giriş hatası. Tekrar deneyin.");
+            }
+        }
+    }
+}
+
+/// Adres defterini yükler; okunamazsa uyarı verip boş defterle devam eder
+fn load_address_book() -> AddressBook {
+    AddressBook::load_default().unwrap_or_else(|e| {
+        println!("[⚠️] Adres defteri yüklenemedi: {}", e);
+        AddressBook::default()
+    })
+}
+
+/// Adres defterini kaydeder; hata senkronizasyonu başarısız saymaz
+fn save_address_book(book: &AddressBook) {
+    if let Err(e) = book.save() {
+        println!("[⚠️] Adres defteri kaydedilemedi: {}", e);
+    }
+}
+
+/// Adres defterindeki kayıtları listeler
+fn print_address_book(peers: &[PeerEntry]) {
+    if peers.is_empty() {
+        println!("[ℹ️] Adres defterinde kayıtlı sunucu yok.");
+        return;
+    }
+
+    println!("[📒] Adres defteri ({} sunucu):", peers.len());
+    for (i, peer) in peers.iter().enumerate() {
+        let stale = if peer.is_stale() { " (eski)" } else { "" };
+        println!("  {}. {} — {} önce görüldü{}", i + 1, peer.name, format_age(peer.age_secs()), stale);
+        for address in &peer.addresses {
+            println!("     Adres: {}", address);
+        }
+        if let Some(rtt) = peer.last_rtt_ms {
+            println!("     Son RTT: {:.1} ms", rtt);
+        }
+    }
+}
+
+/// Saniye cinsinden süreyi okunabilir hale getirir
+fn format_age(secs: u64) -> String {
+    match secs {
+        0..=59 => format!("{} sn", secs),
+        60..=3599 => format!("{} dk", secs / 60),
+        3600..=86_399 => format!("{} sa", secs / 3600),
+        _ => format!("{} gün", secs / 86_400),
+    }
+}
+
 /// Sunucu adresini çözümler
 fn resolve_server_address(address: Option<&str>) -> Result<String> {
     match address {
         Some(addr) => Ok(addr.to_string()),
         None => {
-            // Otomatik IP detection
-            let local_ip = get_local_ip()?;
-            let default_port = 12345;
-            let server_address = format!("{}:{}", local_ip, default_port);
-            println!("🌐 Otomatik adres: {}", server_address);
+            // Varsayılan olarak tüm arayüzlerde dual-stack (IPv4 + IPv6) dinle
+            let server_address = SocketAddr::new(IpAddr::V6(DUAL_STACK_ADDR), DEFAULT_PORT).to_string();
+            match net::local_ip() {
+                Ok(local_ip) => println!("🌐 Otomatik adres: {} (yerel IP: {})", server_address, local_ip),
+                Err(_) => println!("🌐 Otomatik adres: {}", server_address),
+            }
             Ok(server_address)
         }
     }
 }
-
-/// Yerel IP adresini bulur
-fn get_local_ip() -> Result<String> {
-    use std::net::{TcpStream, SocketAddr};
-    
-    // Google DNS'e bağlanarak yerel IP'yi öğren (gerçekte bağlanmaz)
-    let socket = std::net::UdpSocket::bind("0.0.0.0:0")
-        .context("UDP socket oluşturulamadı")?;
-    socket.connect("8.8.8.8:80")
-        .context("Test bağlantısı kurulamadı")?;
-    
-    let local_addr = socket.local_addr()
-        .context("Yerel adres alınamadı")?;
-    
-    Ok(local_addr.ip().to_string())
-}
\ No newline at end of file
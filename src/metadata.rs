@@ -0,0 +1,240 @@
+//! Dosya metadata'sı
+//!
+//! İstemci her dosyanın Unix izinlerini, erişim/değişiklik zamanlarını ve
+//! sahiplik bilgisini `FileHeader` ile gönderir; istenirse şifreli xattr ve
+//! ACL listesi de eklenir (bkz. [`crate::xattrs`]). Sunucu dosyayı yerine
+//! taşıdıktan sonra hangi özniteliklerin uygulanacağına kendi `--preserve`
+//! ve `--xattrs` ayarlarıyla karar verir.
+
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+use filetime::FileTime;
+use serde::{Deserialize, Serialize};
+
+/// Sahiplik korunmadığında uygulanan izin maskesi; setuid/setgid/sticky
+/// bitleri yalnızca sahiplik de korunuyorsa uygulanır
+const PERMISSION_BITS: u32 = 0o777;
+
+/// İzin ve özel bitlerin tamamı
+const MODE_BITS: u32 = 0o7777;
+
+/// Unix zaman damgası
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timestamp {
+    pub secs: i64,
+    pub nanos: u32,
+}
+
+impl From<FileTime> for Timestamp {
+    fn from(time: FileTime) -> Self {
+        Self { secs: time.unix_seconds(), nanos: time.nanoseconds() }
+    }
+}
+
+impl From<Timestamp> for FileTime {
+    fn from(time: Timestamp) -> Self {
+        FileTime::from_unix_time(time.secs, time.nanos)
+    }
+}
+
+/// Bir dosyanın aktarılan öznitelikleri. Kaynak platformda bulunmayan
+/// alanlar boş kalır.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct FileMetadata {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mtime: Option<Timestamp>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub atime: Option<Timestamp>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uid: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gid: Option<u32>,
+    /// İçerikle aynı anahtarla şifrelenmiş xattr listesi (hex)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub xattrs: Option<String>,
+}
+
+impl FileMetadata {
+    /// Yerel dosya sisteminden okunan metadata'dan oluşturur
+    pub fn from_fs(metadata: &std::fs::Metadata) -> Self {
+        #[cfg(unix)]
+        let (mode, uid, gid) = {
+            use std::os::unix::fs::MetadataExt;
+            (Some(metadata.mode() & MODE_BITS), Some(metadata.uid()), Some(metadata.gid()))
+        };
+        #[cfg(not(unix))]
+        let (mode, uid, gid) = (None, None, None);
+
+        Self {
+            mode,
+            mtime: Some(FileTime::from_last_modification_time(metadata).into()),
+            atime: Some(FileTime::from_last_access_time(metadata).into()),
+            uid,
+            gid,
+            xattrs: None,
+        }
+    }
+}
+
+/// Sunucunun uyguladığı öznitelikler
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Preserve {
+    pub mode: bool,
+    pub times: bool,
+    /// Sahiplik değiştirmek genellikle root yetkisi ister
+    pub owner: bool,
+}
+
+impl Preserve {
+    pub const NONE: Preserve = Preserve { mode: false, times: false, owner: false };
+    pub const ALL: Preserve = Preserve { mode: true, times: true, owner: true };
+}
+
+impl Default for Preserve {
+    fn default() -> Self {
+        Preserve { mode: true, times: true, owner: false }
+    }
+}
+
+impl FromStr for Preserve {
+    type Err = String;
+
+    /// `none`, `all` veya `mode`, `times`, `owner` listesini kabul eder
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let mut preserve = Preserve::NONE;
+        for item in value.split(',').map(str::trim).filter(|item| !item.is_empty()) {
+            match item.to_ascii_lowercase().as_str() {
+                "none" => {}
+                "all" => preserve = Preserve::ALL,
+                "mode" => preserve.mode = true,
+                "times" => preserve.times = true,
+                "owner" => preserve.owner = true,
+                other => return Err(format!("Bilinmeyen öznitelik: '{}' (mode, times, owner, all, none)", other)),
+            }
+        }
+        Ok(preserve)
+    }
+}
+
+impl fmt::Display for Preserve {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names: Vec<&str> = [(self.mode, "mode"), (self.times, "times"), (self.owner, "owner")]
+            .into_iter()
+            .filter_map(|(on, name)| on.then_some(name))
+            .collect();
+        if names.is_empty() {
+            write!(f, "none")
+        } else {
+            write!(f, "{}", names.join(","))
+        }
+    }
+}
+
+/// Seçilen öznitelikleri dosyaya uygular. Uygulanamayanlar hata mesajı
+/// olarak döner; dosyanın kendisi etkilenmez.
+pub fn apply(path: &Path, metadata: &FileMetadata, preserve: Preserve) -> Result<(), String> {
+    let mut errors = Vec::new();
+
+    // Sahiplik önce değişir; chown özel izin bitlerini temizleyebilir
+    #[cfg(unix)]
+    if preserve.owner && (metadata.uid.is_some() || metadata.gid.is_some())
+        && let Err(e) = std::os::unix::fs::chown(path, metadata.uid, metadata.gid)
+    {
+        errors.push(format!("sahiplik: {}", e));
+    }
+
+    #[cfg(unix)]
+    if preserve.mode && let Some(mode) = metadata.mode {
+        use std::os::unix::fs::PermissionsExt;
+        let mask = if preserve.owner { MODE_BITS } else { PERMISSION_BITS };
+        if let Err(e) = std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode & mask)) {
+            errors.push(format!("izinler: {}", e));
+        }
+    }
+
+    if preserve.times && let Some(mtime) = metadata.mtime {
+        let result = match metadata.atime {
+            Some(atime) => filetime::set_file_times(path, atime.into(), mtime.into()),
+            None => filetime::set_file_mtime(path, mtime.into()),
+        };
+        if let Err(e) = result {
+            errors.push(format!("zamanlar: {}", e));
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_preserve() {
+        assert_eq!("mode,times".parse::<Preserve>().unwrap(), Preserve::default());
+        assert_eq!("all".parse::<Preserve>().unwrap(), Preserve::ALL);
+        assert_eq!("none".parse::<Preserve>().unwrap(), Preserve::NONE);
+        assert_eq!("owner".parse::<Preserve>().unwrap(), Preserve { mode: false, times: false, owner: true });
+        assert!("acl".parse::<Preserve>().is_err());
+        assert_eq!(Preserve::default().to_string(), "mode,times");
+        assert_eq!(Preserve::NONE.to_string(), "none");
+    }
+
+    #[test]
+    fn test_metadata_roundtrip() {
+        let dir = std::env::temp_dir().join(format!("deltasafe-metadata-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let source = dir.join("kaynak.sh");
+        let target = dir.join("hedef.sh");
+        std::fs::write(&source, "#!/bin/sh").unwrap();
+        std::fs::write(&target, "#!/bin/sh").unwrap();
+
+        let mtime = Timestamp { secs: 1_600_000_000, nanos: 123_456_000 };
+        filetime::set_file_times(&source, FileTime::from_unix_time(1_600_000_100, 0), mtime.into()).unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&source, std::fs::Permissions::from_mode(0o754)).unwrap();
+        }
+
+        let metadata = FileMetadata::from_fs(&std::fs::metadata(&source).unwrap());
+        assert_eq!(metadata.mtime, Some(mtime));
+
+        // Hiçbiri istenmezse dosyaya dokunulmaz
+        apply(&target, &metadata, Preserve::NONE).unwrap();
+        let untouched = FileMetadata::from_fs(&std::fs::metadata(&target).unwrap());
+        assert_ne!(untouched.mtime, Some(mtime));
+
+        apply(&target, &metadata, Preserve::default()).unwrap();
+        let applied = FileMetadata::from_fs(&std::fs::metadata(&target).unwrap());
+        assert_eq!(applied.mtime, Some(mtime));
+        assert_eq!(applied.atime, Some(Timestamp { secs: 1_600_000_100, nanos: 0 }));
+        #[cfg(unix)]
+        assert_eq!(applied.mode, Some(0o754));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_special_bits_need_owner() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = std::env::temp_dir().join(format!("deltasafe-setuid-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("araç");
+        std::fs::write(&path, "").unwrap();
+
+        let metadata = FileMetadata { mode: Some(0o4755), ..FileMetadata::default() };
+        apply(&path, &metadata, Preserve::default()).unwrap();
+        assert_eq!(std::fs::metadata(&path).unwrap().permissions().mode() & MODE_BITS, 0o755);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
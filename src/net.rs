@@ -0,0 +1,302 @@
+//! Ağ adresi yardımcı fonksiyonları
+//!
+//! Bu modül IPv4/IPv6 hedef adreslerini çözümler (köşeli parantezli IPv6 ve
+//! scope id içeren link-local adresler dahil), yerel IP adresini bulur ve
+//! sunucu için dual-stack dinleme soketi oluşturur.
+
+use anyhow::{Result, Context};
+use socket2::{Domain, Protocol, Socket, Type};
+use std::net::{IpAddr, Ipv6Addr, SocketAddr, SocketAddrV6, TcpListener, ToSocketAddrs, UdpSocket};
+
+/// Varsayılan Deltasafe portu
+pub const DEFAULT_PORT: u16 = 12345;
+
+/// Dual-stack dinleme adresi (`[::]`), IPv4 bağlantılarını da kabul eder
+pub const DUAL_STACK_ADDR: Ipv6Addr = Ipv6Addr::UNSPECIFIED;
+
+/// Kullanıcının verdiği hedef adresini soket adreslerine çözümler.
+///
+/// Desteklenen formatlar:
+/// * `192.168.1.10:12345`, `192.168.1.10`
+/// * `[2001:db8::1]:12345`, `2001:db8::1`
+/// * `[fe80::1%eth0]:12345`, `fe80::1%2` (link-local, scope id ile)
+/// * `sunucu.local:12345`, `sunucu.local`
+///
+/// Port belirtilmezse [`DEFAULT_PORT`] kullanılır.
+pub fn resolve_target(target: &str) -> Result<Vec<SocketAddr>> {
+    let target = target.trim();
+    if target.is_empty() {
+        anyhow::bail!("Hedef adres boş olamaz");
+    }
+
+    if let Ok(addr) = target.parse::<SocketAddr>() {
+        if let SocketAddr::V6(v6) = addr {
+            check_link_local_scope(v6.ip(), v6.scope_id())?;
+        }
+        return Ok(vec![addr]);
+    }
+
+    // Köşeli parantezli IPv6: [adres%scope]:port
+    if let Some(rest) = target.strip_prefix('[') {
+        let (host, after) = rest.split_once(']')
+            .with_context(|| format!("Kapanmayan köşeli parantez: {}", target))?;
+        let port = match after {
+            "" => DEFAULT_PORT,
+            _ => parse_port(after.strip_prefix(':')
+                .with_context(|| format!("Geçersiz IPv6 adres formatı: {}", target))?)?,
+        };
+        return Ok(vec![parse_ipv6_with_scope(host, port)?]);
+    }
+
+    // Parantezsiz IPv6 (port olmadan), örn. fe80::1%eth0
+    if target.matches(':').count() > 1 {
+        return Ok(vec![parse_ipv6_with_scope(target, DEFAULT_PORT)?]);
+    }
+
+    if let Ok(ip) = target.parse::<IpAddr>() {
+        return Ok(vec![SocketAddr::new(ip, DEFAULT_PORT)]);
+    }
+
+    // Host adı (opsiyonel port ile)
+    let addrs: Vec<SocketAddr> = match target.rsplit_once(':') {
+        Some((host, port)) => (host, parse_port(port)?).to_socket_addrs(),
+        None => (target, DEFAULT_PORT).to_socket_addrs(),
+    }
+    .with_context(|| format!("Adres çözümlenemedi: {}", target))?
+    .collect();
+
+    if addrs.is_empty() {
+        anyhow::bail!("Adres için IP bulunamadı: {}", target);
+    }
+    Ok(addrs)
+}
+
+fn parse_port(port: &str) -> Result<u16> {
+    port.parse::<u16>()
+        .with_context(|| format!("Geçersiz port: {}", port))
+}
+
+/// `adres%scope` biçimindeki IPv6 adresini çözümler
+fn parse_ipv6_with_scope(host: &str, port: u16) -> Result<SocketAddr> {
+    let (ip, scope) = match host.split_once('%') {
+        Some((ip, zone)) => (ip, parse_scope_id(zone)?),
+        None => (host, 0),
+    };
+    let ip: Ipv6Addr = ip.parse()
+        .with_context(|| format!("Geçersiz IPv6 adresi: {}", ip))?;
+
+    check_link_local_scope(&ip, scope)?;
+    Ok(SocketAddr::V6(SocketAddrV6::new(ip, port, 0, scope)))
+}
+
+/// Link-local adresler arayüz (scope id) olmadan yönlendirilemez
+fn check_link_local_scope(ip: &Ipv6Addr, scope: u32) -> Result<()> {
+    if scope == 0 && ip.is_unicast_link_local() {
+        anyhow::bail!("Link-local IPv6 adresi için arayüz belirtilmeli (örn. [{}%eth0])", ip);
+    }
+    Ok(())
+}
+
+/// Scope id'yi sayı ya da arayüz adı olarak çözümler (`2`, `eth0`)
+pub fn parse_scope_id(zone: &str) -> Result<u32> {
+    if let Ok(index) = zone.parse::<u32>() {
+        return Ok(index);
+    }
+    interface_index(zone)
+}
+
+#[cfg(unix)]
+fn interface_index(name: &str) -> Result<u32> {
+    let c_name = std::ffi::CString::new(name)
+        .with_context(|| format!("Geçersiz arayüz adı: {}", name))?;
+    // SAFETY: c_name geçerli, NUL ile sonlanan bir C string'dir
+    let index = unsafe { libc::if_nametoindex(c_name.as_ptr()) };
+    if index == 0 {
+        anyhow::bail!("Ağ arayüzü bulunamadı: {}", name);
+    }
+    Ok(index)
+}
+
+#[cfg(not(unix))]
+fn interface_index(name: &str) -> Result<u32> {
+    anyhow::bail!("Arayüz adı ile scope id bu platformda desteklenmiyor, sayısal değer kullanın: {}", name)
+}
+
+/// Yerel IP adresini bulur (önce IPv4, yoksa IPv6)
+pub fn local_ip() -> Result<IpAddr> {
+    local_ipv4().or_else(|_| local_ipv6())
+        .context("Yerel IP adresi bulunamadı")
+}
+
+/// Yerel IPv4 adresini bulur
+pub fn local_ipv4() -> Result<IpAddr> {
+    // Google DNS'e bağlanarak yerel IP'yi öğren (gerçekte bağlanmaz)
+    probe_local_ip("0.0.0.0:0", "8.8.8.8:80")
+}
+
+/// Yerel (global ya da ULA) IPv6 adresini bulur
+pub fn local_ipv6() -> Result<IpAddr> {
+    probe_local_ip("[::]:0", "[2001:4860:4860::8888]:80")
+}
+
+fn probe_local_ip(bind: &str, probe: &str) -> Result<IpAddr> {
+    let socket = UdpSocket::bind(bind)
+        .context("UDP socket oluşturulamadı")?;
+    socket.connect(probe)
+        .context("Test bağlantısı kurulamadı")?;
+    let local_addr = socket.local_addr()
+        .context("Yerel adres alınamadı")?;
+    Ok(local_addr.ip())
+}
+
+/// Sunucu için dinleme soketi oluşturur.
+///
+/// Adres `[::]` ise soket dual-stack açılır ve IPv4 istemcileri de
+/// (IPv4-mapped adresler üzerinden) kabul eder.
+pub fn bind_listener(addr: SocketAddr) -> Result<TcpListener> {
+    let socket = Socket::new(Domain::for_address(addr), Type::STREAM, Some(Protocol::TCP))
+        .context("TCP socket oluşturulamadı")?;
+
+    if let SocketAddr::V6(v6) = addr
+        && v6.ip().is_unspecified()
+    {
+        socket.set_only_v6(false)
+            .context("Dual-stack mod etkinleştirilemedi")?;
+    }
+
+    socket.set_reuse_address(true)
+        .context("SO_REUSEADDR ayarlanamadı")?;
+    socket.bind(&addr.into())
+        .with_context(|| format!("Adrese bağlanılamadı: {}", addr))?;
+    socket.listen(128)
+        .context("Soket dinlemeye alınamadı")?;
+
+    Ok(socket.into())
+}
+
+/// IPv4-mapped IPv6 adreslerini (`::ffff:a.b.c.d`) sade IPv4'e çevirir
+pub fn canonical_addr(addr: SocketAddr) -> SocketAddr {
+    match addr {
+        SocketAddr::V6(v6) => match v6.ip().to_ipv4_mapped() {
+            Some(v4) => SocketAddr::new(IpAddr::V4(v4), v6.port()),
+            None => addr,
+        },
+        SocketAddr::V4(_) => addr,
+    }
+}
+
+/// Döngü (loopback) dışındaki ağ arayüzlerinin adı ve indeksi
+pub fn network_interfaces() -> Result<Vec<(String, u32)>> {
+    let mut interfaces: Vec<(String, u32)> = if_addrs::get_if_addrs()
+        .context("Ağ arayüzleri listelenemedi")?
+        .into_iter()
+        .filter(|interface| !interface.is_loopback())
+        .filter_map(|interface| Some((interface.name, interface.index?)))
+        .collect();
+    interfaces.sort();
+    interfaces.dedup();
+    Ok(interfaces)
+}
+
+/// Döngü dışındaki arayüzlerin IPv4 adresleri
+pub fn local_ipv4_addrs() -> Result<Vec<IpAddr>> {
+    let mut addrs: Vec<IpAddr> = if_addrs::get_if_addrs()
+        .context("Ağ arayüzleri listelenemedi")?
+        .into_iter()
+        .filter(|interface| !interface.is_loopback())
+        .map(|interface| interface.ip())
+        .filter(IpAddr::is_ipv4)
+        .collect();
+    addrs.sort();
+    addrs.dedup();
+    Ok(addrs)
+}
+
+/// Bu makinenin host adını döndürür (mDNS kaydı için)
+pub fn local_hostname() -> String {
+    std::fs::read_to_string("/proc/sys/kernel/hostname")
+        .or_else(|_| std::fs::read_to_string("/etc/hostname"))
+        .ok()
+        .or_else(|| std::env::var("HOSTNAME").ok())
+        .or_else(|| std::env::var("COMPUTERNAME").ok())
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "deltasafe".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::Ipv4Addr;
+
+    #[test]
+    fn test_resolve_ipv4_with_and_without_port() {
+        let addrs = resolve_target("192.168.1.10:4000").unwrap();
+        assert_eq!(addrs, vec![SocketAddr::new(IpAddr::V4(Ipv4Addr::new(192, 168, 1, 10)), 4000)]);
+
+        let addrs = resolve_target("192.168.1.10").unwrap();
+        assert_eq!(addrs[0].port(), DEFAULT_PORT);
+    }
+
+    #[test]
+    fn test_resolve_bracketed_ipv6() {
+        let addrs = resolve_target("[2001:db8::1]:4000").unwrap();
+        assert_eq!(addrs[0], "[2001:db8::1]:4000".parse::<SocketAddr>().unwrap());
+
+        // Port olmadan köşeli parantez ve parantezsiz adres
+        assert_eq!(resolve_target("[2001:db8::1]").unwrap()[0].port(), DEFAULT_PORT);
+        assert_eq!(resolve_target("2001:db8::1").unwrap()[0].port(), DEFAULT_PORT);
+    }
+
+    #[test]
+    fn test_resolve_link_local_scope_id() {
+        let addrs = resolve_target("[fe80::1%3]:4000").unwrap();
+        match addrs[0] {
+            SocketAddr::V6(v6) => assert_eq!(v6.scope_id(), 3),
+            _ => panic!("IPv6 adres bekleniyordu"),
+        }
+
+        // Arayüz adıyla scope (loopback her Unix sistemde vardır)
+        #[cfg(target_os = "linux")]
+        {
+            let addrs = resolve_target("[fe80::1%lo]:4000").unwrap();
+            match addrs[0] {
+                SocketAddr::V6(v6) => assert!(v6.scope_id() > 0),
+                _ => panic!("IPv6 adres bekleniyordu"),
+            }
+        }
+
+        // Scope olmadan link-local kabul edilmez
+        assert!(resolve_target("[fe80::1]:4000").is_err());
+        assert!(resolve_target("[fe80::1%yok_boyle_arayuz]:4000").is_err());
+    }
+
+    #[test]
+    fn test_resolve_invalid_targets() {
+        assert!(resolve_target("").is_err());
+        assert!(resolve_target("[2001:db8::1").is_err());
+        assert!(resolve_target("[2001:db8::1]:port").is_err());
+    }
+
+    #[test]
+    fn test_canonical_addr() {
+        let mapped: SocketAddr = "[::ffff:192.168.1.5]:12345".parse().unwrap();
+        assert_eq!(canonical_addr(mapped), "192.168.1.5:12345".parse::<SocketAddr>().unwrap());
+
+        let v6: SocketAddr = "[2001:db8::1]:12345".parse().unwrap();
+        assert_eq!(canonical_addr(v6), v6);
+    }
+
+    #[test]
+    fn test_dual_stack_listener_accepts_ipv4() {
+        let listener = match bind_listener(SocketAddr::new(IpAddr::V6(DUAL_STACK_ADDR), 0)) {
+            Ok(l) => l,
+            Err(_) => return, // IPv6 desteklemeyen ortam
+        };
+        let port = listener.local_addr().unwrap().port();
+
+        let client = std::net::TcpStream::connect(("127.0.0.1", port)).unwrap();
+        let (_, peer) = listener.accept().unwrap();
+        assert_eq!(canonical_addr(peer).ip(), client.local_addr().unwrap().ip());
+    }
+}
@@ -0,0 +1,276 @@
+//! Sunucu adres defteri
+//!
+//! Keşfedilen ve senkronizasyon yapılan sunucular yerel bir JSON dosyasında
+//! saklanır (isim, son görülen adresler, son RTT). Böylece
+//! `sync --target ofis-nas` keşif beklemeden anında çözümlenir.
+
+use anyhow::{Result, Context};
+use serde::{Serialize, Deserialize};
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use crate::discovery::DiscoveredServer;
+
+/// Adres defteri dosya adı
+const ADDRESS_BOOK_FILE: &str = "peers.json";
+
+/// Bir kayıt bu süreden daha uzun süredir görülmediyse eski kabul edilir
+pub const STALE_AFTER: Duration = Duration::from_secs(15 * 60);
+
+/// Kayıt başına saklanan en fazla adres sayısı
+const MAX_ADDRESSES_PER_PEER: usize = 8;
+
+/// Adres defterindeki tek bir sunucu kaydı
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PeerEntry {
+    pub name: String,
+    /// Son görülen adresler, en yenisi başta
+    pub addresses: Vec<SocketAddr>,
+    /// Son görülme zamanı (Unix saniye)
+    pub last_seen: u64,
+    /// Son ölçülen RTT (milisaniye)
+    pub last_rtt_ms: Option<f64>,
+    /// Son başarılı senkronizasyon zamanı (Unix saniye)
+    pub last_synced: Option<u64>,
+    /// Senkronizasyonun başarıyla tamamlandığı adresler, en yenisi başta
+    #[serde(default)]
+    pub synced_addresses: Vec<SocketAddr>,
+}
+
+impl PeerEntry {
+    /// Kaydın yenilenmesi gerekip gerekmediğini döndürür
+    pub fn is_stale(&self) -> bool {
+        unix_now().saturating_sub(self.last_seen) > STALE_AFTER.as_secs()
+    }
+
+    /// Kaydın kaç saniye önce görüldüğü
+    pub fn age_secs(&self) -> u64 {
+        unix_now().saturating_sub(self.last_seen)
+    }
+
+    /// Adresi listenin başına taşır (yoksa ekler)
+    fn touch_address(&mut self, address: SocketAddr) {
+        self.addresses.retain(|a| *a != address);
+        self.addresses.insert(0, address);
+        self.addresses.truncate(MAX_ADDRESSES_PER_PEER);
+    }
+}
+
+/// Kalıcı sunucu adres defteri
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct AddressBook {
+    pub peers: Vec<PeerEntry>,
+    #[serde(skip)]
+    path: Option<PathBuf>,
+}
+
+impl AddressBook {
+    /// Varsayılan konumdaki adres defterini yükler (dosya yoksa boş döner)
+    pub fn load_default() -> Result<Self> {
+        Self::load(&config_dir()?.join(ADDRESS_BOOK_FILE))
+    }
+
+    /// Belirtilen dosyadaki adres defterini yükler (dosya yoksa boş döner)
+    pub fn load(path: &Path) -> Result<Self> {
+        let mut book = if path.exists() {
+            let content = std::fs::read_to_string(path)
+                .with_context(|| format!("Adres defteri okunamadı: {}", path.display()))?;
+            serde_json::from_str::<AddressBook>(&content)
+                .with_context(|| format!("Adres defteri bozuk: {}", path.display()))?
+        } else {
+            AddressBook::default()
+        };
+        book.path = Some(path.to_path_buf());
+        Ok(book)
+    }
+
+    /// Adres defterini diske yazar (geçici dosya + rename ile atomik)
+    pub fn save(&self) -> Result<()> {
+        let path = self.path.as_ref()
+            .context("Adres defteri için dosya yolu yok")?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Dizin oluşturulamadı: {}", parent.display()))?;
+        }
+
+        let content = serde_json::to_string_pretty(self)
+            .context("Adres defteri serialize edilemedi")?;
+        let tmp_path = path.with_extension("json.tmp");
+        std::fs::write(&tmp_path, content)
+            .with_context(|| format!("Adres defteri yazılamadı: {}", tmp_path.display()))?;
+        std::fs::rename(&tmp_path, path)
+            .with_context(|| format!("Adres defteri kaydedilemedi: {}", path.display()))?;
+        Ok(())
+    }
+
+    /// İsme göre kayıt arar (büyük/küçük harf duyarsız)
+    pub fn lookup(&self, name: &str) -> Option<&PeerEntry> {
+        self.peers.iter().find(|p| p.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Daha önce senkronizasyon yapılmış tüm adresler
+    pub fn synced_addresses(&self) -> Vec<SocketAddr> {
+        self.peers.iter()
+            .flat_map(|p| p.synced_addresses.iter().copied())
+            .collect()
+    }
+
+    /// Adreslerinden biri verilen adres olan kaydı arar
+    pub fn lookup_by_address(&self, address: SocketAddr) -> Option<&PeerEntry> {
+        self.peers.iter().find(|p| p.addresses.contains(&address))
+    }
+
+    /// Keşif sonuçlarını adres defterine işler. İsmi olmayan sunucular
+    /// (örn. yalnızca port taramasıyla bulunanlar) kaydedilmez.
+    pub fn record_discovered(&mut self, servers: &[DiscoveredServer]) {
+        let now = unix_now();
+        for server in servers {
+            let Some(name) = &server.name else { continue };
+
+            let index = match self.peers.iter().position(|p| p.name.eq_ignore_ascii_case(name)) {
+                Some(index) => index,
+                None => {
+                    self.peers.push(PeerEntry {
+                        name: name.clone(),
+                        addresses: Vec::new(),
+                        last_seen: now,
+                        last_rtt_ms: None,
+                        last_synced: None,
+                        synced_addresses: Vec::new(),
+                    });
+                    self.peers.len() - 1
+                }
+            };
+
+            let entry = &mut self.peers[index];
+            entry.touch_address(server.address);
+            entry.last_seen = now;
+            if let Some(rtt) = server.rtt {
+                entry.last_rtt_ms = Some(rtt.as_secs_f64() * 1000.0);
+            }
+        }
+        self.peers.sort_by_key(|p| p.name.to_lowercase());
+    }
+
+    /// Başarılı bir senkronizasyonu, adresi bilinen kayda işler
+    pub fn record_sync(&mut self, address: SocketAddr) -> bool {
+        let now = unix_now();
+        match self.peers.iter_mut().find(|p| p.addresses.contains(&address)) {
+            Some(entry) => {
+                entry.touch_address(address);
+                entry.last_seen = now;
+                entry.last_synced = Some(now);
+                entry.synced_addresses.retain(|a| *a != address);
+                entry.synced_addresses.insert(0, address);
+                entry.synced_addresses.truncate(MAX_ADDRESSES_PER_PEER);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Deltasafe yapılandırma dizini.
+///
+/// Sırasıyla `DELTASAFE_CONFIG_DIR`, `XDG_CONFIG_HOME/deltasafe`,
+/// `APPDATA\deltasafe` ve `~/.config/deltasafe` denenir.
+pub fn config_dir() -> Result<PathBuf> {
+    if let Some(dir) = std::env::var_os("DELTASAFE_CONFIG_DIR") {
+        return Ok(PathBuf::from(dir));
+    }
+    if let Some(dir) = std::env::var_os("XDG_CONFIG_HOME") {
+        return Ok(PathBuf::from(dir).join("deltasafe"));
+    }
+    if let Some(dir) = std::env::var_os("APPDATA") {
+        return Ok(PathBuf::from(dir).join("deltasafe"));
+    }
+    let home = std::env::var_os("HOME")
+        .context("Yapılandırma dizini bulunamadı (HOME tanımlı değil)")?;
+    Ok(PathBuf::from(home).join(".config").join("deltasafe"))
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::discovery::{DiscoveryMethod, ServerIdSource};
+
+    fn server(name: Option<&str>, address: &str, rtt_ms: u64) -> DiscoveredServer {
+        DiscoveredServer {
+            address: address.parse().unwrap(),
+            name: name.map(str::to_string),
+            discovery_method: DiscoveryMethod::MDns,
+            server_id: Some("00112233aabbccdd".to_string()),
+            server_id_source: Some(ServerIdSource::MDns),
+            rtt: Some(Duration::from_millis(rtt_ms)),
+            protocol_version: None,
+            proven: None,
+        }
+    }
+
+    #[test]
+    fn test_record_and_lookup() {
+        let mut book = AddressBook::default();
+        book.record_discovered(&[
+            server(Some("ofis-nas"), "192.168.1.20:12345", 3),
+            server(Some("ofis-nas"), "[2001:db8::20]:12345", 2),
+            server(None, "192.168.1.30:12345", 1),
+        ]);
+
+        assert_eq!(book.peers.len(), 1);
+        let entry = book.lookup("OFIS-NAS").unwrap();
+        // En son görülen adres başta
+        assert_eq!(entry.addresses[0], "[2001:db8::20]:12345".parse::<SocketAddr>().unwrap());
+        assert_eq!(entry.addresses.len(), 2);
+        assert_eq!(entry.last_rtt_ms, Some(2.0));
+        assert!(!entry.is_stale());
+
+        assert!(book.lookup("yok").is_none());
+        assert!(book.lookup_by_address("192.168.1.20:12345".parse().unwrap()).is_some());
+    }
+
+    #[test]
+    fn test_record_sync_updates_known_peer_only() {
+        let mut book = AddressBook::default();
+        book.record_discovered(&[server(Some("ofis-nas"), "192.168.1.20:12345", 3)]);
+
+        assert!(book.record_sync("192.168.1.20:12345".parse().unwrap()));
+        assert!(book.peers[0].last_synced.is_some());
+        assert_eq!(book.peers[0].synced_addresses, vec!["192.168.1.20:12345".parse::<SocketAddr>().unwrap()]);
+        assert!(!book.record_sync("10.0.0.1:12345".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_stale_entries() {
+        let mut book = AddressBook::default();
+        book.record_discovered(&[server(Some("ofis-nas"), "192.168.1.20:12345", 3)]);
+        book.peers[0].last_seen -= STALE_AFTER.as_secs() + 1;
+        assert!(book.peers[0].is_stale());
+
+        // Yeniden görülünce taze olur
+        book.record_discovered(&[server(Some("ofis-nas"), "192.168.1.20:12345", 3)]);
+        assert!(!book.peers[0].is_stale());
+    }
+
+    #[test]
+    fn test_save_and_load_roundtrip() {
+        let dir = std::env::temp_dir().join(format!("deltasafe_peers_test_{}", std::process::id()));
+        let path = dir.join(ADDRESS_BOOK_FILE);
+
+        let mut book = AddressBook::load(&path).unwrap();
+        assert!(book.peers.is_empty());
+        book.record_discovered(&[server(Some("ofis-nas"), "192.168.1.20:12345", 3)]);
+        book.save().unwrap();
+
+        let loaded = AddressBook::load(&path).unwrap();
+        assert_eq!(loaded.peers, book.peers);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
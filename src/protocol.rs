@@ -0,0 +1,510 @@
+//! Deltasafe ağ protokolü
+//!
+//! Sürüm 8 tel formatı. Her mesaj bir çerçevedir:
+//! `u8` tür + `u32` (big-endian) gövde uzunluğu + gövde.
+//!
+//! 1. İstemci `Hello` (istenen akış sayısı, rastgele nonce) gönderir; sunucu
+//!    `Welcome` ile verilen akış sayısını, oturum kimliğini ve anahtarı
+//!    bildiğini gösteren kanıtı döner. İstemci de `Auth` ile kendi kanıtını
+//!    gönderir. Ek akışlar `Hello.join` ile aynı oturuma katılır ve aynı
+//!    el sıkışmadan geçer. `Hello` ve `Welcome` ayrıca oturumun en büyük
+//!    chunk boyutunu ve sıkıştırma algoritmasını pazarlık eder. Keşifte
+//!    `Hello.probe` ile gelen bağlantı `Welcome`'dan sonra kapanır; oturum
+//!    açılmaz
+//! 2. İstemci her dosya için oturum içinde tekil bir `u32` kimlikle `File`
+//!    çerçevesi (kimlik + JSON `FileHeader`) gönderir; onay beklemeden
+//!    chunk'lara geçer. Bir dosyanın parçalarını taşıyan her akış, önce o
+//!    dosyanın `File` çerçevesini gönderir
+//! 3. Dosya içeriği `Chunk` çerçeveleriyle gelir:
+//!    kimlik + `u64` ofset + IV (16 bayt) + AES-256-CBC şifreli veri.
+//!    Sıkıştırma kabul edildiyse veri şifrelemeden önce zstd ile
+//!    sıkıştırılıp `ChunkZstd` türüyle gönderilebilir; ofset ham veriye göredir.
+//!    Farklı dosyaların chunk'ları aynı bağlantıda iç içe geçebilir; büyük
+//!    dosyaların aralıkları farklı akışlardan gelebilir
+//!    Seyrek dosyaların delikleri `Hole` çerçeveleriyle (kimlik + `u64`
+//!    ofset + `u64` uzunluk) bildirilir; sunucu bu bölgeleri yazmaz.
+//!    Klasör, sembolik bağlantı ve hardlink'ler içeriksiz `Entry`
+//!    çerçeveleriyle (kimlik + JSON `EntryHeader`) gelir
+//! 4. Sunucu dosyanın tüm baytları geldiğinde hash'i doğrular ve
+//!    tamamlayan akış üzerinden `FileResult` döner (`Entry` için hemen); istemci aynı anda en
+//!    fazla "pencere" kadar dosyayı yanıtsız bırakır
+//! 5. Dosya göndermek yerine oturumda `Request` (JSON) ile sunucudan bilgi
+//!    veya içerik istenebilir. Sunucu `Response` (JSON) ile yanıtlar; içerik
+//!    isteklerinde dosyayı istemciye aynı `File` + `Chunk` çerçeveleriyle
+//!    gönderir. Başarısız istekler `Error` ile yanıtlanır, oturum sürer
+//! 6. Kasa modundaki sunucu dosya kabul etmez; istemci içeriği kendisi
+//!    şifreleyip `Blob` çerçeveleriyle (32 baytlık BLAKE3 kimliği + şifreli
+//!    veri) gönderir, hangi blob'ların eksik olduğunu ve indeksi `Request`
+//!    ile sorar. Sunucu istenen blob'ları da aynı çerçeveyle döner
+//!
+//! `FileResult` ve `Error` reddin nedenini bir hata koduyla taşır
+//! ([`ErrorCode`]: 1 kimlik doğrulama, 2 protokol ihlali, 3 bütünlük, 4 G/Ç,
+//! 5 ağ, 6 izin; başarılı `FileResult` için 0), ardından UTF-8 mesaj gelir.
+//!
+//! Sunucular protokol sürümünü mDNS TXT kaydında (`proto`) ve broadcast
+//! beacon'ında duyurur; istemci uyumsuz sunucuları otomatik seçimde eler.
+
+use serde::{Serialize, Deserialize};
+use std::io;
+use std::path::PathBuf;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use crate::entry::EntryHeader;
+use crate::error::{Error, ErrorCode};
+use crate::sync::FileHeader;
+use crate::versions::VersionInfo;
+
+/// Bu derlemenin konuştuğu protokol sürümü
+pub const PROTOCOL_VERSION: u32 = 8;
+
+/// Kabul edilen en büyük çerçeve gövdesi
+pub const MAX_FRAME_SIZE: usize = 16 * 1024 * 1024;
+
+/// Verilen protokol sürümüyle konuşulabilir mi
+pub fn is_compatible(version: u32) -> bool {
+    version == PROTOCOL_VERSION
+}
+
+const KIND_HELLO: u8 = 0x01;
+const KIND_FILE: u8 = 0x02;
+const KIND_CHUNK: u8 = 0x03;
+const KIND_AUTH: u8 = 0x04;
+const KIND_CHUNK_ZSTD: u8 = 0x05;
+const KIND_ENTRY: u8 = 0x06;
+const KIND_HOLE: u8 = 0x07;
+const KIND_REQUEST: u8 = 0x08;
+const KIND_BLOB: u8 = 0x09;
+const KIND_WELCOME: u8 = 0x81;
+const KIND_FILE_RESULT: u8 = 0x82;
+const KIND_RESPONSE: u8 = 0x83;
+const KIND_ERROR: u8 = 0x8F;
+
+/// İstemcinin bağlantı başında gönderdiği tanıtım
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Hello {
+    pub protocol: u32,
+    /// İstenen paralel akış sayısı
+    pub streams: u32,
+    /// Ek akışlarda katılınacak oturumun kimliği
+    #[serde(default)]
+    pub join: Option<String>,
+    pub nonce: String,
+    /// İstemcinin kullanmak istediği en büyük chunk boyutu (0: varsayılan)
+    #[serde(default)]
+    pub chunk_size: u32,
+    /// İstenen sıkıştırma algoritması
+    #[serde(default)]
+    pub compression: Option<String>,
+    /// Keşif sınaması: istemci yalnızca `Welcome`'daki kanıtı doğrular,
+    /// oturum açılmaz
+    #[serde(default)]
+    pub probe: bool,
+}
+
+/// Sunucunun `Hello` yanıtı
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Welcome {
+    pub protocol: u32,
+    /// Sunucunun duyurduğu ad
+    pub name: String,
+    /// Sunucunun izin verdiği akış sayısı
+    pub streams: u32,
+    /// Oturumda kullanılabilecek en büyük chunk boyutu
+    #[serde(default)]
+    pub chunk_size: u32,
+    /// Kabul edilen sıkıştırma algoritması
+    #[serde(default)]
+    pub compression: Option<String>,
+    pub session: String,
+    pub nonce: String,
+    /// Sunucunun anahtarı bildiğini gösteren kanıt
+    pub proof: String,
+}
+
+/// İstemcinin el sıkışma kanıtı
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Auth {
+    pub proof: String,
+}
+
+/// İstemcinin oturum içindeki isteği
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Request {
+    /// Bir yolun saklanan sürümlerini listele
+    Versions { path: PathBuf },
+    /// Bir sürümün içeriğini istemciye gönder
+    Restore { path: PathBuf, version: String },
+    /// Kasada bulunmayan blob'ları sor (hex kimlikler)
+    MissingBlobs { ids: Vec<String> },
+    /// Blob'ları istemciye `Blob` çerçeveleriyle gönder
+    GetBlobs { ids: Vec<String> },
+    /// Şifreli indeksi oluşturan blob'ları yeni bir indeks olarak kaydet
+    PutIndex { blobs: Vec<String> },
+    /// Kayıtlı indeksleri yeniden eskiye listele
+    Indexes,
+    /// Bir indeksin blob'larını döndür; kimlik verilmezse en yenisinin
+    Index { id: Option<String> },
+}
+
+/// Sunucunun `Request` yanıtı
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Response {
+    Versions { versions: Vec<VersionInfo> },
+    MissingBlobs { ids: Vec<String> },
+    IndexStored { id: String },
+    Indexes { ids: Vec<String> },
+    Index { id: String, blobs: Vec<String> },
+}
+
+/// El sıkışma kanıtını kimin ürettiği
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    Client,
+    Server,
+}
+
+/// Erişim anahtarı ([`crate::crypto::access_key`]), iki nonce ve oturum
+/// kimliği üzerinden el sıkışma kanıtı üretir. Nonce'lar her bağlantıda yeni
+/// olduğu için kanıt tekrar oynatılamaz.
+pub fn handshake_proof(access: &[u8; 32], role: Role, client_nonce: &str, server_nonce: &str, session: &str) -> String {
+    let tag: &[u8] = match role {
+        Role::Client => b"client",
+        Role::Server => b"server",
+    };
+    let mut hasher = blake3::Hasher::new_keyed(access);
+    for part in [tag, client_nonce.as_bytes(), server_nonce.as_bytes(), session.as_bytes()] {
+        hasher.update(&(part.len() as u32).to_be_bytes());
+        hasher.update(part);
+    }
+    hasher.finalize().to_hex().to_string()
+}
+
+/// El sıkışma kanıtını sabit zamanlı olarak doğrular
+pub fn verify_handshake_proof(access: &[u8; 32], role: Role, client_nonce: &str, server_nonce: &str, session: &str, proof: &str) -> bool {
+    let expected = handshake_proof(access, role, client_nonce, server_nonce, session);
+    match (blake3::Hash::from_hex(&expected), blake3::Hash::from_hex(proof)) {
+        (Ok(expected), Ok(proof)) => expected == proof,
+        _ => false,
+    }
+}
+
+/// Tel üzerindeki tek bir mesaj
+#[derive(Debug)]
+pub enum Frame {
+    Hello(Hello),
+    Auth(Auth),
+    File { id: u32, header: FileHeader },
+    /// `offset`: verinin dosyadaki konumu, `data`: IV + şifreli veri,
+    /// `compressed`: şifreli verinin açılınca zstd ile sıkıştırılmış olduğu
+    Chunk { id: u32, offset: u64, compressed: bool, data: Vec<u8> },
+    /// Dosyanın `offset` konumundan başlayan `len` baytlık sıfır bölgesi
+    Hole { id: u32, offset: u64, len: u64 },
+    /// İçeriği olmayan girdi: klasör, sembolik bağlantı veya hardlink
+    Entry { id: u32, entry: EntryHeader },
+    Request(Request),
+    /// Kasadaki şifreli içerik parçası; `id` şifreli verinin BLAKE3 özeti
+    Blob { id: [u8; 32], data: Vec<u8> },
+    Welcome(Welcome),
+    FileResult { id: u32, result: Result<(), Error> },
+    Response(Response),
+    Error(Error),
+}
+
+impl Frame {
+    /// Çerçeveyi tel formatına çevirir
+    pub fn encode(&self) -> io::Result<Vec<u8>> {
+        let (kind, body) = match self {
+            Frame::Hello(hello) => (KIND_HELLO, to_json(hello)?),
+            Frame::Auth(auth) => (KIND_AUTH, to_json(auth)?),
+            Frame::File { id, header } => (KIND_FILE, with_id(*id, &to_json(header)?)),
+            Frame::Chunk { id, offset, compressed, data } => {
+                let mut body = with_id(*id, &offset.to_be_bytes());
+                body.extend_from_slice(data);
+                (if *compressed { KIND_CHUNK_ZSTD } else { KIND_CHUNK }, body)
+            }
+            Frame::Hole { id, offset, len } => {
+                let mut body = with_id(*id, &offset.to_be_bytes());
+                body.extend_from_slice(&len.to_be_bytes());
+                (KIND_HOLE, body)
+            }
+            Frame::Entry { id, entry } => (KIND_ENTRY, with_id(*id, &to_json(entry)?)),
+            Frame::Request(request) => (KIND_REQUEST, to_json(request)?),
+            Frame::Blob { id, data } => {
+                let mut body = Vec::with_capacity(id.len() + data.len());
+                body.extend_from_slice(id);
+                body.extend_from_slice(data);
+                (KIND_BLOB, body)
+            }
+            Frame::Welcome(welcome) => (KIND_WELCOME, to_json(welcome)?),
+            Frame::Response(response) => (KIND_RESPONSE, to_json(response)?),
+            Frame::FileResult { id, result } => match result {
+                Ok(()) => (KIND_FILE_RESULT, with_id(*id, &[0])),
+                Err(error) => (KIND_FILE_RESULT, with_id(*id, &error_body(error))),
+            },
+            Frame::Error(error) => (KIND_ERROR, error_body(error)),
+        };
+
+        let mut bytes = Vec::with_capacity(5 + body.len());
+        bytes.push(kind);
+        bytes.extend_from_slice(&(body.len() as u32).to_be_bytes());
+        bytes.extend_from_slice(&body);
+        Ok(bytes)
+    }
+
+    fn decode(kind: u8, body: Vec<u8>) -> io::Result<Self> {
+        Ok(match kind {
+            KIND_HELLO => Frame::Hello(from_json(&body)?),
+            KIND_AUTH => Frame::Auth(from_json(&body)?),
+            KIND_FILE => {
+                let (id, rest) = split_id(&body)?;
+                Frame::File { id, header: from_json(rest)? }
+            }
+            KIND_CHUNK | KIND_CHUNK_ZSTD => {
+                let (id, rest) = split_id(&body)?;
+                let offset: [u8; 8] = rest.get(..8)
+                    .and_then(|bytes| bytes.try_into().ok())
+                    .ok_or_else(|| invalid("Eksik chunk ofseti"))?;
+                let offset = u64::from_be_bytes(offset);
+                let mut data = body;
+                data.drain(..12);
+                Frame::Chunk { id, offset, compressed: kind == KIND_CHUNK_ZSTD, data }
+            }
+            KIND_HOLE => {
+                let (id, rest) = split_id(&body)?;
+                let fields: [u8; 16] = rest.try_into()
+                    .map_err(|_| invalid("Geçersiz delik çerçevesi"))?;
+                let (offset, len) = fields.split_at(8);
+                Frame::Hole {
+                    id,
+                    offset: u64::from_be_bytes(offset.try_into().unwrap_or_default()),
+                    len: u64::from_be_bytes(len.try_into().unwrap_or_default()),
+                }
+            }
+            KIND_ENTRY => {
+                let (id, rest) = split_id(&body)?;
+                Frame::Entry { id, entry: from_json(rest)? }
+            }
+            KIND_REQUEST => Frame::Request(from_json(&body)?),
+            KIND_BLOB => {
+                let id: [u8; 32] = body.get(..32)
+                    .and_then(|bytes| bytes.try_into().ok())
+                    .ok_or_else(|| invalid("Eksik blob kimliği"))?;
+                let mut data = body;
+                data.drain(..32);
+                Frame::Blob { id, data }
+            }
+            KIND_WELCOME => Frame::Welcome(from_json(&body)?),
+            KIND_RESPONSE => Frame::Response(from_json(&body)?),
+            KIND_FILE_RESULT => {
+                let (id, rest) = split_id(&body)?;
+                let result = match rest {
+                    [] => return Err(invalid("Eksik dosya sonucu")),
+                    [0] => Ok(()),
+                    error => Err(parse_error(error)?),
+                };
+                Frame::FileResult { id, result }
+            }
+            KIND_ERROR => Frame::Error(parse_error(&body)?),
+            other => return Err(invalid(format!("Bilinmeyen çerçeve türü: {:#04x}", other))),
+        })
+    }
+}
+
+/// Bir çerçeve okur. Bağlantı çerçeve sınırında kapandıysa `None` döner.
+pub async fn read_frame<R: AsyncRead + Unpin>(reader: &mut R) -> io::Result<Option<Frame>> {
+    let kind = match reader.read_u8().await {
+        Ok(kind) => kind,
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    let len = reader.read_u32().await? as usize;
+    if len > MAX_FRAME_SIZE {
+        return Err(invalid(format!("Çerçeve çok büyük: {} bayt", len)));
+    }
+    let mut body = vec![0; len];
+    reader.read_exact(&mut body).await?;
+    Frame::decode(kind, body).map(Some)
+}
+
+/// Bir çerçeve yazar
+pub async fn write_frame<W: AsyncWrite + Unpin>(writer: &mut W, frame: &Frame) -> io::Result<()> {
+    writer.write_all(&frame.encode()?).await
+}
+
+fn to_json<T: Serialize>(value: &T) -> io::Result<Vec<u8>> {
+    serde_json::to_vec(value).map_err(|e| invalid(e.to_string()))
+}
+
+fn from_json<'a, T: Deserialize<'a>>(body: &'a [u8]) -> io::Result<T> {
+    serde_json::from_slice(body).map_err(|e| invalid(e.to_string()))
+}
+
+fn with_id(id: u32, rest: &[u8]) -> Vec<u8> {
+    let mut body = Vec::with_capacity(4 + rest.len());
+    body.extend_from_slice(&id.to_be_bytes());
+    body.extend_from_slice(rest);
+    body
+}
+
+fn split_id(body: &[u8]) -> io::Result<(u32, &[u8])> {
+    if body.len() < 4 {
+        return Err(invalid("Eksik dosya kimliği"));
+    }
+    let (id, rest) = body.split_at(4);
+    Ok((u32::from_be_bytes([id[0], id[1], id[2], id[3]]), rest))
+}
+
+/// Hata kodu + UTF-8 mesaj
+fn error_body(error: &Error) -> Vec<u8> {
+    let mut body = vec![error.code() as u8];
+    body.extend_from_slice(error.message().as_bytes());
+    body
+}
+
+fn parse_error(body: &[u8]) -> io::Result<Error> {
+    let (&code, message) = body.split_first().ok_or_else(|| invalid("Eksik hata kodu"))?;
+    let code = ErrorCode::from_u8(code).ok_or_else(|| invalid(format!("Bilinmeyen hata kodu: {}", code)))?;
+    Ok(Error::new(code, String::from_utf8_lossy(message)))
+}
+
+fn invalid(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entry::EntryKind;
+    use crate::metadata::FileMetadata;
+
+    async fn roundtrip(frame: Frame) -> Frame {
+        let bytes = frame.encode().unwrap();
+        let mut reader = &bytes[..];
+        let decoded = read_frame(&mut reader).await.unwrap().unwrap();
+        assert!(reader.is_empty());
+        decoded
+    }
+
+    #[tokio::test]
+    async fn test_frame_roundtrip() {
+        let header = FileHeader {
+            file_name: "a.txt".to_string(),
+            file_size: 3,
+            file_hash: "abcd".to_string(),
+            relative_path: PathBuf::from("alt/a.txt"),
+            metadata: FileMetadata::default(),
+        };
+        match roundtrip(Frame::File { id: 7, header }).await {
+            Frame::File { id, header } => {
+                assert_eq!(id, 7);
+                assert_eq!(header.relative_path, PathBuf::from("alt/a.txt"));
+            }
+            other => panic!("beklenmeyen çerçeve: {:?}", other),
+        }
+
+        match roundtrip(Frame::Chunk { id: 9, offset: 1 << 33, compressed: false, data: vec![1, 2, 3] }).await {
+            Frame::Chunk { id, offset, compressed, data } => assert_eq!((id, offset, compressed, data), (9, 1 << 33, false, vec![1, 2, 3])),
+            other => panic!("beklenmeyen çerçeve: {:?}", other),
+        }
+        match roundtrip(Frame::Chunk { id: 9, offset: 7, compressed: true, data: vec![4, 5] }).await {
+            Frame::Chunk { id, offset, compressed, data } => assert_eq!((id, offset, compressed, data), (9, 7, true, vec![4, 5])),
+            other => panic!("beklenmeyen çerçeve: {:?}", other),
+        }
+
+        // 32 bite sığmayan ofset ve uzunluklar
+        match roundtrip(Frame::Hole { id: 3, offset: 5 << 30, len: (1 << 32) + 7 }).await {
+            Frame::Hole { id, offset, len } => assert_eq!((id, offset, len), (3, 5 << 30, (1 << 32) + 7)),
+            other => panic!("beklenmeyen çerçeve: {:?}", other),
+        }
+
+        let entry = EntryHeader {
+            relative_path: PathBuf::from("bin/sh"),
+            kind: EntryKind::Symlink { target: PathBuf::from("bash") },
+            metadata: FileMetadata::default(),
+        };
+        match roundtrip(Frame::Entry { id: 4, entry: entry.clone() }).await {
+            Frame::Entry { id, entry: decoded } => assert_eq!((id, decoded), (4, entry)),
+            other => panic!("beklenmeyen çerçeve: {:?}", other),
+        }
+
+        let rejected = Err(Error::Integrity("hash".to_string()));
+        match roundtrip(Frame::FileResult { id: 1, result: rejected.clone() }).await {
+            Frame::FileResult { id, result } => assert_eq!((id, result), (1, rejected)),
+            other => panic!("beklenmeyen çerçeve: {:?}", other),
+        }
+        match roundtrip(Frame::FileResult { id: 2, result: Ok(()) }).await {
+            Frame::FileResult { id, result } => assert_eq!((id, result), (2, Ok(()))),
+            other => panic!("beklenmeyen çerçeve: {:?}", other),
+        }
+        match roundtrip(Frame::Error(Error::Permission("yasak".to_string()))).await {
+            Frame::Error(error) => assert_eq!(error, Error::Permission("yasak".to_string())),
+            other => panic!("beklenmeyen çerçeve: {:?}", other),
+        }
+        // Bilinmeyen hata kodu bozuk çerçevedir
+        let mut reader = &[KIND_ERROR, 0, 0, 0, 2, 9, b'x'][..];
+        assert_eq!(read_frame(&mut reader).await.unwrap_err().kind(), io::ErrorKind::InvalidData);
+
+        let request = Request::Restore { path: PathBuf::from("docs/plan.txt"), version: "20240331T120000.000000Z".to_string() };
+        match roundtrip(Frame::Request(request.clone())).await {
+            Frame::Request(decoded) => assert_eq!(decoded, request),
+            other => panic!("beklenmeyen çerçeve: {:?}", other),
+        }
+        let response = Response::Versions { versions: vec![VersionInfo { id: "20240331T120000.000000Z".to_string(), size: 1 << 33 }] };
+        match roundtrip(Frame::Response(response.clone())).await {
+            Frame::Response(decoded) => assert_eq!(decoded, response),
+            other => panic!("beklenmeyen çerçeve: {:?}", other),
+        }
+
+        let response = Response::Index { id: "20240331T120000.000000Z".to_string(), blobs: vec!["ab".repeat(32)] };
+        match roundtrip(Frame::Response(response.clone())).await {
+            Frame::Response(decoded) => assert_eq!(decoded, response),
+            other => panic!("beklenmeyen çerçeve: {:?}", other),
+        }
+        match roundtrip(Frame::Blob { id: [7; 32], data: vec![1, 2, 3] }).await {
+            Frame::Blob { id, data } => assert_eq!((id, data), ([7; 32], vec![1, 2, 3])),
+            other => panic!("beklenmeyen çerçeve: {:?}", other),
+        }
+
+        let hello = Hello { protocol: PROTOCOL_VERSION, streams: 4, join: Some("abcd".to_string()), nonce: "01".to_string(), chunk_size: 1 << 20, compression: Some("zstd".to_string()), probe: false };
+        match roundtrip(Frame::Hello(hello.clone())).await {
+            Frame::Hello(decoded) => assert_eq!(decoded, hello),
+            other => panic!("beklenmeyen çerçeve: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_handshake_proof() {
+        let key = [3u8; 32];
+        let proof = handshake_proof(&key, Role::Server, "c", "s", "oturum");
+        assert!(verify_handshake_proof(&key, Role::Server, "c", "s", "oturum", &proof));
+
+        // Rol, nonce, oturum veya anahtar farklıysa kanıt geçersiz
+        assert!(!verify_handshake_proof(&key, Role::Client, "c", "s", "oturum", &proof));
+        assert!(!verify_handshake_proof(&key, Role::Server, "c2", "s", "oturum", &proof));
+        assert!(!verify_handshake_proof(&key, Role::Server, "c", "s", "diger", &proof));
+        assert!(!verify_handshake_proof(&[4u8; 32], Role::Server, "c", "s", "oturum", &proof));
+        assert!(!verify_handshake_proof(&key, Role::Server, "c", "s", "oturum", "bozuk"));
+    }
+
+    #[tokio::test]
+    async fn test_read_frame_rejects_garbage() {
+        let mut empty: &[u8] = &[];
+        assert!(read_frame(&mut empty).await.unwrap().is_none());
+
+        let mut unknown: &[u8] = &[0x42, 0, 0, 0, 0];
+        assert!(read_frame(&mut unknown).await.is_err());
+
+        let mut oversized: &[u8] = &[KIND_CHUNK, 0xFF, 0xFF, 0xFF, 0xFF];
+        assert!(read_frame(&mut oversized).await.is_err());
+
+        let mut truncated: &[u8] = &[KIND_CHUNK, 0, 0, 0, 2, 0];
+        assert!(read_frame(&mut truncated).await.is_err());
+
+        let mut short_hole: &[u8] = &[KIND_HOLE, 0, 0, 0, 6, 0, 0, 0, 1, 0, 0];
+        assert!(read_frame(&mut short_hole).await.is_err());
+
+        let mut short_blob: &[u8] = &[KIND_BLOB, 0, 0, 0, 2, 0, 0];
+        assert!(read_frame(&mut short_blob).await.is_err());
+    }
+}
@@ -0,0 +1,379 @@
+//! QUIC bağlantı katmanı
+//!
+//! Tüm akışlar tek bir QUIC bağlantısı üzerinde ayrı çift yönlü akışlar
+//! olarak taşınır; bir akışta kaybolan paket diğerlerini bekletmez. İstemcinin
+//! adresi değiştiğinde (örn. dizüstü bilgisayar başka bir erişim noktasına
+//! geçtiğinde) bağlantı yeni yola taşınır.
+//!
+//! TLS sertifikası paylaşılan anahtarın erişim anahtarından türetilen Ed25519
+//! kimliğiyle imzalanır. Sertifika tamamen belirlenimci olduğundan istemci
+//! sunucunun sunduğu sertifikayı kendi türettiğiyle bayt bayt karşılaştırır;
+//! herhangi bir sertifika otoritesine ihtiyaç duyulmaz.
+
+use std::fmt;
+use std::future::Future;
+use std::io;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context as TaskContext, Poll};
+use std::time::Duration;
+use anyhow::{Context, Result};
+use quinn::crypto::rustls::{QuicClientConfig, QuicServerConfig};
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::crypto::CryptoProvider;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer, ServerName, UnixTime};
+use rustls::{DigitallySignedStruct, SignatureScheme};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::sync::{mpsc, Mutex};
+use crate::transport::{BoxRead, BoxWrite, Connection, Connector, Listener, Peer, Transport};
+
+/// TLS el sıkışmasında anlaşılan uygulama protokolü
+const ALPN: &[u8] = b"deltasafe";
+
+/// Sertifikada kullanılan sunucu adı; doğrulama adı değil sertifikayı kullanır
+const SERVER_NAME: &str = "deltasafe";
+
+/// Boşta bağlantıyı (ve NAT eşlemesini) canlı tutan ping aralığı
+const KEEP_ALIVE: Duration = Duration::from_secs(5);
+
+/// Bu süre boyunca karşı taraftan ses çıkmazsa bağlantı kapanır
+const IDLE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Kabul edilmiş ama henüz oturuma alınmamış akış kuyruğu
+const ACCEPT_QUEUE: usize = 64;
+
+/// Ed25519 PKCS#8 v1 önekleri; ardından 32 baytlık tohum gelir
+const ED25519_PKCS8_PREFIX: [u8; 16] = [
+    0x30, 0x2e, 0x02, 0x01, 0x00, 0x30, 0x05, 0x06, 0x03, 0x2b, 0x65, 0x70, 0x04, 0x22, 0x04, 0x20,
+];
+
+/// Erişim anahtarından türetilen TLS kimliği
+pub struct Identity {
+    pub certificate: CertificateDer<'static>,
+    pub private_key: PrivateKeyDer<'static>,
+}
+
+impl Identity {
+    /// Aynı erişim anahtarı her zaman aynı sertifikayı üretir
+    pub fn derive(access: &[u8; 32]) -> Result<Self> {
+        let seed = blake3::derive_key("deltasafe 2024 quic identity", access);
+        let mut pkcs8 = ED25519_PKCS8_PREFIX.to_vec();
+        pkcs8.extend_from_slice(&seed);
+        let pkcs8 = PrivatePkcs8KeyDer::from(pkcs8);
+        let key_pair = rcgen::KeyPair::from_pkcs8_der_and_sign_algo(&pkcs8, &rcgen::PKCS_ED25519)
+            .context("QUIC kimlik anahtarı oluşturulamadı")?;
+        // Varsayılan geçerlilik tarihleri sabittir; seri numarası açık
+        // anahtardan türetilir
+        let mut params = rcgen::CertificateParams::new(vec![SERVER_NAME.to_string()])
+            .context("Sertifika parametreleri oluşturulamadı")?;
+        params.distinguished_name = rcgen::DistinguishedName::new();
+        params.distinguished_name.push(rcgen::DnType::CommonName, SERVER_NAME);
+        let certificate = params.self_signed(&key_pair)
+            .context("QUIC sertifikası imzalanamadı")?;
+        Ok(Identity {
+            certificate: certificate.der().clone(),
+            private_key: PrivateKeyDer::Pkcs8(pkcs8),
+        })
+    }
+}
+
+fn provider() -> Arc<CryptoProvider> {
+    Arc::new(rustls::crypto::ring::default_provider())
+}
+
+fn transport_config() -> Arc<quinn::TransportConfig> {
+    let mut config = quinn::TransportConfig::default();
+    config.keep_alive_interval(Some(KEEP_ALIVE));
+    config.max_idle_timeout(Some(IDLE_TIMEOUT.try_into().expect("Geçerli boşta kalma süresi")));
+    Arc::new(config)
+}
+
+/// Sunucu yapılandırması: yalnızca TLS 1.3, türetilmiş kimlik ve bağlantı taşıma
+pub fn server_config(access: &[u8; 32]) -> Result<quinn::ServerConfig> {
+    let identity = Identity::derive(access)?;
+    let mut tls = rustls::ServerConfig::builder_with_provider(provider())
+        .with_protocol_versions(&[&rustls::version::TLS13])
+        .context("TLS 1.3 desteklenmiyor")?
+        .with_no_client_auth()
+        .with_single_cert(vec![identity.certificate], identity.private_key)
+        .context("QUIC sertifikası yüklenemedi")?;
+    tls.alpn_protocols = vec![ALPN.to_vec()];
+    let crypto = QuicServerConfig::try_from(tls)
+        .context("QUIC TLS yapılandırması oluşturulamadı")?;
+    let mut config = quinn::ServerConfig::with_crypto(Arc::new(crypto));
+    config.transport_config(transport_config());
+    config.migration(true);
+    Ok(config)
+}
+
+/// İstemci yapılandırması: yalnızca anahtardan türetilen sertifika kabul edilir
+pub fn client_config(access: &[u8; 32]) -> Result<quinn::ClientConfig> {
+    let identity = Identity::derive(access)?;
+    let provider = provider();
+    let verifier = PinnedCertificate { expected: identity.certificate, provider: provider.clone() };
+    let mut tls = rustls::ClientConfig::builder_with_provider(provider)
+        .with_protocol_versions(&[&rustls::version::TLS13])
+        .context("TLS 1.3 desteklenmiyor")?
+        .dangerous()
+        .with_custom_certificate_verifier(Arc::new(verifier))
+        .with_no_client_auth();
+    tls.alpn_protocols = vec![ALPN.to_vec()];
+    let crypto = QuicClientConfig::try_from(tls)
+        .context("QUIC TLS yapılandırması oluşturulamadı")?;
+    let mut config = quinn::ClientConfig::new(Arc::new(crypto));
+    config.transport_config(transport_config());
+    Ok(config)
+}
+
+/// Sunucu sertifikasını beklenen sertifikayla karşılaştıran doğrulayıcı
+#[derive(Debug)]
+struct PinnedCertificate {
+    expected: CertificateDer<'static>,
+    provider: Arc<CryptoProvider>,
+}
+
+impl ServerCertVerifier for PinnedCertificate {
+    fn verify_server_cert(&self, end_entity: &CertificateDer<'_>, _intermediates: &[CertificateDer<'_>], _server_name: &ServerName<'_>, _ocsp_response: &[u8], _now: UnixTime) -> Result<ServerCertVerified, rustls::Error> {
+        if end_entity.as_ref() != self.expected.as_ref() {
+            return Err(rustls::Error::General("Sunucu sertifikası anahtarla eşleşmiyor".to_string()));
+        }
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(&self, _message: &[u8], _cert: &CertificateDer<'_>, _dss: &DigitallySignedStruct) -> Result<HandshakeSignatureValid, rustls::Error> {
+        Err(rustls::Error::General("TLS 1.2 desteklenmiyor".to_string()))
+    }
+
+    fn verify_tls13_signature(&self, message: &[u8], cert: &CertificateDer<'_>, dss: &DigitallySignedStruct) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls13_signature(message, cert, dss, &self.provider.signature_verification_algorithms)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.provider.signature_verification_algorithms.supported_schemes()
+    }
+}
+
+/// Bir QUIC bağlantısındaki çift yönlü akış
+pub struct QuicStream {
+    send: quinn::SendStream,
+    recv: quinn::RecvStream,
+    remote: SocketAddr,
+}
+
+impl AsyncRead for QuicStream {
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut TaskContext<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.recv).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for QuicStream {
+    fn poll_write(mut self: Pin<&mut Self>, cx: &mut TaskContext<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        AsyncWrite::poll_write(Pin::new(&mut self.send), cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<io::Result<()>> {
+        AsyncWrite::poll_flush(Pin::new(&mut self.send), cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<io::Result<()>> {
+        AsyncWrite::poll_shutdown(Pin::new(&mut self.send), cx)
+    }
+}
+
+impl Transport for QuicStream {
+    fn peer(&self) -> Peer {
+        Peer::Quic(self.remote)
+    }
+
+    fn into_split(self) -> (BoxRead, BoxWrite) {
+        (Box::new(self.recv), Box::new(self.send))
+    }
+}
+
+/// Gelen bağlantılardaki akışları kabul eden sunucu ucu
+pub struct QuicListener {
+    endpoint: quinn::Endpoint,
+    streams: mpsc::Receiver<Connection>,
+}
+
+impl QuicListener {
+    pub fn bind(addr: SocketAddr, access: &[u8; 32]) -> Result<Self> {
+        let endpoint = quinn::Endpoint::server(server_config(access)?, addr)
+            .with_context(|| format!("QUIC soketi açılamadı: {}", addr))?;
+        let (tx, streams) = mpsc::channel(ACCEPT_QUEUE);
+        tokio::spawn(accept_connections(endpoint.clone(), tx));
+        Ok(QuicListener { endpoint, streams })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.endpoint.local_addr()
+    }
+
+    /// Açık bağlantıları kapatır ve karşı tarafın bunu öğrenmesini bekler
+    pub async fn close(self) {
+        self.endpoint.close(0u32.into(), b"kapatiliyor");
+        self.endpoint.wait_idle().await;
+    }
+}
+
+impl Listener for QuicListener {
+    async fn accept(&mut self) -> io::Result<Connection> {
+        self.streams.accept().await
+    }
+}
+
+async fn accept_connections(endpoint: quinn::Endpoint, streams: mpsc::Sender<Connection>) {
+    while let Some(incoming) = endpoint.accept().await {
+        let streams = streams.clone();
+        tokio::spawn(async move {
+            let connection = match incoming.await {
+                Ok(connection) => connection,
+                Err(e) => {
+                    log::warn!("[⚠️] QUIC bağlantısı kurulamadı: {}", e);
+                    return;
+                }
+            };
+            // Her çift yönlü akış oturuma ayrı bir bağlantı gibi katılır
+            while let Ok((send, recv)) = connection.accept_bi().await {
+                let stream = QuicStream { send, recv, remote: connection.remote_address() };
+                if streams.send(Connection::new(stream)).await.is_err() {
+                    break;
+                }
+            }
+        });
+    }
+}
+
+/// Tek bir QUIC bağlantısını paylaşarak her istekte yeni akış açan bağlayıcı
+pub struct QuicConnector {
+    addrs: Vec<SocketAddr>,
+    config: quinn::ClientConfig,
+    connection: Mutex<Option<(quinn::Endpoint, quinn::Connection)>>,
+}
+
+impl QuicConnector {
+    pub fn new(addrs: Vec<SocketAddr>, access: &[u8; 32]) -> Result<Self> {
+        if addrs.is_empty() {
+            anyhow::bail!("QUIC hedefi için adres bulunamadı");
+        }
+        Ok(QuicConnector { addrs, config: client_config(access)?, connection: Mutex::new(None) })
+    }
+
+    async fn open(&self) -> io::Result<Connection> {
+        let mut slot = self.connection.lock().await;
+        let reusable = slot.as_ref().is_some_and(|(_, connection)| connection.close_reason().is_none());
+        if !reusable {
+            *slot = Some(self.establish().await?);
+        }
+        let (_, connection) = slot.as_ref().expect("Bağlantı kuruldu");
+        let (send, recv) = connection.open_bi().await.map_err(io::Error::other)?;
+        Ok(Connection::new(QuicStream { send, recv, remote: connection.remote_address() }))
+    }
+
+    /// Açık bağlantıyı yeni bir yerel sokete taşır (örn. ağ değiştiğinde
+    /// işletim sisteminin bildirmesini beklemeden). Açık akışlar kesilmez.
+    pub async fn rebind(&self, socket: std::net::UdpSocket) -> io::Result<()> {
+        match self.connection.lock().await.as_ref() {
+            Some((endpoint, _)) => endpoint.rebind(socket),
+            None => Err(io::Error::new(io::ErrorKind::NotConnected, "QUIC bağlantısı yok")),
+        }
+    }
+
+    /// Adresleri sırayla dener; ilk başarılı bağlantı kullanılır
+    async fn establish(&self) -> io::Result<(quinn::Endpoint, quinn::Connection)> {
+        let mut last_error = io::Error::new(io::ErrorKind::NotFound, "adres yok");
+        for addr in &self.addrs {
+            let local: SocketAddr = match addr {
+                SocketAddr::V4(_) => (std::net::Ipv4Addr::UNSPECIFIED, 0).into(),
+                SocketAddr::V6(_) => (std::net::Ipv6Addr::UNSPECIFIED, 0).into(),
+            };
+            let endpoint = quinn::Endpoint::client(local)?;
+            let connecting = endpoint.connect_with(self.config.clone(), *addr, SERVER_NAME)
+                .map_err(io::Error::other)?;
+            match connecting.await {
+                Ok(connection) => return Ok((endpoint, connection)),
+                Err(e) => last_error = io::Error::new(io::ErrorKind::ConnectionRefused, e),
+            }
+        }
+        Err(last_error)
+    }
+}
+
+impl fmt::Display for QuicConnector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "quic://{}", self.addrs[0])
+    }
+}
+
+impl Connector for QuicConnector {
+    fn connect(&self) -> Pin<Box<dyn Future<Output = io::Result<Connection>> + Send + '_>> {
+        Box::pin(self.open())
+    }
+
+    fn multiplexed(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Error;
+    use crate::protocol::{self, Frame};
+
+    #[test]
+    fn test_identity_is_deterministic() {
+        let first = Identity::derive(&[1u8; 32]).unwrap();
+        let again = Identity::derive(&[1u8; 32]).unwrap();
+        let other = Identity::derive(&[2u8; 32]).unwrap();
+        assert_eq!(first.certificate, again.certificate);
+        assert_ne!(first.certificate, other.certificate);
+    }
+
+    #[tokio::test]
+    async fn test_streams_share_one_connection() {
+        let access = [3u8; 32];
+        let mut listener = QuicListener::bind("127.0.0.1:0".parse().unwrap(), &access).unwrap();
+        let addr = listener.local_addr().unwrap();
+        let connector = QuicConnector::new(vec![addr], &access).unwrap();
+        assert_eq!(connector.to_string(), format!("quic://{}", addr));
+
+        let mut clients = Vec::new();
+        for i in 0..3 {
+            let mut client = connector.connect().await.unwrap();
+            assert_eq!(client.peer, Peer::Quic(addr));
+            protocol::write_frame(&mut client.writer, &Frame::Error(Error::Io(format!("akış {}", i)))).await.unwrap();
+            clients.push(client);
+        }
+        let mut remotes = Vec::new();
+        for i in 0..3 {
+            let mut server = listener.accept().await.unwrap();
+            match protocol::read_frame(&mut server.reader).await.unwrap() {
+                Some(Frame::Error(error)) => assert_eq!(error.message(), format!("akış {}", i)),
+                other => panic!("beklenmeyen çerçeve: {:?}", other),
+            }
+            protocol::write_frame(&mut server.writer, &Frame::Error(Error::Io("tamam".to_string()))).await.unwrap();
+            remotes.push(server.peer.clone());
+        }
+        // Tüm akışlar aynı istemci soketinden gelir
+        assert!(remotes.windows(2).all(|pair| pair[0] == pair[1]));
+        for client in &mut clients {
+            match protocol::read_frame(&mut client.reader).await.unwrap() {
+                Some(Frame::Error(error)) => assert_eq!(error.message(), "tamam"),
+                other => panic!("beklenmeyen çerçeve: {:?}", other),
+            }
+        }
+        listener.close().await;
+    }
+
+    #[tokio::test]
+    async fn test_wrong_key_rejected() {
+        let mut listener = QuicListener::bind("127.0.0.1:0".parse().unwrap(), &[4u8; 32]).unwrap();
+        let addr = listener.local_addr().unwrap();
+        let connector = QuicConnector::new(vec![addr], &[5u8; 32]).unwrap();
+        assert!(connector.connect().await.is_err());
+        // Sunucu tarafına hiçbir akış ulaşmaz
+        assert!(tokio::time::timeout(Duration::from_millis(200), listener.accept()).await.is_err());
+    }
+}
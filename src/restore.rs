@@ -0,0 +1,123 @@
+//! Sunucudan geri yükleme
+//!
+//! İstemci sunucuyla normal el sıkışmayı yapar, ardından dosya göndermek
+//! yerine `Request` çerçeveleriyle sürüm listesi veya sürüm içeriği ister.
+//! İçerik `File` + `Chunk` çerçeveleriyle gelir; geçici dosyaya yazılır,
+//! hash doğrulanınca hedef isme taşınır.
+
+use std::path::Path;
+use std::time::Duration;
+use anyhow::{Context, Result};
+use tokio::io::AsyncWriteExt;
+use crate::crypto;
+use crate::error::Error;
+use crate::metadata::{self, Preserve};
+use crate::protocol::{self, Frame, Request, Response};
+use crate::server::temp_path_for;
+use crate::sync::{calculate_file_hash, open_stream, FileHeader, Stream, SyncOptions};
+use crate::tls::ClientTls;
+use crate::transport::{self, BoxRead};
+use crate::versions::VersionInfo;
+
+/// Sunucu yanıtı için en fazla bekleme; sürümün hash'i önce hesaplanır
+pub const RESPONSE_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Tek akışlı, kimliği doğrulanmış bir oturum açar
+pub async fn connect(target: &str, key: &[u8; 32], tls: Option<&ClientTls>) -> Result<Stream> {
+    let connector = transport::connector(target, key, tls)?;
+    open_stream(connector.as_ref(), key, 1, &SyncOptions::default(), None).await
+}
+
+pub async fn send_request(stream: &mut Stream, request: Request) -> Result<()> {
+    tokio::time::timeout(RESPONSE_TIMEOUT, protocol::write_frame(&mut stream.writer, &Frame::Request(request))).await
+        .context("İstek zaman aşımına uğradı")?
+        .context("İstek gönderilemedi")
+}
+
+/// Sunucunun bir sonraki yanıtını okur; `Error` çerçevesi, türü korunarak
+/// hataya çevrilir
+pub async fn read_reply(reader: &mut BoxRead) -> Result<Frame> {
+    let frame = tokio::time::timeout(RESPONSE_TIMEOUT, protocol::read_frame(reader)).await
+        .context("Sunucu yanıt vermedi")?
+        .context("Sunucu yanıtı okunamadı")?;
+    match frame {
+        Some(Frame::Error(error)) => Err(anyhow::Error::new(error).context("Sunucu isteği reddetti")),
+        Some(frame) => Ok(frame),
+        None => anyhow::bail!(Error::Network("Sunucu bağlantıyı kapattı".to_string())),
+    }
+}
+
+/// İsteğe uymayan sunucu yanıtı
+pub fn unexpected_reply(frame: Frame) -> Error {
+    Error::Protocol(format!("Beklenmeyen sunucu yanıtı: {:?}", frame))
+}
+
+/// Sunucuda saklanan, yola ait sürümleri yeniden eskiye listeler
+pub async fn list_versions(target: &str, key: &[u8; 32], tls: Option<&ClientTls>, path: &Path) -> Result<Vec<VersionInfo>, Error> {
+    let mut stream = connect(target, key, tls).await?;
+    send_request(&mut stream, Request::Versions { path: path.to_path_buf() }).await?;
+    match read_reply(&mut stream.reader).await? {
+        Frame::Response(Response::Versions { versions }) => Ok(versions),
+        other => Err(unexpected_reply(other)),
+    }
+}
+
+/// Yolun verilen sürümünü sunucudan indirip `output` dosyasına yazar.
+/// Hash doğrulanmadan hedef dosyaya dokunulmaz. Yazılan bayt sayısını döner.
+pub async fn restore_version(target: &str, key: &[u8; 32], tls: Option<&ClientTls>, path: &Path, version: &str, output: &Path) -> Result<u64, Error> {
+    let mut stream = connect(target, key, tls).await?;
+    send_request(&mut stream, Request::Restore { path: path.to_path_buf(), version: version.to_string() }).await?;
+    let header = match read_reply(&mut stream.reader).await? {
+        Frame::File { header, .. } => header,
+        other => return Err(unexpected_reply(other)),
+    };
+    receive_file(&mut stream.reader, key, &header, output).await?;
+    Ok(header.file_size)
+}
+
+/// Dosyanın chunk'larını sırayla alır, doğrular ve yerine taşır
+async fn receive_file(reader: &mut BoxRead, key: &[u8; 32], header: &FileHeader, output: &Path) -> Result<()> {
+    let temp_path = temp_path_for(output);
+    let received = write_chunks(reader, key, header, &temp_path).await;
+    let verified = received.and_then(|()| {
+        let hash = calculate_file_hash(&temp_path)
+            .context("Geri yüklenen dosyanın hash'i hesaplanamadı")?;
+        if hash != header.file_hash {
+            anyhow::bail!(Error::Integrity(format!("Hash doğrulaması başarısız! Beklenen: {}, Hesaplanan: {}", header.file_hash, hash)));
+        }
+        Ok(())
+    });
+    if let Err(e) = verified {
+        let _ = tokio::fs::remove_file(&temp_path).await;
+        return Err(e);
+    }
+
+    tokio::fs::rename(&temp_path, output).await
+        .with_context(|| format!("Dosya yerine taşınamadı: {}", output.display()))?;
+    if let Err(e) = metadata::apply(output, &header.metadata, Preserve::default()) {
+        log::warn!("[⚠️] '{}' öznitelikleri uygulanamadı: {}", output.display(), e);
+    }
+    Ok(())
+}
+
+async fn write_chunks(reader: &mut BoxRead, key: &[u8; 32], header: &FileHeader, temp_path: &Path) -> Result<()> {
+    let mut file = tokio::fs::File::create(temp_path).await
+        .with_context(|| format!("Dosya oluşturulamadı: {}", temp_path.display()))?;
+    let mut position = 0u64;
+    while position < header.file_size {
+        let (offset, data) = match read_reply(reader).await? {
+            Frame::Chunk { id: 0, offset, compressed: false, data } => (offset, data),
+            other => anyhow::bail!(unexpected_reply(other)),
+        };
+        let data = crypto::open(&data, key)
+            .ok_or_else(|| Error::Integrity("Chunk çözülemedi (anahtar uyuşmuyor olabilir)".to_string()))?;
+        if offset != position || data.len() as u64 > header.file_size - position {
+            anyhow::bail!(Error::Protocol(format!("Sunucu geçersiz aralık gönderdi: {} (+{} bayt)", offset, data.len())));
+        }
+        file.write_all(&data).await
+            .context("Veri dosyaya yazılamadı")?;
+        position += data.len() as u64;
+    }
+    file.sync_all().await
+        .context("Dosya diske yazılamadı")
+}
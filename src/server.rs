@@ -1,170 +1,2854 @@
-use std::fs::{self, OpenOptions};
-use std::io::{Write, Read, BufReader};
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::net::SocketAddr;
 use std::path::{Path, PathBuf};
-use std::net::{TcpListener, TcpStream};
-use aes::Aes256;
-use cipher::{block_padding::Pkcs7, BlockDecryptMut, KeyIvInit};
-use serde::{Serialize, Deserialize};
-use serde_json;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::time::Duration;
 use blake3;
+use anyhow::{Result, Context};
+use tokio::fs;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio::sync::{mpsc, watch, Mutex, MutexGuard, Semaphore};
+use tokio::task::JoinSet;
+use crate::beacon::spawn_beacon;
+use crate::chunk::{CHUNK_SIZE, MAX_CHUNK_SIZE, MIN_CHUNK_SIZE};
+use crate::compress::{decompress_chunk, ZSTD};
+use crate::bandwidth::{Bandwidth, RateLimiter};
+use crate::metadata::{self, FileMetadata, Preserve};
+use crate::xattrs::{self, Xattr, XattrFilter};
+use crate::versions::{self, Retention, VersionInfo};
+use crate::vault;
+use crate::storage::{LocalStorage, Storage, TempWrite};
+use crate::entry::{is_safe_relative_path, is_safe_symlink_target, EntryHeader, EntryKind};
+use crate::crypto::{self, access_key, random_nonce, IV_SIZE};
+use crate::discovery::advertise_service;
+use crate::error::Error;
+use crate::net;
+use crate::quic::QuicListener;
+use crate::tls::ServerTls;
+use crate::events::{EventKind, Events};
+use crate::transport::{BoxRead, BoxWrite, Connection, Listener, Peer};
+use crate::protocol::{self, handshake_proof, verify_handshake_proof, Frame, Request, Response, Role, Welcome, PROTOCOL_VERSION};
+use crate::sync::{calculate_file_hash, encode_chunk, FileHeader};
 
-// Yeni tip tanımı: CBC ile AES256
-// Aes256Cbc = Cbc<Aes256, Pkcs7>
-type Aes256CbcDec = cbc::Decryptor<Aes256>;
+/// Bir oturumda aynı anda açık tutulabilecek en fazla dosya
+const MAX_OPEN_FILES: usize = 256;
 
-#[derive(Serialize, Deserialize, Debug)]
-struct FileHeader {
-    file_name: String,
-    file_size: u64,
-    file_hash: String,
-    relative_path: PathBuf,
+/// Okuyucu görevden oturum döngüsüne giden çerçeve kuyruğunun derinliği
+const FRAME_QUEUE_DEPTH: usize = 8;
+
+/// Geçici dosyaların uzantısı; hash doğrulanınca asıl isme taşınır
+pub const TEMP_SUFFIX: &str = ".deltasafe-tmp";
+
+/// Sunucu ayarları
+#[derive(Debug, Clone)]
+pub struct ServerOptions {
+    /// mDNS ve beacon ile duyurulan sunucu (paylaşım) adı
+    pub name: String,
+    /// Alınan dosyaların kaydedildiği dizin
+    pub root: PathBuf,
+    /// Alınan içeriğin yazıldığı depolama; verilmezse `root` altındaki yerel
+    /// dosya sistemi kullanılır
+    pub storage: Option<Arc<dyn Storage>>,
+    /// Aynı anda hizmet verilen en fazla oturum; dolunca yeni bağlantılar
+    /// kabul edilmez ve işletim sisteminin bekleme kuyruğunda kalır
+    pub max_sessions: usize,
+    /// Bağlantı başına okuma/yazma zaman aşımı
+    pub io_timeout: Duration,
+    /// Bir istemcinin açabileceği en fazla paralel akış. Her akış bir
+    /// oturum yeri kullanır
+    pub max_streams: usize,
+    /// Pazarlıkta izin verilen en büyük chunk boyutu
+    pub max_chunk_size: usize,
+    /// İstemci isterse sıkıştırılmış chunk'lar kabul edilir
+    pub compression: bool,
+    /// İstemci oturumu başına alım hızı sınırı (tüm akışlar toplamı)
+    pub client_bwlimit: Bandwidth,
+    /// Dosya yerine taşındıktan sonra uygulanan öznitelikler
+    pub preserve: Preserve,
+    /// Uygulanan xattr ad alanları; diğerleri yok sayılır
+    pub xattrs: XattrFilter,
+    /// Üzerine yazılan dosyaların eski içerikleri için saklama kuralları;
+    /// varsayılan olarak sürüm tutulmaz
+    pub retention: Retention,
+    /// Kasa modu: dosya yerine istemcinin şifrelediği parçalar ve indeks
+    /// saklanır, sunucu hiçbir içeriği çözmez
+    pub vault: bool,
+    /// Verilen anahtar yalnızca erişim anahtarıdır ([`access_key`]); içerik
+    /// çözülemeyeceği için kasa modu gerekir
+    pub access_only: bool,
+    /// Verilirse her bağlantı önce TLS 1.3 el sıkışmasından geçer
+    pub tls: Option<ServerTls>,
+    /// Oturum ve dosya olaylarının iletildiği işleyici (varsayılan: yok)
+    pub events: Events,
 }
 
-fn decrypt_chunk(ciphertext: &[u8], key: &[u8; 32], iv: &[u8]) -> Vec<u8> {
-    let mut buf = ciphertext.to_vec();
-    let cipher = Aes256CbcDec::new(key.into(), iv.into());
-    let decrypted = cipher.decrypt_padded_mut::<Pkcs7>(&mut buf).unwrap();
-    decrypted.to_vec()
+impl Default for ServerOptions {
+    fn default() -> Self {
+        Self {
+            name: net::local_hostname(),
+            root: PathBuf::from("received_files"),
+            storage: None,
+            max_sessions: 64,
+            io_timeout: Duration::from_secs(30),
+            max_streams: 8,
+            max_chunk_size: MAX_CHUNK_SIZE,
+            compression: true,
+            client_bwlimit: Bandwidth::Unlimited,
+            preserve: Preserve::default(),
+            xattrs: XattrFilter::default(),
+            retention: Retention::default(),
+            vault: false,
+            access_only: false,
+            tls: None,
+            events: Events::default(),
+        }
+    }
 }
 
-/// Bir dosyanın tamamının BLAKE3 hash'ini hesaplar.
-fn calculate_file_hash(path: &Path) -> Result<String, std::io::Error> {
-    let file = fs::File::open(path)?;
-    let mut reader = BufReader::new(file);
-    let mut hasher = blake3::Hasher::new();
-    let mut buffer = [0; 4096];
+impl ServerOptions {
+    /// Kullanılan depolama
+    pub fn storage(&self) -> Arc<dyn Storage> {
+        match &self.storage {
+            Some(storage) => storage.clone(),
+            None => Arc::new(LocalStorage::new(&self.root)),
+        }
+    }
+}
 
-    loop {
-        let bytes_read = reader.read(&mut buffer)?;
-        if bytes_read == 0 {
-            break;
+/// Zaman aşımını `io::ErrorKind::TimedOut` hatasına çevirir
+async fn timed<T>(limit: Duration, operation: impl Future<Output = std::io::Result<T>>) -> std::io::Result<T> {
+    tokio::time::timeout(limit, operation)
+        .await
+        .unwrap_or_else(|_| Err(std::io::Error::new(std::io::ErrorKind::TimedOut, "zaman aşımı")))
+}
+
+/// Dosya veya klasöre yerleştikten sonra uygulanacak öznitelikler
+#[derive(Debug, Clone)]
+struct Attributes {
+    preserve: Preserve,
+    /// Gönderilen xattr'lar ve uygulanacak ad alanları; istemci liste
+    /// göndermediyse mevcut xattr'lara dokunulmaz
+    xattrs: Option<(Vec<Xattr>, XattrFilter)>,
+}
+
+impl Attributes {
+    /// Sunucu ayarlarına göre gönderilen xattr listesini çözer
+    fn unseal(metadata: &FileMetadata, options: &ServerOptions, key: &[u8; 32]) -> Result<Self, Error> {
+        let xattrs = match &metadata.xattrs {
+            Some(sealed) if !options.xattrs.is_empty() => Some((xattrs::open(sealed, key)?, options.xattrs.clone())),
+            _ => None,
+        };
+        Ok(Self { preserve: options.preserve, xattrs })
+    }
+
+    /// Öznitelikleri uygular. Kullanıcı xattr'ları yazma izni istediği için
+    /// izinlerden önce yazılır. Uygulanamayanlar hata mesajı olarak döner.
+    fn apply(&self, path: &Path, metadata: &FileMetadata) -> Result<(), String> {
+        let mut errors = Vec::new();
+        if let Some((attrs, filter)) = &self.xattrs
+            && let Err(e) = xattrs::apply(path, attrs, filter)
+        {
+            errors.push(format!("xattr: {}", e));
+        }
+        if let Err(e) = metadata::apply(path, metadata, self.preserve) {
+            errors.push(e);
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors.join(", "))
         }
-        hasher.update(&buffer[..bytes_read]);
     }
-    Ok(hasher.finalize().to_hex().to_string())
 }
 
-fn handle_client(mut stream: TcpStream, key: &[u8; 32]) {
-    println!("[📥] Bağlantı alındı.");
+/// Chunk'ları gelmekte olan dosya. Büyük dosyaların aralıkları farklı
+/// akışlardan gelebildiği için veri ofsetine yazılır.
+struct IncomingFile {
+    header: FileHeader,
+    storage: Arc<dyn Storage>,
+    /// Tamamlanınca veya reddedilince `None` olur
+    temp: Option<Box<dyn TempWrite>>,
+    received: u64,
+    /// Sıralı gelen veri için artımlı hash; sıra bozulursa commit
+    /// sırasında geçici yazım baştan hash'lenir
+    hasher: blake3::Hasher,
+    hashed_upto: u64,
+    /// Yerine yerleştikten sonra uygulanacak öznitelikler
+    attributes: Attributes,
+    /// Üzerine yazılan içeriğin sürüm olarak saklanması için kurallar
+    retention: Retention,
+}
+
+impl IncomingFile {
+    /// Depolamada geçici yazımı açar
+    async fn create(header: FileHeader, storage: Arc<dyn Storage>, attributes: Attributes, retention: Retention) -> Result<Self, Error> {
+        let unsafe_path = match storage.local_root() {
+            Some(root) => has_symlink_ancestor(root, &header.relative_path).await,
+            None => false,
+        };
+        if !is_safe_relative_path(&header.relative_path) || versions::is_reserved(&header.relative_path) || unsafe_path {
+            return Err(Error::Permission(format!("Geçersiz dosya yolu: {}", header.relative_path.display())));
+        }
 
-    // 1. Başlık uzunluğunu oku (4 bayt)
-    let mut header_len_bytes = [0; 4];
-    if stream.read_exact(&mut header_len_bytes).is_err() {
-        println!("[⚠️] Başlık uzunluğu okunamadı.");
-        return;
+        // Veri önce geçici alana yazılır, hash doğrulanınca asıl isme taşınır.
+        // Baştan tam boyutta açıldığı için `Hole` ile bildirilen bölgelere
+        // hiç yazılmaz.
+        let (task_storage, relative, size) = (storage.clone(), header.relative_path.clone(), header.file_size);
+        let temp = tokio::task::spawn_blocking(move || task_storage.open_temp(&relative, size)).await
+            .map_err(|e| Error::Io(e.to_string()))?
+            .map_err(|e| Error::io("Dosya oluşturulamadı", &e))?;
+
+        Ok(Self {
+            header,
+            storage,
+            temp: Some(temp),
+            received: 0,
+            hasher: blake3::Hasher::new(),
+            hashed_upto: 0,
+            attributes,
+            retention,
+        })
     }
-    let header_len = u32::from_be_bytes(header_len_bytes) as usize;
 
-    // 2. Başlığı oku
-    let mut header_buffer = vec![0; header_len];
-    if stream.read_exact(&mut header_buffer).is_err() {
-        println!("[⚠️] Başlık okunamadı.");
-        return;
+    fn is_complete(&self) -> bool {
+        self.received == self.header.file_size
     }
 
-    // 3. Başlığı deserialize et
-    let header: FileHeader = match serde_json::from_slice(&header_buffer) {
-        Ok(h) => h,
-        Err(e) => {
-            println!("[⚠️] Başlık deserialize edilemedi: {}", e);
-            return;
+    fn is_finished(&self) -> bool {
+        self.temp.is_none()
+    }
+
+    /// Çözülmüş veriyi verilen ofsete yazar. Yazım başarısız olursa geçici
+    /// yazım bırakılır.
+    async fn write_range(&mut self, offset: u64, data: Vec<u8>, io_timeout: Duration) -> Result<(), Error> {
+        let end = offset.checked_add(data.len() as u64)
+            .filter(|end| *end <= self.header.file_size)
+            .ok_or_else(|| Error::Protocol("Dosya başlıkta bildirilen boyuttan büyük".to_string()))?;
+        let mut temp = self.temp.take().ok_or_else(closed)?;
+
+        if offset == self.hashed_upto {
+            self.hasher.update(&data);
+            self.hashed_upto = end;
         }
-    };
+        let len = data.len() as u64;
+        let write = tokio::task::spawn_blocking(move || temp.write_at(offset, &data).map(|()| temp));
+        let written = timed(io_timeout, async { write.await.map_err(std::io::Error::other)? }).await
+            .map_err(|e| Error::io("Veri dosyaya yazılamadı", &e))?;
+        self.temp = Some(written);
+        self.received += len;
+        Ok(())
+    }
+
+    /// Delik olarak bildirilen bölgeyi alınmış sayar; geçici yazım baştan
+    /// tam boyutta açıldığı için yazılacak bir şey yoktur
+    fn skip_range(&mut self, offset: u64, len: u64) -> Result<(), Error> {
+        let end = offset.checked_add(len)
+            .filter(|end| *end <= self.header.file_size)
+            .ok_or_else(|| Error::Protocol("Delik dosya boyutunu aşıyor".to_string()))?;
+        if self.is_finished() {
+            return Err(closed());
+        }
+
+        if offset == self.hashed_upto {
+            static ZEROS: [u8; 64 * 1024] = [0; 64 * 1024];
+            let mut remaining = len;
+            while remaining > 0 {
+                let step = remaining.min(ZEROS.len() as u64) as usize;
+                self.hasher.update(&ZEROS[..step]);
+                remaining -= step as u64;
+            }
+            self.hashed_upto = end;
+        }
+        self.received += len;
+        Ok(())
+    }
+
+    /// Hash'i doğrular; başarılıysa içeriği atomik olarak yerine yerleştirir.
+    /// Başarısızlıkta geçici yazım bırakılır.
+    async fn commit(&mut self) -> Result<(), Error> {
+        let Some(mut temp) = self.temp.take() else {
+            return Err(closed());
+        };
+
+        let calculated_hash = if self.hashed_upto == self.header.file_size {
+            self.hasher.finalize().to_hex().to_string()
+        } else {
+            // Aralıklar sırasız geldi; birleşmiş içeriği baştan hash'le
+            match tokio::task::spawn_blocking(move || temp.hash().map(|hash| (temp, hash))).await {
+                Ok(Ok((hashed, hash))) => {
+                    temp = hashed;
+                    hash
+                }
+                _ => return Err(Error::Io("Geçici dosyanın hash'i hesaplanamadı".to_string())),
+            }
+        };
+        if calculated_hash != self.header.file_hash {
+            log::error!("[❌] Dosya hash doğrulaması BAŞARISIZ! Beklenen: {}, Hesaplanan: {}", self.header.file_hash, calculated_hash);
+            return Err(Error::Integrity("Hash doğrulaması başarısız".to_string()));
+        }
+
+        // Eski içerik silinmeden önce sürüm olarak saklanır; sürümler yalnızca
+        // yerel depolamada açılabilir
+        let root = self.storage.local_root().map(Path::to_path_buf);
+        if let Some(root) = &root
+            && self.retention.is_enabled()
+        {
+            let (root, relative, retention) = (root.clone(), self.header.relative_path.clone(), self.retention);
+            let new_hash = calculated_hash.clone();
+            let archived = tokio::task::spawn_blocking(move || versions::archive(&root, &relative, &new_hash, retention, chrono::Utc::now())).await
+                .unwrap_or_else(|e| Err(std::io::Error::other(e.to_string())));
+            match archived {
+                Ok(Some(id)) => log::info!("[🗂️] '{}' önceki içeriği {} sürümü olarak saklandı.", self.header.relative_path.display(), id),
+                Ok(None) => {}
+                Err(e) => return Err(Error::io("Önceki sürüm saklanamadı", &e)),
+            }
+        }
+        tokio::task::spawn_blocking(move || temp.commit()).await
+            .map_err(|e| Error::Io(e.to_string()))?
+            .map_err(|e| Error::io("Dosya yerine taşınamadı", &e))?;
+
+        // İçerik doğrulandı; uygulanamayan öznitelikler dosyayı reddettirmez
+        if let Some(root) = root {
+            let full_path = root.join(&self.header.relative_path);
+            let file_metadata = self.header.metadata.clone();
+            let attributes = self.attributes.clone();
+            let applied = tokio::task::spawn_blocking(move || attributes.apply(&full_path, &file_metadata)).await
+                .unwrap_or_else(|e| Err(e.to_string()));
+            if let Err(e) = applied {
+                log::warn!("[⚠️] '{}' öznitelikleri uygulanamadı: {}", self.header.relative_path.display(), e);
+            }
+        }
+
+        log::info!("[📂] Dosya '{}' başarıyla alındı ve kaydedildi. Toplam {} bayt.", self.header.file_name, self.received);
+        log::info!("[✅] Dosya hash doğrulaması başarılı: {}", calculated_hash);
+        Ok(())
+    }
+
+    /// Geçici yazımı bırakır
+    async fn abort(&mut self) {
+        if let Some(temp) = self.temp.take() {
+            let _ = tokio::task::spawn_blocking(move || drop(temp)).await;
+        }
+    }
+}
+
+/// Reddedilmiş veya tamamlanmış dosyaya yeniden yazılmaya çalışıldı
+fn closed() -> Error {
+    Error::Protocol("Dosya zaten kapatıldı".to_string())
+}
+
+/// Göreli yolun kökteki mevcut üst klasörlerinden biri sembolik bağlantı
+/// mı. Bağlantı üzerinden yazmak, hedefi kökte kalan bağlantıların
+/// zincirlenmesiyle kökün dışına çıkabilir.
+async fn has_symlink_ancestor(root: &Path, relative: &Path) -> bool {
+    let mut current = root.to_path_buf();
+    let Some(parent) = relative.parent() else { return false };
+    for component in parent.components() {
+        current.push(component);
+        match fs::symlink_metadata(&current).await {
+            Ok(metadata) if metadata.file_type().is_symlink() => return true,
+            Ok(_) => {}
+            // Henüz yoksa altı da yoktur
+            Err(_) => return false,
+        }
+    }
+    false
+}
 
-    println!("[📄] Alınan dosya başlığı: {:?}", header);
+/// Aktarımın dosya durumu
+#[derive(Default)]
+struct TransferFiles {
+    open: HashMap<u32, Arc<Mutex<IncomingFile>>>,
+    /// Bir akışın şu anda oluşturmakta olduğu dosyalar
+    opening: HashMap<u32, Opening>,
+    /// Reddedilen dosyalar; diğer akışlardan gelen kalan chunk'ları atlanır
+    rejected: HashSet<u32>,
+    /// Sonucu henüz bildirilmemiş dosya ve girdilerin yolu ve boyutu
+    names: HashMap<u32, (PathBuf, u64)>,
+}
+
+/// Oluşturulmakta olan dosya. Oluşturan akış kapıyı kilitli tutar; aynı
+/// dosyanın diğer akışlardan gelen aralıkları kapıda bekler.
+struct Opening {
+    header: FileHeader,
+    gate: Arc<Mutex<()>>,
+}
+
+/// Bir istemcinin paralel akışlarının paylaştığı aktarım oturumu
+struct Transfer {
+    id: String,
+    /// Oturumu açan akışın karşı tarafı; olaylar bununla bildirilir
+    peer: Peer,
+    events: Events,
+    /// El sıkışmada verilen akış sayısı
+    streams: usize,
+    /// El sıkışmada pazarlık edilen en büyük chunk boyutu
+    chunk_size: usize,
+    /// El sıkışmada zstd sıkıştırması kabul edildi mi
+    compression: bool,
+    /// Oturumun tüm akışlarının paylaştığı alım hızı sınırı
+    limiter: Arc<RateLimiter>,
+    /// Şimdiye kadar katılan akış sayısı
+    joined: AtomicUsize,
+    /// Açık bağlantı sayısı; sıfıra düşünce yarım dosyalar silinir
+    live: AtomicUsize,
+    files: Mutex<TransferFiles>,
+    /// Oturum sonunda bildirilen sayılar
+    verified: AtomicUsize,
+    failed: AtomicUsize,
+    bytes: AtomicU64,
+}
+
+impl Transfer {
+    fn emit(&self, kind: EventKind) {
+        self.events.emit(&self.peer, kind);
+    }
 
-    // Hedef yolu oluştur ve dizinleri oluştur
-    let received_dir = Path::new("received_files");
-    fs::create_dir_all(received_dir).expect("Ana dizin oluşturulamadı");
-    
-    let full_path = received_dir.join(&header.relative_path);
-    if let Some(parent) = full_path.parent() {
-        if let Err(e) = fs::create_dir_all(parent) {
-            println!("[⚠️] Dizin oluşturulamadı: {}", e);
+    /// İstemciye bildirilen dosya sonucunu olay olarak yayınlar
+    async fn report(&self, id: u32, outcome: &Result<(), Error>) {
+        let Some((path, size)) = self.files.lock().await.names.remove(&id) else {
             return;
+        };
+        match outcome {
+            Ok(()) => {
+                self.verified.fetch_add(1, Ordering::Relaxed);
+                self.bytes.fetch_add(size, Ordering::Relaxed);
+                self.emit(EventKind::FileVerified { path, size });
+            }
+            Err(error) => {
+                self.failed.fetch_add(1, Ordering::Relaxed);
+                self.emit(EventKind::Error { path: Some(path), message: error.to_string() });
+            }
         }
     }
+}
 
-    // 4. İstemciye onay gönder
-    if stream.write_all(&[1]).is_err() {
-        println!("[⚠️] İstemciye onay gönderilemedi.");
-        return;
+/// Sunucudaki aktif aktarımlar. `joined` ve `live` sayaçları yalnızca bu
+/// kilit tutulurken değiştirilir.
+#[derive(Default)]
+struct TransferRegistry {
+    transfers: std::sync::Mutex<HashMap<String, Arc<Transfer>>>,
+}
+
+impl TransferRegistry {
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, Arc<Transfer>>> {
+        self.transfers.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Yeni aktarımı kaydeder
+    fn start(&self, id: String, streams: usize, chunk_size: usize, compression: bool, peer: &Peer, options: &ServerOptions) -> Arc<Transfer> {
+        let transfer = Arc::new(Transfer {
+            id: id.clone(),
+            peer: peer.clone(),
+            events: options.events.clone(),
+            streams,
+            chunk_size,
+            compression,
+            limiter: Arc::new(RateLimiter::new(options.client_bwlimit)),
+            joined: AtomicUsize::new(1),
+            live: AtomicUsize::new(1),
+            files: Mutex::new(TransferFiles::default()),
+            verified: AtomicUsize::new(0),
+            failed: AtomicUsize::new(0),
+            bytes: AtomicU64::new(0),
+        });
+        self.lock().insert(id, transfer.clone());
+        transfer.emit(EventKind::SessionStarted { files: 0, bytes: 0 });
+        transfer
     }
 
-    let path = &full_path;
-    let mut file = match OpenOptions::new()
-        .create(true)
-        .write(true)
-        .truncate(true)  // Mevcut dosyayı temizle
-        .open(path) {
-        Ok(f) => f,
+    /// Var olan aktarımın akış sayısını, chunk boyutunu ve sıkıştırma
+    /// kararını döndürür
+    fn limits_of(&self, id: &str) -> Option<(usize, usize, bool)> {
+        self.lock().get(id).map(|transfer| (transfer.streams, transfer.chunk_size, transfer.compression))
+    }
+
+    /// Ek akışı aktarıma bağlar
+    fn join(&self, id: &str) -> Result<Arc<Transfer>, Error> {
+        let transfers = self.lock();
+        let transfer = transfers.get(id).ok_or_else(|| Error::Protocol("Bilinmeyen oturum".to_string()))?;
+        if transfer.joined.load(Ordering::SeqCst) >= transfer.streams {
+            return Err(Error::Protocol("Oturumun akış sınırı aşıldı".to_string()));
+        }
+        transfer.joined.fetch_add(1, Ordering::SeqCst);
+        transfer.live.fetch_add(1, Ordering::SeqCst);
+        Ok(transfer.clone())
+    }
+
+    /// Akışı aktarımdan ayırır; son akışsa aktarımı kaldırır ve `true` döner
+    fn leave(&self, transfer: &Transfer) -> bool {
+        let mut transfers = self.lock();
+        if transfer.live.fetch_sub(1, Ordering::SeqCst) == 1 {
+            transfers.remove(&transfer.id);
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// El sıkışmayı yürütür ve bağlantının ait olduğu aktarımı döndürür
+async fn accept_stream(reader: &mut BoxRead, writer: &mut BoxWrite, peer: &Peer, key: &[u8; 32], options: &ServerOptions, registry: &TransferRegistry, shutdown: &mut watch::Receiver<bool>) -> Option<Arc<Transfer>> {
+    let io_timeout = options.io_timeout;
+
+    // Henüz dosya yokken kapatma isteği beklenmeden uygulanır
+    let hello = tokio::select! {
+        biased;
+        _ = shutdown.changed() => {
+            log::info!("[🛑] {} oturumu kapatılıyor.", peer);
+            return None;
+        }
+        hello = timed(io_timeout, protocol::read_frame(reader)) => hello,
+    };
+    let hello = match hello {
+        Ok(Some(Frame::Hello(hello))) if protocol::is_compatible(hello.protocol) => hello,
+        Ok(Some(Frame::Hello(hello))) => {
+            log::warn!("[⚠️] {} uyumsuz protokol sürümü kullanıyor: {}", peer, hello.protocol);
+            let error = Error::Protocol(format!("Desteklenmeyen protokol sürümü: {} (sunucu: {})", hello.protocol, PROTOCOL_VERSION));
+            options.events.emit(peer, EventKind::Error { path: None, message: error.to_string() });
+            reject_stream(writer, io_timeout, error).await;
+            return None;
+        }
+        Ok(Some(other)) => {
+            log::warn!("[⚠️] {} el sıkışma yerine beklenmeyen mesaj gönderdi: {:?}", peer, other);
+            return None;
+        }
+        Ok(None) => return None,
         Err(e) => {
-            println!("[⚠️] Dosya oluşturulamadı: {}", e);
-            return;
+            log::warn!("[⚠️] {} ile el sıkışma başarısız: {}", peer, e);
+            return None;
         }
     };
 
-    let mut total_bytes_read = 0;
-    let mut buffer = [0; 4096]; // 4KB chunk
+    let (session, streams, chunk_size, compression) = match &hello.join {
+        Some(id) => match registry.limits_of(id) {
+            Some((streams, chunk_size, compression)) => (id.clone(), streams, chunk_size, compression),
+            None => {
+                reject_stream(writer, io_timeout, Error::Protocol("Bilinmeyen oturum".to_string())).await;
+                return None;
+            }
+        },
+        None => {
+            let requested_chunk = match hello.chunk_size {
+                0 => CHUNK_SIZE,
+                size => size as usize,
+            };
+            // Hız sınırı varken bir chunk yaklaşık bir saniyelik veriyi aşmaz;
+            // böylece istemcinin yazma zaman aşımı tetiklenmez
+            let min_chunk = MIN_CHUNK_SIZE.min(options.max_chunk_size);
+            let max_chunk = match options.client_bwlimit {
+                Bandwidth::Limited(rate) => options.max_chunk_size.min(rate as usize).max(min_chunk),
+                Bandwidth::Unlimited => options.max_chunk_size,
+            };
+            (
+                random_nonce(),
+                (hello.streams as usize).clamp(1, options.max_streams.max(1)),
+                requested_chunk.clamp(min_chunk, max_chunk),
+                options.compression && hello.compression.as_deref() == Some(ZSTD),
+            )
+        }
+    };
+
+    // Yalnızca erişim anahtarıyla çalışan sunucuya verilen anahtar zaten
+    // erişim anahtarıdır
+    let access = if options.access_only { *key } else { access_key(key) };
+    let nonce = random_nonce();
+    let welcome = Frame::Welcome(Welcome {
+        protocol: PROTOCOL_VERSION,
+        name: options.name.clone(),
+        streams: streams as u32,
+        chunk_size: chunk_size as u32,
+        compression: compression.then(|| ZSTD.to_string()),
+        proof: handshake_proof(&access, Role::Server, &hello.nonce, &nonce, &session),
+        session: session.clone(),
+        nonce: nonce.clone(),
+    });
+    if let Err(e) = timed(io_timeout, protocol::write_frame(writer, &welcome)).await {
+        log::warn!("[⚠️] {} ile el sıkışma başarısız: {}", peer, e);
+        return None;
+    }
+
+    // Keşif sınaması yalnızca sunucunun kanıtını ister; oturum açılmaz
+    if hello.probe {
+        log::debug!("[🔎] {} sunucunun kimliğini sınadı.", peer);
+        return None;
+    }
+
+    // İstemci anahtarı bildiğini kanıtlamalı
+    let authenticated = match timed(io_timeout, protocol::read_frame(reader)).await {
+        Ok(Some(Frame::Auth(auth))) => verify_handshake_proof(&access, Role::Client, &hello.nonce, &nonce, &session, &auth.proof),
+        _ => false,
+    };
+    if !authenticated {
+        log::warn!("[🚫] {} kimlik doğrulamasını geçemedi (anahtar uyuşmuyor olabilir).", peer);
+        let error = Error::Auth("Kimlik doğrulaması başarısız".to_string());
+        options.events.emit(peer, EventKind::Error { path: None, message: error.to_string() });
+        reject_stream(writer, io_timeout, error).await;
+        return None;
+    }
+
+    match hello.join {
+        Some(_) => match registry.join(&session) {
+            Ok(transfer) => {
+                log::info!("[🔀] {} oturuma ek akış olarak katıldı ({}/{}).", peer, transfer.joined.load(Ordering::SeqCst), transfer.streams);
+                Some(transfer)
+            }
+            Err(error) => {
+                reject_stream(writer, io_timeout, error).await;
+                None
+            }
+        },
+        None => Some(registry.start(session, streams, chunk_size, compression, peer, options)),
+    }
+}
+
+/// Akışı kapatmadan önce reddin nedenini istemciye bildirir
+async fn reject_stream(writer: &mut BoxWrite, io_timeout: Duration, error: Error) {
+    let _ = timed(io_timeout, protocol::write_frame(writer, &Frame::Error(error))).await;
+}
 
-    while total_bytes_read < header.file_size {
-        let bytes_to_read = std::cmp::min(buffer.len(), (header.file_size - total_bytes_read) as usize);
-        let bytes_read = match stream.read_exact(&mut buffer[..bytes_to_read]) {
-            Ok(_) => bytes_to_read,
+async fn handle_client(connection: Connection, key: &[u8; 32], options: &ServerOptions, registry: &TransferRegistry, mut shutdown: watch::Receiver<bool>) {
+    log::info!("[📥] Bağlantı alındı: {}", connection.peer);
+    let connection_peer = connection.peer.clone();
+    let connection = match &options.tls {
+        Some(tls) => match timed(options.io_timeout, tls.accept(connection)).await {
+            Ok(connection) => connection,
             Err(e) => {
-                println!("[⚠️] Chunk okuma hatası: {}", e);
+                log::warn!("[🚫] TLS el sıkışması başarısız: {}", e);
+                options.events.emit(&connection_peer, EventKind::Error { path: None, message: format!("TLS el sıkışması başarısız: {}", e) });
+                return;
+            }
+        },
+        None => connection,
+    };
+    let Connection { mut reader, mut writer, peer } = connection;
+
+    let Some(transfer) = accept_stream(&mut reader, &mut writer, &peer, key, options, registry, &mut shutdown).await else {
+        return;
+    };
+
+    receive_frames(reader, &mut writer, &peer, key, options, &transfer, shutdown).await;
+
+    if registry.leave(&transfer) {
+        let mut files = transfer.files.lock().await;
+        for (_, incoming) in files.open.drain() {
+            let mut incoming = incoming.lock().await;
+            log::warn!("[⚠️] Yarım kalan dosya silindi: {}", incoming.header.relative_path.display());
+            incoming.abort().await;
+            transfer.failed.fetch_add(1, Ordering::Relaxed);
+            transfer.emit(EventKind::Error { path: Some(incoming.header.relative_path.clone()), message: "Aktarım yarım kaldı".to_string() });
+        }
+        drop(files);
+        transfer.emit(EventKind::SessionFinished {
+            verified: transfer.verified.load(Ordering::Relaxed),
+            failed: transfer.failed.load(Ordering::Relaxed),
+            bytes: transfer.bytes.load(Ordering::Relaxed),
+        });
+    }
+}
+
+/// Akıştan gelen dosya ve chunk çerçevelerini işler
+async fn receive_frames(mut reader: BoxRead, writer: &mut BoxWrite, peer: &Peer, key: &[u8; 32], options: &ServerOptions, transfer: &Transfer, mut shutdown: watch::Receiver<bool>) {
+    let io_timeout = options.io_timeout;
+
+    // Çerçeveler ayrı bir görevde okunur; böylece kapatma isteği yarım
+    // okunmuş bir çerçeveyi iptal etmez. Hız sınırı okuma yavaşlatılarak
+    // uygulanır; TCP akış denetimi istemciyi de yavaşlatır.
+    let (frames_tx, mut frames_rx) = mpsc::channel(FRAME_QUEUE_DEPTH);
+    let limiter = transfer.limiter.clone();
+    let reader_task = tokio::spawn(async move {
+        loop {
+            let frame = timed(io_timeout, protocol::read_frame(&mut reader)).await;
+            if let Ok(Some(Frame::Chunk { data, .. } | Frame::Blob { data, .. })) = &frame {
+                limiter.acquire(data.len()).await;
+            }
+            let done = !matches!(frame, Ok(Some(_)));
+            if frames_tx.send(frame).await.is_err() || done {
+                break;
+            }
+        }
+    });
+
+    let mut draining = false;
+
+    // İstemci bağlantıyı kapatana kadar dosya almaya devam et. Kapatma
+    // isteğinde yeni dosya kabul edilmez, açık dosyalar tamamlanınca çıkılır.
+    loop {
+        let frame = tokio::select! {
+            biased;
+            _ = shutdown.changed(), if !draining => {
+                draining = true;
+                let open = transfer.files.lock().await.open.len();
+                if open == 0 {
+                    log::info!("[🛑] {} oturumu kapatılıyor.", peer);
+                    break;
+                }
+                log::info!("[🛑] {} oturumu kapatılıyor, {} dosya tamamlanıyor...", peer, open);
+                continue;
+            }
+            frame = frames_rx.recv() => frame,
+        };
+
+        let handled = match frame {
+            // Kasa modunda içerik yalnızca istemcinin şifrelediği blob'lar olarak gelir
+            Some(Ok(Some(Frame::File { .. } | Frame::Entry { .. } | Frame::Chunk { .. } | Frame::Hole { .. }))) if options.vault => {
+                Err(Error::Protocol("Sunucu kasa modunda; dosyalar `push` ile gönderilmeli".to_string()))
+            }
+            Some(Ok(Some(Frame::File { id, header }))) => open_file(transfer, id, header, key, options, draining).await,
+            Some(Ok(Some(Frame::Entry { id, entry }))) => create_entry(transfer, id, entry, key, options, draining).await,
+            Some(Ok(Some(Frame::Chunk { id, offset, compressed, data }))) => receive_chunk(transfer, id, offset, compressed, &data, key, io_timeout).await,
+            Some(Ok(Some(Frame::Hole { id, offset, len }))) => receive_hole(transfer, id, offset, len).await,
+            Some(Ok(Some(Frame::Blob { id, data }))) => receive_blob(options, id, data).await.map(|()| None),
+            Some(Ok(Some(Frame::Request(request)))) => match answer_request(writer, peer, request, key, options, transfer.chunk_size).await {
+                true => Ok(None),
+                false => break,
+            },
+            Some(Ok(Some(other))) => {
+                log::warn!("[⚠️] {} beklenmeyen mesaj gönderdi: {:?}", peer, other);
+                break;
+            }
+            Some(Ok(None)) | None => break,
+            Some(Err(e)) => {
+                log::warn!("[⚠️] {} bağlantısından okuma hatası: {}", peer, e);
+                transfer.emit(EventKind::Error { path: None, message: format!("Okuma hatası: {}", e) });
+                break;
+            }
+        };
+        // Oturumu bitiren hatanın nedeni istemciye bildirilir
+        let result = match handled {
+            Ok(result) => result,
+            Err(error) => {
+                log::warn!("[⚠️] {} oturumu sonlandırıldı: {}", peer, error);
+                transfer.emit(EventKind::Error { path: None, message: error.to_string() });
+                reject_stream(writer, io_timeout, error).await;
                 break;
             }
         };
 
-        // IV ve şifreli veri ayrıştırılıyor
-        if bytes_read > 16 {
-            let iv = &buffer[..16];
-            let ciphertext = &buffer[16..bytes_read];
-            let decrypted = decrypt_chunk(ciphertext, key, iv);
-            file.write_all(&decrypted).expect("Veri dosyaya yazılamadı");
-            println!("[📦] Alınan ve çözülen chunk: {} bayt", decrypted.len());
-            total_bytes_read += decrypted.len() as u64;
-        } else {
-            println!("[⚠️] Chunk boyutu çok küçük, IV ve veri ayrıştırılamadı.");
+        if let Some((id, outcome)) = result {
+            if let Err(error) = &outcome {
+                log::warn!("[⚠️] Dosya alınamadı: {}", error);
+            }
+            transfer.report(id, &outcome).await;
+            if !send_result(writer, io_timeout, id, outcome).await {
+                break;
+            }
+        }
+
+        if draining && transfer.files.lock().await.open.is_empty() {
+            log::info!("[🛑] {} oturumu kapatılıyor.", peer);
             break;
         }
     }
 
-    println!("[📂] Dosya '{}' başarıyla alındı ve kaydedildi. Toplam {} bayt.", header.file_name, total_bytes_read);
+    reader_task.abort();
+}
+
+/// Dosya sonucu: istemciye bildirilecek kimlik ve sonuç
+type FileOutcome = Option<(u32, Result<(), Error>)>;
+
+/// Oturumu sonlandıran protokol ihlali
+fn violation(message: String) -> Error {
+    Error::Protocol(format!("Protokol ihlali: {}", message))
+}
+
+/// `File` çerçevesini işler. Aynı dosyanın başlığı her akıştan bir kez
+/// gelir; ilk gelen dosyayı açar. Protokol ihlalinde `Err` döner.
+///
+/// Dosyanın oluşturulması, commit'i ve açık dosyanın kilidini beklemek
+/// aktarımın kilidi dışında yapılır; diğer akışlar ve dosyalar beklemez.
+async fn open_file(transfer: &Transfer, id: u32, header: FileHeader, key: &[u8; 32], options: &ServerOptions, draining: bool) -> Result<FileOutcome, Error> {
+    let mut files = transfer.files.lock().await;
+    if let Some(existing) = files.open.get(&id).cloned() {
+        drop(files);
+        return check_repeated_header(id, &existing.lock().await.header, &header);
+    }
+    if let Some(opening) = files.opening.get(&id) {
+        return check_repeated_header(id, &opening.header, &header);
+    }
+    if files.rejected.contains(&id) {
+        return Ok(None);
+    }
+    if files.open.len() + files.opening.len() >= MAX_OPEN_FILES {
+        return Err(violation(format!("aynı anda {} dosyadan fazlası açılamaz", MAX_OPEN_FILES)));
+    }
+
+    log::info!("[📄] Alınan dosya başlığı: {:?}", header);
+    if draining {
+        files.rejected.insert(id);
+        transfer.emit(EventKind::FileSkipped { path: header.relative_path, reason: "Sunucu kapanıyor".to_string() });
+        return Ok(Some((id, Err(Error::Network("Sunucu kapanıyor".to_string())))));
+    }
+    files.names.insert(id, (header.relative_path.clone(), header.file_size));
+    let gate = Arc::new(Mutex::new(()));
+    let _opening = gate.clone().lock_owned().await;
+    files.opening.insert(id, Opening { header: header.clone(), gate });
+    drop(files);
+
+    transfer.emit(EventKind::FileStarted { path: header.relative_path.clone(), size: header.file_size });
+    let created = match Attributes::unseal(&header.metadata, options, key) {
+        Ok(attributes) => IncomingFile::create(header, options.storage(), attributes, options.retention).await,
+        Err(error) => Err(error),
+    };
+    let (opened, outcome) = match created {
+        Ok(mut incoming) if incoming.is_complete() => (None, Some(incoming.commit().await)),
+        Ok(incoming) => (Some(incoming), None),
+        Err(error) => (None, Some(Err(error))),
+    };
+
+    let mut files = transfer.files.lock().await;
+    files.opening.remove(&id);
+    if let Some(incoming) = opened {
+        files.open.insert(id, Arc::new(Mutex::new(incoming)));
+    }
+    if outcome.as_ref().is_some_and(Result::is_err) {
+        files.rejected.insert(id);
+    }
+    Ok(outcome.map(|outcome| (id, outcome)))
+}
+
+/// Aynı dosyanın başka bir akıştan gelen başlığı ilkiyle aynı olmalı
+fn check_repeated_header(id: u32, existing: &FileHeader, header: &FileHeader) -> Result<FileOutcome, Error> {
+    if existing.file_size != header.file_size || existing.file_hash != header.file_hash || existing.relative_path != header.relative_path {
+        return Err(violation(format!("dosya kimliği {} farklı başlıkla yeniden kullanıldı", id)));
+    }
+    Ok(None)
+}
+
+/// `Entry` çerçevesini işler; girdi hemen oluşturulup sonucu döner.
+/// Açık bir dosyanın kimliği kullanılırsa `Err` döner.
+async fn create_entry(transfer: &Transfer, id: u32, entry: EntryHeader, key: &[u8; 32], options: &ServerOptions, draining: bool) -> Result<FileOutcome, Error> {
+    let reused = {
+        let files = transfer.files.lock().await;
+        files.open.contains_key(&id) || files.opening.contains_key(&id)
+    };
+    if reused {
+        return Err(violation(format!("dosya kimliği {} girdi için yeniden kullanıldı", id)));
+    }
+
+    log::info!("[📄] Alınan girdi: {:?}", entry);
+    if draining {
+        transfer.emit(EventKind::FileSkipped { path: entry.relative_path, reason: "Sunucu kapanıyor".to_string() });
+        return Ok(Some((id, Err(Error::Network("Sunucu kapanıyor".to_string())))));
+    }
+    transfer.files.lock().await.names.insert(id, (entry.relative_path.clone(), 0));
+    let storage = options.storage();
+    let Some(root) = storage.local_root() else {
+        // Klasörler nesne depolarında ayrıca tutulmaz; bağlantılar saklanamaz
+        return Ok(Some((id, match entry.kind {
+            EntryKind::Directory => Ok(()),
+            _ => Err(Error::Permission(format!("Depolama bağlantıları desteklemiyor: {}", entry.relative_path.display()))),
+        })));
+    };
+    if !is_safe_relative_path(&entry.relative_path) || versions::is_reserved(&entry.relative_path) || has_symlink_ancestor(root, &entry.relative_path).await {
+        return Ok(Some((id, Err(Error::Permission(format!("Geçersiz girdi yolu: {}", entry.relative_path.display()))))));
+    }
+    match &entry.kind {
+        EntryKind::Symlink { target } if !is_safe_symlink_target(&entry.relative_path, target) => {
+            let message = format!("Sembolik bağlantı kökün dışını gösteriyor: {} -> {}", entry.relative_path.display(), target.display());
+            return Ok(Some((id, Err(Error::Permission(message)))));
+        }
+        EntryKind::Hardlink { target } if !is_safe_relative_path(target) || versions::is_reserved(target) || has_symlink_ancestor(root, target).await => {
+            return Ok(Some((id, Err(Error::Permission(format!("Geçersiz hardlink hedefi: {}", target.display()))))));
+        }
+        _ => {}
+    }
+
+    let attributes = match Attributes::unseal(&entry.metadata, options, key) {
+        Ok(attributes) => attributes,
+        Err(error) => return Ok(Some((id, Err(error)))),
+    };
+    let root = root.to_path_buf();
+    let outcome = tokio::task::spawn_blocking(move || apply_entry(&root, &entry, &attributes)).await
+        .unwrap_or_else(|e| Err(Error::Io(e.to_string())));
+    Ok(Some((id, outcome)))
+}
+
+/// Doğrulanmış girdiyi diske uygular. Bağlantılar geçici isimle oluşturulup
+/// yerine taşınır; hedefte aynı isimde dosya varsa yerini alırlar.
+fn apply_entry(root: &Path, entry: &EntryHeader, attributes: &Attributes) -> Result<(), Error> {
+    let full_path = root.join(&entry.relative_path);
+    let parent = full_path.parent().unwrap_or(root);
+    std::fs::create_dir_all(parent)
+        .map_err(|e| Error::io("Dizin oluşturulamadı", &e))?;
 
-    // Dosya hash'ini doğrula
-    match calculate_file_hash(path) {
-        Ok(calculated_hash) => {
-            if calculated_hash == header.file_hash {
-                println!("[✅] Dosya hash doğrulaması başarılı: {}", calculated_hash);
-            } else {
-                println!("[❌] Dosya hash doğrulaması BAŞARISIZ! Beklenen: {}, Hesaplanan: {}", header.file_hash, calculated_hash);
+    match &entry.kind {
+        EntryKind::Directory => {
+            // Önceki bir eşitlemeden kalan bağlantının hedefine dokunulmaz
+            if std::fs::symlink_metadata(&full_path).is_ok_and(|m| m.file_type().is_symlink()) {
+                std::fs::remove_file(&full_path)
+                    .map_err(|e| Error::io("Bağlantı kaldırılamadı", &e))?;
             }
+            std::fs::create_dir_all(&full_path)
+                .map_err(|e| Error::io("Dizin oluşturulamadı", &e))?;
+            if let Err(e) = attributes.apply(&full_path, &entry.metadata) {
+                log::warn!("[⚠️] '{}' öznitelikleri uygulanamadı: {}", entry.relative_path.display(), e);
+            }
+            log::info!("[📁] Klasör '{}' oluşturuldu.", entry.relative_path.display());
         }
-        Err(e) => {
-            println!("[⚠️] Kaydedilen dosyanın hash'i hesaplanamadı: {}", e);
+        EntryKind::Symlink { target } => {
+            let temp_path = temp_path_for(&full_path);
+            let _ = std::fs::remove_file(&temp_path);
+            create_symlink(target, &temp_path)
+                .map_err(|e| Error::io("Sembolik bağlantı oluşturulamadı", &e))?;
+            if let Err(e) = std::fs::rename(&temp_path, &full_path) {
+                let _ = std::fs::remove_file(&temp_path);
+                return Err(Error::io("Sembolik bağlantı yerine taşınamadı", &e));
+            }
+            log::info!("[🔗] Sembolik bağlantı '{}' -> '{}' oluşturuldu.", entry.relative_path.display(), target.display());
+        }
+        EntryKind::Hardlink { target } => {
+            let source = root.join(target);
+            if !std::fs::symlink_metadata(&source).is_ok_and(|m| m.file_type().is_file()) {
+                return Err(Error::Io(format!("Hardlink hedefi bulunamadı: {}", target.display())));
+            }
+            let temp_path = temp_path_for(&full_path);
+            let _ = std::fs::remove_file(&temp_path);
+            std::fs::hard_link(&source, &temp_path)
+                .map_err(|e| Error::io("Hardlink oluşturulamadı", &e))?;
+            if let Err(e) = std::fs::rename(&temp_path, &full_path) {
+                let _ = std::fs::remove_file(&temp_path);
+                return Err(Error::io("Hardlink yerine taşınamadı", &e));
+            }
+            // Hedef zaten aynı dosyaya bağlıysa rename hiçbir şey yapmaz
+            let _ = std::fs::remove_file(&temp_path);
+            log::info!("[🔗] Hardlink '{}' -> '{}' oluşturuldu.", entry.relative_path.display(), target.display());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+pub fn create_symlink(target: &Path, link: &Path) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(target, link)
+}
+
+#[cfg(windows)]
+pub fn create_symlink(target: &Path, link: &Path) -> std::io::Result<()> {
+    std::os::windows::fs::symlink_file(target, link)
+}
+
+/// `Chunk` çerçevesini çözer (gerekirse açar) ve dosyaya yazar. Dosyanın son
+/// baytı geldiğinde hash doğrulanıp sonuç döner. Protokol ihlalinde `Err` döner.
+async fn receive_chunk(transfer: &Transfer, id: u32, offset: u64, compressed: bool, frame: &[u8], key: &[u8; 32], io_timeout: Duration) -> Result<FileOutcome, Error> {
+    if compressed && !transfer.compression {
+        return Err(violation("pazarlık edilmemiş sıkıştırılmış chunk".to_string()));
+    }
+    let Some(entry) = incoming_file(transfer, id).await? else {
+        return Ok(None);
+    };
+
+    // Şifre çözme ve açma dosya kilidi dışında yapılır; aynı dosyanın diğer
+    // akışlardan gelen aralıkları beklemez. Açılan veri de pazarlık edilen
+    // boyutla sınırlıdır.
+    let decrypted = open_chunk(frame, key).and_then(|data| {
+        if compressed {
+            decompress_chunk(&data, transfer.chunk_size)
+                .ok_or_else(|| Error::Integrity("Sıkıştırılmış chunk açılamadı".to_string()))
+        } else if data.len() > transfer.chunk_size {
+            Err(Error::Protocol(format!("Chunk pazarlık edilen boyutu aşıyor: {} bayt", data.len())))
+        } else {
+            Ok(data)
+        }
+    });
+
+    let mut incoming = entry.lock().await;
+    if incoming.is_finished() {
+        // Başka bir akışta reddedildi
+        return Ok(None);
+    }
+    let written = match decrypted {
+        Ok(data) => {
+            let len = data.len() as u64;
+            incoming.write_range(offset, data, io_timeout).await.map(|()| len)
+        }
+        Err(error) => Err(error),
+    };
+    if let Ok(len) = written {
+        transfer.emit(EventKind::ChunkSent { path: incoming.header.relative_path.clone(), offset, bytes: len });
+    }
+    Ok(finish_range(transfer, id, incoming, written.map(drop)).await)
+}
+
+/// `Hole` çerçevesini işler: bölge veri gönderilmeden alınmış sayılır
+async fn receive_hole(transfer: &Transfer, id: u32, offset: u64, len: u64) -> Result<FileOutcome, Error> {
+    let Some(entry) = incoming_file(transfer, id).await? else {
+        return Ok(None);
+    };
+    let mut incoming = entry.lock().await;
+    if incoming.is_finished() {
+        return Ok(None);
+    }
+    let skipped = incoming.skip_range(offset, len);
+    if skipped.is_ok() {
+        transfer.emit(EventKind::ChunkSent { path: incoming.header.relative_path.clone(), offset, bytes: len });
+    }
+    Ok(finish_range(transfer, id, incoming, skipped).await)
+}
+
+/// Açık dosyayı bulur; reddedilmiş dosyanın kalan çerçeveleri için `None`,
+/// bilinmeyen kimlik için `Err` döner. Dosya başka bir akışta
+/// oluşturuluyorsa bitmesi beklenir.
+async fn incoming_file(transfer: &Transfer, id: u32) -> Result<Option<Arc<Mutex<IncomingFile>>>, Error> {
+    loop {
+        let files = transfer.files.lock().await;
+        let gate = match files.open.get(&id) {
+            Some(entry) => return Ok(Some(entry.clone())),
+            None if files.rejected.contains(&id) => return Ok(None),
+            None => match files.opening.get(&id) {
+                Some(opening) => opening.gate.clone(),
+                None => return Err(violation(format!("bilinmeyen dosya kimliği {}", id))),
+            },
+        };
+        drop(files);
+        let _ = gate.lock().await;
+    }
+}
+
+/// Bir aralık işlendikten sonra dosya tamamlandıysa commit eder, hata varsa
+/// reddeder; her iki durumda da sonucu döndürür
+async fn finish_range(transfer: &Transfer, id: u32, mut incoming: MutexGuard<'_, IncomingFile>, result: Result<(), Error>) -> FileOutcome {
+    let outcome = match result {
+        Ok(()) if !incoming.is_complete() => return None,
+        Ok(()) => incoming.commit().await,
+        Err(error) => {
+            incoming.abort().await;
+            Err(error)
         }
+    };
+    drop(incoming);
+
+    let mut files = transfer.files.lock().await;
+    files.open.remove(&id);
+    if outcome.is_err() {
+        files.rejected.insert(id);
     }
+    Some((id, outcome))
 }
 
-pub fn start_server(address: &str, key: &[u8; 32]) {
-    let listener = TcpListener::bind(address).expect("Sunucu başlatılamadı");
+/// Chunk çerçevesini (IV + şifreli veri) çözer
+fn open_chunk(frame: &[u8], key: &[u8; 32]) -> Result<Vec<u8>, Error> {
+    if frame.len() <= IV_SIZE {
+        return Err(Error::Protocol(format!("Geçersiz chunk boyutu: {} bayt", frame.len())));
+    }
 
-    println!("[📡] Sunucu başlatıldı: {}", address);
+    crypto::open(frame, key)
+        .ok_or_else(|| Error::Integrity("Chunk çözülemedi (anahtar uyuşmuyor olabilir).".to_string()))
+}
 
-    for stream in listener.incoming() {
-        match stream {
-            Ok(stream) => {
-                // Her bağlantıyı ayrı bir thread'de ele al
-                let key_clone = key.clone();
-                std::thread::spawn(move || {
-                    handle_client(stream, &key_clone);
-                });
+/// Oturum içi isteği yanıtlar. Başarısız istek `Error` ile bildirilir ve
+/// oturum sürer; bağlantı koptuysa `false` döner.
+async fn answer_request(writer: &mut BoxWrite, peer: &Peer, request: Request, key: &[u8; 32], options: &ServerOptions, chunk_size: usize) -> bool {
+    let io_timeout = options.io_timeout;
+    let is_vault_request = matches!(request, Request::MissingBlobs { .. } | Request::GetBlobs { .. } | Request::PutIndex { .. } | Request::Indexes | Request::Index { .. });
+    let sent = match request {
+        _ if is_vault_request != options.vault => {
+            let message = match options.vault {
+                true => "Sunucu kasa modunda; yalnızca kasa istekleri yanıtlanır",
+                false => "Sunucu kasa modunda değil",
+            };
+            timed(io_timeout, protocol::write_frame(writer, &Frame::Error(Error::Protocol(message.to_string())))).await
+        }
+        Request::MissingBlobs { ids } => match vault_task(options, move |storage| vault::missing_blobs(storage, &ids)).await {
+            Ok(ids) => timed(io_timeout, protocol::write_frame(writer, &Frame::Response(Response::MissingBlobs { ids }))).await,
+            Err(error) => timed(io_timeout, protocol::write_frame(writer, &Frame::Error(error))).await,
+        },
+        Request::GetBlobs { ids } => send_blobs(writer, options, ids).await,
+        Request::PutIndex { blobs } => match vault_task(options, move |storage| vault::store_index(storage, &blobs, chrono::Utc::now())).await {
+            Ok(id) => {
+                log::info!("[🔐] {} kasaya yeni indeks kaydetti: {}", peer, id);
+                timed(io_timeout, protocol::write_frame(writer, &Frame::Response(Response::IndexStored { id }))).await
             }
-            Err(e) => {
-                println!("[⚠️] Bağlantı hatası: {}", e);
+            Err(error) => timed(io_timeout, protocol::write_frame(writer, &Frame::Error(error))).await,
+        },
+        Request::Indexes => match vault_task(options, |storage| vault::list_indexes(storage).map_err(|e| Error::io("İndeksler okunamadı", &e))).await {
+            Ok(ids) => timed(io_timeout, protocol::write_frame(writer, &Frame::Response(Response::Indexes { ids }))).await,
+            Err(error) => timed(io_timeout, protocol::write_frame(writer, &Frame::Error(error))).await,
+        },
+        Request::Index { id } => match vault_task(options, move |storage| vault::read_index(storage, id.as_deref())).await {
+            Ok((id, blobs)) => timed(io_timeout, protocol::write_frame(writer, &Frame::Response(Response::Index { id, blobs }))).await,
+            Err(error) => timed(io_timeout, protocol::write_frame(writer, &Frame::Error(error))).await,
+        },
+        Request::Versions { path } => match list_versions(options, path).await {
+            Ok(versions) => timed(io_timeout, protocol::write_frame(writer, &Frame::Response(Response::Versions { versions }))).await,
+            Err(error) => timed(io_timeout, protocol::write_frame(writer, &Frame::Error(error))).await,
+        },
+        Request::Restore { path, version } => match open_version(options, &path, &version).await {
+            Ok((file, header)) => {
+                log::info!("[📤] '{}' dosyasının {} sürümü {} adresine gönderiliyor.", path.display(), version, peer);
+                send_file(writer, file, header, key, chunk_size, io_timeout).await
             }
+            Err(error) => timed(io_timeout, protocol::write_frame(writer, &Frame::Error(error))).await,
+        },
+    };
+    match sent {
+        Ok(()) => true,
+        Err(e) => {
+            log::warn!("[⚠️] {} isteğine yanıt gönderilemedi: {}", peer, e);
+            false
+        }
+    }
+}
+
+/// Kasa deposu işlemini sunucunun depolamasında, engelleyen bir görevde
+/// çalıştırır
+async fn vault_task<T: Send + 'static>(options: &ServerOptions, task: impl FnOnce(&dyn Storage) -> Result<T, Error> + Send + 'static) -> Result<T, Error> {
+    let storage = options.storage();
+    tokio::task::spawn_blocking(move || task(storage.as_ref())).await
+        .map_err(|e| Error::Io(e.to_string()))?
+}
+
+/// Gelen blob'u kasaya yazar
+async fn receive_blob(options: &ServerOptions, id: [u8; 32], data: Vec<u8>) -> Result<(), Error> {
+    if !options.vault {
+        return Err(Error::Protocol("Sunucu kasa modunda değil".to_string()));
+    }
+    vault_task(options, move |storage| vault::store_blob(storage, &id, &data)).await.map(|_| ())
+}
+
+/// İstenen blob'ları sırayla gönderir; bulunamayan ilk blob için `Error` döner
+async fn send_blobs(writer: &mut BoxWrite, options: &ServerOptions, ids: Vec<String>) -> std::io::Result<()> {
+    for id in ids {
+        let frame = match vault_task(options, move |storage| vault::read_blob(storage, &id)).await {
+            Ok((id, data)) => Frame::Blob { id, data },
+            Err(error) => return timed(options.io_timeout, protocol::write_frame(writer, &Frame::Error(error))).await,
+        };
+        timed(options.io_timeout, protocol::write_frame(writer, &frame)).await?;
+    }
+    Ok(())
+}
+
+/// Sürümlerin tutulduğu yerel kök; sürümler yalnızca yerel depolamada tutulur
+fn versions_root(options: &ServerOptions) -> Result<PathBuf, Error> {
+    options.storage().local_root()
+        .map(Path::to_path_buf)
+        .ok_or_else(|| Error::Protocol("Sunucu sürümleri yerel depolama dışında tutmuyor".to_string()))
+}
+
+async fn list_versions(options: &ServerOptions, relative: PathBuf) -> Result<Vec<VersionInfo>, Error> {
+    if !is_safe_relative_path(&relative) || versions::is_reserved(&relative) {
+        return Err(Error::Permission(format!("Geçersiz dosya yolu: {}", relative.display())));
+    }
+    let root = versions_root(options)?;
+    tokio::task::spawn_blocking(move || versions::list(&root, &relative)).await
+        .map_err(|e| Error::Io(e.to_string()))?
+        .map_err(|e| Error::io("Sürümler okunamadı", &e))
+}
+
+/// Sürüm dosyasını açar ve istemcinin doğrulayacağı başlığı hazırlar
+async fn open_version(options: &ServerOptions, relative: &Path, id: &str) -> Result<(fs::File, FileHeader), Error> {
+    let path = versions::locate(&versions_root(options)?, relative, id)?;
+    let hash_path = path.clone();
+    let file_hash = tokio::task::spawn_blocking(move || calculate_file_hash(&hash_path)).await
+        .map_err(|e| Error::Io(e.to_string()))?
+        .map_err(|e| Error::from(e).context("Sürüm okunamadı"))?;
+    let file = fs::File::open(&path).await
+        .map_err(|e| Error::io("Sürüm açılamadı", &e))?;
+    let metadata = file.metadata().await
+        .map_err(|e| Error::io("Sürüm okunamadı", &e))?;
+    let header = FileHeader {
+        file_name: relative.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default(),
+        file_size: metadata.len(),
+        file_hash,
+        relative_path: relative.to_path_buf(),
+        metadata: FileMetadata::from_fs(&metadata),
+    };
+    Ok((file, header))
+}
+
+/// Dosyayı istemciye `File` + şifreli `Chunk` çerçeveleriyle gönderir
+async fn send_file(writer: &mut BoxWrite, mut file: fs::File, header: FileHeader, key: &[u8; 32], chunk_size: usize, io_timeout: Duration) -> std::io::Result<()> {
+    let size = header.file_size;
+    timed(io_timeout, protocol::write_frame(writer, &Frame::File { id: 0, header })).await?;
+    let mut buffer = vec![0; chunk_size];
+    let mut offset = 0u64;
+    while offset < size {
+        let wanted = (chunk_size as u64).min(size - offset) as usize;
+        let read = file.read(&mut buffer[..wanted]).await?;
+        if read == 0 {
+            return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof));
+        }
+        let frame = encode_chunk(0, offset, &buffer[..read], key)
+            .map_err(|e| std::io::Error::other(e.to_string()))?;
+        timed(io_timeout, writer.write_all(&frame)).await?;
+        offset += read as u64;
+    }
+    Ok(())
+}
+
+/// İstemciye dosya sonucunu bildirir; bağlantı koptuysa `false` döner
+async fn send_result(writer: &mut BoxWrite, io_timeout: Duration, id: u32, result: Result<(), Error>) -> bool {
+    match timed(io_timeout, protocol::write_frame(writer, &Frame::FileResult { id, result })).await {
+        Ok(()) => true,
+        Err(e) => {
+            log::warn!("[⚠️] İstemciye sonuç gönderilemedi: {}", e);
+            false
         }
     }
 }
+
+/// `dizin/dosya.txt` için `dizin/.dosya.txt.deltasafe-tmp`
+pub fn temp_path_for(path: &Path) -> PathBuf {
+    let file_name = path.file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    path.with_file_name(format!(".{}{}", file_name, TEMP_SUFFIX))
+}
+
+/// SIGINT (Ctrl+C) veya SIGTERM gelene kadar bekler
+pub async fn shutdown_signal() {
+    let ctrl_c = async {
+        let _ = tokio::signal::ctrl_c().await;
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(_) => std::future::pending::<()>().await,
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+}
+
+/// Sunucuyu başlatır; SIGINT/SIGTERM gelince aktif dosyalar tamamlanıp kapanır.
+#[deprecated(note = "Server::builder() kullanın; hatalar Result olarak döner")]
+pub async fn start_server(address: &str, key: &[u8; 32], options: &ServerOptions) {
+    let bound = Server::builder()
+        .address(address)
+        .key(*key)
+        .options(options.clone())
+        .announce(true)
+        .bind()
+        .await;
+    let result = match bound {
+        Ok(server) => server.run(shutdown_signal()).await,
+        Err(e) => Err(e),
+    };
+    if let Err(e) = result {
+        log::error!("[❌] Sunucu hatası: {}", e);
+    }
+}
+
+/// Kütüphane olarak sunucu. [`Server::builder`] ile adres bağlanır,
+/// [`Server::run`] ile kapatma isteğine kadar bağlantı kabul edilir.
+///
+/// ```rust,no_run
+/// # async fn run() -> anyhow::Result<()> {
+/// let server = deltasafe::Server::builder()
+///     .address("0.0.0.0:12345")
+///     .key([0u8; 32])
+///     .bind()
+///     .await?;
+/// server.run(deltasafe::server::shutdown_signal()).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct Server {
+    listener: ServerListener,
+    key: [u8; 32],
+    options: ServerOptions,
+    /// Sunucu düşürülünce duyurular da durur
+    announcement: Option<Announcement>,
+}
+
+enum ServerListener {
+    Tcp(TcpListener),
+    #[cfg(unix)]
+    Unix(tokio::net::UnixListener, PathBuf),
+    Quic(QuicListener),
+}
+
+/// LAN keşfi için mDNS kaydı ve broadcast beacon
+struct Announcement {
+    mdns: Option<mdns_sd::ServiceDaemon>,
+    beacon: Option<tokio::task::JoinHandle<()>>,
+}
+
+impl Drop for Announcement {
+    fn drop(&mut self) {
+        if let Some(beacon) = &self.beacon {
+            beacon.abort();
+        }
+        if let Some(mdns) = &self.mdns {
+            let _ = mdns.shutdown();
+        }
+    }
+}
+
+/// [`Server`] kurucusu
+#[derive(Default)]
+pub struct ServerBuilder {
+    address: Option<String>,
+    key: Option<[u8; 32]>,
+    options: ServerOptions,
+    announce: bool,
+}
+
+impl ServerBuilder {
+    /// `host:port`, `unix:/yol` veya `quic://host:port`
+    pub fn address(mut self, address: impl Into<String>) -> Self {
+        self.address = Some(address.into());
+        self
+    }
+
+    pub fn key(mut self, key: [u8; 32]) -> Self {
+        self.key = Some(key);
+        self
+    }
+
+    pub fn options(mut self, options: ServerOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// TCP sunucusunu mDNS ve broadcast beacon ile LAN'a duyurur
+    /// (varsayılan: kapalı)
+    pub fn announce(mut self, announce: bool) -> Self {
+        self.announce = announce;
+        self
+    }
+
+    /// Ayarları doğrular ve adrese bağlanır; bağlantılar [`Server::run`]
+    /// çağrılana kadar kabul edilmez.
+    pub async fn bind(self) -> Result<Server, Error> {
+        let address = self.address.ok_or_else(|| Error::Io("Sunucu adresi belirtilmedi".to_string()))?;
+        let key = self.key.ok_or_else(|| Error::Io("Anahtar belirtilmedi".to_string()))?;
+        let options = self.options;
+        check_options(&options)?;
+
+        let listener = if let Some(path) = address.strip_prefix("unix:") {
+            bind_unix(Path::new(path))?
+        } else if let Some(rest) = address.strip_prefix("quic://") {
+            bind_quic(rest.trim_end_matches('/'), &key, &options)?
+        } else {
+            bind_tcp(&address)?
+        };
+        let announcement = match &listener {
+            ServerListener::Tcp(tcp) if self.announce => {
+                let port = tcp.local_addr().context("Sunucu adresi alınamadı")?.port();
+                Some(announce(port, &key, &options))
+            }
+            _ => None,
+        };
+        let server = Server { listener, key, options, announcement };
+
+        log::info!("[📡] Sunucu başlatıldı: {} (en fazla {} oturum)", server.endpoint(), server.options.max_sessions);
+        if server.options.vault {
+            log::info!("[🔐] Kasa modu: içerik yalnızca istemcide şifrelenmiş blob'lar olarak saklanır.");
+        }
+        if let Some(storage) = &server.options.storage
+            && storage.local_root().is_none()
+        {
+            log::info!("[🗄️] İçerik harici depolamaya yazılıyor: {:?}", storage);
+        }
+        Ok(server)
+    }
+}
+
+impl Server {
+    pub fn builder() -> ServerBuilder {
+        ServerBuilder::default()
+    }
+
+    /// Bağlanılan TCP veya QUIC adresi; Unix soketinde `None`
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        match &self.listener {
+            ServerListener::Tcp(listener) => listener.local_addr().ok(),
+            #[cfg(unix)]
+            ServerListener::Unix(..) => None,
+            ServerListener::Quic(listener) => listener.local_addr().ok(),
+        }
+    }
+
+    /// İstemcilerin hedef olarak verebileceği adres (örn. `quic://…`)
+    pub fn endpoint(&self) -> String {
+        match &self.listener {
+            #[cfg(unix)]
+            ServerListener::Unix(_, path) => format!("unix:{}", path.display()),
+            ServerListener::Quic(_) => format!("quic://{}", self.local_addr().map(|addr| addr.to_string()).unwrap_or_default()),
+            ServerListener::Tcp(_) => self.local_addr().map(|addr| addr.to_string()).unwrap_or_default(),
+        }
+    }
+
+    /// `shutdown` tamamlanana kadar bağlantı kabul eder; ayrıntılar için
+    /// bkz. [`serve`].
+    pub async fn run(self, shutdown: impl Future<Output = ()>) -> Result<(), Error> {
+        let Server { listener, key, options, announcement } = self;
+        let result = match listener {
+            ServerListener::Tcp(listener) => serve(listener, &key, &options, shutdown).await,
+            #[cfg(unix)]
+            ServerListener::Unix(listener, path) => {
+                let result = serve(listener, &key, &options, shutdown).await;
+                let _ = std::fs::remove_file(path);
+                result
+            }
+            ServerListener::Quic(listener) => serve_quic(listener, &key, &options, shutdown).await,
+        };
+        drop(announcement);
+        result
+    }
+}
+
+fn bind_tcp(address: &str) -> Result<ServerListener> {
+    let addr = net::resolve_target(address)?
+        .into_iter()
+        .next()
+        .context("Sunucu adresi çözümlenemedi")?;
+    let std_listener = net::bind_listener(addr)
+        .context("Sunucu başlatılamadı")?;
+    std_listener.set_nonblocking(true)
+        .context("Soket non-blocking yapılamadı")?;
+    let listener = TcpListener::from_std(std_listener)
+        .context("Soket tokio'ya aktarılamadı")?;
+    Ok(ServerListener::Tcp(listener))
+}
+
+/// mDNS (IPv4 + IPv6) duyurusu ve imzalı broadcast beacon başlatır;
+/// başlatılamayanlar için uyarır.
+fn announce(port: u16, key: &[u8; 32], options: &ServerOptions) -> Announcement {
+    // Kimlik anahtardan türetilmez; duyurular parola tahmini için kullanılamaz
+    let server_id = random_nonce();
+    let mdns = match advertise_service(&options.name, port, &server_id) {
+        Ok(daemon) => Some(daemon),
+        Err(e) => {
+            log::warn!("[⚠️] mDNS duyurusu başlatılamadı: {}", e);
+            None
+        }
+    };
+
+    // Multicast filtrelenen ağlar için imzalı UDP broadcast beacon; imza
+    // anahtarın kendisini gerektirdiğinden erişim anahtarıyla yayın yapılmaz
+    let beacon = if options.access_only {
+        log::info!("[ℹ️] Erişim anahtarıyla çalışıldığı için broadcast beacon yayınlanmıyor (sunucu kimliği: {})", server_id);
+        None
+    } else {
+        match spawn_beacon(options.name.clone(), port, server_id.clone(), *key) {
+            Ok(handle) => {
+                log::info!("[📣] Broadcast beacon yayını başladı (sunucu kimliği: {})", server_id);
+                Some(handle)
+            }
+            Err(e) => {
+                log::warn!("[⚠️] Broadcast beacon başlatılamadı: {}", e);
+                None
+            }
+        }
+    };
+    Announcement { mdns, beacon }
+}
+
+/// Verilen dinleyici üzerinde `shutdown` tamamlanana kadar bağlantı kabul eder.
+///
+/// Oturum sayısı `max_sessions` ile sınırlıdır; sınır dolduğunda yeni
+/// bağlantılar bir oturum bitene kadar kabul edilmez. Kapatma isteği
+/// geldiğinde yeni bağlantı alınmaz, aktif oturumlar ellerindeki dosyayı
+/// tamamlayıp kapanır.
+pub async fn serve(mut listener: impl Listener, key: &[u8; 32], options: &ServerOptions, shutdown: impl Future<Output = ()>) -> Result<(), Error> {
+    check_options(options)?;
+    if let Some(tls) = &options.tls {
+        let mutual = if tls.is_mutual() { ", istemci sertifikası zorunlu" } else { "" };
+        log::info!("[🔒] TLS 1.3 etkin{} (sertifika parmak izi: {})", mutual, tls.pin());
+    }
+    let sessions = Arc::new(Semaphore::new(options.max_sessions.max(1)));
+    let (shutdown_tx, shutdown_rx) = watch::channel(false);
+    let registry = Arc::new(TransferRegistry::default());
+    let mut tasks = JoinSet::new();
+    tokio::pin!(shutdown);
+
+    loop {
+        // Backpressure: boş oturum yoksa accept çağrılmaz
+        if sessions.available_permits() == 0 {
+            log::info!("[⏳] Oturum sınırına ulaşıldı ({}), yeni bağlantılar bekletiliyor.", options.max_sessions);
+        }
+        let permit = tokio::select! {
+            _ = &mut shutdown => break,
+            permit = sessions.clone().acquire_owned() => permit.context("Oturum semaforu kapandı")?,
+        };
+
+        let connection = tokio::select! {
+            _ = &mut shutdown => break,
+            accepted = listener.accept() => match accepted {
+                Ok(accepted) => accepted,
+                Err(e) => {
+                    log::warn!("[⚠️] Bağlantı hatası: {}", e);
+                    // Dosya tanımlayıcısı tükenmesi gibi durumlarda döngüyü yakma
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                    continue;
+                }
+            },
+        };
+
+        let key = *key;
+        let options = options.clone();
+        let registry = registry.clone();
+        let shutdown_rx = shutdown_rx.clone();
+        tasks.spawn(async move {
+            let _permit = permit;
+            handle_client(connection, &key, &options, &registry, shutdown_rx).await;
+        });
+
+        // Biten oturumları topla
+        while tasks.try_join_next().is_some() {}
+    }
+
+    while tasks.try_join_next().is_some() {}
+    if !tasks.is_empty() {
+        log::info!("[🛑] Kapatma isteği alındı, {} aktif oturumun dosyaları tamamlanıyor...", tasks.len());
+    }
+    let _ = shutdown_tx.send(true);
+    while tasks.join_next().await.is_some() {}
+    log::info!("[🛑] Sunucu kapatıldı.");
+    Ok(())
+}
+
+/// Unix soketinde dinler. Yalnızca yerel bağlantılar geldiği için mDNS ve
+/// beacon duyurusu yapılmaz.
+#[cfg(unix)]
+fn bind_unix(path: &Path) -> Result<ServerListener> {
+    use std::os::unix::fs::FileTypeExt;
+    // Önceki çalışmadan kalan soket dosyası bind'ı engeller
+    if std::fs::symlink_metadata(path).is_ok_and(|metadata| metadata.file_type().is_socket()) {
+        std::fs::remove_file(path)
+            .with_context(|| format!("Eski soket silinemedi: {}", path.display()))?;
+    }
+    let listener = tokio::net::UnixListener::bind(path)
+        .with_context(|| format!("Unix soketi açılamadı: {}", path.display()))?;
+    Ok(ServerListener::Unix(listener, path.to_path_buf()))
+}
+
+#[cfg(not(unix))]
+fn bind_unix(_path: &Path) -> Result<ServerListener> {
+    anyhow::bail!("Unix soketleri bu platformda desteklenmiyor")
+}
+
+/// QUIC (UDP) üzerinde dinler. Sertifika erişim anahtarından türetilir;
+/// keşif duyuruları TCP adresi bildirdiği için yapılmaz.
+fn bind_quic(address: &str, key: &[u8; 32], options: &ServerOptions) -> Result<ServerListener> {
+    let addr = net::resolve_target(address)?
+        .into_iter()
+        .next()
+        .context("Sunucu adresi çözümlenemedi")?;
+    if options.tls.is_some() {
+        anyhow::bail!("QUIC bağlantıları zaten TLS 1.3 kullanır; --tls-cert QUIC ile kullanılamaz");
+    }
+    let access = if options.access_only { *key } else { access_key(key) };
+    Ok(ServerListener::Quic(QuicListener::bind(addr, &access)?))
+}
+
+/// [`serve`] ile aynı; dönmeden önce QUIC bağlantılarını düzgünce kapatır
+pub async fn serve_quic(mut listener: QuicListener, key: &[u8; 32], options: &ServerOptions, shutdown: impl Future<Output = ()>) -> Result<(), Error> {
+    let result = serve(&mut listener, key, options, shutdown).await;
+    listener.close().await;
+    result
+}
+
+/// Tek bir bağlantıya (örn. `ssh` altında stdin/stdout) hizmet verir ve
+/// oturum bitince döner. Ek akış açılamayacağı için tek akışa izin verilir;
+/// kapatma isteğinde açık dosyalar tamamlanır.
+pub async fn serve_connection(connection: Connection, key: &[u8; 32], options: &ServerOptions, shutdown: impl Future<Output = ()>) -> Result<(), Error> {
+    check_options(options)?;
+    let options = ServerOptions { max_streams: 1, ..options.clone() };
+    let (shutdown_tx, shutdown_rx) = watch::channel(false);
+    let registry = TransferRegistry::default();
+    let session = handle_client(connection, key, &options, &registry, shutdown_rx);
+    tokio::pin!(session);
+    tokio::select! {
+        _ = &mut session => return Ok(()),
+        _ = shutdown => {}
+    }
+    let _ = shutdown_tx.send(true);
+    session.await;
+    Ok(())
+}
+
+/// Birbiriyle çelişen sunucu ayarlarını reddeder
+fn check_options(options: &ServerOptions) -> Result<()> {
+    if options.access_only && !options.vault {
+        anyhow::bail!("Yalnızca erişim anahtarıyla içerik çözülemez; kasa modu gerekli");
+    }
+    if options.retention.is_enabled() && options.storage().local_root().is_none() {
+        anyhow::bail!("Sürüm saklama yalnızca yerel depolamada kullanılabilir");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::SocketAddr;
+    use tokio::net::TcpStream;
+    use crate::protocol::{Auth, Hello};
+    use crate::chunk::ChunkSize;
+    use crate::compress::Compression;
+    use crate::filter::FilterOptions;
+    use crate::metadata::FileMetadata;
+    use crate::entry::SymlinkPolicy;
+    use crate::error::ErrorCode;
+    use tokio::io::AsyncReadExt;
+    use crate::sync::{encode_chunk, sync_files, sync_files_over, sync_files_with_options, FileOutcome as SyncOutcome, SyncClient, SyncOptions};
+    use crate::transport::Connector;
+    use std::pin::Pin;
+    use crate::storage::MemoryStorage;
+    use std::io::Read;
+
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("deltasafe_server_{}_{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    async fn start_test_server(root: PathBuf, max_sessions: usize, io_timeout: Duration) -> (SocketAddr, tokio::sync::oneshot::Sender<()>, tokio::task::JoinHandle<Result<(), Error>>) {
+        spawn_test_server(ServerOptions {
+            name: "test".to_string(),
+            root,
+            storage: None,
+            max_sessions,
+            io_timeout,
+            max_streams: 4,
+            max_chunk_size: MAX_CHUNK_SIZE,
+            compression: true,
+            client_bwlimit: Bandwidth::Unlimited,
+            preserve: Preserve::default(),
+            xattrs: XattrFilter::default(),
+            retention: Retention::default(),
+            vault: false,
+            access_only: false,
+            tls: None,
+            events: Events::default(),
+        }).await
+    }
+
+    async fn spawn_test_server(options: ServerOptions) -> (SocketAddr, tokio::sync::oneshot::Sender<()>, tokio::task::JoinHandle<Result<(), Error>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (stop_tx, stop_rx) = tokio::sync::oneshot::channel::<()>();
+        let handle = tokio::spawn(async move {
+            serve(listener, &[9u8; 32], &options, async { let _ = stop_rx.await; }).await
+        });
+        (addr, stop_tx, handle)
+    }
+
+    /// Ham protokolle el sıkışır; `auth_key` ile istemci kanıtı üretilir
+    async fn raw_handshake(stream: &mut TcpStream, auth_key: &[u8; 32], join: Option<&str>) -> Option<Welcome> {
+        let hello = Hello { protocol: PROTOCOL_VERSION, streams: 2, join: join.map(str::to_string), nonce: "istemci".to_string(), chunk_size: 0, compression: None, probe: false };
+        protocol::write_frame(stream, &Frame::Hello(hello)).await.unwrap();
+        let welcome = match protocol::read_frame(stream).await.unwrap() {
+            Some(Frame::Welcome(welcome)) => welcome,
+            _ => return None,
+        };
+        let proof = handshake_proof(&access_key(auth_key), Role::Client, "istemci", &welcome.nonce, &welcome.session);
+        protocol::write_frame(stream, &Frame::Auth(Auth { proof })).await.unwrap();
+        Some(welcome)
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_files_received_and_verified() {
+        let dir = test_dir("receive");
+        let source = dir.join("source");
+        let root = dir.join("received");
+        std::fs::create_dir_all(source.join("alt")).unwrap();
+        std::fs::write(source.join("a.txt"), "merhaba").unwrap();
+        std::fs::write(source.join("alt/b.bin"), vec![7u8; 10_000]).unwrap();
+        std::fs::write(source.join("bos.txt"), "").unwrap();
+
+        let (addr, stop, handle) = start_test_server(root.clone(), 4, Duration::from_secs(5)).await;
+
+        sync_files(&source.to_string_lossy(), &addr.to_string(), &[9u8; 32]).await.unwrap();
+
+        stop.send(()).unwrap();
+        handle.await.unwrap().unwrap();
+
+        assert_eq!(std::fs::read_to_string(root.join("a.txt")).unwrap(), "merhaba");
+        assert_eq!(std::fs::read(root.join("alt/b.bin")).unwrap(), vec![7u8; 10_000]);
+        assert_eq!(std::fs::read(root.join("bos.txt")).unwrap(), Vec::<u8>::new());
+        assert!(!temp_path_for(&root.join("a.txt")).exists());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_files_received_into_memory_storage() {
+        let dir = test_dir("memory");
+        let source = dir.join("source");
+        std::fs::create_dir_all(source.join("alt")).unwrap();
+        std::fs::create_dir_all(source.join("bos")).unwrap();
+        std::fs::write(source.join("a.txt"), "merhaba").unwrap();
+        let big: Vec<u8> = (0..3_000_000u32).map(|i| (i % 251) as u8).collect();
+        std::fs::write(source.join("alt/buyuk.bin"), &big).unwrap();
+
+        let storage = MemoryStorage::new();
+        let root = dir.join("received");
+        let options = ServerOptions { root: root.clone(), storage: Some(Arc::new(storage.clone())), ..ServerOptions::default() };
+        let (addr, stop, handle) = spawn_test_server(options).await;
+        let sync_options = SyncOptions { streams: 4, ..SyncOptions::default() };
+        sync_files_with_options(&source.to_string_lossy(), &addr.to_string(), &[9u8; 32], &sync_options).await.unwrap();
+        stop.send(()).unwrap();
+        handle.await.unwrap().unwrap();
+
+        let read = |path: &str| {
+            let mut data = Vec::new();
+            storage.read(Path::new(path)).unwrap().read_to_end(&mut data).unwrap();
+            data
+        };
+        assert_eq!(read("a.txt"), b"merhaba");
+        assert!(read("alt/buyuk.bin") == big);
+        // Hiçbir şey yerel köke yazılmaz
+        assert!(!root.exists());
+
+        // Sürümler yerel depolama gerektirir
+        let options = ServerOptions { storage: Some(Arc::new(storage)), retention: Retention { keep_last: 1, keep_daily: 0 }, ..ServerOptions::default() };
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        assert!(serve(listener, &[9u8; 32], &options, async {}).await.is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_wrong_key_does_not_commit_file() {
+        let dir = test_dir("wrong_key");
+        let source = dir.join("source");
+        let root = dir.join("received");
+        std::fs::create_dir_all(&source).unwrap();
+        std::fs::write(source.join("gizli.txt"), "gizli veri").unwrap();
+
+        let (addr, stop, handle) = start_test_server(root.clone(), 4, Duration::from_secs(5)).await;
+
+        // Sunucu dosyayı reddeder, istemci hatayı raporlar
+        let error = sync_files(&source.to_string_lossy(), &addr.to_string(), &[1u8; 32]).await.unwrap_err();
+        assert_eq!(error.code(), ErrorCode::Auth);
+
+        stop.send(()).unwrap();
+        handle.await.unwrap().unwrap();
+
+        assert!(!root.join("gizli.txt").exists());
+        assert!(!temp_path_for(&root.join("gizli.txt")).exists());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_idle_connection_times_out() {
+        let dir = test_dir("idle");
+        let (addr, stop, handle) = start_test_server(dir.clone(), 4, Duration::from_millis(200)).await;
+
+        let mut idle = TcpStream::connect(addr).await.unwrap();
+        let mut buf = [0u8; 1];
+        // Sunucu zaman aşımında bağlantıyı kapatır (EOF)
+        let read = tokio::time::timeout(Duration::from_secs(5), idle.read(&mut buf)).await.unwrap();
+        assert_eq!(read.unwrap(), 0);
+
+        stop.send(()).unwrap();
+        handle.await.unwrap().unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_session_limit_applies_backpressure() {
+        let dir = test_dir("limit");
+        let (addr, stop, handle) = start_test_server(dir.clone(), 1, Duration::from_millis(500)).await;
+
+        // İlk bağlantı tek oturumu doldurur
+        let _first = TcpStream::connect(addr).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        // İkinci bağlantı TCP seviyesinde kurulur ama ilk oturum zaman aşımına
+        // uğrayana kadar sunucu tarafından ele alınmaz
+        let started = std::time::Instant::now();
+        let mut second = TcpStream::connect(addr).await.unwrap();
+        let mut buf = [0u8; 1];
+        let read = tokio::time::timeout(Duration::from_secs(5), second.read(&mut buf)).await.unwrap();
+        assert_eq!(read.unwrap(), 0);
+        assert!(started.elapsed() >= Duration::from_millis(800));
+
+        stop.send(()).unwrap();
+        handle.await.unwrap().unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_closes_idle_sessions() {
+        let dir = test_dir("shutdown");
+        let (addr, stop, handle) = start_test_server(dir.clone(), 4, Duration::from_secs(30)).await;
+
+        let mut idle = TcpStream::connect(addr).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        stop.send(()).unwrap();
+
+        tokio::time::timeout(Duration::from_secs(5), handle).await.unwrap().unwrap().unwrap();
+        let mut buf = [0u8; 1];
+        assert_eq!(idle.read(&mut buf).await.unwrap(), 0);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_rejects_paths_outside_root() {
+        assert!(is_safe_relative_path(Path::new("a.txt")));
+        assert!(is_safe_relative_path(Path::new("alt/a.txt")));
+        assert!(!is_safe_relative_path(Path::new("../a.txt")));
+        assert!(!is_safe_relative_path(Path::new("alt/../../a.txt")));
+        assert!(!is_safe_relative_path(Path::new("/etc/passwd")));
+        assert!(!is_safe_relative_path(Path::new("")));
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_finishes_in_flight_file() {
+        let dir = test_dir("drain");
+        let key = [9u8; 32];
+        let (addr, stop, handle) = start_test_server(dir.clone(), 4, Duration::from_secs(5)).await;
+
+        let content = b"0123456789";
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        raw_handshake(&mut stream, &key, None).await.unwrap();
+
+        let header = FileHeader {
+            file_name: "yarim.txt".to_string(),
+            file_size: content.len() as u64,
+            file_hash: blake3::hash(content).to_hex().to_string(),
+            relative_path: PathBuf::from("yarim.txt"),
+            metadata: FileMetadata::default(),
+        };
+        protocol::write_frame(&mut stream, &Frame::File { id: 1, header }).await.unwrap();
+        stream.write_all(&encode_chunk(1, 0, &content[..5], &key).unwrap()).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        // Kapatma isteği dosya yarımken gelir; sunucu dosyayı bekler
+        stop.send(()).unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!handle.is_finished());
+
+        stream.write_all(&encode_chunk(1, 5, &content[5..], &key).unwrap()).await.unwrap();
+        match protocol::read_frame(&mut stream).await.unwrap() {
+            Some(Frame::FileResult { id, result }) => assert!(id == 1 && result.is_ok()),
+            other => panic!("beklenmeyen yanıt: {:?}", other),
+        }
+
+        tokio::time::timeout(Duration::from_secs(5), handle).await.unwrap().unwrap().unwrap();
+        assert_eq!(std::fs::read(dir.join("yarim.txt")).unwrap(), content);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_parallel_streams_reassemble_ranges() {
+        let dir = test_dir("streams");
+        let source = dir.join("source");
+        let root = dir.join("received");
+        std::fs::create_dir_all(&source).unwrap();
+        // Aralık boyutundan büyük dosya birden fazla akışa bölünür
+        let big: Vec<u8> = (0..20 * 1024 * 1024u32).map(|i| (i % 251) as u8).collect();
+        std::fs::write(source.join("buyuk.bin"), &big).unwrap();
+        for i in 0..10 {
+            std::fs::write(source.join(format!("kucuk{}.txt", i)), format!("dosya {}", i)).unwrap();
+        }
+
+        let (addr, stop, handle) = start_test_server(root.clone(), 8, Duration::from_secs(5)).await;
+
+        let options = SyncOptions { window: 4, streams: 3, ..SyncOptions::default() };
+        sync_files_with_options(&source.to_string_lossy(), &addr.to_string(), &[9u8; 32], &options).await.unwrap();
+
+        stop.send(()).unwrap();
+        handle.await.unwrap().unwrap();
+
+        assert!(std::fs::read(root.join("buyuk.bin")).unwrap() == big);
+        for i in 0..10 {
+            assert_eq!(std::fs::read_to_string(root.join(format!("kucuk{}.txt", i))).unwrap(), format!("dosya {}", i));
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// Her bağlantıda süreç içi bir boru çifti açıp sunucu ucunu dinleyiciye iletir
+    struct DuplexConnector(tokio::sync::mpsc::Sender<Connection>);
+
+    impl std::fmt::Display for DuplexConnector {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "duplex")
+        }
+    }
+
+    impl Connector for DuplexConnector {
+        fn connect(&self) -> Pin<Box<dyn Future<Output = std::io::Result<Connection>> + Send + '_>> {
+            Box::pin(async move {
+                let (client, server) = tokio::io::duplex(64 * 1024);
+                self.0.send(Connection::new(server)).await
+                    .map_err(|_| std::io::Error::new(std::io::ErrorKind::ConnectionRefused, "dinleyici kapalı"))?;
+                Ok(Connection::new(client))
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_busy_file_does_not_block_transfer() {
+        let root = test_dir("busy_file");
+        let options = ServerOptions { root: root.clone(), ..ServerOptions::default() };
+        let key = [8u8; 32];
+        let registry = TransferRegistry::default();
+        let transfer = registry.start("oturum".to_string(), 2, MAX_CHUNK_SIZE, false, &Peer::Pipe("test".to_string()), &options);
+        let header = |name: &str| FileHeader {
+            file_name: name.to_string(),
+            file_size: 10,
+            file_hash: "00".repeat(32),
+            relative_path: PathBuf::from(name),
+            metadata: FileMetadata::default(),
+        };
+
+        assert!(open_file(&transfer, 1, header("a.txt"), &key, &options, false).await.unwrap().is_none());
+        // Dosya kilidi uzun bir commit sırasındaki gibi tutulurken
+        let busy = incoming_file(&transfer, 1).await.unwrap().unwrap();
+        let guard = busy.lock().await;
+
+        // Başka akıştan gelen aynı başlık dosya kilidini bekler...
+        let repeated = open_file(&transfer, 1, header("a.txt"), &key, &options, false);
+        tokio::pin!(repeated);
+        assert!(tokio::time::timeout(Duration::from_millis(50), &mut repeated).await.is_err());
+
+        // ...ama aktarımın diğer dosyaları beklemez
+        let other = tokio::time::timeout(Duration::from_secs(1), open_file(&transfer, 2, header("b.txt"), &key, &options, false)).await;
+        assert!(other.unwrap().unwrap().is_none());
+        assert!(tokio::time::timeout(Duration::from_secs(1), incoming_file(&transfer, 2)).await.unwrap().unwrap().is_some());
+
+        drop(guard);
+        assert!(repeated.await.unwrap().is_none());
+        let changed = FileHeader { file_size: 11, ..header("a.txt") };
+        assert_eq!(open_file(&transfer, 1, changed, &key, &options, false).await.unwrap_err().code(), ErrorCode::Protocol);
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_sync_over_duplex_pipes() {
+        let dir = test_dir("duplex");
+        let source = dir.join("source");
+        let root = dir.join("received");
+        std::fs::create_dir_all(source.join("alt")).unwrap();
+        let big: Vec<u8> = (0..12 * 1024 * 1024u32).map(|i| (i % 239) as u8).collect();
+        std::fs::write(source.join("buyuk.bin"), &big).unwrap();
+        std::fs::write(source.join("alt/a.txt"), "boru").unwrap();
+
+        // Çoklu akış: ek akışlar da aynı borular üzerinden oturuma katılır
+        let (tx, rx) = tokio::sync::mpsc::channel(8);
+        let options = ServerOptions { root: root.clone(), ..ServerOptions::default() };
+        let (stop_tx, stop_rx) = tokio::sync::oneshot::channel::<()>();
+        let server = tokio::spawn(async move {
+            serve(rx, &[9u8; 32], &options, async { let _ = stop_rx.await; }).await
+        });
+        let connector = DuplexConnector(tx);
+        let sync_options = SyncOptions { streams: 3, ..SyncOptions::default() };
+        sync_files_over(&source.to_string_lossy(), &connector, &[9u8; 32], &sync_options).await.unwrap();
+        stop_tx.send(()).unwrap();
+        server.await.unwrap().unwrap();
+        assert!(std::fs::read(root.join("buyuk.bin")).unwrap() == big);
+        assert_eq!(std::fs::read_to_string(root.join("alt/a.txt")).unwrap(), "boru");
+
+        // Tek bağlantılık sunucu (ör. `serve --stdio`) akış sayısını bire indirir
+        let root = dir.join("single");
+        let options = ServerOptions { root: root.clone(), ..ServerOptions::default() };
+        let (client, server_half) = tokio::io::duplex(64 * 1024);
+        let server = tokio::spawn(async move {
+            serve_connection(Connection::new(server_half), &[9u8; 32], &options, std::future::pending()).await
+        });
+        let connector = SingleConnector(std::sync::Mutex::new(Some(client)));
+        sync_files_over(&source.to_string_lossy(), &connector, &[9u8; 32], &sync_options).await.unwrap();
+        server.await.unwrap().unwrap();
+        assert!(std::fs::read(root.join("buyuk.bin")).unwrap() == big);
+        assert_eq!(std::fs::read_to_string(root.join("alt/a.txt")).unwrap(), "boru");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_sync_over_quic() {
+        let dir = test_dir("quic");
+        let source = dir.join("source");
+        let root = dir.join("received");
+        std::fs::create_dir_all(source.join("alt")).unwrap();
+        let big: Vec<u8> = (0..12 * 1024 * 1024u32).map(|i| (i % 241) as u8).collect();
+        std::fs::write(source.join("buyuk.bin"), &big).unwrap();
+        for i in 0..12 {
+            std::fs::write(source.join(format!("alt/kucuk{}.txt", i)), format!("quic {}", i)).unwrap();
+        }
+
+        let listener = QuicListener::bind("127.0.0.1:0".parse().unwrap(), &access_key(&[9u8; 32])).unwrap();
+        let addr = listener.local_addr().unwrap();
+        let options = ServerOptions { root: root.clone(), ..ServerOptions::default() };
+        let (stop_tx, stop_rx) = tokio::sync::oneshot::channel::<()>();
+        let server = tokio::spawn(async move {
+            serve_quic(listener, &[9u8; 32], &options, async { let _ = stop_rx.await; }).await
+        });
+
+        sync_files_with_options(&source.to_string_lossy(), &format!("quic://{}", addr), &[9u8; 32], &SyncOptions::default()).await.unwrap();
+        assert!(std::fs::read(root.join("buyuk.bin")).unwrap() == big);
+        for i in 0..12 {
+            assert_eq!(std::fs::read_to_string(root.join(format!("alt/kucuk{}.txt", i))).unwrap(), format!("quic {}", i));
+        }
+
+        // Başka anahtarla türetilen sertifika reddedilir
+        let error = sync_files_with_options(&source.to_string_lossy(), &format!("quic://{}", addr), &[8u8; 32], &SyncOptions::default()).await.unwrap_err();
+        assert!(format!("{:#}", error).contains("Sunucuya bağlanılamadı"));
+
+        stop_tx.send(()).unwrap();
+        server.await.unwrap().unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_quic_sync_survives_client_rebind() {
+        let dir = test_dir("quic_rebind");
+        let source = dir.join("source");
+        let root = dir.join("received");
+        std::fs::create_dir_all(&source).unwrap();
+        let data: Vec<u8> = (0..2 * 1024 * 1024u32).map(|i| (i % 239) as u8).collect();
+        std::fs::write(source.join("veri.bin"), &data).unwrap();
+        for i in 0..4 {
+            std::fs::write(source.join(format!("kucuk{}.txt", i)), format!("tasima {}", i)).unwrap();
+        }
+
+        let listener = QuicListener::bind("127.0.0.1:0".parse().unwrap(), &access_key(&[9u8; 32])).unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (server_events, mut server_rx) = Events::channel();
+        let options = ServerOptions { root: root.clone(), events: server_events, ..ServerOptions::default() };
+        let (stop_tx, stop_rx) = tokio::sync::oneshot::channel::<()>();
+        let server = tokio::spawn(async move {
+            serve_quic(listener, &[9u8; 32], &options, async { let _ = stop_rx.await; }).await
+        });
+
+        // Hız sınırıyla aktarım yaklaşık bir saniye sürer; ortasında istemcinin
+        // yerel soketi değiştirilir (başka ağa geçen dizüstü bilgisayar gibi)
+        let connector = crate::quic::QuicConnector::new(vec![addr], &access_key(&[9u8; 32])).unwrap();
+        let sync_options = SyncOptions { bwlimit: Bandwidth::Limited(2 * 1024 * 1024), ..SyncOptions::default() };
+        let socket = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        let moved_to = socket.local_addr().unwrap();
+        let migrate = async {
+            tokio::time::sleep(Duration::from_millis(300)).await;
+            connector.rebind(socket).await.unwrap();
+        };
+        let source_path = source.to_string_lossy();
+        let (synced, ()) = tokio::join!(sync_files_over(&source_path, &connector, &[9u8; 32], &sync_options), migrate);
+        synced.unwrap();
+        assert!(std::fs::read(root.join("veri.bin")).unwrap() == data);
+        for i in 0..4 {
+            assert_eq!(std::fs::read_to_string(root.join(format!("kucuk{}.txt", i))).unwrap(), format!("tasima {}", i));
+        }
+
+        // Bağlantı yeniden kurulmadı; sonraki oturumun akışları yeni adresten gelir
+        while server_rx.try_recv().is_ok() {}
+        std::fs::write(source.join("kucuk0.txt"), "yeni adres").unwrap();
+        sync_files_over(&source_path, &connector, &[9u8; 32], &SyncOptions::default()).await.unwrap();
+        let started = std::iter::from_fn(|| server_rx.try_recv().ok())
+            .find(|event| matches!(event.kind, EventKind::SessionStarted { .. }))
+            .unwrap();
+        assert_eq!(started.peer, Peer::Quic(moved_to));
+
+        stop_tx.send(()).unwrap();
+        server.await.unwrap().unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_builders_report_file_outcomes() {
+        let dir = test_dir("builders");
+        let source = dir.join("source");
+        let root = dir.join("received");
+        std::fs::create_dir_all(source.join("alt")).unwrap();
+        std::fs::write(source.join("alt/iyi.txt"), "gönderildi").unwrap();
+        std::fs::write(source.join("cakisma.txt"), "reddedilir").unwrap();
+        // Hedefte aynı adlı dolu bir klasör olduğu için dosya yazılamaz
+        std::fs::create_dir_all(root.join("cakisma.txt/ic")).unwrap();
+
+        assert!(Server::builder().key([9u8; 32]).bind().await.is_err());
+        let options = ServerOptions { root: root.clone(), io_timeout: Duration::from_secs(2), ..ServerOptions::default() };
+        let server = Server::builder()
+            .address("127.0.0.1:0")
+            .key([9u8; 32])
+            .options(options)
+            .bind()
+            .await
+            .unwrap();
+        let target = server.endpoint();
+        assert_eq!(server.local_addr().map(|addr| addr.to_string()), Some(target.clone()));
+        let (stop_tx, stop_rx) = tokio::sync::oneshot::channel::<()>();
+        let handle = tokio::spawn(server.run(async { let _ = stop_rx.await; }));
+
+        assert!(SyncClient::builder().source(&source).key([9u8; 32]).build().is_err());
+        let client = SyncClient::builder()
+            .source(&source)
+            .target(target)
+            .key([9u8; 32])
+            .build()
+            .unwrap();
+        let report = client.sync().await.unwrap();
+        assert_eq!(report.server_name, ServerOptions::default().name);
+        let outcome = |path: &str| report.files.iter()
+            .find(|file| file.path == Path::new(path))
+            .map(|file| file.outcome.clone());
+        assert_eq!(outcome("alt/iyi.txt"), Some(SyncOutcome::Sent));
+        assert!(matches!(outcome("cakisma.txt"), Some(SyncOutcome::Rejected(Error::Io(_)))));
+        assert!(matches!(outcome("alt"), Some(SyncOutcome::Sent)));
+        assert!(!report.is_success());
+        assert_eq!(report.bytes_sent(), "gönderildi".len() as u64);
+        let error = report.ensure_success().unwrap_err();
+        assert!(error.to_string().contains("cakisma.txt"));
+        assert_eq!(error.code(), ErrorCode::Io);
+        assert_eq!(std::fs::read_to_string(root.join("alt/iyi.txt")).unwrap(), "gönderildi");
+
+        stop_tx.send(()).unwrap();
+        handle.await.unwrap().unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_events_on_client_and_server() {
+        let dir = test_dir("events");
+        let source = dir.join("source");
+        let root = dir.join("received");
+        std::fs::create_dir_all(source.join("alt")).unwrap();
+        std::fs::write(source.join("alt/iyi.txt"), vec![7u8; 300_000]).unwrap();
+        std::fs::write(source.join("cakisma.txt"), "reddedilir").unwrap();
+        std::fs::create_dir_all(root.join("cakisma.txt/ic")).unwrap();
+        #[cfg(unix)]
+        create_symlink(Path::new("/etc/passwd"), &source.join("disari")).unwrap();
+
+        let (server_events, mut server_rx) = Events::channel();
+        let options = ServerOptions { root: root.clone(), io_timeout: Duration::from_secs(2), events: server_events, ..ServerOptions::default() };
+        let (addr, stop, handle) = spawn_test_server(options).await;
+
+        let (client_events, mut client_rx) = Events::channel();
+        let sync_options = SyncOptions { streams: 1, chunk_size: ChunkSize::Fixed(64 * 1024), events: client_events, ..SyncOptions::default() };
+        let report = SyncClient::builder()
+            .source(&source)
+            .target(addr.to_string())
+            .key([9u8; 32])
+            .options(sync_options)
+            .build()
+            .unwrap()
+            .sync()
+            .await
+            .unwrap();
+        assert!(!report.is_success());
+
+        let mut client = Vec::new();
+        while let Ok(event) = client_rx.try_recv() {
+            assert_eq!(event.peer, report.peer);
+            client.push(event.kind);
+        }
+        assert_eq!(client[0], EventKind::SessionStarted { files: 2, bytes: 300_010 });
+        #[cfg(unix)]
+        assert!(client.contains(&EventKind::FileSkipped { path: PathBuf::from("disari"), reason: "Kökün dışını gösteren sembolik bağlantı".to_string() }));
+        assert!(client.contains(&EventKind::FileStarted { path: PathBuf::from("alt/iyi.txt"), size: 300_000 }));
+        let sent: u64 = client.iter()
+            .filter_map(|kind| match kind {
+                EventKind::ChunkSent { path, bytes, .. } if path == Path::new("alt/iyi.txt") => Some(*bytes),
+                _ => None,
+            })
+            .sum();
+        assert_eq!(sent, 300_000);
+        assert!(client.contains(&EventKind::FileVerified { path: PathBuf::from("alt/iyi.txt"), size: 300_000 }));
+        assert!(client.contains(&EventKind::FileVerified { path: PathBuf::from("alt"), size: 0 }));
+        assert!(client.iter().any(|kind| matches!(kind, EventKind::Error { path: Some(path), .. } if path == Path::new("cakisma.txt"))));
+        assert_eq!(client.last(), Some(&EventKind::SessionFinished { verified: 2, failed: 1, bytes: 300_000 }));
+
+        // Sunucu oturumu istemci bağlantıyı kapatınca bitirir
+        let mut server = Vec::new();
+        loop {
+            let event = tokio::time::timeout(Duration::from_secs(5), server_rx.recv()).await.unwrap().unwrap();
+            let finished = matches!(event.kind, EventKind::SessionFinished { .. });
+            server.push(event.kind);
+            if finished {
+                break;
+            }
+        }
+        assert_eq!(server[0], EventKind::SessionStarted { files: 0, bytes: 0 });
+        assert!(server.contains(&EventKind::FileStarted { path: PathBuf::from("alt/iyi.txt"), size: 300_000 }));
+        assert!(server.iter().any(|kind| matches!(kind, EventKind::ChunkSent { offset: 65536, bytes: 65536, .. })));
+        assert!(server.contains(&EventKind::FileVerified { path: PathBuf::from("alt/iyi.txt"), size: 300_000 }));
+        assert!(server.iter().any(|kind| matches!(kind, EventKind::Error { path: Some(path), .. } if path == Path::new("cakisma.txt"))));
+        assert_eq!(server.last(), Some(&EventKind::SessionFinished { verified: 2, failed: 1, bytes: 300_000 }));
+
+        // Anahtarı bilmeyen istemci sunucuda hata olayı üretir
+        assert!(sync_files(&source.to_string_lossy(), &addr.to_string(), &[1u8; 32]).await.is_err());
+        let event = tokio::time::timeout(Duration::from_secs(5), server_rx.recv()).await.unwrap().unwrap();
+        assert_eq!(event.kind, EventKind::Error { path: None, message: "Kimlik doğrulaması başarısız".to_string() });
+
+        stop.send(()).unwrap();
+        handle.await.unwrap().unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_sync_over_tls() {
+        let dir = test_dir("tls");
+        let source = dir.join("source");
+        let root = dir.join("received");
+        std::fs::create_dir_all(&source).unwrap();
+        std::fs::write(source.join("gizli.txt"), "tls ile").unwrap();
+
+        let certified = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+        std::fs::write(dir.join("server.crt"), certified.cert.pem()).unwrap();
+        std::fs::write(dir.join("server.key"), certified.key_pair.serialize_pem()).unwrap();
+        let tls = ServerTls::new(&dir.join("server.crt"), &dir.join("server.key"), None).unwrap();
+        let pin = tls.pin().to_string();
+        let options = ServerOptions { root: root.clone(), tls: Some(tls), io_timeout: Duration::from_secs(2), ..ServerOptions::default() };
+        let (addr, stop, handle) = spawn_test_server(options).await;
+
+        let client = crate::tls::ClientTls::new(None, Some(&pin), None).unwrap();
+        let sync_options = SyncOptions { tls: Some(client), ..SyncOptions::default() };
+        sync_files_with_options(&source.to_string_lossy(), &addr.to_string(), &[9u8; 32], &sync_options).await.unwrap();
+        assert_eq!(std::fs::read_to_string(root.join("gizli.txt")).unwrap(), "tls ile");
+
+        // TLS'siz istemci el sıkışmayı geçemez
+        std::fs::write(source.join("gizli.txt"), "duz").unwrap();
+        assert!(sync_files(&source.to_string_lossy(), &addr.to_string(), &[9u8; 32]).await.is_err());
+        assert_eq!(std::fs::read_to_string(root.join("gizli.txt")).unwrap(), "tls ile");
+
+        stop.send(()).unwrap();
+        handle.await.unwrap().unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// Yalnızca bir kez bağlanabilen, hazır bir boru ucunu döndüren bağlayıcı
+    struct SingleConnector(std::sync::Mutex<Option<tokio::io::DuplexStream>>);
+
+    impl std::fmt::Display for SingleConnector {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "tek boru")
+        }
+    }
+
+    impl Connector for SingleConnector {
+        fn connect(&self) -> Pin<Box<dyn Future<Output = std::io::Result<Connection>> + Send + '_>> {
+            let stream = self.0.lock().unwrap().take();
+            Box::pin(async move {
+                stream.map(Connection::new)
+                    .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::ConnectionRefused, "boru zaten kullanıldı"))
+            })
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_chunk_sizes_negotiated() {
+        let dir = test_dir("chunks");
+        let source = dir.join("source");
+        std::fs::create_dir_all(&source).unwrap();
+        let data: Vec<u8> = (0..9 * 1024 * 1024u32).map(|i| (i % 253) as u8).collect();
+        std::fs::write(source.join("veri.bin"), &data).unwrap();
+        std::fs::write(source.join("kucuk.txt"), "kucuk").unwrap();
+
+        // Her boyut, sunucu sınırı ile birlikte uçtan uca denenir
+        let cases = [
+            (ChunkSize::Fixed(MIN_CHUNK_SIZE), MAX_CHUNK_SIZE),
+            (ChunkSize::Fixed(1024 * 1024), MAX_CHUNK_SIZE),
+            (ChunkSize::Fixed(MAX_CHUNK_SIZE), MAX_CHUNK_SIZE),
+            (ChunkSize::Auto, MAX_CHUNK_SIZE),
+            (ChunkSize::Fixed(MAX_CHUNK_SIZE), MIN_CHUNK_SIZE),
+            (ChunkSize::Auto, 128 * 1024),
+        ];
+        for (i, (chunk_size, max_chunk_size)) in cases.into_iter().enumerate() {
+            let root = dir.join(format!("received{}", i));
+            let (addr, stop, handle) = spawn_test_server(ServerOptions {
+                root: root.clone(),
+                max_chunk_size,
+                ..ServerOptions::default()
+            }).await;
+
+            let options = SyncOptions { streams: 2, chunk_size, ..SyncOptions::default() };
+            sync_files_with_options(&source.to_string_lossy(), &addr.to_string(), &[9u8; 32], &options).await.unwrap();
+
+            stop.send(()).unwrap();
+            handle.await.unwrap().unwrap();
+            assert!(std::fs::read(root.join("veri.bin")).unwrap() == data, "{:?} / {}", chunk_size, max_chunk_size);
+            assert_eq!(std::fs::read_to_string(root.join("kucuk.txt")).unwrap(), "kucuk");
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_chunk_size_clamped_in_welcome() {
+        let dir = test_dir("chunk-welcome");
+        let (addr, stop, handle) = spawn_test_server(ServerOptions {
+            root: dir.clone(),
+            max_chunk_size: 1024 * 1024,
+            ..ServerOptions::default()
+        }).await;
+
+        // Eski istemci (0) varsayılanı, aşırı istek sunucu sınırını alır
+        for (requested, expected) in [(0, CHUNK_SIZE), (1024, MIN_CHUNK_SIZE), (128 * 1024, 128 * 1024), (u32::MAX, 1024 * 1024)] {
+            let mut stream = TcpStream::connect(addr).await.unwrap();
+            let hello = Hello { protocol: PROTOCOL_VERSION, streams: 1, join: None, nonce: "istemci".to_string(), chunk_size: requested, compression: None, probe: false };
+            protocol::write_frame(&mut stream, &Frame::Hello(hello)).await.unwrap();
+            match protocol::read_frame(&mut stream).await.unwrap() {
+                Some(Frame::Welcome(welcome)) => assert_eq!(welcome.chunk_size as usize, expected),
+                other => panic!("beklenmeyen yanıt: {:?}", other),
+            }
+        }
+
+        stop.send(()).unwrap();
+        handle.await.unwrap().unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_compressed_transfer() {
+        let dir = test_dir("compress");
+        let source = dir.join("source");
+        std::fs::create_dir_all(&source).unwrap();
+        // Sıkışan metin, sıkışmayan rastgele veri ve sıkıştırılmış uzantılı dosya
+        let log = "2024-01-01 12:00:00 INFO istek tamamlandı süre=12ms\n".repeat(100_000);
+        let noise: Vec<u8> = (0..3 * 1024 * 1024).map(|_| rand::random::<u8>()).collect();
+        std::fs::write(source.join("kayit.log"), &log).unwrap();
+        std::fs::write(source.join("gurultu.bin"), &noise).unwrap();
+        std::fs::write(source.join("arsiv.gz"), &log).unwrap();
+        std::fs::write(source.join("bos.txt"), "").unwrap();
+
+        // Sunucu sıkıştırmayı reddederse aktarım sıkıştırmasız sürer
+        let cases = [(Compression::Zstd(1), true), (Compression::Zstd(19), true), (Compression::Zstd(3), false)];
+        for (i, (compression, accepted)) in cases.into_iter().enumerate() {
+            let root = dir.join(format!("received{}", i));
+            let (addr, stop, handle) = spawn_test_server(ServerOptions {
+                root: root.clone(),
+                compression: accepted,
+                ..ServerOptions::default()
+            }).await;
+
+            let options = SyncOptions { streams: 2, compression, chunk_size: ChunkSize::Fixed(MIN_CHUNK_SIZE), ..SyncOptions::default() };
+            sync_files_with_options(&source.to_string_lossy(), &addr.to_string(), &[9u8; 32], &options).await.unwrap();
+
+            stop.send(()).unwrap();
+            handle.await.unwrap().unwrap();
+            assert_eq!(std::fs::read_to_string(root.join("kayit.log")).unwrap(), log);
+            assert_eq!(std::fs::read_to_string(root.join("arsiv.gz")).unwrap(), log);
+            assert!(std::fs::read(root.join("gurultu.bin")).unwrap() == noise);
+            assert_eq!(std::fs::read(root.join("bos.txt")).unwrap().len(), 0);
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_rejects_unnegotiated_compressed_chunk() {
+        let dir = test_dir("compress-reject");
+        let key = [9u8; 32];
+        let (addr, stop, handle) = start_test_server(dir.clone(), 4, Duration::from_secs(5)).await;
+
+        // El sıkışmada sıkıştırma istenmedi
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        let welcome = raw_handshake(&mut stream, &key, None).await.unwrap();
+        assert_eq!(welcome.compression, None);
+
+        let header = FileHeader {
+            file_name: "a.txt".to_string(),
+            file_size: 4,
+            file_hash: blake3::hash(b"abcd").to_hex().to_string(),
+            relative_path: PathBuf::from("a.txt"),
+            metadata: FileMetadata::default(),
+        };
+        protocol::write_frame(&mut stream, &Frame::File { id: 1, header }).await.unwrap();
+        let chunk = Frame::Chunk { id: 1, offset: 0, compressed: true, data: vec![0; 32] };
+        protocol::write_frame(&mut stream, &chunk).await.unwrap();
+
+        // Protokol ihlali bağlantıyı kapatır ve nedeni bildirilir, dosya yazılmaz
+        match tokio::time::timeout(Duration::from_secs(5), protocol::read_frame(&mut stream)).await.unwrap().unwrap() {
+            Some(Frame::Error(Error::Protocol(message))) => assert!(message.contains("sıkıştırılmış chunk"), "{}", message),
+            other => panic!("beklenmeyen yanıt: {:?}", other),
+        }
+        let mut rest = Vec::new();
+        let _ = tokio::time::timeout(Duration::from_secs(5), stream.read_to_end(&mut rest)).await.unwrap();
+        assert!(rest.is_empty());
+        assert!(!dir.join("a.txt").exists());
+
+        stop.send(()).unwrap();
+        handle.await.unwrap().unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_bandwidth_limits() {
+        let dir = test_dir("bwlimit");
+        let source = dir.join("source");
+        std::fs::create_dir_all(&source).unwrap();
+        let data = vec![3u8; 768 * 1024];
+        std::fs::write(source.join("veri.bin"), &data).unwrap();
+
+        // Sunucu sınırı istemcinin tüm akışlarına birlikte uygulanır, istemci
+        // sınırı kendi gönderimine; her iki durumda 768 KB ~0.75 saniye sürer
+        let limit = Bandwidth::Limited(1024 * 1024);
+        let cases = [(limit, Bandwidth::Unlimited), (Bandwidth::Unlimited, limit)];
+        for (i, (server_limit, client_limit)) in cases.into_iter().enumerate() {
+            let root = dir.join(format!("received{}", i));
+            let (addr, stop, handle) = spawn_test_server(ServerOptions {
+                root: root.clone(),
+                client_bwlimit: server_limit,
+                ..ServerOptions::default()
+            }).await;
+
+            let options = SyncOptions { streams: 2, bwlimit: client_limit, ..SyncOptions::default() };
+            let started = std::time::Instant::now();
+            sync_files_with_options(&source.to_string_lossy(), &addr.to_string(), &[9u8; 32], &options).await.unwrap();
+            let elapsed = started.elapsed();
+            assert!(elapsed >= Duration::from_millis(600), "{:?} / {:?}: {:?}", server_limit, client_limit, elapsed);
+
+            stop.send(()).unwrap();
+            handle.await.unwrap().unwrap();
+            assert!(std::fs::read(root.join("veri.bin")).unwrap() == data);
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_bandwidth_limit_caps_chunk_size() {
+        let dir = test_dir("bwlimit-chunk");
+
+        // Chunk bir saniyelik veriyi aşmaz, ama en küçük boyutun altına inmez
+        for (limit, expected) in [(100 * 1024, 100 * 1024), (10 * 1024, MIN_CHUNK_SIZE)] {
+            let (addr, stop, handle) = spawn_test_server(ServerOptions {
+                root: dir.clone(),
+                client_bwlimit: Bandwidth::Limited(limit),
+                ..ServerOptions::default()
+            }).await;
+
+            let mut stream = TcpStream::connect(addr).await.unwrap();
+            let welcome = raw_handshake(&mut stream, &[9u8; 32], None).await.unwrap();
+            assert_eq!(welcome.chunk_size as usize, expected);
+
+            stop.send(()).unwrap();
+            handle.await.unwrap().unwrap();
+        }
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_filtered_sync_skips_excluded_files() {
+        let dir = test_dir("filter");
+        let source = dir.join("source");
+        let root = dir.join("received");
+        for (path, content) in [
+            (".deltasafeignore", "*.tmp\n"),
+            (".gitignore", "target/\n"),
+            ("src/main.rs", "fn main() {}"),
+            ("src/cache.tmp", "gecici"),
+            ("target/debug/app", "ikili"),
+            ("node_modules/pkg/index.js", "js"),
+        ] {
+            let path = source.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        }
+
+        let (addr, stop, handle) = start_test_server(root.clone(), 4, Duration::from_secs(5)).await;
+        let options = SyncOptions {
+            filter: FilterOptions { exclude: vec!["node_modules/".to_string()], gitignore: true, ..FilterOptions::default() },
+            ..SyncOptions::default()
+        };
+        sync_files_with_options(&source.to_string_lossy(), &addr.to_string(), &[9u8; 32], &options).await.unwrap();
+        stop.send(()).unwrap();
+        handle.await.unwrap().unwrap();
+
+        assert_eq!(std::fs::read_to_string(root.join("src/main.rs")).unwrap(), "fn main() {}");
+        assert!(root.join(".deltasafeignore").exists());
+        assert!(!root.join("src/cache.tmp").exists());
+        assert!(!root.join("target").exists());
+        assert!(!root.join("node_modules").exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_metadata_restored_after_commit() {
+        let dir = test_dir("metadata");
+        let source = dir.join("source");
+        std::fs::create_dir_all(source.join("bin")).unwrap();
+        let script = source.join("bin/calistir.sh");
+        std::fs::write(&script, "#!/bin/sh\necho merhaba\n").unwrap();
+        std::fs::write(source.join("bos.txt"), "").unwrap();
+        let mtime = filetime::FileTime::from_unix_time(1_500_000_000, 250_000_000);
+        filetime::set_file_mtime(&script, mtime).unwrap();
+        filetime::set_file_mtime(source.join("bos.txt"), mtime).unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o750)).unwrap();
+        }
+
+        // Varsayılan ayar izin ve zamanları uygular, `none` hiçbirini
+        for (i, preserve) in [Preserve::default(), Preserve::NONE].into_iter().enumerate() {
+            let root = dir.join(format!("received{}", i));
+            let (addr, stop, handle) = spawn_test_server(ServerOptions { root: root.clone(), preserve, ..ServerOptions::default() }).await;
+            sync_files(&source.to_string_lossy(), &addr.to_string(), &[9u8; 32]).await.unwrap();
+            stop.send(()).unwrap();
+            handle.await.unwrap().unwrap();
+
+            let received = FileMetadata::from_fs(&std::fs::metadata(root.join("bin/calistir.sh")).unwrap());
+            let empty = FileMetadata::from_fs(&std::fs::metadata(root.join("bos.txt")).unwrap());
+            let expected_mtime = Some(mtime.into());
+            assert_eq!(received.mtime == expected_mtime, preserve.times);
+            assert_eq!(empty.mtime == expected_mtime, preserve.times);
+            #[cfg(unix)]
+            assert_eq!(received.mode == Some(0o750), preserve.mode);
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_links_and_empty_directories() {
+        use std::os::unix::fs::{symlink, MetadataExt, PermissionsExt};
+        let dir = test_dir("entries");
+        let source = dir.join("source");
+        std::fs::create_dir_all(source.join("lib")).unwrap();
+        std::fs::create_dir_all(source.join("bos/ic")).unwrap();
+        std::fs::write(source.join("lib/app.so.1"), "elf").unwrap();
+        std::fs::write(source.join("veri.bin"), "aynı içerik").unwrap();
+        std::fs::hard_link(source.join("veri.bin"), source.join("lib/kopya.bin")).unwrap();
+        symlink("app.so.1", source.join("lib/app.so")).unwrap();
+        symlink("lib", source.join("kutuphane")).unwrap();
+        std::fs::set_permissions(source.join("bos"), std::fs::Permissions::from_mode(0o750)).unwrap();
+        let mtime = filetime::FileTime::from_unix_time(1_500_000_000, 0);
+        filetime::set_file_mtime(source.join("lib"), mtime).unwrap();
+
+        let root = dir.join("received");
+        let (addr, stop, handle) = spawn_test_server(ServerOptions { root: root.clone(), ..ServerOptions::default() }).await;
+        let options = SyncOptions { hardlinks: true, ..SyncOptions::default() };
+        sync_files_with_options(&source.to_string_lossy(), &addr.to_string(), &[9u8; 32], &options).await.unwrap();
+
+        // Bağlantılar bağlantı olarak, boş klasörler öznitelikleriyle gelir
+        assert_eq!(std::fs::read_link(root.join("lib/app.so")).unwrap(), PathBuf::from("app.so.1"));
+        assert_eq!(std::fs::read_link(root.join("kutuphane")).unwrap(), PathBuf::from("lib"));
+        assert_eq!(std::fs::read_to_string(root.join("kutuphane/app.so")).unwrap(), "elf");
+        assert!(root.join("bos/ic").is_dir());
+        assert_eq!(std::fs::metadata(root.join("bos")).unwrap().mode() & 0o777, 0o750);
+        let lib = FileMetadata::from_fs(&std::fs::metadata(root.join("lib")).unwrap());
+        assert_eq!(lib.mtime, Some(mtime.into()));
+
+        // Hardlink grubu sunucuda da tek dosyadır
+        let original = std::fs::metadata(root.join("veri.bin")).unwrap();
+        let linked = std::fs::metadata(root.join("lib/kopya.bin")).unwrap();
+        assert_eq!(original.ino(), linked.ino());
+        assert_eq!(original.nlink(), 2);
+
+        // İkinci eşitleme bağlantıları yerinde günceller; `skip` bağlantı göndermez
+        let options = SyncOptions {
+            filter: FilterOptions { symlinks: SymlinkPolicy::Skip, ..FilterOptions::default() },
+            hardlinks: true,
+            ..SyncOptions::default()
+        };
+        sync_files_with_options(&source.to_string_lossy(), &addr.to_string(), &[9u8; 32], &options).await.unwrap();
+        let original = std::fs::metadata(root.join("veri.bin")).unwrap();
+        assert_eq!(std::fs::metadata(root.join("lib/kopya.bin")).unwrap().ino(), original.ino());
+        let leftovers: Vec<_> = walk_names(&root).into_iter().filter(|name| name.ends_with(TEMP_SUFFIX)).collect();
+        assert!(leftovers.is_empty(), "{:?}", leftovers);
+
+        stop.send(()).unwrap();
+        handle.await.unwrap().unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    fn walk_names(root: &Path) -> Vec<String> {
+        let mut names = Vec::new();
+        for entry in std::fs::read_dir(root).unwrap() {
+            let entry = entry.unwrap();
+            names.push(entry.file_name().to_string_lossy().into_owned());
+            if entry.file_type().unwrap().is_dir() {
+                names.extend(walk_names(&entry.path()));
+            }
+        }
+        names
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_rejects_links_escaping_root() {
+        use crate::entry::EntryHeader;
+        let dir = test_dir("entry-escape");
+        let key = [9u8; 32];
+        let (addr, stop, handle) = start_test_server(dir.clone(), 4, Duration::from_secs(5)).await;
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        raw_handshake(&mut stream, &key, None).await.unwrap();
+
+        let entry = |path: &str, kind: EntryKind| EntryHeader { relative_path: PathBuf::from(path), kind, metadata: FileMetadata::default() };
+        let link = |target: &str| EntryKind::Symlink { target: PathBuf::from(target) };
+        let cases = [
+            (entry("kacak", link("/etc")), false),
+            (entry("alt/kacak", link("../..")), false),
+            (entry("../disari", EntryKind::Directory), false),
+            (entry("sifre", EntryKind::Hardlink { target: PathBuf::from("../../etc/passwd") }), false),
+            // Kökü gösteren bağlantı geçerli, ancak içinden yazmak zincirleme
+            // ile dışarı çıkabileceği için reddedilir
+            (entry("alt/kok", link("..")), true),
+            (entry("alt/kok/kacak", link("../..")), false),
+            (entry("alt/kok/yeni", EntryKind::Directory), false),
+        ];
+        for (id, (entry, accepted)) in cases.into_iter().enumerate() {
+            protocol::write_frame(&mut stream, &Frame::Entry { id: id as u32, entry }).await.unwrap();
+            match protocol::read_frame(&mut stream).await.unwrap() {
+                Some(Frame::FileResult { id: result_id, result }) => {
+                    // Güvensiz girdiler izin hatasıyla reddedilir
+                    let expected = if accepted { Ok(()) } else { Err(ErrorCode::Permission) };
+                    assert_eq!((result_id, result.map_err(|e| e.code())), (id as u32, expected));
+                }
+                other => panic!("beklenmeyen yanıt: {:?}", other),
+            }
+        }
+        assert!(std::fs::symlink_metadata(dir.join("kacak")).is_err());
+        assert!(std::fs::symlink_metadata(dir.join("alt/kok")).unwrap().file_type().is_symlink());
+
+        // Bağlantının altına dosya da yazılamaz
+        let header = FileHeader {
+            file_name: "a.txt".to_string(),
+            file_size: 0,
+            file_hash: blake3::hash(b"").to_hex().to_string(),
+            relative_path: PathBuf::from("alt/kok/a.txt"),
+            metadata: FileMetadata::default(),
+        };
+        protocol::write_frame(&mut stream, &Frame::File { id: 100, header }).await.unwrap();
+        assert!(matches!(protocol::read_frame(&mut stream).await.unwrap(), Some(Frame::FileResult { id: 100, result: Err(Error::Permission(_)) })));
+        assert!(!dir.join("a.txt").exists());
+
+        drop(stream);
+        stop.send(()).unwrap();
+        handle.await.unwrap().unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_xattrs_filtered_by_namespace() {
+        let dir = test_dir("xattrs");
+        let source = dir.join("source");
+        std::fs::create_dir_all(source.join("klasor")).unwrap();
+        let file = source.join("tasarim.psd");
+        std::fs::write(&file, "katmanlar").unwrap();
+        // Dosya sistemi kullanıcı özniteliklerini desteklemiyorsa atla
+        if xattr::set(&file, "user.etiket", b"onayli").is_err() {
+            std::fs::remove_dir_all(&dir).unwrap();
+            return;
+        }
+        xattr::set(&file, "user.renk", b"mavi").unwrap();
+        xattr::set(source.join("klasor"), "user.proje", b"kis").unwrap();
+
+        let cases = [("user", "user", Some("onayli")), ("user", "none", None), ("none", "user", None), ("user", "user.renk", None)];
+        for (i, (sent, applied, expected)) in cases.into_iter().enumerate() {
+            let root = dir.join(format!("received{}", i));
+            let (addr, stop, handle) = spawn_test_server(ServerOptions { root: root.clone(), xattrs: applied.parse().unwrap(), ..ServerOptions::default() }).await;
+            let options = SyncOptions { xattrs: sent.parse().unwrap(), ..SyncOptions::default() };
+            sync_files_with_options(&source.to_string_lossy(), &addr.to_string(), &[9u8; 32], &options).await.unwrap();
+            stop.send(()).unwrap();
+            handle.await.unwrap().unwrap();
+
+            let received = xattr::get(root.join("tasarim.psd"), "user.etiket").unwrap();
+            assert_eq!(received.as_deref(), expected.map(str::as_bytes), "{} -> {}", sent, applied);
+            let folder = xattr::get(root.join("klasor"), "user.proje").unwrap();
+            assert_eq!(folder.is_some(), expected.is_some());
+            // Yalnızca süzgece uyan ad uygulanır
+            let color = xattr::get(root.join("tasarim.psd"), "user.renk").unwrap();
+            assert_eq!(color.is_some(), sent == "user" && applied.starts_with("user"));
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_versions_kept_and_restored() {
+        let dir = test_dir("versions");
+        let source = dir.join("source");
+        let root = dir.join("received");
+        std::fs::create_dir_all(&source).unwrap();
+        let (addr, stop, handle) = spawn_test_server(ServerOptions {
+            root: root.clone(),
+            retention: Retention { keep_last: 2, keep_daily: 0 },
+            ..ServerOptions::default()
+        }).await;
+        let target = addr.to_string();
+        let key = [9u8; 32];
+
+        // Aynı içerik yeniden gönderilince sürüm oluşmaz; en eski sürüm kuraldan dolayı silinir
+        for content in ["v1", "v1", "v2", "v3", "v4"] {
+            std::fs::write(source.join("plan.txt"), content).unwrap();
+            sync_files(&source.to_string_lossy(), &target, &key).await.unwrap();
+        }
+        let versions = crate::restore::list_versions(&target, &key, None, Path::new("plan.txt")).await.unwrap();
+        assert_eq!(versions.len(), 2);
+        assert!(versions[0].id > versions[1].id);
+        assert_eq!(std::fs::read_to_string(root.join("plan.txt")).unwrap(), "v4");
+
+        let output = dir.join("geri.txt");
+        let size = crate::restore::restore_version(&target, &key, None, Path::new("plan.txt"), &versions[1].id, &output).await.unwrap();
+        assert_eq!(size, 2);
+        assert_eq!(std::fs::read_to_string(&output).unwrap(), "v2");
+
+        // Bilinmeyen sürüm, sunucu klasörü ve yanlış anahtar reddedilir
+        let error = crate::restore::restore_version(&target, &key, None, Path::new("plan.txt"), "20000101T000000.000000Z", &output).await.unwrap_err();
+        assert_eq!(error.code(), ErrorCode::Io);
+        let error = crate::restore::list_versions(&target, &key, None, Path::new(".deltasafe/versions")).await.unwrap_err();
+        assert_eq!(error.code(), ErrorCode::Permission);
+        let error = crate::restore::list_versions(&target, &[1u8; 32], None, Path::new("plan.txt")).await.unwrap_err();
+        assert_eq!(error.code(), ErrorCode::Auth);
+        assert_eq!(std::fs::read_to_string(&output).unwrap(), "v2");
+
+        std::fs::create_dir_all(source.join(".deltasafe")).unwrap();
+        std::fs::write(source.join(".deltasafe/sahte"), "x").unwrap();
+        assert_eq!(sync_files(&source.to_string_lossy(), &target, &key).await.unwrap_err().code(), ErrorCode::Permission);
+        assert!(!root.join(".deltasafe/sahte").exists());
+
+        stop.send(()).unwrap();
+        handle.await.unwrap().unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_vault_push_and_pull() {
+        let dir = test_dir("vault");
+        let source = dir.join("source");
+        let root = dir.join("received");
+        std::fs::create_dir_all(source.join("docs/bos")).unwrap();
+        let large: Vec<u8> = (0..vault::BLOB_SIZE * 2 + 100).map(|i| (i % 251) as u8).collect();
+        std::fs::write(source.join("docs/gizli.txt"), "çok gizli içerik").unwrap();
+        std::fs::write(source.join("buyuk.bin"), &large).unwrap();
+        std::fs::write(source.join("kopya.bin"), &large).unwrap();
+
+        // Sunucu yalnızca erişim anahtarını bilir
+        let key = [9u8; 32];
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let target = listener.local_addr().unwrap().to_string();
+        let (stop, stop_rx) = tokio::sync::oneshot::channel::<()>();
+        let options = ServerOptions { root: root.clone(), vault: true, access_only: true, ..ServerOptions::default() };
+        let handle = tokio::spawn(async move {
+            serve(listener, &access_key(&key), &options, async { let _ = stop_rx.await; }).await
+        });
+
+        let pushed = vault::push(&source, &target, &key, None).await.unwrap();
+        assert_eq!((pushed.files, pushed.bytes), (3, large.len() as u64 * 2 + "çok gizli içerik".len() as u64));
+        // Aynı içerikli dosyalar blob'ları paylaşır; yeniden gönderimde en fazla
+        // erişim zamanları değişen indeks gönderilir
+        assert_eq!(pushed.uploaded, 5);
+        assert!(vault::push(&source, &target, &key, None).await.unwrap().uploaded <= 1);
+
+        // Sunucuda düz metin yok: yalnızca kasa klasörü var ve blob'larda içerik geçmiyor
+        assert_eq!(std::fs::read_dir(&root).unwrap().count(), 1);
+        let blobs = walk_names(&root.join(".deltasafe/vault/blobs"));
+        assert!(!blobs.is_empty());
+        for blob in blobs.iter().filter(|name| name.len() == 64) {
+            let data = std::fs::read(root.join(".deltasafe/vault/blobs").join(&blob[..2]).join(blob)).unwrap();
+            assert!(!data.windows(9).any(|window| window == "çok gizli".as_bytes() || window == &large[..9]));
+        }
+
+        let output = dir.join("output");
+        let pulled = vault::pull(&target, &key, None, None, &output).await.unwrap();
+        assert_eq!(pulled.files, 3);
+        assert_eq!(std::fs::read_to_string(output.join("docs/gizli.txt")).unwrap(), "çok gizli içerik");
+        assert_eq!(std::fs::read(output.join("kopya.bin")).unwrap(), large);
+        assert!(output.join("docs/bos").is_dir());
+        assert_eq!(vault::indexes(&target, &key, None).await.unwrap().len(), 2);
+
+        // Yanlış anahtar, bilinmeyen indeks ve düz dosya gönderimi reddedilir
+        assert_eq!(vault::pull(&target, &[1u8; 32], None, None, &output).await.unwrap_err().code(), ErrorCode::Auth);
+        assert_eq!(vault::pull(&target, &key, None, Some("20000101T000000.000000Z"), &output).await.unwrap_err().code(), ErrorCode::Io);
+        assert_eq!(sync_files(&source.to_string_lossy(), &target, &key).await.unwrap_err().code(), ErrorCode::Protocol);
+        assert_eq!(crate::restore::list_versions(&target, &key, None, Path::new("buyuk.bin")).await.unwrap_err().code(), ErrorCode::Protocol);
+        assert_eq!(std::fs::read_dir(&root).unwrap().count(), 1);
+
+        stop.send(()).unwrap();
+        handle.await.unwrap().unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_sparse_file_larger_than_4gb() {
+        use std::io::{Read, Seek, SeekFrom, Write};
+        use std::os::unix::fs::MetadataExt;
+
+        let dir = test_dir("sparse");
+        let source = dir.join("source");
+        let root = dir.join("received");
+        std::fs::create_dir_all(&source).unwrap();
+        // 5 GiB'lık seyrek dosya: başta, 4 GiB sınırının iki yanında ve
+        // sonda veri; gerisi delik
+        const SIZE: u64 = 5 << 30;
+        let marks = [(0, b"bas".to_vec()), ((4 << 30) - 3, b"sinir".to_vec()), (SIZE - 3, b"son".to_vec())];
+        let mut file = std::fs::File::create(source.join("disk.img")).unwrap();
+        file.set_len(SIZE).unwrap();
+        for (offset, data) in &marks {
+            file.seek(SeekFrom::Start(*offset)).unwrap();
+            file.write_all(data).unwrap();
+        }
+        drop(file);
+
+        let (addr, stop, handle) = start_test_server(root.clone(), 8, Duration::from_secs(30)).await;
+        let options = SyncOptions { streams: 2, ..SyncOptions::default() };
+        sync_files_with_options(&source.to_string_lossy(), &addr.to_string(), &[9u8; 32], &options).await.unwrap();
+        stop.send(()).unwrap();
+        handle.await.unwrap().unwrap();
+
+        let received = root.join("disk.img");
+        let metadata = std::fs::metadata(&received).unwrap();
+        assert_eq!(metadata.len(), SIZE);
+        let mut file = std::fs::File::open(&received).unwrap();
+        for (offset, data) in &marks {
+            let mut read = vec![0; data.len()];
+            file.seek(SeekFrom::Start(*offset)).unwrap();
+            file.read_exact(&mut read).unwrap();
+            assert_eq!(&read, data, "ofset {}", offset);
+        }
+        // Kaynak dosya sistemi delik bildiriyorsa hedef de seyrek kalır
+        if std::fs::metadata(source.join("disk.img")).unwrap().blocks() * 512 < SIZE / 2 {
+            assert!(metadata.blocks() * 512 < SIZE / 2, "{} blok", metadata.blocks());
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_stream_authentication() {
+        let dir = test_dir("auth");
+        let (addr, stop, handle) = start_test_server(dir.clone(), 8, Duration::from_secs(5)).await;
+
+        // Yanlış anahtarla üretilen kanıt reddedilir
+        let mut intruder = TcpStream::connect(addr).await.unwrap();
+        raw_handshake(&mut intruder, &[1u8; 32], None).await.unwrap();
+        assert!(matches!(protocol::read_frame(&mut intruder).await.unwrap(), Some(Frame::Error(_))));
+
+        // Bilinmeyen oturuma katılınamaz
+        let mut stranger = TcpStream::connect(addr).await.unwrap();
+        assert!(raw_handshake(&mut stranger, &[9u8; 32], Some("yok")).await.is_none());
+
+        // Geçerli oturuma verilen akış sayısı kadar katılınabilir
+        let mut primary = TcpStream::connect(addr).await.unwrap();
+        let welcome = raw_handshake(&mut primary, &[9u8; 32], None).await.unwrap();
+        assert_eq!(welcome.streams, 2);
+        let mut second = TcpStream::connect(addr).await.unwrap();
+        assert!(raw_handshake(&mut second, &[9u8; 32], Some(&welcome.session)).await.is_some());
+        let mut third = TcpStream::connect(addr).await.unwrap();
+        raw_handshake(&mut third, &[9u8; 32], Some(&welcome.session)).await.unwrap();
+        assert!(matches!(protocol::read_frame(&mut third).await.unwrap(), Some(Frame::Error(_))));
+
+        drop((primary, second));
+        stop.send(()).unwrap();
+        handle.await.unwrap().unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_discovery_probe_checks_handshake_proof() {
+        let dir = test_dir("probe");
+        let (addr, stop, handle) = start_test_server(dir.clone(), 4, Duration::from_secs(5)).await;
+
+        let (rtt, proven) = crate::discovery::probe_address(addr, Some(&[9u8; 32])).await;
+        assert!(rtt.is_some());
+        assert_eq!(proven, Some(true));
+        assert_eq!(crate::discovery::probe_address(addr, Some(&[8u8; 32])).await.1, Some(false));
+        assert_eq!(crate::discovery::probe_address(addr, None).await.1, None);
+
+        // Sınama oturum açmaz; ardından normal el sıkışma çalışır
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        assert!(raw_handshake(&mut stream, &[9u8; 32], None).await.is_some());
+
+        drop(stream);
+        stop.send(()).unwrap();
+        handle.await.unwrap().unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
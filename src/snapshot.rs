@@ -0,0 +1,411 @@
+//! Paylaşımın anlık görüntüleri
+//!
+//! Anlık görüntü, paylaşım kökünün belirli bir andaki halini kaydeden,
+//! değiştirilmeyen bir JSON manifestidir: her dosyanın yolu, BLAKE3 hash'i,
+//! boyutu ve öznitelikleri ile klasörler ve sembolik bağlantılar. Dosya
+//! içerikleri `.deltasafe/objects` altında hash'leriyle adlandırılıp bir kez
+//! saklanır; aynı içerik ne kadar çok görüntüde geçerse geçsin yer kaplamaz.
+//!
+//! Geri yükleme kökü manifestteki hale getirir: fazla dosyaları siler, eksik
+//! veya farklı olanları nesnelerden yazar. Geri dönülebilmesi için önce
+//! mevcut halin görüntüsü alınır. Geri yükleme sırasında sunucunun
+//! durdurulması önerilir.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use chrono::{DateTime, NaiveDateTime, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+use crate::entry::{is_safe_relative_path, is_safe_symlink_target};
+use crate::metadata::{self, FileMetadata, Preserve};
+use crate::server::{create_symlink, temp_path_for, TEMP_SUFFIX};
+use crate::sync::calculate_file_hash;
+use crate::versions::{is_reserved, STATE_DIR};
+
+/// `STATE_DIR` altındaki içerik deposu
+const OBJECTS_DIR: &str = "objects";
+
+/// `STATE_DIR` altındaki manifest klasörü
+const SNAPSHOTS_DIR: &str = "snapshots";
+
+/// Görüntü kimliğinin biçimi (UTC)
+const ID_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+
+/// Geri yüklemeden önce alınan görüntünün etiketi
+const SAFETY_LABEL: &str = "geri yükleme öncesi";
+
+/// Görüntüdeki bir girdinin türü
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum SnapshotKind {
+    File { hash: String, size: u64 },
+    Directory,
+    /// `target`: bağlantının kendi klasörüne göre hedefi
+    Symlink { target: PathBuf },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SnapshotEntry {
+    pub path: PathBuf,
+    #[serde(flatten)]
+    pub kind: SnapshotKind,
+    #[serde(default)]
+    pub metadata: FileMetadata,
+}
+
+/// Bir anlık görüntünün manifesti; girdiler üst klasörler önce gelecek
+/// şekilde yola göre sıralıdır
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Manifest {
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    pub entries: Vec<SnapshotEntry>,
+}
+
+impl Manifest {
+    /// Görüntünün alındığı zaman
+    pub fn time(&self) -> Option<DateTime<Utc>> {
+        NaiveDateTime::parse_from_str(&self.id, ID_FORMAT).ok().map(|time| time.and_utc())
+    }
+
+    /// Dosya sayısı ve toplam boyut
+    pub fn totals(&self) -> (usize, u64) {
+        self.entries.iter().fold((0, 0), |(files, bytes), entry| match entry.kind {
+            SnapshotKind::File { size, .. } => (files + 1, bytes + size),
+            _ => (files, bytes),
+        })
+    }
+
+    /// Yolların ve hedeflerin kökte kaldığını, hash'lerin geçerli olduğunu doğrular
+    fn validate(&self) -> Result<(), String> {
+        for entry in &self.entries {
+            if !is_safe_relative_path(&entry.path) || is_reserved(&entry.path) {
+                return Err(format!("Manifestte geçersiz yol: {}", entry.path.display()));
+            }
+            match &entry.kind {
+                SnapshotKind::File { hash, .. } if blake3::Hash::from_hex(hash).is_err() => {
+                    return Err(format!("Manifestte geçersiz hash: {}", hash));
+                }
+                SnapshotKind::Symlink { target } if !is_safe_symlink_target(&entry.path, target) => {
+                    return Err(format!("Manifestte kökün dışını gösteren bağlantı: {}", entry.path.display()));
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Geri yüklemenin özeti
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Restored {
+    /// Geri yüklemeden önce alınan görüntünün kimliği
+    pub safety: String,
+    /// İçeriği yazılan dosyalar
+    pub written: usize,
+    /// Silinen dosya, klasör ve bağlantılar
+    pub removed: usize,
+}
+
+fn snapshots_dir(root: &Path) -> PathBuf {
+    root.join(STATE_DIR).join(SNAPSHOTS_DIR)
+}
+
+fn object_path(root: &Path, hash: &str) -> PathBuf {
+    root.join(STATE_DIR).join(OBJECTS_DIR).join(&hash[..2]).join(hash)
+}
+
+/// Kökteki girdileri, sunucu klasörü ve yarım dosyalar hariç, üst klasörler
+/// önce gelecek şekilde toplar. Sembolik bağlantılar izlenmez.
+pub fn scan(root: &Path) -> io::Result<Vec<(PathBuf, fs::Metadata)>> {
+    fn walk(root: &Path, relative: &Path, entries: &mut Vec<(PathBuf, fs::Metadata)>) -> io::Result<()> {
+        let mut children: Vec<_> = fs::read_dir(root.join(relative))?.collect::<io::Result<_>>()?;
+        children.sort_by_key(|child| child.file_name());
+        for child in children {
+            let path = relative.join(child.file_name());
+            if is_reserved(&path) || child.file_name().to_string_lossy().ends_with(TEMP_SUFFIX) {
+                continue;
+            }
+            let metadata = fs::symlink_metadata(child.path())?;
+            let is_dir = metadata.is_dir();
+            entries.push((path.clone(), metadata));
+            if is_dir {
+                walk(root, &path, entries)?;
+            }
+        }
+        Ok(())
+    }
+
+    let mut entries = Vec::new();
+    walk(root, Path::new(""), &mut entries)?;
+    Ok(entries)
+}
+
+/// Dosyanın içeriğini depoya ekler ve hash'ini döner. Aynı içerik zaten
+/// varsa kopyalanmaz; kopya hash'lendiği için nesne adı her zaman içerikle
+/// eşleşir.
+fn store_object(root: &Path, path: &Path) -> io::Result<(String, u64)> {
+    let hash = calculate_file_hash(path)?;
+    let object = object_path(root, &hash);
+    if let Ok(metadata) = fs::metadata(&object) {
+        return Ok((hash, metadata.len()));
+    }
+
+    let staging = root.join(STATE_DIR).join(OBJECTS_DIR).join(format!("{}{}", hash, TEMP_SUFFIX));
+    fs::create_dir_all(root.join(STATE_DIR).join(OBJECTS_DIR))?;
+    fs::copy(path, &staging)?;
+    let copied = calculate_file_hash(&staging)?;
+    let object = object_path(root, &copied);
+    fs::create_dir_all(object.parent().unwrap_or(root))?;
+    let mut permissions = fs::metadata(&staging)?.permissions();
+    permissions.set_readonly(true);
+    fs::set_permissions(&staging, permissions)?;
+    let size = fs::metadata(&staging)?.len();
+    fs::rename(&staging, &object)?;
+    Ok((copied, size))
+}
+
+/// Kökün anlık görüntüsünü alır ve manifesti kaydeder
+pub fn create(root: &Path, label: Option<&str>, now: DateTime<Utc>) -> io::Result<Manifest> {
+    let mut entries = Vec::new();
+    for (path, fs_metadata) in scan(root)? {
+        let full_path = root.join(&path);
+        let kind = if fs_metadata.file_type().is_symlink() {
+            SnapshotKind::Symlink { target: fs::read_link(&full_path)? }
+        } else if fs_metadata.is_dir() {
+            SnapshotKind::Directory
+        } else if fs_metadata.is_file() {
+            let (hash, size) = store_object(root, &full_path)?;
+            SnapshotKind::File { hash, size }
+        } else {
+            continue;
+        };
+        entries.push(SnapshotEntry { path, kind, metadata: FileMetadata::from_fs(&fs_metadata) });
+    }
+
+    let dir = snapshots_dir(root);
+    fs::create_dir_all(&dir)?;
+    let mut time = now;
+    let id = loop {
+        let id = time.format(ID_FORMAT).to_string();
+        if !dir.join(format!("{}.json", id)).exists() {
+            break id;
+        }
+        time += TimeDelta::seconds(1);
+    };
+    let manifest = Manifest { id, label: label.map(str::to_string), entries };
+
+    // Manifest bir kez, geçici dosya üzerinden yazılır ve salt okunur kalır
+    let path = dir.join(format!("{}.json", manifest.id));
+    let temp_path = temp_path_for(&path);
+    fs::write(&temp_path, serde_json::to_vec_pretty(&manifest)?)?;
+    let mut permissions = fs::metadata(&temp_path)?.permissions();
+    permissions.set_readonly(true);
+    fs::set_permissions(&temp_path, permissions)?;
+    fs::rename(&temp_path, &path)?;
+    Ok(manifest)
+}
+
+/// Kayıtlı görüntüleri yeniden eskiye listeler
+pub fn list(root: &Path) -> io::Result<Vec<Manifest>> {
+    let entries = match fs::read_dir(snapshots_dir(root)) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let mut manifests = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        if path.extension().is_some_and(|extension| extension == "json") {
+            let manifest: Manifest = serde_json::from_slice(&fs::read(&path)?)?;
+            manifests.push(manifest);
+        }
+    }
+    manifests.sort_by(|a, b| b.id.cmp(&a.id));
+    Ok(manifests)
+}
+
+/// Kimliği veya kimliğin tek bir görüntüyle eşleşen başını (`20240326` gibi)
+/// çözümler
+pub fn find(root: &Path, id: &str) -> Result<Manifest, String> {
+    let manifests = list(root).map_err(|e| format!("Görüntüler okunamadı: {}", e))?;
+    if let Some(manifest) = manifests.iter().find(|manifest| manifest.id == id) {
+        return Ok(manifest.clone());
+    }
+    let mut matches = manifests.into_iter().filter(|manifest| manifest.id.starts_with(id));
+    match (matches.next(), matches.next()) {
+        (Some(manifest), None) if !id.is_empty() => Ok(manifest),
+        (Some(_), Some(_)) => Err(format!("'{}' birden fazla görüntüyle eşleşiyor", id)),
+        _ => Err(format!("'{}' görüntüsü bulunamadı", id)),
+    }
+}
+
+/// Kökü manifestteki hale getirir; önce mevcut halin görüntüsünü alır
+pub fn restore(root: &Path, manifest: &Manifest, now: DateTime<Utc>) -> io::Result<Restored> {
+    manifest.validate().map_err(io::Error::other)?;
+    for entry in &manifest.entries {
+        if let SnapshotKind::File { hash, .. } = &entry.kind
+            && !object_path(root, hash).is_file()
+        {
+            return Err(io::Error::other(format!("'{}' içeriği depoda yok: {}", entry.path.display(), hash)));
+        }
+    }
+    let safety = create(root, Some(SAFETY_LABEL), now)?;
+
+    // Manifestte olmayan veya türü değişen girdiler, alt girdilerden başlanarak silinir
+    let wanted: HashMap<&Path, &SnapshotKind> = manifest.entries.iter().map(|entry| (entry.path.as_path(), &entry.kind)).collect();
+    let mut removed = 0;
+    for entry in safety.entries.iter().rev() {
+        let keep = match (wanted.get(entry.path.as_path()), &entry.kind) {
+            (Some(SnapshotKind::Directory), SnapshotKind::Directory) => true,
+            (Some(SnapshotKind::File { .. }), SnapshotKind::File { .. }) => true,
+            (Some(SnapshotKind::Symlink { target }), SnapshotKind::Symlink { target: current }) => target == current,
+            _ => false,
+        };
+        if keep {
+            continue;
+        }
+        let full_path = root.join(&entry.path);
+        match entry.kind {
+            SnapshotKind::Directory => fs::remove_dir_all(&full_path)?,
+            _ => fs::remove_file(&full_path)?,
+        }
+        removed += 1;
+    }
+
+    let current: HashMap<&Path, &SnapshotKind> = safety.entries.iter().map(|entry| (entry.path.as_path(), &entry.kind)).collect();
+    let mut written = 0;
+    for entry in &manifest.entries {
+        let full_path = root.join(&entry.path);
+        match &entry.kind {
+            SnapshotKind::Directory => fs::create_dir_all(&full_path)?,
+            SnapshotKind::File { hash, .. } => {
+                let unchanged = matches!(current.get(entry.path.as_path()), Some(SnapshotKind::File { hash: existing, .. }) if existing == hash)
+                    && full_path.is_file();
+                if !unchanged {
+                    let temp_path = temp_path_for(&full_path);
+                    // Nesnenin salt okunur izinleri taşınmaz; izinler manifestten gelir
+                    let mut object = fs::File::open(object_path(root, hash))?;
+                    io::copy(&mut object, &mut fs::File::create(&temp_path)?)?;
+                    fs::rename(&temp_path, &full_path)?;
+                    written += 1;
+                }
+                if let Err(e) = metadata::apply(&full_path, &entry.metadata, Preserve::default()) {
+                    log::warn!("[⚠️] '{}' öznitelikleri uygulanamadı: {}", entry.path.display(), e);
+                }
+            }
+            SnapshotKind::Symlink { target } => {
+                if fs::symlink_metadata(&full_path).is_err() {
+                    create_symlink(target, &full_path)?;
+                }
+            }
+        }
+    }
+
+    // Klasör zamanları içleri doldurulduktan sonra uygulanır
+    for entry in manifest.entries.iter().rev().filter(|entry| entry.kind == SnapshotKind::Directory) {
+        if let Err(e) = metadata::apply(&root.join(&entry.path), &entry.metadata, Preserve::default()) {
+            log::warn!("[⚠️] '{}' öznitelikleri uygulanamadı: {}", entry.path.display(), e);
+        }
+    }
+    Ok(Restored { safety: safety.id, written, removed })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tree(root: &Path) -> Vec<(String, String)> {
+        scan(root).unwrap().into_iter().map(|(path, metadata)| {
+            let full_path = root.join(&path);
+            let content = if metadata.file_type().is_symlink() {
+                format!("-> {}", fs::read_link(&full_path).unwrap().display())
+            } else if metadata.is_dir() {
+                "/".to_string()
+            } else {
+                fs::read_to_string(&full_path).unwrap()
+            };
+            (path.to_string_lossy().into_owned(), content)
+        }).collect()
+    }
+
+    fn object_count(root: &Path) -> usize {
+        fs::read_dir(root.join(STATE_DIR).join(OBJECTS_DIR)).unwrap()
+            .map(|dir| fs::read_dir(dir.unwrap().path()).unwrap().count())
+            .sum()
+    }
+
+    #[test]
+    fn test_snapshot_create_and_restore() {
+        let root = std::env::temp_dir().join(format!("deltasafe-snapshot-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("build/lib")).unwrap();
+        fs::create_dir_all(root.join("bos")).unwrap();
+        fs::write(root.join("build/app"), "v1").unwrap();
+        fs::write(root.join("build/lib/a.so"), "ortak").unwrap();
+        fs::write(root.join("build/lib/b.so"), "ortak").unwrap();
+        #[cfg(unix)]
+        std::os::unix::fs::symlink("lib/a.so", root.join("build/latest")).unwrap();
+        let before = tree(&root);
+
+        let tuesday = DateTime::parse_from_rfc3339("2024-03-26T18:00:00Z").unwrap().with_timezone(&Utc);
+        let first = create(&root, Some("salı"), tuesday).unwrap();
+        assert_eq!(first.id, "20240326T180000Z");
+        assert_eq!(first.totals(), (3, 12));
+        // Aynı içerik bir kez saklanır
+        assert_eq!(object_count(&root), 2);
+
+        // Değişiklikler: dosya güncellenir, silinir, yenisi ve yeni klasör eklenir
+        fs::write(root.join("build/app"), "v2").unwrap();
+        fs::remove_file(root.join("build/lib/b.so")).unwrap();
+        fs::remove_dir(root.join("bos")).unwrap();
+        fs::create_dir_all(root.join("build/yeni")).unwrap();
+        fs::write(root.join("build/yeni/c.txt"), "fazla").unwrap();
+        let wednesday = tuesday + TimeDelta::days(1);
+        create(&root, None, wednesday).unwrap();
+        assert_eq!(object_count(&root), 4);
+
+        let manifest = find(&root, "20240326").unwrap();
+        assert_eq!(manifest.label.as_deref(), Some("salı"));
+        let restored = restore(&root, &manifest, wednesday + TimeDelta::hours(1)).unwrap();
+        assert_eq!(tree(&root), before);
+        assert_eq!((restored.written, restored.removed), (2, 2));
+
+        // Geri yükleme öncesi hal de bir görüntüdür; yeniden geri dönülebilir
+        let snapshots = list(&root).unwrap();
+        assert_eq!(snapshots.len(), 3);
+        assert_eq!(snapshots[0].id, restored.safety);
+        assert_eq!(snapshots[0].label.as_deref(), Some(SAFETY_LABEL));
+        assert!(find(&root, "2024032").is_err());
+        assert!(find(&root, "1999").is_err());
+
+        restore(&root, &snapshots[0], wednesday + TimeDelta::hours(2)).unwrap();
+        assert_eq!(fs::read_to_string(root.join("build/yeni/c.txt")).unwrap(), "fazla");
+        assert_eq!(fs::read_to_string(root.join("build/app")).unwrap(), "v2");
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_rejects_tampered_manifest() {
+        let root = std::env::temp_dir().join(format!("deltasafe-snapshot-bad-{}", std::process::id()));
+        fs::create_dir_all(&root).unwrap();
+        let entry = |path: &str, kind| SnapshotEntry { path: PathBuf::from(path), kind, metadata: FileMetadata::default() };
+        let cases = [
+            entry("../disari", SnapshotKind::Directory),
+            entry(".deltasafe/objects", SnapshotKind::Directory),
+            entry("a", SnapshotKind::Symlink { target: PathBuf::from("../../etc") }),
+            entry("b", SnapshotKind::File { hash: "yok".to_string(), size: 0 }),
+            entry("c", SnapshotKind::File { hash: blake3::hash(b"kayip").to_hex().to_string(), size: 5 }),
+        ];
+        for case in cases {
+            let manifest = Manifest { id: "20240101T000000Z".to_string(), label: None, entries: vec![case] };
+            assert!(restore(&root, &manifest, Utc::now()).is_err());
+        }
+        // Reddedilen geri yükleme görüntü de almaz
+        assert!(list(&root).unwrap().is_empty());
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,98 @@
+//! Seyrek dosyalar
+//!
+//! Sanal disk imajları ve veritabanı dosyaları çoğunlukla deliklerden
+//! oluşur. İstemci `SEEK_DATA`/`SEEK_HOLE` ile yalnızca veri içeren
+//! bölgeleri okuyup gönderir; delikler içeriksiz `Hole` çerçeveleriyle
+//! bildirilir. Sunucu geçici dosyayı baştan tam boyuta genişlettiği için
+//! delikler hedefte de yer kaplamaz.
+//!
+//! Delik bilgisi sunmayan dosya sistemlerinde ve platformlarda tüm aralık
+//! veri olarak kabul edilir.
+
+use std::io;
+use std::path::Path;
+
+/// `[start, end)` aralığındaki veri bölgelerini `(başlangıç, bitiş)`
+/// çiftleri olarak döndürür; aralıkta kalmayan kısımlar deliktir.
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd", target_os = "macos"))]
+pub fn data_segments(path: &Path, start: u64, end: u64) -> io::Result<Vec<(u64, u64)>> {
+    use std::os::unix::io::AsRawFd;
+
+    let file = std::fs::File::open(path)?;
+    let fd = file.as_raw_fd();
+    // Konum `off_t`'ye sığmıyorsa (32 bit) delik aranmaz
+    let seek = |offset: u64, whence: libc::c_int| -> Option<io::Result<u64>> {
+        let offset = libc::off_t::try_from(offset).ok()?;
+        // SAFETY: `fd` bu fonksiyon boyunca açık kalan `file`a aittir
+        let result = unsafe { libc::lseek(fd, offset, whence) };
+        Some(if result < 0 { Err(io::Error::last_os_error()) } else { Ok(result as u64) })
+    };
+
+    let mut segments = Vec::new();
+    let mut position = start;
+    while position < end {
+        let data = match seek(position, libc::SEEK_DATA) {
+            Some(Ok(data)) => data,
+            // Dosyanın sonuna kadar delik
+            Some(Err(e)) if e.raw_os_error() == Some(libc::ENXIO) => break,
+            _ => return Ok(vec![(start, end)]),
+        };
+        if data >= end {
+            break;
+        }
+        let hole = match seek(data, libc::SEEK_HOLE) {
+            Some(Ok(hole)) => hole.min(end),
+            _ => return Ok(vec![(start, end)]),
+        };
+        if hole <= data {
+            return Ok(vec![(start, end)]);
+        }
+        segments.push((data, hole));
+        position = hole;
+    }
+    Ok(segments)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android", target_os = "freebsd", target_os = "macos")))]
+pub fn data_segments(_path: &Path, start: u64, end: u64) -> io::Result<Vec<(u64, u64)>> {
+    Ok(vec![(start, end)])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Seek, SeekFrom, Write};
+
+    #[test]
+    fn test_data_segments() {
+        const MB: u64 = 1024 * 1024;
+        let path = std::env::temp_dir().join(format!("deltasafe-sparse-{}", std::process::id()));
+        let mut file = std::fs::File::create(&path).unwrap();
+        file.set_len(16 * MB).unwrap();
+        file.seek(SeekFrom::Start(4 * MB)).unwrap();
+        file.write_all(&[1; 4096]).unwrap();
+        file.seek(SeekFrom::Start(16 * MB - 10)).unwrap();
+        file.write_all(&[2; 10]).unwrap();
+        file.sync_all().unwrap();
+
+        let segments = data_segments(&path, 0, 16 * MB).unwrap();
+        let covered = |offset: u64| segments.iter().any(|&(start, end)| start <= offset && offset < end);
+        assert!(covered(4 * MB) && covered(4 * MB + 4095) && covered(16 * MB - 1));
+        assert!(segments.iter().all(|&(start, end)| start < end && end <= 16 * MB));
+
+        // Dosya sistemi delik bilgisi veriyorsa boş bölgeler atlanır
+        if segments != vec![(0, 16 * MB)] {
+            assert!(!covered(0) && !covered(8 * MB));
+            let data: u64 = segments.iter().map(|(start, end)| end - start).sum();
+            assert!(data < 4 * MB, "{:?}", segments);
+        }
+
+        // Alt aralık sınırlara kırpılır; tamamen delik aralık boş döner
+        let inner = data_segments(&path, 4 * MB + 100, 4 * MB + 200).unwrap();
+        assert_eq!(inner, vec![(4 * MB + 100, 4 * MB + 200)]);
+        if segments != vec![(0, 16 * MB)] {
+            assert!(data_segments(&path, 8 * MB, 12 * MB).unwrap().is_empty());
+        }
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,918 @@
+//! Sunucu depolama katmanı
+//!
+//! Sunucunun alınan içeriği nereye yazdığı [`Storage`] trait'iyle soyutlanır.
+//! Yollar depolama köküne görelidir. Yazımlar önce geçici bir alana gider;
+//! [`TempWrite::commit`] ile hedef yola atomik olarak yerleşir, o ana kadar
+//! okuyanlar eski içeriği görür. Geçici yazım commit edilmeden bırakılırsa
+//! silinir.
+//!
+//! - [`LocalStorage`]: varsayılan; kök klasör altında dosya sistemi
+//! - [`MemoryStorage`]: bellek içi; testler ve gömülü kullanım için
+//! - [`S3Storage`]: S3 uyumlu nesne deposu (MinIO vb.)
+//!
+//! Metotlar engelleyicidir; sunucu bunları `spawn_blocking` içinde çağırır.
+//! Klasörler, bağlantılar, öznitelikler, sürümler ve anlık görüntüler gerçek
+//! bir dosya sistemi gerektirir ve yalnızca [`Storage::local_root`] dönen
+//! depolamalarda desteklenir.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io::{self, BufRead, BufReader, Cursor, Read, Seek, SeekFrom, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+use crate::crypto::random_nonce;
+use crate::server::temp_path_for;
+use crate::sync::calculate_file_hash;
+
+/// Depolamadaki bir yolun bilgisi
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Stat {
+    pub size: u64,
+    pub is_dir: bool,
+}
+
+/// Klasör listesindeki bir girdi
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ListEntry {
+    pub name: String,
+    pub is_dir: bool,
+    pub size: u64,
+}
+
+/// Sunucunun içerik deposu
+pub trait Storage: Send + Sync + fmt::Debug {
+    /// `path` için `size` baytlık, sıfırlarla dolu bir geçici yazım açar
+    fn open_temp(&self, path: &Path, size: u64) -> io::Result<Box<dyn TempWrite>>;
+
+    /// Yolun bilgisi; yol yoksa `None`
+    fn stat(&self, path: &Path) -> io::Result<Option<Stat>>;
+
+    /// Klasörün doğrudan altındaki girdileri ada göre sıralı döner; klasör
+    /// yoksa liste boştur
+    fn list(&self, dir: &Path) -> io::Result<Vec<ListEntry>>;
+
+    /// Dosyayı veya klasörü altındakilerle birlikte siler
+    fn delete(&self, path: &Path) -> io::Result<()>;
+
+    /// Dosyanın içeriğini okur
+    fn read(&self, path: &Path) -> io::Result<Box<dyn Read + Send>>;
+
+    /// Depolama yerel dosya sistemindeyse kök klasörü
+    fn local_root(&self) -> Option<&Path> {
+        None
+    }
+}
+
+/// Henüz görünmeyen bir yazım. Commit edilmeden bırakılırsa silinir.
+pub trait TempWrite: Send {
+    /// Veriyi verilen ofsete yazar
+    fn write_at(&mut self, offset: u64, data: &[u8]) -> io::Result<()>;
+
+    /// Yazılan içeriğin BLAKE3 hash'i (hex)
+    fn hash(&mut self) -> io::Result<String>;
+
+    /// İçeriği hedef yola atomik olarak yerleştirir
+    fn commit(self: Box<Self>) -> io::Result<()>;
+}
+
+/// Yolun kökte kaldığını doğrular
+fn check_relative(path: &Path) -> io::Result<()> {
+    if path.components().all(|component| matches!(component, Component::Normal(_) | Component::CurDir)) {
+        Ok(())
+    } else {
+        Err(io::Error::new(io::ErrorKind::InvalidInput, format!("Geçersiz depolama yolu: {}", path.display())))
+    }
+}
+
+/// Göreli yolu `/` ile ayrılmış anahtara çevirir
+fn path_key(path: &Path) -> String {
+    path.components()
+        .filter_map(|component| match component {
+            Component::Normal(name) => Some(name.to_string_lossy()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Kök klasör altında dosya sistemi
+#[derive(Debug, Clone)]
+pub struct LocalStorage {
+    root: PathBuf,
+}
+
+impl LocalStorage {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+}
+
+impl Storage for LocalStorage {
+    fn open_temp(&self, path: &Path, size: u64) -> io::Result<Box<dyn TempWrite>> {
+        check_relative(path)?;
+        let full_path = self.root.join(path);
+        fs::create_dir_all(full_path.parent().unwrap_or(&self.root))?;
+        let temp_path = temp_path_for(&full_path);
+        let file = fs::File::create(&temp_path)?;
+        // Baştan tam boyuta genişletilir; yazılmayan bölgeler seyrek kalır
+        file.set_len(size)?;
+        Ok(Box::new(LocalTemp { file, temp_path, full_path, position: 0, committed: false }))
+    }
+
+    fn stat(&self, path: &Path) -> io::Result<Option<Stat>> {
+        check_relative(path)?;
+        match fs::symlink_metadata(self.root.join(path)) {
+            Ok(metadata) => Ok(Some(Stat { size: metadata.len(), is_dir: metadata.is_dir() })),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn list(&self, dir: &Path) -> io::Result<Vec<ListEntry>> {
+        check_relative(dir)?;
+        let entries = match fs::read_dir(self.root.join(dir)) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+        let mut list = Vec::new();
+        for entry in entries {
+            let entry = entry?;
+            let metadata = entry.metadata()?;
+            list.push(ListEntry { name: entry.file_name().to_string_lossy().into_owned(), is_dir: metadata.is_dir(), size: metadata.len() });
+        }
+        list.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(list)
+    }
+
+    fn delete(&self, path: &Path) -> io::Result<()> {
+        check_relative(path)?;
+        let full_path = self.root.join(path);
+        if fs::symlink_metadata(&full_path)?.is_dir() {
+            fs::remove_dir_all(full_path)
+        } else {
+            fs::remove_file(full_path)
+        }
+    }
+
+    fn read(&self, path: &Path) -> io::Result<Box<dyn Read + Send>> {
+        check_relative(path)?;
+        Ok(Box::new(fs::File::open(self.root.join(path))?))
+    }
+
+    fn local_root(&self) -> Option<&Path> {
+        Some(&self.root)
+    }
+}
+
+/// Hedefin yanındaki gizli geçici dosya; commit'te yerine taşınır
+struct LocalTemp {
+    file: fs::File,
+    temp_path: PathBuf,
+    full_path: PathBuf,
+    /// Dosya imlecinin konumu (gereksiz seek'leri önlemek için)
+    position: u64,
+    committed: bool,
+}
+
+impl TempWrite for LocalTemp {
+    fn write_at(&mut self, offset: u64, data: &[u8]) -> io::Result<()> {
+        if offset != self.position {
+            self.file.seek(SeekFrom::Start(offset))?;
+        }
+        self.file.write_all(data)?;
+        self.position = offset + data.len() as u64;
+        Ok(())
+    }
+
+    fn hash(&mut self) -> io::Result<String> {
+        calculate_file_hash(&self.temp_path)
+    }
+
+    fn commit(mut self: Box<Self>) -> io::Result<()> {
+        self.file.sync_all()?;
+        fs::rename(&self.temp_path, &self.full_path)?;
+        self.committed = true;
+        Ok(())
+    }
+}
+
+impl Drop for LocalTemp {
+    fn drop(&mut self) {
+        if !self.committed {
+            let _ = fs::remove_file(&self.temp_path);
+        }
+    }
+}
+
+/// Bellek içi depolama. Kopyaları aynı içeriği paylaşır; klasörler dosya
+/// yollarından çıkarılır.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryStorage {
+    files: Arc<Mutex<BTreeMap<String, Arc<Vec<u8>>>>>,
+}
+
+impl MemoryStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, BTreeMap<String, Arc<Vec<u8>>>> {
+        self.files.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Klasörün altındaki dosya anahtarları
+fn keys_under(files: &BTreeMap<String, Arc<Vec<u8>>>, dir: &str) -> Vec<String> {
+    let prefix = if dir.is_empty() { String::new() } else { format!("{}/", dir) };
+    files.keys().filter(|key| key.starts_with(&prefix)).cloned().collect()
+}
+
+impl Storage for MemoryStorage {
+    fn open_temp(&self, path: &Path, size: u64) -> io::Result<Box<dyn TempWrite>> {
+        check_relative(path)?;
+        let size = usize::try_from(size).map_err(|_| io::Error::from(io::ErrorKind::OutOfMemory))?;
+        Ok(Box::new(MemoryTemp { storage: self.clone(), key: path_key(path), data: vec![0; size] }))
+    }
+
+    fn stat(&self, path: &Path) -> io::Result<Option<Stat>> {
+        check_relative(path)?;
+        let key = path_key(path);
+        let files = self.lock();
+        if let Some(data) = files.get(&key) {
+            return Ok(Some(Stat { size: data.len() as u64, is_dir: false }));
+        }
+        let is_dir = key.is_empty() || !keys_under(&files, &key).is_empty();
+        Ok(is_dir.then_some(Stat { size: 0, is_dir: true }))
+    }
+
+    fn list(&self, dir: &Path) -> io::Result<Vec<ListEntry>> {
+        check_relative(dir)?;
+        let dir = path_key(dir);
+        let skip = if dir.is_empty() { 0 } else { dir.len() + 1 };
+        let files = self.lock();
+        let mut list: Vec<ListEntry> = Vec::new();
+        for key in keys_under(&files, &dir) {
+            let rest = &key[skip..];
+            let entry = match rest.split_once('/') {
+                Some((name, _)) => ListEntry { name: name.to_string(), is_dir: true, size: 0 },
+                None => ListEntry { name: rest.to_string(), is_dir: false, size: files[&key].len() as u64 },
+            };
+            if list.last() != Some(&entry) {
+                list.push(entry);
+            }
+        }
+        Ok(list)
+    }
+
+    fn delete(&self, path: &Path) -> io::Result<()> {
+        check_relative(path)?;
+        let key = path_key(path);
+        let mut files = self.lock();
+        if files.remove(&key).is_some() {
+            return Ok(());
+        }
+        let under = keys_under(&files, &key);
+        if under.is_empty() {
+            return Err(io::Error::from(io::ErrorKind::NotFound));
+        }
+        for key in under {
+            files.remove(&key);
+        }
+        Ok(())
+    }
+
+    fn read(&self, path: &Path) -> io::Result<Box<dyn Read + Send>> {
+        check_relative(path)?;
+        let data = self.lock().get(&path_key(path)).cloned()
+            .ok_or_else(|| io::Error::from(io::ErrorKind::NotFound))?;
+        Ok(Box::new(Cursor::new(data.to_vec())))
+    }
+}
+
+struct MemoryTemp {
+    storage: MemoryStorage,
+    key: String,
+    data: Vec<u8>,
+}
+
+impl TempWrite for MemoryTemp {
+    fn write_at(&mut self, offset: u64, data: &[u8]) -> io::Result<()> {
+        let start = usize::try_from(offset).ok()
+            .filter(|start| start.checked_add(data.len()).is_some_and(|end| end <= self.data.len()))
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Yazım dosya boyutunu aşıyor"))?;
+        self.data[start..start + data.len()].copy_from_slice(data);
+        Ok(())
+    }
+
+    fn hash(&mut self) -> io::Result<String> {
+        Ok(blake3::hash(&self.data).to_hex().to_string())
+    }
+
+    fn commit(self: Box<Self>) -> io::Result<()> {
+        let MemoryTemp { storage, key, data } = *self;
+        storage.lock().insert(key, Arc::new(data));
+        Ok(())
+    }
+}
+
+/// S3 isteklerinde bağlantı ve okuma zaman aşımı
+const S3_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// İmzalanmayan gövde için SigV4 değeri; gövde akış olarak gönderilir
+const UNSIGNED_PAYLOAD: &str = "UNSIGNED-PAYLOAD";
+
+/// S3 uyumlu nesne deposu (yol tarzı adresleme, AWS Signature V4).
+///
+/// Yalnızca `http://` uç noktaları desteklenir; TLS gerekiyorsa depo önüne
+/// bir TLS sonlandırıcı konmalıdır. Dosyalar önce yerel bir geçici dosyada
+/// birleştirilir, commit'te tek `PUT` ile yüklenir. Klasörler, altında nesne
+/// bulunan `/` önekleridir.
+#[derive(Clone)]
+pub struct S3Storage {
+    /// `host:port`
+    host: String,
+    bucket: String,
+    /// Tüm anahtarların önüne eklenen önek (boş olabilir)
+    prefix: String,
+    region: String,
+    access_key: String,
+    secret_key: String,
+}
+
+impl fmt::Debug for S3Storage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("S3Storage")
+            .field("host", &self.host)
+            .field("bucket", &self.bucket)
+            .field("prefix", &self.prefix)
+            .field("region", &self.region)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Nesneler (anahtar, boyut) ve ortak önekler
+type ObjectList = (Vec<(String, u64)>, Vec<String>);
+
+/// Gövdesi henüz okunmamış HTTP yanıtı
+struct HttpResponse {
+    status: u16,
+    content_length: Option<u64>,
+    chunked: bool,
+    reader: BufReader<TcpStream>,
+}
+
+impl HttpResponse {
+    fn into_body(self) -> io::Result<Vec<u8>> {
+        let mut body = Vec::new();
+        self.into_reader().read_to_end(&mut body)?;
+        Ok(body)
+    }
+
+    fn into_reader(mut self) -> Box<dyn Read + Send> {
+        if self.chunked {
+            return match read_chunked(&mut self.reader) {
+                Ok(body) => Box::new(Cursor::new(body)),
+                Err(e) => Box::new(FailedRead(Some(e))),
+            };
+        }
+        match self.content_length {
+            Some(len) => Box::new(self.reader.take(len)),
+            None => Box::new(self.reader),
+        }
+    }
+}
+
+/// İlk okumada hata döndüren okuyucu
+struct FailedRead(Option<io::Error>);
+
+impl Read for FailedRead {
+    fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+        match self.0.take() {
+            Some(e) => Err(e),
+            None => Ok(0),
+        }
+    }
+}
+
+fn read_chunked(reader: &mut impl BufRead) -> io::Result<Vec<u8>> {
+    let mut body = Vec::new();
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+        let size = line.trim().split(';').next().unwrap_or_default();
+        let size = usize::from_str_radix(size, 16)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Geçersiz chunked kodlama"))?;
+        if size == 0 {
+            return Ok(body);
+        }
+        let start = body.len();
+        body.resize(start + size, 0);
+        reader.read_exact(&mut body[start..])?;
+        let mut crlf = [0; 2];
+        reader.read_exact(&mut crlf)?;
+    }
+}
+
+/// SigV4 URI kodlaması; `/` yalnızca yol içinde korunur
+fn uri_encode(text: &str, keep_slash: bool) -> String {
+    let mut encoded = String::with_capacity(text.len());
+    for byte in text.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => encoded.push(byte as char),
+            b'/' if keep_slash => encoded.push('/'),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC her anahtar uzunluğunu kabul eder");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// SigV4 imza anahtarı: gizli anahtardan gün, bölge ve servise göre türetilir
+fn signing_key(secret_key: &str, date: &str, region: &str, service: &str) -> Vec<u8> {
+    let key = hmac_sha256(format!("AWS4{}", secret_key).as_bytes(), date.as_bytes());
+    let key = hmac_sha256(&key, region.as_bytes());
+    let key = hmac_sha256(&key, service.as_bytes());
+    hmac_sha256(&key, b"aws4_request")
+}
+
+/// XML'deki `<tag>` değerlerini sırayla döner
+fn xml_values<'a>(xml: &'a str, tag: &str) -> Vec<&'a str> {
+    let (open, close) = (format!("<{}>", tag), format!("</{}>", tag));
+    let mut values = Vec::new();
+    let mut rest = xml;
+    while let Some(start) = rest.find(&open) {
+        rest = &rest[start + open.len()..];
+        let Some(end) = rest.find(&close) else { break };
+        values.push(&rest[..end]);
+        rest = &rest[end + close.len()..];
+    }
+    values
+}
+
+fn xml_unescape(text: &str) -> String {
+    text.replace("&lt;", "<").replace("&gt;", ">").replace("&quot;", "\"").replace("&apos;", "'").replace("&amp;", "&")
+}
+
+impl S3Storage {
+    /// `http://host:port/bucket[/önek]` biçimindeki adresten oluşturur
+    pub fn new(url: &str, region: &str, access_key: &str, secret_key: &str) -> io::Result<Self> {
+        let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidInput, format!("{}: {}", message, url));
+        let rest = url.strip_prefix("http://")
+            .ok_or_else(|| invalid("Yalnızca http:// S3 adresleri destekleniyor"))?;
+        let (host, path) = rest.split_once('/').ok_or_else(|| invalid("S3 adresinde kova adı yok"))?;
+        let (bucket, prefix) = path.split_once('/').unwrap_or((path, ""));
+        if host.is_empty() || bucket.is_empty() {
+            return Err(invalid("Geçersiz S3 adresi"));
+        }
+        let host = if host.contains(':') && !host.ends_with(']') { host.to_string() } else { format!("{}:80", host) };
+        Ok(Self {
+            host,
+            bucket: bucket.to_string(),
+            prefix: prefix.trim_matches('/').to_string(),
+            region: region.to_string(),
+            access_key: access_key.to_string(),
+            secret_key: secret_key.to_string(),
+        })
+    }
+
+    /// Kimlik bilgilerini `AWS_ACCESS_KEY_ID`, `AWS_SECRET_ACCESS_KEY` ve
+    /// `AWS_REGION` (varsayılan: `us-east-1`) ortam değişkenlerinden alır
+    pub fn from_env(url: &str) -> io::Result<Self> {
+        let var = |name: &str| std::env::var(name)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, format!("{} ortam değişkeni tanımlı değil", name)));
+        let region = std::env::var("AWS_REGION").unwrap_or_else(|_| "us-east-1".to_string());
+        Self::new(url, &region, &var("AWS_ACCESS_KEY_ID")?, &var("AWS_SECRET_ACCESS_KEY")?)
+    }
+
+    /// Göreli yolun nesne anahtarı
+    fn object_key(&self, path: &Path) -> String {
+        let key = path_key(path);
+        match (self.prefix.is_empty(), key.is_empty()) {
+            (true, _) => key,
+            (false, true) => self.prefix.clone(),
+            (false, false) => format!("{}/{}", self.prefix, key),
+        }
+    }
+
+    /// İmzalı bir istek gönderir; gövde verilirse akış olarak yüklenir
+    fn request(&self, method: &str, key: &str, query: &[(&str, &str)], body: Option<(&mut dyn Read, u64)>) -> io::Result<HttpResponse> {
+        let now = chrono::Utc::now();
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let date = now.format("%Y%m%d").to_string();
+
+        let uri = if key.is_empty() { format!("/{}", uri_encode(&self.bucket, false)) } else { format!("/{}/{}", uri_encode(&self.bucket, false), uri_encode(key, true)) };
+        let mut query: Vec<(String, String)> = query.iter().map(|(k, v)| (uri_encode(k, false), uri_encode(v, false))).collect();
+        query.sort();
+        let query = query.iter().map(|(k, v)| format!("{}={}", k, v)).collect::<Vec<_>>().join("&");
+
+        let signed_headers = "host;x-amz-content-sha256;x-amz-date";
+        let canonical = format!("{}\n{}\n{}\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\n{}\n{}",
+            method, uri, query, self.host, UNSIGNED_PAYLOAD, amz_date, signed_headers, UNSIGNED_PAYLOAD);
+        let scope = format!("{}/{}/s3/aws4_request", date, self.region);
+        let string_to_sign = format!("AWS4-HMAC-SHA256\n{}\n{}\n{}", amz_date, scope, hex::encode(Sha256::digest(canonical.as_bytes())));
+        let signature = hex::encode(hmac_sha256(&signing_key(&self.secret_key, &date, &self.region, "s3"), string_to_sign.as_bytes()));
+
+        let address = self.host.to_socket_addrs()?.next()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("S3 adresi çözümlenemedi: {}", self.host)))?;
+        let mut stream = TcpStream::connect_timeout(&address, S3_TIMEOUT)?;
+        stream.set_read_timeout(Some(S3_TIMEOUT))?;
+        stream.set_write_timeout(Some(S3_TIMEOUT))?;
+        let target = if query.is_empty() { uri } else { format!("{}?{}", uri, query) };
+        let length = body.as_ref().map_or(0, |(_, len)| *len);
+        let head = format!(
+            "{} {} HTTP/1.1\r\nHost: {}\r\nx-amz-date: {}\r\nx-amz-content-sha256: {}\r\nAuthorization: AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            method, target, self.host, amz_date, UNSIGNED_PAYLOAD, self.access_key, scope, signed_headers, signature, length);
+        stream.write_all(head.as_bytes())?;
+        if let Some((body, len)) = body {
+            let copied = io::copy(&mut body.take(len), &mut stream)?;
+            if copied != len {
+                return Err(io::Error::from(io::ErrorKind::UnexpectedEof));
+            }
+        }
+        stream.flush()?;
+
+        let mut reader = BufReader::new(stream);
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+        let status = line.split_whitespace().nth(1).and_then(|status| status.parse().ok())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, format!("Geçersiz S3 yanıtı: {}", line.trim())))?;
+        let (mut content_length, mut chunked) = (None, false);
+        loop {
+            line.clear();
+            if reader.read_line(&mut line)? == 0 || line.trim().is_empty() {
+                break;
+            }
+            if let Some((name, value)) = line.split_once(':') {
+                let value = value.trim();
+                if name.eq_ignore_ascii_case("content-length") {
+                    content_length = value.parse().ok();
+                } else if name.eq_ignore_ascii_case("transfer-encoding") && value.eq_ignore_ascii_case("chunked") {
+                    chunked = true;
+                }
+            }
+        }
+        // HEAD yanıtının gövdesi yoktur
+        let chunked = chunked && method != "HEAD";
+        Ok(HttpResponse { status, content_length, chunked, reader })
+    }
+
+    /// Başarısız yanıtı hataya çevirir; 404 `NotFound` olur
+    fn check(method: &str, key: &str, response: HttpResponse) -> io::Result<HttpResponse> {
+        match response.status {
+            200..=299 => Ok(response),
+            404 => Err(io::Error::new(io::ErrorKind::NotFound, format!("S3 nesnesi bulunamadı: {}", key))),
+            status => {
+                let body = if method == "HEAD" { Vec::new() } else { response.into_body().unwrap_or_default() };
+                let code = xml_values(&String::from_utf8_lossy(&body), "Code").first().map(|code| code.to_string()).unwrap_or_default();
+                Err(io::Error::other(format!("S3 {} {} başarısız: HTTP {} {}", method, key, status, code)))
+            }
+        }
+    }
+
+    /// Önekin altındaki nesneleri (anahtar, boyut) ve (ayraç verilirse) alt
+    /// önekleri listeler
+    fn list_objects(&self, prefix: &str, delimiter: bool) -> io::Result<ObjectList> {
+        let (mut objects, mut prefixes) = (Vec::new(), Vec::new());
+        let mut token: Option<String> = None;
+        loop {
+            let mut query = vec![("list-type", "2"), ("prefix", prefix)];
+            if delimiter {
+                query.push(("delimiter", "/"));
+            }
+            if let Some(token) = &token {
+                query.push(("continuation-token", token));
+            }
+            let response = Self::check("GET", prefix, self.request("GET", "", &query, None)?)?;
+            let xml = String::from_utf8_lossy(&response.into_body()?).into_owned();
+            for contents in xml_values(&xml, "Contents") {
+                let key = xml_values(contents, "Key").first().map(|key| xml_unescape(key)).unwrap_or_default();
+                let size = xml_values(contents, "Size").first().and_then(|size| size.parse().ok()).unwrap_or(0);
+                objects.push((key, size));
+            }
+            for common in xml_values(&xml, "CommonPrefixes") {
+                prefixes.extend(xml_values(common, "Prefix").first().map(|prefix| xml_unescape(prefix)));
+            }
+            match xml_values(&xml, "IsTruncated").first() {
+                Some(&"true") => token = xml_values(&xml, "NextContinuationToken").first().map(|token| xml_unescape(token)),
+                _ => break,
+            }
+            if token.is_none() {
+                break;
+            }
+        }
+        Ok((objects, prefixes))
+    }
+
+    /// Klasörün liste öneki (`anahtar/`)
+    fn dir_prefix(key: &str) -> String {
+        if key.is_empty() { String::new() } else { format!("{}/", key) }
+    }
+}
+
+impl Storage for S3Storage {
+    fn open_temp(&self, path: &Path, size: u64) -> io::Result<Box<dyn TempWrite>> {
+        check_relative(path)?;
+        let temp_path = std::env::temp_dir().join(format!("deltasafe-s3-{}{}", random_nonce(), crate::server::TEMP_SUFFIX));
+        let file = fs::OpenOptions::new().read(true).write(true).create_new(true).open(&temp_path)?;
+        file.set_len(size)?;
+        Ok(Box::new(S3Temp { storage: self.clone(), key: self.object_key(path), file, temp_path, size }))
+    }
+
+    fn stat(&self, path: &Path) -> io::Result<Option<Stat>> {
+        check_relative(path)?;
+        if path_key(path).is_empty() {
+            return Ok(Some(Stat { size: 0, is_dir: true }));
+        }
+        let key = self.object_key(path);
+        let response = self.request("HEAD", &key, &[], None)?;
+        if response.status != 404 {
+            let response = Self::check("HEAD", &key, response)?;
+            return Ok(Some(Stat { size: response.content_length.unwrap_or(0), is_dir: false }));
+        }
+        let (objects, prefixes) = self.list_objects(&Self::dir_prefix(&key), true)?;
+        Ok((!objects.is_empty() || !prefixes.is_empty()).then_some(Stat { size: 0, is_dir: true }))
+    }
+
+    fn list(&self, dir: &Path) -> io::Result<Vec<ListEntry>> {
+        check_relative(dir)?;
+        let prefix = Self::dir_prefix(&self.object_key(dir));
+        let (objects, prefixes) = self.list_objects(&prefix, true)?;
+        let mut list: Vec<ListEntry> = objects.into_iter()
+            .filter_map(|(key, size)| key.strip_prefix(&prefix).filter(|name| !name.is_empty()).map(|name| ListEntry { name: name.to_string(), is_dir: false, size }))
+            .chain(prefixes.iter().filter_map(|key| key.strip_prefix(&prefix)).map(|name| ListEntry { name: name.trim_end_matches('/').to_string(), is_dir: true, size: 0 }))
+            .collect();
+        list.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(list)
+    }
+
+    fn delete(&self, path: &Path) -> io::Result<()> {
+        check_relative(path)?;
+        let key = self.object_key(path);
+        let keys = match self.stat(path)? {
+            Some(Stat { is_dir: false, .. }) => vec![key.clone()],
+            Some(_) => self.list_objects(&Self::dir_prefix(&key), false)?.0.into_iter().map(|(key, _)| key).collect(),
+            None => return Err(io::Error::new(io::ErrorKind::NotFound, format!("S3 nesnesi bulunamadı: {}", key))),
+        };
+        for key in keys {
+            Self::check("DELETE", &key, self.request("DELETE", &key, &[], None)?)?;
+        }
+        Ok(())
+    }
+
+    fn read(&self, path: &Path) -> io::Result<Box<dyn Read + Send>> {
+        check_relative(path)?;
+        let key = self.object_key(path);
+        Ok(Self::check("GET", &key, self.request("GET", &key, &[], None)?)?.into_reader())
+    }
+}
+
+/// Commit'e kadar yerel geçici dosyada birleşen S3 nesnesi
+struct S3Temp {
+    storage: S3Storage,
+    key: String,
+    file: fs::File,
+    temp_path: PathBuf,
+    size: u64,
+}
+
+impl TempWrite for S3Temp {
+    fn write_at(&mut self, offset: u64, data: &[u8]) -> io::Result<()> {
+        self.file.seek(SeekFrom::Start(offset))?;
+        self.file.write_all(data)
+    }
+
+    fn hash(&mut self) -> io::Result<String> {
+        calculate_file_hash(&self.temp_path)
+    }
+
+    fn commit(mut self: Box<Self>) -> io::Result<()> {
+        self.file.seek(SeekFrom::Start(0))?;
+        let size = self.size;
+        let response = self.storage.request("PUT", &self.key, &[], Some((&mut self.file, size)))?;
+        S3Storage::check("PUT", &self.key, response).map(|_| ())
+    }
+}
+
+impl Drop for S3Temp {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.temp_path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+
+    fn write_file(storage: &dyn Storage, path: &str, data: &[u8]) {
+        let mut temp = storage.open_temp(Path::new(path), data.len() as u64).unwrap();
+        // Aralıklar sırasız gelebilir
+        let middle = data.len() / 2;
+        temp.write_at(middle as u64, &data[middle..]).unwrap();
+        temp.write_at(0, &data[..middle]).unwrap();
+        assert_eq!(temp.hash().unwrap(), blake3::hash(data).to_hex().to_string());
+        temp.commit().unwrap();
+    }
+
+    fn read_file(storage: &dyn Storage, path: &str) -> Vec<u8> {
+        let mut data = Vec::new();
+        storage.read(Path::new(path)).unwrap().read_to_end(&mut data).unwrap();
+        data
+    }
+
+    /// Tüm depolamaların aynı davranması gereken senaryo
+    fn exercise(storage: &dyn Storage) {
+        assert_eq!(storage.stat(Path::new("a/b.txt")).unwrap(), None);
+        assert!(storage.list(Path::new("a")).unwrap().is_empty());
+
+        write_file(storage, "a/b.txt", b"merhaba dunya");
+        write_file(storage, "a/alt/c.bin", &[7; 1000]);
+        write_file(storage, "kok.txt", b"");
+        // Commit edilmeyen yazım görünmez
+        let mut abandoned = storage.open_temp(Path::new("a/yarim.txt"), 3).unwrap();
+        abandoned.write_at(0, b"abc").unwrap();
+        drop(abandoned);
+
+        assert_eq!(storage.stat(Path::new("a/b.txt")).unwrap(), Some(Stat { size: 13, is_dir: false }));
+        assert!(storage.stat(Path::new("a/alt")).unwrap().is_some_and(|stat| stat.is_dir));
+        assert_eq!(storage.stat(Path::new("a/yarim.txt")).unwrap(), None);
+        assert_eq!(read_file(storage, "a/b.txt"), b"merhaba dunya");
+        assert_eq!(read_file(storage, "a/alt/c.bin"), vec![7; 1000]);
+
+        let names: Vec<(String, bool)> = storage.list(Path::new("a")).unwrap().into_iter().map(|entry| (entry.name, entry.is_dir)).collect();
+        assert_eq!(names, vec![("alt".to_string(), true), ("b.txt".to_string(), false)]);
+
+        // Üzerine yazma eski içeriğin yerini alır
+        write_file(storage, "a/b.txt", b"yeni");
+        assert_eq!(read_file(storage, "a/b.txt"), b"yeni");
+
+        storage.delete(Path::new("a/alt")).unwrap();
+        assert_eq!(storage.stat(Path::new("a/alt/c.bin")).unwrap(), None);
+        storage.delete(Path::new("a/b.txt")).unwrap();
+        assert!(storage.read(Path::new("a/b.txt")).is_err());
+        assert!(storage.delete(Path::new("a/b.txt")).is_err());
+        assert!(storage.open_temp(Path::new("../disari.txt"), 1).is_err());
+        assert!(storage.stat(Path::new("/etc/passwd")).is_err());
+    }
+
+    #[test]
+    fn test_local_storage() {
+        let root = std::env::temp_dir().join(format!("deltasafe-storage-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let storage = LocalStorage::new(&root);
+        exercise(&storage);
+        assert_eq!(storage.local_root(), Some(root.as_path()));
+        // Yarım yazım geride geçici dosya bırakmaz
+        assert_eq!(fs::read_dir(root.join("a")).unwrap().count(), 0);
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_memory_storage() {
+        let storage = MemoryStorage::new();
+        exercise(&storage);
+        assert!(storage.local_root().is_none());
+        assert!(storage.open_temp(Path::new("x"), 2).unwrap().write_at(1, b"ab").is_err());
+    }
+
+    #[test]
+    fn test_sigv4_signing_key() {
+        // AWS belgelerindeki örnek değerler
+        let key = signing_key("wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY", "20120215", "us-east-1", "iam");
+        assert_eq!(hex::encode(key), "f4780e2d9f65fa895f9c67b32ce1baf0b0d8a43505a000a1a9e090d414db404d");
+        assert_eq!(uri_encode("a b/ç~", true), "a%20b/%C3%A7~");
+        assert_eq!(uri_encode("a/b", false), "a%2Fb");
+    }
+
+    /// S3'ün yol tarzı PUT/GET/HEAD/DELETE ve ListObjectsV2 uçlarını taklit
+    /// eden, imzalı istek bekleyen küçük bir sunucu
+    fn spawn_s3_stand_in() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        std::thread::spawn(move || {
+            let mut objects: BTreeMap<String, Vec<u8>> = BTreeMap::new();
+            for stream in listener.incoming() {
+                let Ok(stream) = stream else { continue };
+                let _ = serve_s3_request(stream, &mut objects);
+            }
+        });
+        address
+    }
+
+    fn serve_s3_request(stream: TcpStream, objects: &mut BTreeMap<String, Vec<u8>>) -> io::Result<()> {
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+        let mut parts = line.split_whitespace();
+        let (method, target) = (parts.next().unwrap_or_default().to_string(), parts.next().unwrap_or_default().to_string());
+        let (mut length, mut signed) = (0usize, false);
+        loop {
+            let mut header = String::new();
+            reader.read_line(&mut header)?;
+            if header.trim().is_empty() {
+                break;
+            }
+            let (name, value) = header.split_once(':').unwrap_or_default();
+            if name.eq_ignore_ascii_case("content-length") {
+                length = value.trim().parse().unwrap_or(0);
+            }
+            signed |= name.eq_ignore_ascii_case("authorization") && value.trim().starts_with("AWS4-HMAC-SHA256 Credential=test/");
+        }
+        let mut body = vec![0; length];
+        reader.read_exact(&mut body)?;
+
+        let decode = |text: &str| {
+            let bytes = text.as_bytes();
+            let mut out = Vec::new();
+            let mut i = 0;
+            while i < bytes.len() {
+                if bytes[i] == b'%' && i + 2 < bytes.len() {
+                    out.push(u8::from_str_radix(&text[i + 1..i + 3], 16).unwrap_or(b'?'));
+                    i += 3;
+                } else {
+                    out.push(bytes[i]);
+                    i += 1;
+                }
+            }
+            String::from_utf8_lossy(&out).into_owned()
+        };
+        let (path, query) = target.split_once('?').unwrap_or((&target, ""));
+        let key = decode(path.trim_start_matches('/').split_once('/').map_or("", |(_, key)| key));
+        let params: BTreeMap<String, String> = query.split('&').filter_map(|pair| pair.split_once('=')).map(|(k, v)| (k.to_string(), decode(v))).collect();
+
+        let (status, response) = if !signed {
+            (403, b"<Error><Code>AccessDenied</Code></Error>".to_vec())
+        } else {
+            match method.as_str() {
+                "PUT" => {
+                    objects.insert(key, body);
+                    (200, Vec::new())
+                }
+                "GET" if params.get("list-type").is_some_and(|t| t == "2") => {
+                    let prefix = params.get("prefix").cloned().unwrap_or_default();
+                    let delimiter = params.contains_key("delimiter");
+                    let mut xml = String::from("<ListBucketResult><IsTruncated>false</IsTruncated>");
+                    let mut common = Vec::new();
+                    for (key, data) in objects.range(prefix.clone()..).take_while(|(key, _)| key.starts_with(&prefix)) {
+                        match key[prefix.len()..].split_once('/') {
+                            Some((dir, _)) if delimiter => {
+                                let dir = format!("{}{}/", prefix, dir);
+                                if common.last() != Some(&dir) {
+                                    common.push(dir);
+                                }
+                            }
+                            _ => xml.push_str(&format!("<Contents><Key>{}</Key><Size>{}</Size></Contents>", key.replace('&', "&amp;"), data.len())),
+                        }
+                    }
+                    for dir in common {
+                        xml.push_str(&format!("<CommonPrefixes><Prefix>{}</Prefix></CommonPrefixes>", dir));
+                    }
+                    xml.push_str("</ListBucketResult>");
+                    (200, xml.into_bytes())
+                }
+                "GET" | "HEAD" => match objects.get(&key) {
+                    Some(data) => (200, data.clone()),
+                    None => (404, Vec::new()),
+                },
+                "DELETE" => {
+                    objects.remove(&key);
+                    (204, Vec::new())
+                }
+                _ => (405, Vec::new()),
+            }
+        };
+        let mut stream = stream;
+        write!(stream, "HTTP/1.1 {} X\r\nContent-Length: {}\r\nConnection: close\r\n\r\n", status, response.len())?;
+        if method != "HEAD" {
+            stream.write_all(&response)?;
+        }
+        stream.flush()
+    }
+
+    #[test]
+    fn test_s3_storage_against_stand_in() {
+        let address = spawn_s3_stand_in();
+        let storage = S3Storage::new(&format!("http://{}/kova/yedek", address), "us-east-1", "test", "gizli").unwrap();
+        exercise(&storage);
+        assert!(storage.local_root().is_none());
+
+        // Yanlış kimlik bilgisi reddedilir
+        let intruder = S3Storage::new(&format!("http://{}/kova", address), "us-east-1", "baska", "gizli").unwrap();
+        assert!(intruder.stat(Path::new("x")).is_err());
+        let error = intruder.list(Path::new("")).unwrap_err();
+        assert!(error.to_string().contains("HTTP 403 AccessDenied"));
+        assert!(S3Storage::new("https://s3.example.com/kova", "us-east-1", "a", "b").is_err());
+        assert!(S3Storage::new("http://s3.example.com", "us-east-1", "a", "b").is_err());
+    }
+}
@@ -1,27 +1,115 @@
+use std::collections::{HashMap, HashSet};
 use std::fs::{self, File};
-use std::io::{BufReader, Read};
+use std::io::{BufReader, Read, SeekFrom};
 use std::path::{Path, PathBuf};
-use std::net::TcpStream;
-use std::io::{Write};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
 use blake3;
-use aes::Aes256;
-use cipher::{block_padding::Pkcs7, BlockEncryptMut, KeyIvInit};
-use rand::Rng;
 use serde::{Serialize, Deserialize};
-use serde_json;
-use walkdir::WalkDir;
-use indicatif::{ProgressBar, ProgressStyle};
 use anyhow::{Result, Context};
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt, BufWriter};
+use tokio::sync::{mpsc, Mutex, Semaphore};
+use tokio::task::JoinSet;
+use crate::crypto::{self, access_key, random_nonce};
+use crate::error::{Error, ErrorCode};
+use crate::transport::{self, BoxRead, BoxWrite, Connector, Peer};
+use crate::tls::ClientTls;
+use crate::events::{EventKind, Events};
+use crate::protocol::{self, handshake_proof, verify_handshake_proof, Auth, Frame, Hello, Role, Welcome, PROTOCOL_VERSION};
+use crate::chunk::{format_size, ChunkSize, ChunkSizer};
+use crate::filter::{scan_entries, FilterOptions, SourceEntry, SourceKind};
+use crate::entry::{EntryHeader, EntryKind};
+use crate::metadata::FileMetadata;
+use crate::xattrs::{self, XattrFilter};
+use crate::sparse;
+use crate::bandwidth::{Bandwidth, RateLimiter, Schedule};
+use crate::compress::{is_precompressed, ChunkCompressor, Compression, CompressionStats, ZSTD};
 
+pub use crate::chunk::CHUNK_SIZE;
 
-pub const CHUNK_SIZE: usize = 4096; // 4 KB
+/// Varsayılan pencere: sunucudan yanıt beklenmeden aynı anda gönderilen dosya sayısı
+pub const DEFAULT_WINDOW: usize = 8;
 
-#[derive(Serialize, Deserialize, Debug)]
+/// Varsayılan paralel akış sayısı
+pub const DEFAULT_STREAMS: usize = 4;
+
+/// Birden fazla akış varken büyük dosyalar bu boyutta aralıklara bölünür
+const RANGE_SIZE: u64 = 8 * 1024 * 1024;
+
+/// Akış başına aralık işlerini okuyup şifreleyen işçi sayısı (QUIC dışında)
+const WORKERS_PER_STREAM: usize = 2;
+
+/// Ek akışların el sıkışması için en fazla bekleme
+const JOIN_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Yazıcı görevine giden çerçeve kuyruğunun derinliği
+const FRAME_QUEUE_DEPTH: usize = 64;
+
+/// Soket yazma tamponu
+const WRITE_BUFFER_SIZE: usize = 256 * 1024;
+
+/// Ağ okuma/yazma zaman aşımı
+const IO_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Gönderim bağlantı hatasıyla kesildiğinde sunucunun ret nedeninin
+/// gelmesi için beklenen süre
+const REJECTION_GRACE: Duration = Duration::from_secs(2);
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct FileHeader {
     pub file_name: String,
     pub file_size: u64,
     pub file_hash: String,
     pub relative_path: PathBuf,
+    /// İzinler, zamanlar ve sahiplik; sunucu ayarına göre uygulanır
+    #[serde(default)]
+    pub metadata: FileMetadata,
+}
+
+/// İstemci ayarları
+#[derive(Debug, Clone)]
+pub struct SyncOptions {
+    /// Sunucu onayı beklenmeden aynı anda işlenen en fazla dosya
+    pub window: usize,
+    /// İstenen paralel akış sayısı; sunucu daha azına izin verebilir
+    pub streams: usize,
+    /// Chunk boyutu tercihi; sunucu daha küçüğünü dayatabilir
+    pub chunk_size: ChunkSize,
+    /// Şifrelemeden önce uygulanacak sıkıştırma; sunucu reddedebilir
+    pub compression: Compression,
+    /// Tüm akışların toplam gönderim hızı sınırı
+    pub bwlimit: Bandwidth,
+    /// Saate göre `bwlimit` yerine geçen sınırlar
+    pub schedule: Option<Schedule>,
+    /// Dahil etme/hariç tutma desenleri, ignore dosyaları ve symlink politikası
+    pub filter: FilterOptions,
+    /// Aynı dosyaya bağlı hardlink'leri bir kez gönderip sunucuda bağla
+    pub hardlinks: bool,
+    /// Şifrelenip gönderilecek xattr ad alanları (varsayılan: hiçbiri)
+    pub xattrs: XattrFilter,
+    /// Verilirse bağlantı TLS 1.3 ile sarılır
+    pub tls: Option<ClientTls>,
+    /// Aktarım olaylarının iletildiği işleyici (varsayılan: yok)
+    pub events: Events,
+}
+
+impl Default for SyncOptions {
+    fn default() -> Self {
+        Self {
+            window: DEFAULT_WINDOW,
+            streams: DEFAULT_STREAMS,
+            chunk_size: ChunkSize::Auto,
+            compression: Compression::None,
+            bwlimit: Bandwidth::Unlimited,
+            schedule: None,
+            filter: FilterOptions::default(),
+            hardlinks: false,
+            xattrs: XattrFilter::default(),
+            tls: None,
+            events: Events::default(),
+        }
+    }
 }
 
 /// Bir dosyanın tamamının BLAKE3 hash'ini hesaplar.
@@ -29,7 +117,8 @@ pub fn calculate_file_hash(path: &Path) -> Result<String, std::io::Error> {
     let file = File::open(path)?;
     let mut reader = BufReader::new(file);
     let mut hasher = blake3::Hasher::new();
-    let mut buffer = [0; 4096];
+    // Çok büyük dosyalarda küçük okumaların maliyeti öne çıkar
+    let mut buffer = vec![0; 64 * 1024];
 
     loop {
         let bytes_read = reader.read(&mut buffer)?;
@@ -41,156 +130,960 @@ pub fn calculate_file_hash(path: &Path) -> Result<String, std::io::Error> {
     Ok(hasher.finalize().to_hex().to_string())
 }
 
-/// TCP üzerinden chunk'ı hedef IP'ye gönderir.
-pub fn send_chunk_to_server(stream: &mut TcpStream, chunk_data: &[u8], key: &[u8; 32], progress: &ProgressBar) -> Result<()> {
-    let (encrypted_chunk, iv) = encrypt_chunk(chunk_data, key);
-    let mut payload = Vec::new();
-    payload.extend_from_slice(&iv); // IV başa ekleniyor
-    payload.extend_from_slice(&encrypted_chunk); // Şifreli veri
+/// Chunk'ı şifreleyip `Chunk` çerçevesine çevirir.
+pub fn encode_chunk(id: u32, offset: u64, chunk_data: &[u8], key: &[u8; 32]) -> Result<Vec<u8>> {
+    seal_chunk(id, offset, false, chunk_data, key)
+}
 
-    // Şifreli chunk'ı gönder
-    stream.write_all(&payload)
-        .context("Chunk gönderilemedi")?;
-    
-    progress.inc(chunk_data.len() as u64);
-    Ok(())
+/// Chunk'ı şifreler; `compressed` ise veri zstd ile sıkıştırılmıştır.
+fn seal_chunk(id: u32, offset: u64, compressed: bool, chunk_data: &[u8], key: &[u8; 32]) -> Result<Vec<u8>> {
+    let data = crypto::seal(chunk_data, key);
+    Frame::Chunk { id, offset, compressed, data }.encode()
+        .context("Chunk çerçevesi oluşturulamadı")
+}
+
+/// Kaynak klasördeki tüm dosyaları hedef sunucuya gönderir; hata olursa
+/// yalnızca kaydeder.
+#[deprecated(note = "SyncClient::builder() kullanın; hata ve dosya sonuçları SyncReport ile döner")]
+pub async fn start_sync(source: &str, target: &str, key: &[u8; 32]) {
+    if let Err(e) = sync_files(source, target, key).await {
+        log::error!("[❌] Senkronizasyon hatası: {}", e);
+    }
+}
+
+/// Kaynak klasördeki tüm dosyaları hedef sunucuya gönderir.
+///
+/// [`start_sync`]'ten farklı olarak hatayı çağırana döndürür; sunucunun
+/// reddettiği dosya varsa da hata döner.
+pub async fn sync_files(source: &str, target: &str, key: &[u8; 32]) -> Result<(), Error> {
+    sync_files_with_options(source, target, key, &SyncOptions::default()).await
+}
+
+/// [`sync_files`] ile aynı, ancak ayarlar verilebilir. Hedefin biçimleri için
+/// bkz. [`transport::connector`].
+pub async fn sync_files_with_options(source: &str, target: &str, key: &[u8; 32], options: &SyncOptions) -> Result<(), Error> {
+    let connector = transport::connector(target, key, options.tls.as_ref())?;
+    sync_files_over(source, connector.as_ref(), key, options).await
+}
+
+/// Kaynak klasörü verilen bağlantı yolu üzerinden gönderir; reddedilen
+/// dosya varsa ilkinin türüyle hata döner.
+pub async fn sync_files_over(source: &str, connector: &dyn Connector, key: &[u8; 32], options: &SyncOptions) -> Result<(), Error> {
+    run_sync(Path::new(source), connector, key, options).await?
+        .ensure_success()
+}
+
+/// Bir senkronizasyonun özeti
+#[derive(Debug, Clone)]
+pub struct SyncReport {
+    /// Bağlanılan uç nokta
+    pub peer: Peer,
+    /// Sunucunun kendini tanıttığı ad
+    pub server_name: String,
+    /// Kullanılan paralel akış sayısı
+    pub streams: usize,
+    /// Son kullanılan chunk boyutu (otomatik modda ayarlanmış hali)
+    pub chunk_size: usize,
+    /// Sunucunun kabul ettiği zstd seviyesi
+    pub compression: Option<i32>,
+    /// Sıkıştırmadan önceki ve sonraki gönderilen veri
+    pub raw_bytes: u64,
+    pub sent_bytes: u64,
+    /// Okunmadan delik olarak bildirilen seyrek bölgeler
+    pub holes: u64,
+    /// Gönderim sırasıyla her dosya ve girdinin sonucu
+    pub files: Vec<FileReport>,
+    pub elapsed: Duration,
+}
+
+/// Tek bir dosyanın veya girdinin sonucu
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileReport {
+    /// Kaynak klasöre göre yol
+    pub path: PathBuf,
+    /// `None`: içeriğiyle gönderilen normal dosya
+    pub kind: Option<EntryKind>,
+    /// İçerik boyutu; girdiler için 0
+    pub size: u64,
+    pub outcome: FileOutcome,
+}
+
+/// Sunucunun bir dosya için verdiği karar
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileOutcome {
+    Sent,
+    /// Sunucunun ret nedeni
+    Rejected(Error),
+}
+
+impl SyncReport {
+    /// Başarıyla gönderilen dosya ve girdiler
+    pub fn sent(&self) -> impl Iterator<Item = &FileReport> {
+        self.files.iter().filter(|file| file.outcome == FileOutcome::Sent)
+    }
+
+    /// Sunucunun reddettiği dosya ve girdiler
+    pub fn rejected(&self) -> impl Iterator<Item = &FileReport> {
+        self.files.iter().filter(|file| file.outcome != FileOutcome::Sent)
+    }
+
+    pub fn is_success(&self) -> bool {
+        self.rejected().next().is_none()
+    }
+
+    /// Gönderilen dosyaların toplam içerik boyutu
+    pub fn bytes_sent(&self) -> u64 {
+        self.sent().map(|file| file.size).sum()
+    }
+
+    /// Reddedilen dosya varsa ilkinin türünde ve onu içeren bir hata döner
+    pub fn ensure_success(&self) -> Result<(), Error> {
+        let mut rejected = self.rejected();
+        if let Some(first) = rejected.next()
+            && let FileOutcome::Rejected(error) = &first.outcome
+        {
+            let message = format!("{} dosya sunucu tarafından reddedildi ({}: {})", rejected.count() + 1, first.path.display(), error);
+            return Err(Error::new(error.code(), message));
+        }
+        Ok(())
+    }
 }
 
-pub fn start_sync(source: &str, target: &str, key: &[u8; 32]) {
-    if let Err(e) = sync_files(source, target, key) {
-        eprintln!("[❌] Senkronizasyon hatası: {}", e);
+/// Kütüphane olarak senkronizasyon istemcisi; [`SyncClient::builder`] ile
+/// oluşturulur.
+///
+/// ```rust,no_run
+/// # async fn run() -> deltasafe::Result<()> {
+/// let client = deltasafe::SyncClient::builder()
+///     .source("./source_folder")
+///     .target("192.168.1.100:12345")
+///     .key([0u8; 32])
+///     .build()?;
+/// let report = client.sync().await?;
+/// for file in report.rejected() {
+///     eprintln!("{}: {:?}", file.path.display(), file.outcome);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct SyncClient {
+    source: PathBuf,
+    connector: Box<dyn Connector>,
+    key: [u8; 32],
+    options: SyncOptions,
+}
+
+/// [`SyncClient`] kurucusu
+#[derive(Default)]
+pub struct SyncClientBuilder {
+    source: Option<PathBuf>,
+    target: Option<String>,
+    connector: Option<Box<dyn Connector>>,
+    key: Option<[u8; 32]>,
+    options: SyncOptions,
+}
+
+impl SyncClient {
+    pub fn builder() -> SyncClientBuilder {
+        SyncClientBuilder::default()
+    }
+
+    /// Kaynak klasörü gönderir. Sunucunun reddettiği dosyalar hata değildir,
+    /// raporda [`FileOutcome::Rejected`] olarak döner; bağlantı veya tarama
+    /// hataları ise `Err` olur.
+    pub async fn sync(&self) -> Result<SyncReport, Error> {
+        Ok(run_sync(&self.source, self.connector.as_ref(), &self.key, &self.options).await?)
     }
 }
 
-fn sync_files(source: &str, target: &str, key: &[u8; 32]) -> Result<()> {
-    println!("[🔍] Kaynak klasör taranıyor: {}", source);
+impl SyncClientBuilder {
+    /// Gönderilecek klasör
+    pub fn source(mut self, source: impl Into<PathBuf>) -> Self {
+        self.source = Some(source.into());
+        self
+    }
+
+    /// Sunucu adresi; biçimler için bkz. [`transport::connector`]
+    pub fn target(mut self, target: impl Into<String>) -> Self {
+        self.target = Some(target.into());
+        self
+    }
+
+    /// Adres yerine hazır bir bağlantı yolu (örn. süreç içi boru)
+    pub fn connector(mut self, connector: Box<dyn Connector>) -> Self {
+        self.connector = Some(connector);
+        self
+    }
+
+    pub fn key(mut self, key: [u8; 32]) -> Self {
+        self.key = Some(key);
+        self
+    }
+
+    /// Ayarlar; önceden verilen olay işleyicisi korunmaz
+    pub fn options(mut self, options: SyncOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Aktarım olaylarını alacak işleyici; `options` ile verilen işleyicinin
+    /// yerine geçer
+    pub fn events(mut self, events: Events) -> Self {
+        self.options.events = events;
+        self
+    }
+
+    pub fn build(self) -> Result<SyncClient, Error> {
+        let source = self.source.ok_or_else(|| Error::Io("Kaynak klasör belirtilmedi".to_string()))?;
+        let key = self.key.ok_or_else(|| Error::Io("Anahtar belirtilmedi".to_string()))?;
+        let connector = match (self.connector, self.target) {
+            (Some(_), Some(_)) => return Err(Error::Io("Hedef adres ve bağlantı yolu birlikte verilemez".to_string())),
+            (Some(connector), None) => connector,
+            (None, Some(target)) => transport::connector(&target, &key, self.options.tls.as_ref())?,
+            (None, None) => return Err(Error::Io("Hedef sunucu belirtilmedi".to_string())),
+        };
+        Ok(SyncClient {
+            source,
+            connector,
+            key,
+            options: self.options,
+        })
+    }
+}
+
+/// Kaynak klasörü verilen bağlantı yolu üzerinden gönderir.
+///
+/// Dosyalar boru hattı şeklinde işlenir: pencere dolana kadar her dosya için
+/// ayrı bir görev hash hesaplar ve dosyayı aralık işlerine böler; her akışın
+/// işçileri bu işleri ortak kuyruktan alıp okur ve şifreler, akışın yazıcı
+/// görevi çerçeveleri sokete aktarır. Okuyucu görevler sunucunun dosya
+/// sonuçlarını toplayıp pencerede yer açar.
+async fn run_sync(path: &Path, connector: &dyn Connector, key: &[u8; 32], options: &SyncOptions) -> Result<SyncReport> {
+    let started = Instant::now();
+    log::info!("[🔍] Kaynak klasör taranıyor: {}", path.display());
 
-    let path = Path::new(source);
     if !path.exists() || !path.is_dir() {
-        anyhow::bail!("'{}' bir klasör değil veya bulunamadı.", source);
+        anyhow::bail!("'{}' bir klasör değil veya bulunamadı.", path.display());
     }
 
-    // Önce tüm dosyaları say ve toplam boyutu hesapla
+    // Önce süzgeçten geçen girdileri topla ve toplam boyutu hesapla
+    let mut skipped = Vec::new();
+    let plan = plan_entries(path, scan_entries(path, &options.filter, &mut skipped)?, options, key)?;
     let mut files = Vec::new();
-    let mut total_size = 0u64;
-    
-    for entry in WalkDir::new(path).into_iter().filter_map(|e| e.ok()) {
-        let file_path = entry.path();
-        if file_path.is_file() {
-            let metadata = fs::metadata(&file_path)
-                .context("Dosya metadata'sı okunamadı")?;
-            total_size += metadata.len();
-            files.push(file_path.to_path_buf());
-        }
-    }
-
-    println!("[📊] {} dosya bulundu, toplam boyut: {} bayt", files.len(), total_size);
-
-    // Progress bar oluştur
-    let progress = ProgressBar::new(total_size);
-    progress.set_style(
-        ProgressStyle::default_bar()
-            .template("[{elapsed_precise}] {bar:40.cyan/blue} {pos:>7}/{len:7} {msg}")
-            .unwrap()
-            .progress_chars("##-")
-    );
-
-    println!("[🔗] Sunucuya bağlanılıyor: {}", target);
-    let mut stream = TcpStream::connect(target)
-        .context("Sunucuya bağlanılamadı")?;
-    
-    println!("[📡] Bağlantı kuruldu: {}", target);
-
-    for file_path in files {
-        let file_name = file_path.file_name()
-            .and_then(|n| n.to_str())
-            .context("Geçersiz dosya adı")?
-            .to_string();
-        
-        let file_metadata = fs::metadata(&file_path)
+    for file_path in &plan.files {
+        let metadata = fs::metadata(file_path)
             .context("Dosya metadata'sı okunamadı")?;
-        let file_size = file_metadata.len();
-        
         let relative_path = file_path.strip_prefix(path)
             .context("Relative path hesaplanamadı")?
             .to_path_buf();
+        files.push(FileReport { path: relative_path, kind: None, size: metadata.len(), outcome: FileOutcome::Sent });
+    }
+    let total_size = files.iter().map(|file| file.size).sum::<u64>();
 
-        let file_hash = calculate_file_hash(&file_path)
-            .context("Dosya hash'i hesaplanamadı")?;
+    log::info!("[📊] {} dosya bulundu, toplam boyut: {} bayt", plan.files.len(), total_size);
+    if !plan.directories.is_empty() || !plan.links.is_empty() || !plan.hardlinks.is_empty() {
+        log::info!("[📁] {} klasör, {} sembolik bağlantı, {} hardlink", plan.directories.len(), plan.links.len(), plan.hardlinks.len());
+    }
 
-        progress.set_message(format!("Gönderiliyor: {}", file_name));
+    log::info!("[🔗] Sunucuya bağlanılıyor: {}", connector);
+    let streams = open_streams(connector, key, options).await?;
+    let peer = streams[0].peer.clone();
+    let server_name = streams[0].welcome.name.clone();
+    log::info!("[📡] Bağlantı kuruldu: {} ({})", peer, server_name);
+    let stream_count = streams.len();
+    if stream_count > 1 {
+        log::info!("[🔀] {} paralel akış kullanılıyor.", stream_count);
+    }
+    let events = &options.events;
+    events.emit(&peer, EventKind::SessionStarted { files: files.len(), bytes: total_size });
+    for (skipped_path, reason) in skipped {
+        let skipped_path = skipped_path.strip_prefix(path).map(Path::to_path_buf).unwrap_or(skipped_path);
+        events.emit(&peer, EventKind::FileSkipped { path: skipped_path, reason });
+    }
 
-        let header = FileHeader {
-            file_name: file_name.clone(),
-            file_size,
-            file_hash,
-            relative_path,
-        };
+    let sent = transfer(path, plan, &mut files, streams, connector.multiplexed(), key, options).await;
+    let failed = files.iter().filter(|file| file.outcome != FileOutcome::Sent).count();
+    let sent = match sent {
+        Ok(sent) => sent,
+        Err(e) => {
+            events.emit(&peer, EventKind::Error { path: None, message: format!("{:#}", e) });
+            return Err(e);
+        }
+    };
+    let bytes = files.iter().filter(|file| file.outcome == FileOutcome::Sent).map(|file| file.size).sum();
+    events.emit(&peer, EventKind::SessionFinished { verified: files.len() - failed, failed, bytes });
 
-        let serialized_header = serde_json::to_string(&header)
-            .context("Header serialize edilemedi")?;
-        let header_len = serialized_header.len() as u32;
+    Ok(SyncReport {
+        peer,
+        server_name,
+        streams: stream_count,
+        chunk_size: sent.chunk_size,
+        compression: sent.compression,
+        raw_bytes: sent.raw_bytes,
+        sent_bytes: sent.sent_bytes,
+        holes: sent.holes,
+        files,
+        elapsed: started.elapsed(),
+    })
+}
 
-        // Başlık gönder
-        stream.write_all(&header_len.to_be_bytes())
-            .context("Header uzunluğu gönderilemedi")?;
-        stream.write_all(serialized_header.as_bytes())
-            .context("Header gönderilemedi")?;
+/// Aktarımın rapora giren sayıları
+struct Transferred {
+    chunk_size: usize,
+    compression: Option<i32>,
+    raw_bytes: u64,
+    sent_bytes: u64,
+    holes: u64,
+}
 
-        // Sunucudan onay bekle
-        let mut response_buffer = [0; 1];
-        stream.read_exact(&mut response_buffer)
-            .context("Sunucudan yanıt alınamadı")?;
-        
-        if response_buffer[0] != 1 {
-            anyhow::bail!("Sunucudan onay alınamadı: {}", file_name);
+/// Planı açılmış akışlar üzerinden gönderir ve her girdinin sonucunu
+/// `files` içine yazar. `multiplexed` ise her akışı tek işçi kullanır.
+async fn transfer(path: &Path, plan: Plan, files: &mut Vec<FileReport>, streams: Vec<Stream>, multiplexed: bool, key: &[u8; 32], options: &SyncOptions) -> Result<Transferred> {
+    let peer = streams[0].peer.clone();
+    let events = &options.events;
+    let negotiated_chunk = match streams[0].welcome.chunk_size {
+        0 => CHUNK_SIZE,
+        size => size as usize,
+    };
+    if let ChunkSize::Fixed(size) = options.chunk_size
+        && negotiated_chunk < size
+    {
+        log::info!("[ℹ️] Sunucu chunk boyutunu {} ile sınırladı.", format_size(negotiated_chunk));
+    }
+    let sizer = Arc::new(ChunkSizer::new(options.chunk_size, negotiated_chunk));
+    // Sunucu kabul etmediyse sıkıştırmasız gönderilir
+    let compression = match options.compression {
+        Compression::Zstd(level) if streams[0].welcome.compression.as_deref() == Some(ZSTD) => {
+            log::info!("[🗜️] zstd sıkıştırma etkin (seviye {}).", level);
+            Some(level)
+        }
+        Compression::Zstd(_) => {
+            log::info!("[ℹ️] Sunucu sıkıştırmayı kabul etmedi; veri sıkıştırılmadan gönderilecek.");
+            None
         }
+        Compression::None => None,
+    };
+    let stats = Arc::new(CompressionStats::default());
+    let holes = Arc::new(AtomicU64::new(0));
+    let limiter = Arc::new(RateLimiter::with_schedule(options.bwlimit, options.schedule.clone()));
+    if !limiter.is_unlimited() {
+        let scheduled = if options.schedule.is_some() { " (zamanlama etkin)" } else { "" };
+        log::info!("[🐢] Bant genişliği sınırı: {}{}", limiter.current_limit(), scheduled);
+    }
+    // Tek akışta aralıklara bölmenin faydası yok
+    let range_size = if streams.len() > 1 { RANGE_SIZE } else { u64::MAX };
 
-        // Dosya içeriğini gönder
-        let file = File::open(&file_path)
-            .context("Dosya açılamadı")?;
-        let mut reader = BufReader::new(file);
-        let mut buffer = vec![0u8; CHUNK_SIZE];
+    if !options.xattrs.is_empty() {
+        log::info!("[🏷️] Genişletilmiş öznitelikler gönderiliyor: {}", options.xattrs);
+    }
+    let xattr_filter = Arc::new(options.xattrs.clone());
 
-        loop {
-            let bytes_read = reader.read(&mut buffer)
-                .context("Dosya okunamadı")?;
-            if bytes_read == 0 {
+    let window_size = options.window.max(1);
+    let window = Arc::new(Semaphore::new(window_size));
+    // Kimlikler gönderim sırasına göre: dosyalar, bağlantılar, hardlink'ler, klasörler
+    let file_count = plan.files.len();
+    let mut next_id = file_count as u32;
+    let mut number = |entries: Vec<EntryHeader>| -> Vec<(u32, EntryHeader)> {
+        entries.into_iter().map(|entry| {
+            next_id += 1;
+            (next_id - 1, entry)
+        }).collect()
+    };
+    let phases = [number(plan.links), number(plan.hardlinks), number(plan.directories)];
+    files.extend(phases.iter().flatten().map(|(_, entry)| {
+        FileReport { path: entry.relative_path.clone(), kind: Some(entry.kind.clone()), size: 0, outcome: FileOutcome::Sent }
+    }));
+    let reported = files.iter().map(|file| (file.path.clone(), file.size)).collect();
+
+    // Çoklanan bağlantıda akış bol; bir akışı paylaşan iki dosya birbirini bekletmesin
+    let workers_per_stream = if multiplexed { 1 } else { WORKERS_PER_STREAM };
+    let (jobs_tx, jobs_rx) = mpsc::channel::<Job>(streams.len() * workers_per_stream);
+    let jobs_rx = Arc::new(Mutex::new(jobs_rx));
+    let (results_tx, results_rx) = mpsc::unbounded_channel();
+    let mut writers = JoinSet::new();
+    let mut readers = JoinSet::new();
+    let mut workers = JoinSet::new();
+    for stream in streams {
+        let (frames_tx, frames_rx) = mpsc::channel::<Vec<u8>>(FRAME_QUEUE_DEPTH);
+        writers.spawn(write_frames(stream.writer, frames_rx, limiter.clone()));
+        readers.spawn(read_results(stream.reader, results_tx.clone()));
+
+        let announced = Arc::new(Mutex::new(HashSet::new()));
+        for _ in 0..workers_per_stream {
+            let worker = StreamWorker {
+                jobs: jobs_rx.clone(),
+                frames: frames_tx.clone(),
+                announced: announced.clone(),
+                key: *key,
+                events: events.clone(),
+                peer: peer.clone(),
+                sizer: sizer.clone(),
+                compression,
+                stats: stats.clone(),
+                holes: holes.clone(),
+            };
+            workers.spawn(worker.run());
+        }
+    }
+    drop(results_tx);
+    drop(jobs_rx);
+    let results_task = tokio::spawn(collect_results(results_rx, window.clone(), reported, events.clone(), peer.clone()));
+
+    // Gönderim aşaması; hata sunucunun oturumu kapatmasından kaynaklanıyorsa
+    // aşağıda sunucunun bildirdiği neden tercih edilir
+    let sent: Result<()> = async {
+        let mut hashers = JoinSet::new();
+        for (id, file_path) in plan.files.into_iter().enumerate() {
+            // Pencere doluysa bir dosyanın sonucu gelene kadar bekle. İzin,
+            // sonuç toplayıcı görev sonucu aldığında geri eklenir.
+            match window.acquire().await {
+                Ok(permit) => permit.forget(),
+                Err(_) => break, // Sonuç toplayıcı sonlandı; hata aşağıda raporlanır
+            }
+
+            let relative_path = file_path.strip_prefix(path)
+                .context("Relative path hesaplanamadı")?
+                .to_path_buf();
+            events.emit(&peer, EventKind::FileStarted { path: relative_path.clone(), size: files[id].size });
+            let jobs_tx = jobs_tx.clone();
+            hashers.spawn(prepare_file(id as u32, file_path, relative_path, range_size, xattr_filter.clone(), *key, jobs_tx));
+
+            // Biten görevlerin hatalarını erkenden yakala
+            while let Some(finished) = hashers.try_join_next() {
+                finished.context("Hash görevi çöktü")??;
+            }
+            while let Some(finished) = workers.try_join_next() {
+                finished.context("Gönderim görevi çöktü")??;
+            }
+        }
+        while let Some(finished) = hashers.join_next().await {
+            finished.context("Hash görevi çöktü")??;
+        }
+
+        // Sembolik bağlantılar dosyalarla birlikte gider. Hardlink'ler hedefleri,
+        // klasörler ise içerikleri onaylandıktan sonra gönderilir; böylece klasör
+        // izinleri ve zamanları en son uygulanır.
+        let [links, hardlinks, directories] = phases;
+        let mut open = send_entries(links, &window, &jobs_tx).await?;
+        for phase in [hardlinks, directories] {
+            if !open || phase.is_empty() {
+                continue;
+            }
+            // Tüm izinler geri döndüğünde önceki girdilerin hepsi onaylanmıştır
+            open = window.acquire_many(window_size as u32).await.is_ok()
+                && send_entries(phase, &window, &jobs_tx).await?;
+        }
+        drop(jobs_tx);
+        while let Some(finished) = workers.join_next().await {
+            finished.context("Gönderim görevi çöktü")??;
+        }
+        while let Some(finished) = writers.join_next().await {
+            finished.context("Yazıcı görev çöktü")??;
+        }
+        Ok(())
+    }.await;
+    if let Err(e) = sent {
+        let error = Error::from(e);
+        if error.code() == ErrorCode::Network
+            && let Ok(Ok(Err(rejected))) = tokio::time::timeout(REJECTION_GRACE, results_task).await
+            && rejected.chain().any(|cause| cause.is::<Error>())
+        {
+            return Err(rejected);
+        }
+        return Err(error.into());
+    }
+    let failures = results_task.await.context("Sonuç toplayıcı görev çöktü")??;
+    readers.abort_all();
+
+    for (id, error) in failures {
+        if let Some(file) = files.get_mut(id as usize) {
+            file.outcome = FileOutcome::Rejected(error);
+        }
+    }
+
+    let chunk_size = sizer.current();
+    if options.chunk_size == ChunkSize::Auto {
+        log::info!("[📏] Otomatik chunk boyutu: {}", format_size(chunk_size));
+    }
+    let holes = holes.load(Ordering::Relaxed);
+    if holes > 0 {
+        log::info!("[🕳️] Seyrek bölgeler: {} bayt okunmadan delik olarak bildirildi.", holes);
+    }
+    if compression.is_some() {
+        log::info!("[🗜️] Sıkıştırma: {} bayt → {} bayt (oran {:.2}x)", stats.raw(), stats.sent(), stats.ratio());
+    }
+    Ok(Transferred { chunk_size, compression, raw_bytes: stats.raw(), sent_bytes: stats.sent(), holes })
+}
+
+/// El sıkışması tamamlanmış, kimliği doğrulanmış bir akış
+pub struct Stream {
+    pub reader: BoxRead,
+    pub writer: BoxWrite,
+    pub peer: Peer,
+    pub welcome: Welcome,
+}
+
+/// Ana akışı açar, ardından sunucunun izin verdiği kadar ek akışı aynı
+/// oturuma bağlar. Ek akış açılamazsa açılabilenlerle devam edilir.
+/// Akışlar tek bağlantıda çoklanıyorsa (QUIC) pencerede bekleyen her dosyaya
+/// bir akış düşecek kadar akış istenir ve her akışı tek bir işçi kullanır;
+/// böylece gönderilen her dosya (veya aralık) kendi akışında ilerler.
+/// Akışlar dosya başına yeniden açılmaz, çünkü her yeni akış bir el sıkışma
+/// turu daha gerektirir; oturum boyunca yeniden kullanılırlar.
+async fn open_streams(connector: &dyn Connector, key: &[u8; 32], options: &SyncOptions) -> Result<Vec<Stream>> {
+    let requested = match connector.multiplexed() {
+        true => options.streams.max(options.window).max(1),
+        false => options.streams.max(1),
+    };
+    let primary = open_stream(connector, key, requested, options, None).await?;
+    let granted = primary.welcome.streams as usize;
+    if granted < requested {
+        log::info!("[ℹ️] Sunucu {} akış yerine {} akışa izin verdi.", requested, granted);
+    }
+
+    let session = primary.welcome.session.clone();
+    let mut streams = vec![primary];
+    for _ in 1..granted.min(requested) {
+        match tokio::time::timeout(JOIN_TIMEOUT, open_stream(connector, key, granted, options, Some(&session))).await {
+            Ok(Ok(stream)) => streams.push(stream),
+            Ok(Err(e)) => {
+                log::warn!("[⚠️] Ek akış açılamadı: {:#}", e);
+                break;
+            }
+            Err(_) => {
+                // Sunucunun oturum sınırı dolu olabilir
+                log::warn!("[⚠️] Ek akış zaman aşımına uğradı.");
                 break;
             }
-            let chunk_data = &buffer[..bytes_read];
-            send_chunk_to_server(&mut stream, chunk_data, key, &progress)
-                .context("Chunk gönderilemedi")?;
         }
     }
-    
-    progress.finish_with_message("Tüm dosyalar başarıyla gönderildi!");
-    println!("[🚀] Senkronizasyon tamamlandı.");
+    Ok(streams)
+}
+
+/// Tek bir akış açar ve karşılıklı kimlik doğrulamalı el sıkışmayı yapar
+pub async fn open_stream(connector: &dyn Connector, key: &[u8; 32], streams: usize, options: &SyncOptions, join: Option<&str>) -> Result<Stream> {
+    let transport::Connection { mut reader, mut writer, peer } = transport::connect(connector).await?;
+
+    let nonce = random_nonce();
+    let hello = Hello {
+        protocol: PROTOCOL_VERSION,
+        streams: streams as u32,
+        join: join.map(str::to_string),
+        nonce: nonce.clone(),
+        chunk_size: options.chunk_size.requested() as u32,
+        compression: options.compression.algorithm().map(str::to_string),
+        probe: false,
+    };
+    let welcome = greet(&mut reader, &mut writer, key, hello).await?;
+
+    let access = access_key(key);
+    let auth = Frame::Auth(Auth {
+        proof: handshake_proof(&access, Role::Client, &nonce, &welcome.nonce, &welcome.session),
+    });
+    tokio::time::timeout(IO_TIMEOUT, protocol::write_frame(&mut writer, &auth)).await
+        .context("El sıkışma zaman aşımına uğradı")?
+        .context("El sıkışma gönderilemedi")?;
+
+    Ok(Stream { reader, writer, peer, welcome })
+}
+
+/// Oturum açmadan sunucunun anahtarı bildiğini sınar (keşifte kullanılır).
+/// Kanıt uyuşmazsa `Error::Auth` döner.
+pub async fn probe_server(connection: transport::Connection, key: &[u8; 32]) -> Result<()> {
+    let transport::Connection { mut reader, mut writer, .. } = connection;
+    let hello = Hello {
+        protocol: PROTOCOL_VERSION,
+        streams: 1,
+        join: None,
+        nonce: random_nonce(),
+        chunk_size: 0,
+        compression: None,
+        probe: true,
+    };
+    greet(&mut reader, &mut writer, key, hello).await?;
     Ok(())
 }
 
-// Şifreleme ve çözme için yeni CBC tipleri
-// Encryptor ve Decryptor ayrı ayrı
+/// `Hello` gönderir ve yanıttaki sunucu kanıtını doğrular
+async fn greet(reader: &mut BoxRead, writer: &mut BoxWrite, key: &[u8; 32], hello: Hello) -> Result<Welcome> {
+    let nonce = hello.nonce.clone();
+    tokio::time::timeout(IO_TIMEOUT, protocol::write_frame(writer, &Frame::Hello(hello))).await
+        .context("El sıkışma zaman aşımına uğradı")?
+        .context("El sıkışma gönderilemedi")?;
+
+    let reply = tokio::time::timeout(IO_TIMEOUT, protocol::read_frame(reader)).await
+        .context("Sunucu el sıkışmaya yanıt vermedi")?
+        .context("El sıkışma yanıtı okunamadı")?;
+    let welcome = match reply {
+        Some(Frame::Welcome(welcome)) if protocol::is_compatible(welcome.protocol) => welcome,
+        Some(Frame::Welcome(welcome)) => anyhow::bail!(Error::Protocol(format!("Sunucu protokol sürümü uyumsuz: {}", welcome.protocol))),
+        Some(Frame::Error(error)) => return Err(anyhow::Error::new(error).context("Sunucu bağlantıyı reddetti")),
+        Some(other) => anyhow::bail!(Error::Protocol(format!("Beklenmeyen el sıkışma yanıtı: {:?}", other))),
+        None => anyhow::bail!(Error::Network("Sunucu el sıkışma sırasında bağlantıyı kapattı".to_string())),
+    };
+
+    // Sunucu anahtarı bildiğini kanıtlamadan veri gönderilmez
+    if !verify_handshake_proof(&access_key(key), Role::Server, &nonce, &welcome.nonce, &welcome.session, &welcome.proof) {
+        anyhow::bail!(Error::Auth("Sunucunun kimliği doğrulanamadı (anahtar uyuşmuyor olabilir)".to_string()));
+    }
+    Ok(welcome)
+}
+
+/// Taramanın gönderim planı
+struct Plan {
+    /// İçeriği gönderilecek dosyalar
+    files: Vec<PathBuf>,
+    links: Vec<EntryHeader>,
+    hardlinks: Vec<EntryHeader>,
+    directories: Vec<EntryHeader>,
+}
+
+/// Taranan girdileri gönderim sırasına ayırır. `hardlinks` açıksa aynı
+/// dosyaya bağlı yolların yalnızca ilki içerikle gönderilir.
+fn plan_entries(root: &Path, entries: Vec<SourceEntry>, options: &SyncOptions, key: &[u8; 32]) -> Result<Plan> {
+    let mut plan = Plan { files: Vec::new(), links: Vec::new(), hardlinks: Vec::new(), directories: Vec::new() };
+    let mut groups: HashMap<(u64, u64), PathBuf> = HashMap::new();
+    for entry in entries {
+        let relative_path = entry.path.strip_prefix(root)
+            .context("Relative path hesaplanamadı")?
+            .to_path_buf();
+        match entry.kind {
+            SourceKind::File => {
+                if options.hardlinks && let Some(inode) = hardlink_inode(&entry.path) {
+                    if let Some(target) = groups.get(&inode) {
+                        let kind = EntryKind::Hardlink { target: target.clone() };
+                        plan.hardlinks.push(EntryHeader { relative_path, kind, metadata: FileMetadata::default() });
+                        continue;
+                    }
+                    groups.insert(inode, relative_path);
+                }
+                plan.files.push(entry.path);
+            }
+            SourceKind::Symlink(target) => {
+                let metadata = fs::symlink_metadata(&entry.path)
+                    .map(|metadata| FileMetadata::from_fs(&metadata))
+                    .unwrap_or_default();
+                plan.links.push(EntryHeader { relative_path, kind: EntryKind::Symlink { target }, metadata });
+            }
+            SourceKind::Directory => {
+                let mut metadata = fs::metadata(&entry.path)
+                    .map(|metadata| FileMetadata::from_fs(&metadata))
+                    .with_context(|| format!("Klasör metadata'sı okunamadı: {}", entry.path.display()))?;
+                metadata.xattrs = sealed_xattrs(&entry.path, &options.xattrs, key);
+                plan.directories.push(EntryHeader { relative_path, kind: EntryKind::Directory, metadata });
+            }
+        }
+    }
+    // İç klasörler üst klasörlerden önce; üst klasörün zamanı son uygulanır
+    plan.directories.reverse();
+    Ok(plan)
+}
+
+/// Seçilen xattr'ları okuyup şifreler. Okunamazsa uyarır ve `None` döner;
+/// sunucu bu durumda dosyanın özniteliklerine dokunmaz.
+fn sealed_xattrs(path: &Path, filter: &XattrFilter, key: &[u8; 32]) -> Option<String> {
+    if filter.is_empty() {
+        return None;
+    }
+    match xattrs::read(path, filter) {
+        Ok(attrs) => Some(xattrs::seal(&attrs, key)),
+        Err(e) => {
+            log::warn!("[⚠️] '{}' öznitelikleri okunamadı: {}", path.display(), e);
+            None
+        }
+    }
+}
 
-type Aes256CbcEnc = cbc::Encryptor<Aes256>;
+/// Birden fazla bağlantısı olan dosyanın (aygıt, inode) çifti
+#[cfg(unix)]
+fn hardlink_inode(path: &Path) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    let metadata = fs::metadata(path).ok()?;
+    (metadata.nlink() > 1).then(|| (metadata.dev(), metadata.ino()))
+}
 
+#[cfg(not(unix))]
+fn hardlink_inode(_path: &Path) -> Option<(u64, u64)> {
+    None
+}
 
+/// Girdileri pencereye uyarak kuyruğa koyar; sonuç toplayıcı sonlandıysa
+/// `false` döner
+async fn send_entries(entries: Vec<(u32, EntryHeader)>, window: &Semaphore, jobs: &mpsc::Sender<Job>) -> Result<bool> {
+    for (id, entry) in entries {
+        match window.acquire().await {
+            Ok(permit) => permit.forget(),
+            Err(_) => return Ok(false),
+        }
+        jobs.send(Job::Entry { id, entry }).await
+            .map_err(|_| Error::Network("Bağlantı kapandı".to_string()))?;
+    }
+    Ok(true)
+}
 
+/// İşçilerin kuyruktan aldığı iş
+enum Job {
+    Range(RangeJob),
+    /// İçeriği olmayan girdi; tek çerçeve olarak gider
+    Entry { id: u32, entry: EntryHeader },
+}
 
-fn encrypt_chunk(chunk: &[u8], key: &[u8; 32]) -> (Vec<u8>, Vec<u8>) {
-    let mut iv = [0u8; 16];
-    rand::thread_rng().fill(&mut iv);
-    
-    // Buffer'ı padding için yeterli boyutta oluştur
-    let mut buf = chunk.to_vec();
-    // AES block size (16 byte) için padding alanı ekle
-    buf.resize(chunk.len() + 16, 0);
-    
-    let cipher = Aes256CbcEnc::new(key.into(), &iv.into());
-    let ciphertext = cipher.encrypt_padded_mut::<Pkcs7>(&mut buf, chunk.len())
-        .expect("Şifreleme hatası")
-        .to_vec();
-    (ciphertext, iv.to_vec())
+/// Bir dosyanın bir akış üzerinden gönderilecek aralığı
+struct RangeJob {
+    id: u32,
+    path: Arc<PathBuf>,
+    header: Arc<FileHeader>,
+    offset: u64,
+    len: u64,
 }
 
+/// Dosyanın hash'ini hesaplar ve dosyayı aralık işlerine bölüp kuyruğa koyar.
+/// Seçilen xattr'lar aynı anahtarla şifrelenip başlığa eklenir.
+async fn prepare_file(id: u32, file_path: PathBuf, relative_path: PathBuf, range_size: u64, xattr_filter: Arc<XattrFilter>, key: [u8; 32], jobs: mpsc::Sender<Job>) -> Result<()> {
+    let file_name = file_path.file_name()
+        .and_then(|n| n.to_str())
+        .context("Geçersiz dosya adı")?
+        .to_string();
+
+    let hash_path = file_path.clone();
+    let (file_hash, file_size, metadata) = tokio::task::spawn_blocking(move || {
+        let metadata = fs::metadata(&hash_path)?;
+        let mut file_metadata = FileMetadata::from_fs(&metadata);
+        file_metadata.xattrs = sealed_xattrs(&hash_path, &xattr_filter, &key);
+        calculate_file_hash(&hash_path).map(|hash| (hash, metadata.len(), file_metadata))
+    }).await
+        .context("Hash görevi çöktü")?
+        .with_context(|| format!("Dosya hash'i hesaplanamadı: {}", file_path.display()))?;
+
+    let header = Arc::new(FileHeader {
+        file_name,
+        file_size,
+        file_hash,
+        relative_path,
+        metadata,
+    });
+    let path = Arc::new(file_path);
 
+    // Boş dosya da tek bir (boş) iş olarak gönderilir
+    let mut offset = 0;
+    loop {
+        let len = range_size.min(file_size - offset);
+        let job = RangeJob { id, path: path.clone(), header: header.clone(), offset, len };
+        jobs.send(Job::Range(job)).await
+            .map_err(|_| Error::Network("Bağlantı kapandı".to_string()))?;
+        offset += len;
+        if offset >= file_size {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// Kuyruktan aralık işleri alıp tek bir akışa gönderen işçi
+struct StreamWorker {
+    jobs: Arc<Mutex<mpsc::Receiver<Job>>>,
+    frames: mpsc::Sender<Vec<u8>>,
+    /// Bu akışta başlığı gönderilmiş dosyalar
+    announced: Arc<Mutex<HashSet<u32>>>,
+    key: [u8; 32],
+    events: Events,
+    /// Olaylarda bildirilen sunucu
+    peer: Peer,
+    sizer: Arc<ChunkSizer>,
+    /// Pazarlık edilen zstd seviyesi; `None` ise sıkıştırma yok
+    compression: Option<i32>,
+    stats: Arc<CompressionStats>,
+    /// Delik olarak bildirilen bayt sayısı
+    holes: Arc<AtomicU64>,
+}
+
+impl StreamWorker {
+    async fn run(self) -> Result<()> {
+        loop {
+            let job = self.jobs.lock().await.recv().await;
+            match job {
+                Some(Job::Range(job)) => self.send_range(&job).await?,
+                Some(Job::Entry { id, entry }) => {
+                    let entry_frame = Frame::Entry { id, entry }.encode()
+                        .context("Girdi serialize edilemedi")?;
+                    self.frames.send(entry_frame).await
+                        .map_err(|_| Error::Network("Bağlantı kapandı".to_string()))?;
+                }
+                None => break,
+            }
+        }
+        Ok(())
+    }
+
+    async fn send_range(&self, job: &RangeJob) -> Result<()> {
+        // Başlık, aynı akıştaki chunk'lardan önce gitmeli; kilit gönderim
+        // bitene kadar tutulur
+        {
+            let mut announced = self.announced.lock().await;
+            if announced.insert(job.id) {
+                let header = FileHeader::clone(&job.header);
+                let header_frame = Frame::File { id: job.id, header }.encode()
+                    .context("Header serialize edilemedi")?;
+                self.frames.send(header_frame).await
+                    .map_err(|_| Error::Network("Bağlantı kapandı".to_string()))?;
+            }
+        }
+        if job.len == 0 {
+            return Ok(());
+        }
+
+        // Yalnızca veri içeren bölgeler okunur; aradaki delikler tek
+        // çerçeveyle bildirilir
+        let end = job.offset + job.len;
+        let path = job.path.clone();
+        let start = job.offset;
+        let segments = tokio::task::spawn_blocking(move || sparse::data_segments(&path, start, end)).await
+            .context("Delik tarama görevi çöktü")?
+            .with_context(|| format!("Dosya açılamadı: {}", job.path.display()))?;
+
+        let mut file = tokio::fs::File::open(job.path.as_ref()).await
+            .with_context(|| format!("Dosya açılamadı: {}", job.path.display()))?;
+
+        // Zaten sıkıştırılmış dosya türleri olduğu gibi gönderilir
+        let level = self.compression.filter(|_| !is_precompressed(&job.path));
+        let mut compressor = ChunkCompressor::new(level);
+
+        let mut position = job.offset;
+        for (data_start, data_end) in segments.into_iter().chain([(end, end)]) {
+            if data_start > position {
+                self.send_hole(job, position, data_start - position).await?;
+            }
+            if data_end > data_start {
+                self.send_data(job, &mut file, &mut compressor, data_start, data_end).await?;
+            }
+            position = data_end;
+        }
+        Ok(())
+    }
+
+    /// `[start, end)` bölgesini chunk'lar halinde gönderir; chunk boyutu
+    /// otomatik modda değişebilir
+    async fn send_data(&self, job: &RangeJob, file: &mut tokio::fs::File, compressor: &mut ChunkCompressor, start: u64, end: u64) -> Result<()> {
+        file.seek(SeekFrom::Start(start)).await
+            .context("Dosyada konumlanılamadı")?;
+
+        let mut buffer = Vec::new();
+        let mut offset = start;
+        while offset < end {
+            let chunk_size = self.sizer.current();
+            if buffer.len() != chunk_size {
+                buffer.resize(chunk_size, 0);
+            }
+            // 32 bit platformlarda da taşmaması için u64 üzerinden kırpılır
+            let wanted = (chunk_size as u64).min(end - offset) as usize;
+            let bytes_read = file.read(&mut buffer[..wanted]).await
+                .context("Dosya okunamadı")?;
+            if bytes_read == 0 {
+                anyhow::bail!("Dosya gönderim sırasında küçüldü: {}", job.path.display());
+            }
+            let raw = &buffer[..bytes_read];
+            let compressed = compressor.compress(raw);
+            let payload = compressed.as_deref().unwrap_or(raw);
+            self.stats.record(bytes_read, payload.len());
+            let chunk_frame = seal_chunk(job.id, offset, compressed.is_some(), payload, &self.key)?;
+            self.frames.send(chunk_frame).await
+                .map_err(|_| Error::Network("Bağlantı kapandı".to_string()))?;
+            offset += bytes_read as u64;
+            self.sizer.record(bytes_read);
+            self.events.emit(&self.peer, EventKind::ChunkSent { path: job.header.relative_path.clone(), offset: offset - bytes_read as u64, bytes: bytes_read as u64 });
+        }
+        Ok(())
+    }
+
+    async fn send_hole(&self, job: &RangeJob, offset: u64, len: u64) -> Result<()> {
+        let hole_frame = Frame::Hole { id: job.id, offset, len }.encode()
+            .context("Delik çerçevesi oluşturulamadı")?;
+        self.frames.send(hole_frame).await
+            .map_err(|_| Error::Network("Bağlantı kapandı".to_string()))?;
+        self.holes.fetch_add(len, Ordering::Relaxed);
+        self.events.emit(&self.peer, EventKind::ChunkSent { path: job.header.relative_path.clone(), offset, bytes: len });
+        Ok(())
+    }
+}
+
+/// Kuyruktaki çerçeveleri sokete yazar; kuyruk boşaldığında tamponu boşaltır.
+/// Bant genişliği sınırı her çerçeveden önce uygulanır.
+async fn write_frames(writer: BoxWrite, mut frames: mpsc::Receiver<Vec<u8>>, limiter: Arc<RateLimiter>) -> Result<()> {
+    let mut writer = BufWriter::with_capacity(WRITE_BUFFER_SIZE, writer);
+    while let Some(frame) = frames.recv().await {
+        limiter.acquire(frame.len()).await;
+        tokio::time::timeout(IO_TIMEOUT, writer.write_all(&frame)).await
+            .context("Sunucuya yazma zaman aşımına uğradı")?
+            .context("Chunk gönderilemedi")?;
+        if frames.is_empty() {
+            tokio::time::timeout(IO_TIMEOUT, writer.flush()).await
+                .context("Sunucuya yazma zaman aşımına uğradı")?
+                .context("Veri gönderilemedi")?;
+        }
+    }
+    writer.flush().await
+        .context("Veri gönderilemedi")?;
+    // Gönderimin bittiğini sunucuya bildir
+    writer.shutdown().await
+        .context("Bağlantı kapatılamadı")?;
+    Ok(())
+}
+
+/// Sunucudan gelen dosya sonucu: kimlik ve ret nedeni
+type FileResult = Result<(u32, Result<(), Error>)>;
+
+/// Bir akıştan gelen dosya sonuçlarını toplayıcıya iletir
+async fn read_results(mut reader: BoxRead, results: mpsc::UnboundedSender<FileResult>) {
+    loop {
+        let result = match protocol::read_frame(&mut reader).await {
+            Ok(Some(Frame::FileResult { id, result })) => Ok((id, result)),
+            Ok(Some(Frame::Error(error))) => Err(anyhow::Error::new(error).context("Sunucu hatası")),
+            Ok(Some(other)) => Err(Error::Protocol(format!("Beklenmeyen sunucu mesajı: {:?}", other)).into()),
+            Ok(None) => break,
+            Err(e) => Err(anyhow::Error::new(e).context("Sunucudan yanıt alınamadı")),
+        };
+        let failed = result.is_err();
+        if results.send(result).is_err() || failed {
+            break;
+        }
+    }
+}
+
+/// Tüm akışlardan gelen dosya sonuçlarını toplar, her sonuçta pencereye bir
+/// yer açar ve olayını bildirir. `files` kimlik sırasıyla yol ve boyutlardır.
+/// Başarısız dosyaların kimlik ve ret nedenlerini döndürür.
+async fn collect_results(mut results: mpsc::UnboundedReceiver<FileResult>, window: Arc<Semaphore>, files: Vec<(PathBuf, u64)>, events: Events, peer: Peer) -> Result<Vec<(u32, Error)>> {
+    // Toplayıcı hangi yoldan çıkarsa çıksın bekleyen gönderimleri uyandır
+    struct CloseOnDrop(Arc<Semaphore>);
+    impl Drop for CloseOnDrop {
+        fn drop(&mut self) {
+            self.0.close();
+        }
+    }
+    let _close = CloseOnDrop(window.clone());
+
+    let expected = files.len();
+    let mut failures = Vec::new();
+    let mut received = 0;
+    while received < expected {
+        match results.recv().await {
+            Some(result) => {
+                let (id, result) = result?;
+                received += 1;
+                if let Some((path, size)) = files.get(id as usize) {
+                    let kind = match &result {
+                        Ok(()) => EventKind::FileVerified { path: path.clone(), size: *size },
+                        Err(error) => EventKind::Error { path: Some(path.clone()), message: error.to_string() },
+                    };
+                    events.emit(&peer, kind);
+                }
+                if let Err(error) = result {
+                    failures.push((id, error));
+                }
+                window.add_permits(1);
+            }
+            None => anyhow::bail!(Error::Network(format!("Sunucu bağlantıyı kapattı ({}/{} dosya onaylandı)", received, expected))),
+        }
+    }
+    Ok(failures)
+}
@@ -0,0 +1,427 @@
+//! TLS 1.3 katmanı
+//!
+//! Kurumsal kurulumlar için bağlantı, parola tabanlı el sıkışmadan bağımsız
+//! olarak rustls ile sarılabilir. Sunucu kendi sertifikasını sunar; istemci
+//! onu bir CA ile (`--ca`), sertifikanın SHA-256 parmak iziyle (`--pin`) ya da
+//! ikisiyle birden doğrular. Sunucuya bir istemci CA'sı verilirse karşılıklı
+//! TLS zorunlu olur ve yalnızca bu CA'nın imzaladığı sertifikaya sahip
+//! istemciler oturum açabilir.
+//!
+//! TLS, [`Transport`](crate::transport::Transport) soyutlamasının üstünde
+//! çalışır; TCP, Unix soketi veya stdio bağlantısı aynı şekilde sarılır.
+
+use std::fmt;
+use std::future::Future;
+use std::io;
+use std::path::Path;
+use std::pin::Pin;
+use std::sync::Arc;
+use anyhow::{Context, Result};
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::client::WebPkiServerVerifier;
+use rustls::crypto::CryptoProvider;
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName, UnixTime};
+use rustls::server::WebPkiClientVerifier;
+use rustls::{DigitallySignedStruct, RootCertStore, SignatureScheme};
+use sha2::{Digest, Sha256};
+use tokio_rustls::{TlsAcceptor, TlsConnector};
+use crate::transport::{Connection, Connector};
+
+/// Pin değerlerinin öneki
+const PIN_PREFIX: &str = "sha256:";
+
+fn provider() -> Arc<CryptoProvider> {
+    Arc::new(rustls::crypto::ring::default_provider())
+}
+
+fn load_certificates(path: &Path) -> Result<Vec<CertificateDer<'static>>> {
+    let certificates = CertificateDer::pem_file_iter(path)
+        .and_then(|iter| iter.collect::<Result<Vec<_>, _>>())
+        .map_err(|e| anyhow::anyhow!("{:?}", e))
+        .with_context(|| format!("Sertifika okunamadı: {}", path.display()))?;
+    if certificates.is_empty() {
+        anyhow::bail!("Dosyada sertifika bulunamadı: {}", path.display());
+    }
+    Ok(certificates)
+}
+
+fn load_private_key(path: &Path) -> Result<PrivateKeyDer<'static>> {
+    PrivateKeyDer::from_pem_file(path)
+        .map_err(|e| anyhow::anyhow!("{:?}", e))
+        .with_context(|| format!("Özel anahtar okunamadı: {}", path.display()))
+}
+
+fn load_roots(path: &Path) -> Result<RootCertStore> {
+    let mut roots = RootCertStore::empty();
+    for certificate in load_certificates(path)? {
+        roots.add(certificate)
+            .with_context(|| format!("CA sertifikası geçersiz: {}", path.display()))?;
+    }
+    Ok(roots)
+}
+
+/// Sertifikanın `sha256:<hex>` biçimindeki parmak izi
+pub fn certificate_pin(certificate: &[u8]) -> String {
+    format!("{}{}", PIN_PREFIX, hex::encode(Sha256::digest(certificate)))
+}
+
+/// `sha256:<hex>` ya da `openssl x509 -fingerprint -sha256` çıktısındaki gibi
+/// iki nokta ile ayrılmış hex parmak izini çözer
+pub fn parse_pin(pin: &str) -> Result<[u8; 32]> {
+    let trimmed = pin.trim();
+    let digits: String = trimmed.strip_prefix(PIN_PREFIX)
+        .unwrap_or(trimmed)
+        .chars()
+        .filter(|c| *c != ':')
+        .collect();
+    let bytes = hex::decode(&digits)
+        .ok()
+        .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+        .with_context(|| format!("Geçersiz sertifika parmak izi: {} (64 hex karakter bekleniyor)", pin))?;
+    Ok(bytes)
+}
+
+/// Sunucu tarafı TLS ayarları
+#[derive(Clone)]
+pub struct ServerTls {
+    config: Arc<rustls::ServerConfig>,
+    pin: String,
+    mutual: bool,
+}
+
+impl ServerTls {
+    /// PEM sertifika zinciri ve özel anahtarla sunucu yapılandırması kurar.
+    /// `client_ca` verilirse istemcilerin bu CA'nın imzaladığı bir sertifika
+    /// sunması zorunludur.
+    pub fn new(cert: &Path, key: &Path, client_ca: Option<&Path>) -> Result<Self> {
+        let chain = load_certificates(cert)?;
+        let pin = certificate_pin(&chain[0]);
+        let builder = rustls::ServerConfig::builder_with_provider(provider())
+            .with_protocol_versions(&[&rustls::version::TLS13])
+            .context("TLS 1.3 desteklenmiyor")?;
+        let builder = match client_ca {
+            Some(path) => {
+                let verifier = WebPkiClientVerifier::builder_with_provider(Arc::new(load_roots(path)?), provider())
+                    .build()
+                    .context("İstemci sertifika doğrulayıcısı kurulamadı")?;
+                builder.with_client_cert_verifier(verifier)
+            }
+            None => builder.with_no_client_auth(),
+        };
+        let config = builder.with_single_cert(chain, load_private_key(key)?)
+            .context("Sertifika ve özel anahtar eşleşmiyor")?;
+        Ok(ServerTls { config: Arc::new(config), pin, mutual: client_ca.is_some() })
+    }
+
+    /// Sunucu sertifikasının parmak izi; istemcide `--pin` ile kullanılır
+    pub fn pin(&self) -> &str {
+        &self.pin
+    }
+
+    /// İstemci sertifikası zorunlu mu
+    pub fn is_mutual(&self) -> bool {
+        self.mutual
+    }
+
+    /// Bağlantı üzerinde TLS el sıkışmasını sunucu olarak yapar
+    pub async fn accept(&self, connection: Connection) -> io::Result<Connection> {
+        let Connection { reader, writer, peer } = connection;
+        let stream = TlsAcceptor::from(self.config.clone())
+            .accept(tokio::io::join(reader, writer))
+            .await?;
+        let (reader, writer) = tokio::io::split(stream);
+        Ok(Connection { reader: Box::new(reader), writer: Box::new(writer), peer })
+    }
+}
+
+impl fmt::Debug for ServerTls {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ServerTls")
+            .field("pin", &self.pin)
+            .field("mutual", &self.mutual)
+            .finish()
+    }
+}
+
+/// İstemci tarafı TLS ayarları
+#[derive(Clone)]
+pub struct ClientTls {
+    config: Arc<rustls::ClientConfig>,
+    server_name: Option<String>,
+}
+
+impl ClientTls {
+    /// Sunucu sertifikası `ca` ile, `pin` ile ya da ikisiyle birden
+    /// doğrulanır; en az biri gereklidir. `identity` (sertifika, anahtar)
+    /// karşılıklı TLS isteyen sunuculara sunulur.
+    pub fn new(ca: Option<&Path>, pin: Option<&str>, identity: Option<(&Path, &Path)>) -> Result<Self> {
+        if ca.is_none() && pin.is_none() {
+            anyhow::bail!("TLS için sunucu sertifikası --ca veya --pin ile doğrulanmalı");
+        }
+        let provider = provider();
+        let webpki = match ca {
+            Some(path) => Some(
+                WebPkiServerVerifier::builder_with_provider(Arc::new(load_roots(path)?), provider.clone())
+                    .build()
+                    .context("Sunucu sertifika doğrulayıcısı kurulamadı")?,
+            ),
+            None => None,
+        };
+        let pin = pin.map(parse_pin).transpose()?;
+        let verifier = CertificateCheck { webpki, pin, provider: provider.clone() };
+        let builder = rustls::ClientConfig::builder_with_provider(provider)
+            .with_protocol_versions(&[&rustls::version::TLS13])
+            .context("TLS 1.3 desteklenmiyor")?
+            .dangerous()
+            .with_custom_certificate_verifier(Arc::new(verifier));
+        let config = match identity {
+            Some((cert, key)) => builder.with_client_auth_cert(load_certificates(cert)?, load_private_key(key)?)
+                .context("İstemci sertifikası ve özel anahtarı eşleşmiyor")?,
+            None => builder.with_no_client_auth(),
+        };
+        Ok(ClientTls { config: Arc::new(config), server_name: None })
+    }
+
+    /// Sertifikada aranacak sunucu adı; verilmezse hedefin host kısmı kullanılır
+    pub fn with_server_name(mut self, name: impl Into<String>) -> Self {
+        self.server_name = Some(name.into());
+        self
+    }
+
+    /// Bağlantı üzerinde TLS el sıkışmasını istemci olarak yapar
+    pub async fn connect(&self, connection: Connection, server_name: &str) -> io::Result<Connection> {
+        let name = ServerName::try_from(server_name.to_string())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, format!("Geçersiz sunucu adı: {}", server_name)))?;
+        let Connection { reader, writer, peer } = connection;
+        let stream = TlsConnector::from(self.config.clone())
+            .connect(name, tokio::io::join(reader, writer))
+            .await?;
+        let (reader, writer) = tokio::io::split(stream);
+        Ok(Connection { reader: Box::new(reader), writer: Box::new(writer), peer })
+    }
+
+    /// Verilen bağlayıcının açtığı her bağlantıyı TLS ile saran bağlayıcı
+    pub fn wrap(&self, inner: Box<dyn Connector>, target: &str) -> TlsConnect {
+        let server_name = self.server_name.clone().unwrap_or_else(|| host_of(target));
+        TlsConnect { inner, tls: self.clone(), server_name }
+    }
+}
+
+impl fmt::Debug for ClientTls {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ClientTls")
+            .field("server_name", &self.server_name)
+            .finish()
+    }
+}
+
+/// `host:port`, `[v6]:port` veya `ssh://kullanıcı@host` hedefinin host kısmı
+fn host_of(target: &str) -> String {
+    let rest = target.split_once("://").map_or(target, |(_, rest)| rest);
+    let rest = rest.rsplit_once('@').map_or(rest, |(_, host)| host);
+    let host = match rest.strip_prefix('[') {
+        Some(bracketed) => bracketed.split(']').next().unwrap_or(bracketed),
+        None => match rest.rsplit_once(':') {
+            Some((host, port)) if !host.contains(':') && port.parse::<u16>().is_ok() => host,
+            _ => rest,
+        },
+    };
+    // Link-local adreslerin arayüz eki sertifikada bulunmaz
+    host.split('%').next().unwrap_or(host).to_string()
+}
+
+/// CA zinciri ve/veya parmak izi denetimi
+#[derive(Debug)]
+struct CertificateCheck {
+    webpki: Option<Arc<WebPkiServerVerifier>>,
+    pin: Option<[u8; 32]>,
+    provider: Arc<CryptoProvider>,
+}
+
+impl ServerCertVerifier for CertificateCheck {
+    fn verify_server_cert(&self, end_entity: &CertificateDer<'_>, intermediates: &[CertificateDer<'_>], server_name: &ServerName<'_>, ocsp_response: &[u8], now: UnixTime) -> Result<ServerCertVerified, rustls::Error> {
+        if let Some(webpki) = &self.webpki {
+            webpki.verify_server_cert(end_entity, intermediates, server_name, ocsp_response, now)?;
+        }
+        if let Some(pin) = &self.pin
+            && Sha256::digest(end_entity.as_ref()).as_slice() != pin
+        {
+            return Err(rustls::Error::General(format!("Sunucu sertifikası parmak izi eşleşmiyor ({})", certificate_pin(end_entity))));
+        }
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(&self, _message: &[u8], _cert: &CertificateDer<'_>, _dss: &DigitallySignedStruct) -> Result<HandshakeSignatureValid, rustls::Error> {
+        Err(rustls::Error::General("TLS 1.2 desteklenmiyor".to_string()))
+    }
+
+    fn verify_tls13_signature(&self, message: &[u8], cert: &CertificateDer<'_>, dss: &DigitallySignedStruct) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls13_signature(message, cert, dss, &self.provider.signature_verification_algorithms)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.provider.signature_verification_algorithms.supported_schemes()
+    }
+}
+
+/// Alttaki bağlayıcının bağlantılarını TLS ile saran bağlayıcı
+pub struct TlsConnect {
+    inner: Box<dyn Connector>,
+    tls: ClientTls,
+    server_name: String,
+}
+
+impl TlsConnect {
+    async fn open(&self) -> io::Result<Connection> {
+        let connection = self.inner.connect().await?;
+        self.tls.connect(connection, &self.server_name).await
+    }
+}
+
+impl fmt::Display for TlsConnect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (TLS)", self.inner)
+    }
+}
+
+impl Connector for TlsConnect {
+    fn connect(&self) -> Pin<Box<dyn Future<Output = io::Result<Connection>> + Send + '_>> {
+        Box::pin(self.open())
+    }
+
+    fn multiplexed(&self) -> bool {
+        self.inner.multiplexed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use crate::error::Error;
+    use crate::protocol::{self, Frame};
+
+    /// Testler için kurum CA'sı, sunucu ve istemci sertifikaları
+    struct Pki {
+        dir: PathBuf,
+        ca: PathBuf,
+        server_cert: PathBuf,
+        server_key: PathBuf,
+        client_cert: PathBuf,
+        client_key: PathBuf,
+        other_cert: PathBuf,
+        other_key: PathBuf,
+    }
+
+    impl Pki {
+        fn generate(name: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!("deltasafe_tls_{}_{}", name, std::process::id()));
+            let _ = std::fs::remove_dir_all(&dir);
+            std::fs::create_dir_all(&dir).unwrap();
+
+            let ca_key = rcgen::KeyPair::generate().unwrap();
+            let mut ca_params = rcgen::CertificateParams::new(Vec::<String>::new()).unwrap();
+            ca_params.is_ca = rcgen::IsCa::Ca(rcgen::BasicConstraints::Unconstrained);
+            let ca = ca_params.self_signed(&ca_key).unwrap();
+
+            let issue = |names: Vec<String>, stem: &str| {
+                let key = rcgen::KeyPair::generate().unwrap();
+                let cert = rcgen::CertificateParams::new(names).unwrap().signed_by(&key, &ca, &ca_key).unwrap();
+                let cert_path = dir.join(format!("{}.crt", stem));
+                let key_path = dir.join(format!("{}.key", stem));
+                std::fs::write(&cert_path, cert.pem()).unwrap();
+                std::fs::write(&key_path, key.serialize_pem()).unwrap();
+                (cert_path, key_path)
+            };
+            let (server_cert, server_key) = issue(vec!["localhost".to_string(), "127.0.0.1".to_string()], "server");
+            let (client_cert, client_key) = issue(vec!["istemci".to_string()], "client");
+
+            // Kurum CA'sı dışında, kendinden imzalı bir istemci sertifikası
+            let other_key_pair = rcgen::KeyPair::generate().unwrap();
+            let other = rcgen::CertificateParams::new(vec!["yabanci".to_string()]).unwrap().self_signed(&other_key_pair).unwrap();
+            let other_cert = dir.join("other.crt");
+            let other_key = dir.join("other.key");
+            std::fs::write(&other_cert, other.pem()).unwrap();
+            std::fs::write(&other_key, other_key_pair.serialize_pem()).unwrap();
+
+            let ca_path = dir.join("ca.crt");
+            std::fs::write(&ca_path, ca.pem()).unwrap();
+            Pki { dir, ca: ca_path, server_cert, server_key, client_cert, client_key, other_cert, other_key }
+        }
+    }
+
+    /// İki ucu borunun iki yanında el sıkıştırır ve bir çerçeve aktarır
+    async fn handshake(server: &ServerTls, client: &ClientTls, name: &str) -> io::Result<()> {
+        let (a, b) = tokio::io::duplex(64 * 1024);
+        let (accepted, connected) = tokio::join!(
+            server.accept(Connection::new(a)),
+            client.connect(Connection::new(b), name),
+        );
+        let (mut accepted, mut connected) = match (accepted, connected) {
+            (Ok(accepted), Ok(connected)) => (accepted, connected),
+            (Err(e), _) | (_, Err(e)) => return Err(e),
+        };
+        protocol::write_frame(&mut connected.writer, &Frame::Error(Error::Io("gizli".to_string()))).await?;
+        match protocol::read_frame(&mut accepted.reader).await? {
+            Some(Frame::Error(error)) if error.message() == "gizli" => Ok(()),
+            other => Err(io::Error::other(format!("beklenmeyen çerçeve: {:?}", other))),
+        }
+    }
+
+    #[test]
+    fn test_host_and_pin_parsing() {
+        assert_eq!(host_of("nas.local:12345"), "nas.local");
+        assert_eq!(host_of("[2001:db8::1]:12345"), "2001:db8::1");
+        assert_eq!(host_of("[fe80::1%eth0]:12345"), "fe80::1");
+        assert_eq!(host_of("ssh://yedek@nas:22"), "nas");
+        assert_eq!(host_of("10.0.0.5"), "10.0.0.5");
+
+        let pin = certificate_pin(b"sertifika");
+        assert!(pin.starts_with("sha256:"));
+        let bytes = parse_pin(&pin).unwrap();
+        let colons: Vec<String> = bytes.iter().map(|b| format!("{:02X}", b)).collect();
+        assert_eq!(parse_pin(&colons.join(":")).unwrap(), bytes);
+        assert!(parse_pin("sha256:abcd").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_ca_and_pin_verification() {
+        let pki = Pki::generate("verify");
+        let server = ServerTls::new(&pki.server_cert, &pki.server_key, None).unwrap();
+        assert!(!server.is_mutual());
+
+        let by_ca = ClientTls::new(Some(&pki.ca), None, None).unwrap();
+        handshake(&server, &by_ca, "localhost").await.unwrap();
+        // CA doğrulaması sertifikadaki adı da denetler
+        assert!(handshake(&server, &by_ca, "baska.example").await.is_err());
+
+        let by_pin = ClientTls::new(None, Some(server.pin()), None).unwrap();
+        handshake(&server, &by_pin, "herhangi.bir.ad").await.unwrap();
+        let wrong_pin = ClientTls::new(None, Some(&certificate_pin(b"baska")), None).unwrap();
+        assert!(handshake(&server, &wrong_pin, "localhost").await.is_err());
+
+        let both = ClientTls::new(Some(&pki.ca), Some(server.pin()), None).unwrap();
+        handshake(&server, &both, "127.0.0.1").await.unwrap();
+
+        assert!(ClientTls::new(None, None, None).is_err());
+        std::fs::remove_dir_all(&pki.dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_mutual_tls_requires_ca_signed_client() {
+        let pki = Pki::generate("mutual");
+        let server = ServerTls::new(&pki.server_cert, &pki.server_key, Some(&pki.ca)).unwrap();
+        assert!(server.is_mutual());
+
+        let trusted = ClientTls::new(Some(&pki.ca), None, Some((&pki.client_cert, &pki.client_key))).unwrap();
+        handshake(&server, &trusted, "localhost").await.unwrap();
+
+        let anonymous = ClientTls::new(Some(&pki.ca), None, None).unwrap();
+        assert!(handshake(&server, &anonymous, "localhost").await.is_err());
+
+        let stranger = ClientTls::new(Some(&pki.ca), None, Some((&pki.other_cert, &pki.other_key))).unwrap();
+        assert!(handshake(&server, &stranger, "localhost").await.is_err());
+        std::fs::remove_dir_all(&pki.dir).unwrap();
+    }
+}
@@ -0,0 +1,462 @@
+//! Bağlantı katmanı
+//!
+//! Protokol herhangi bir çift yönlü bayt akışı üzerinde çalışır. [`Transport`]
+//! okuma ve yazma yarılarına ayrılabilen, karşı tarafını tanıtan akışları
+//! soyutlar: TCP, Unix soketleri, stdin/stdout ve testlerde
+//! `tokio::io::duplex`.
+//!
+//! İstemci hedefleri ([`Endpoint::parse`]):
+//! - `host:port`, `[v6]:port` — TCP
+//! - `unix:/yol/soket` — Unix soketi
+//! - `ssh://[kullanıcı@]host[:port]` — `ssh` ile uzakta `deltasafe serve --stdio`
+//!   çalıştırılır; uzak komut `DELTASAFE_REMOTE_COMMAND` ile değiştirilebilir
+//! - `quic://host:port` — QUIC; kimlik anahtardan türetildiği için
+//!   [`connector`] ile açılır (bkz. [`crate::quic`])
+
+use std::fmt;
+use std::future::Future;
+use std::io;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::process::Stdio as ProcessStdio;
+use std::task::{Context as TaskContext, Poll};
+use anyhow::{Context, Result};
+use tokio::io::{AsyncRead, AsyncWrite, DuplexStream, ReadBuf};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::process::{Child, ChildStdin, ChildStdout, Command};
+use tokio::sync::mpsc;
+use crate::crypto::access_key;
+use crate::net;
+use crate::quic::QuicConnector;
+use crate::tls::ClientTls;
+
+/// Bağlantının okuma yarısı
+pub type BoxRead = Box<dyn AsyncRead + Send + Unpin>;
+
+/// Bağlantının yazma yarısı
+pub type BoxWrite = Box<dyn AsyncWrite + Send + Unpin>;
+
+/// Uzak komut verilmezse `ssh` hedefinde çalıştırılan komut
+const DEFAULT_REMOTE_COMMAND: &str = "deltasafe serve --stdio";
+
+/// Bağlantının karşı tarafı
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Peer {
+    Tcp(SocketAddr),
+    /// QUIC bağlantısındaki bir akış
+    Quic(SocketAddr),
+    /// Unix soketinin yolu
+    Unix(PathBuf),
+    /// Sürecin stdin/stdout'u
+    Stdio,
+    /// Alt süreç (örn. `ssh`)
+    Command(String),
+    /// Süreç içi boru
+    Pipe(String),
+}
+
+impl fmt::Display for Peer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Peer::Tcp(addr) => write!(f, "{}", addr),
+            Peer::Quic(addr) => write!(f, "quic://{}", addr),
+            Peer::Unix(path) => write!(f, "unix:{}", path.display()),
+            Peer::Stdio => write!(f, "stdio"),
+            Peer::Command(command) => write!(f, "`{}`", command),
+            Peer::Pipe(name) => write!(f, "pipe:{}", name),
+        }
+    }
+}
+
+/// Protokolün üzerinde çalıştığı çift yönlü akış
+pub trait Transport: AsyncRead + AsyncWrite + Send + Unpin + 'static {
+    /// Karşı taraf
+    fn peer(&self) -> Peer;
+
+    /// Akışı bağımsız kullanılabilen okuma ve yazma yarılarına ayırır
+    fn into_split(self) -> (BoxRead, BoxWrite)
+    where
+        Self: Sized,
+    {
+        let (reader, writer) = tokio::io::split(self);
+        (Box::new(reader), Box::new(writer))
+    }
+}
+
+impl Transport for TcpStream {
+    fn peer(&self) -> Peer {
+        let addr = self.peer_addr().unwrap_or_else(|_| SocketAddr::from(([0, 0, 0, 0], 0)));
+        Peer::Tcp(net::canonical_addr(addr))
+    }
+
+    fn into_split(self) -> (BoxRead, BoxWrite) {
+        let (reader, writer) = TcpStream::into_split(self);
+        (Box::new(reader), Box::new(writer))
+    }
+}
+
+#[cfg(unix)]
+impl Transport for tokio::net::UnixStream {
+    fn peer(&self) -> Peer {
+        // Sunucu tarafında karşı uç isimsizdir; dinlenen soketin yolu gösterilir
+        let path = [self.peer_addr(), self.local_addr()].into_iter()
+            .flatten()
+            .find_map(|addr| addr.as_pathname().map(|path| path.to_path_buf()))
+            .unwrap_or_default();
+        Peer::Unix(path)
+    }
+
+    fn into_split(self) -> (BoxRead, BoxWrite) {
+        let (reader, writer) = tokio::net::UnixStream::into_split(self);
+        (Box::new(reader), Box::new(writer))
+    }
+}
+
+impl Transport for DuplexStream {
+    fn peer(&self) -> Peer {
+        Peer::Pipe("duplex".to_string())
+    }
+}
+
+/// Sürecin stdin/stdout'u. Protokol stdout'u kullandığı için aynı süreçte
+/// stdout'a başka bir şey yazılmamalıdır.
+pub struct Stdio {
+    reader: BoxRead,
+    writer: BoxWrite,
+}
+
+impl Stdio {
+    pub fn new() -> io::Result<Self> {
+        Ok(Self::with_output(Self::output()?))
+    }
+
+    /// Protokol çıktısını stdout yerine verilen yazıcıya gönderir
+    pub fn with_output(output: impl AsyncWrite + Send + Unpin + 'static) -> Self {
+        Self { reader: Self::input(), writer: Box::new(output) }
+    }
+
+    /// Tamponsuz stdout; satır tamponlu `std::io::Stdout` ikili veriyi
+    /// bekletebilir
+    #[cfg(unix)]
+    pub fn output() -> io::Result<tokio::fs::File> {
+        use std::os::fd::AsFd;
+        let fd = io::stdout().as_fd().try_clone_to_owned()?;
+        Ok(tokio::fs::File::from_std(std::fs::File::from(fd)))
+    }
+
+    #[cfg(not(unix))]
+    pub fn output() -> io::Result<tokio::io::Stdout> {
+        Ok(tokio::io::stdout())
+    }
+
+    fn input() -> BoxRead {
+        Box::new(tokio::io::stdin())
+    }
+}
+
+impl AsyncRead for Stdio {
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut TaskContext<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.reader).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for Stdio {
+    fn poll_write(mut self: Pin<&mut Self>, cx: &mut TaskContext<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.writer).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.writer).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.writer).poll_shutdown(cx)
+    }
+}
+
+impl Transport for Stdio {
+    fn peer(&self) -> Peer {
+        Peer::Stdio
+    }
+
+    fn into_split(self) -> (BoxRead, BoxWrite) {
+        (self.reader, self.writer)
+    }
+}
+
+/// Alt sürecin stdout'u; süreç bu yarıyla birlikte yaşar ve bırakılınca
+/// sonlandırılır
+struct ChildOutput {
+    stdout: ChildStdout,
+    _child: Child,
+}
+
+impl AsyncRead for ChildOutput {
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut TaskContext<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.stdout).poll_read(cx, buf)
+    }
+}
+
+/// Kimliği doğrulanmamış, yarılarına ayrılmış bağlantı
+pub struct Connection {
+    pub reader: BoxRead,
+    pub writer: BoxWrite,
+    pub peer: Peer,
+}
+
+impl Connection {
+    pub fn new(transport: impl Transport) -> Self {
+        let peer = transport.peer();
+        let (reader, writer) = transport.into_split();
+        Self { reader, writer, peer }
+    }
+}
+
+/// Sunucunun gelen bağlantıları kabul ettiği kaynak
+pub trait Listener: Send {
+    fn accept(&mut self) -> impl Future<Output = io::Result<Connection>> + Send;
+}
+
+impl<L: Listener> Listener for &mut L {
+    fn accept(&mut self) -> impl Future<Output = io::Result<Connection>> + Send {
+        (**self).accept()
+    }
+}
+
+impl Listener for TcpListener {
+    async fn accept(&mut self) -> io::Result<Connection> {
+        let (stream, _) = TcpListener::accept(self).await?;
+        stream.set_nodelay(true)?;
+        Ok(Connection::new(stream))
+    }
+}
+
+#[cfg(unix)]
+impl Listener for tokio::net::UnixListener {
+    async fn accept(&mut self) -> io::Result<Connection> {
+        let (stream, _) = tokio::net::UnixListener::accept(self).await?;
+        Ok(Connection::new(stream))
+    }
+}
+
+/// Kanaldan gelen bağlantılar (süreç içi kullanım ve testler). Kanal
+/// kapanınca yeni bağlantı gelmez.
+impl Listener for mpsc::Receiver<Connection> {
+    async fn accept(&mut self) -> io::Result<Connection> {
+        match self.recv().await {
+            Some(connection) => Ok(connection),
+            None => std::future::pending().await,
+        }
+    }
+}
+
+/// İstemcinin sunucuya bağlantı açma yolu
+pub trait Connector: Send + Sync + fmt::Display {
+    fn connect(&self) -> Pin<Box<dyn Future<Output = io::Result<Connection>> + Send + '_>>;
+
+    /// Yeni akışlar aynı bağlantı üzerinde ucuza açılabiliyorsa `true`
+    fn multiplexed(&self) -> bool {
+        false
+    }
+}
+
+/// Metin olarak verilen hedef
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Endpoint {
+    Tcp(Vec<SocketAddr>),
+    Unix(PathBuf),
+    /// stdin/stdout'u protokolü taşıyan komut
+    Command { program: String, args: Vec<String> },
+}
+
+impl Endpoint {
+    pub fn parse(target: &str) -> Result<Self> {
+        if let Some(path) = target.strip_prefix("unix:") {
+            if path.is_empty() {
+                anyhow::bail!("Unix soket yolu boş: {}", target);
+            }
+            return Ok(Endpoint::Unix(PathBuf::from(path)));
+        }
+        if let Some(rest) = target.strip_prefix("ssh://") {
+            return Self::ssh(rest.trim_end_matches('/'));
+        }
+        Ok(Endpoint::Tcp(net::resolve_target(target)?))
+    }
+
+    /// `[kullanıcı@]host[:port]` için `ssh` komutu
+    fn ssh(destination: &str) -> Result<Self> {
+        let (user, host) = match destination.rsplit_once('@') {
+            Some((user, host)) => (Some(user), host),
+            None => (None, destination),
+        };
+        let (host, port) = match host.rsplit_once(':') {
+            Some((host, port)) if port.parse::<u16>().is_ok() && (!host.contains(':') || host.ends_with(']')) => (host, Some(port)),
+            _ => (host, None),
+        };
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+        if host.is_empty() || user.is_some_and(str::is_empty) {
+            anyhow::bail!("Geçersiz ssh hedefi: ssh://{}", destination);
+        }
+
+        let mut args = Vec::new();
+        if let Some(port) = port {
+            args.extend(["-p".to_string(), port.to_string()]);
+        }
+        args.push(match user {
+            Some(user) => format!("{}@{}", user, host),
+            None => host.to_string(),
+        });
+        let remote = std::env::var("DELTASAFE_REMOTE_COMMAND").unwrap_or_else(|_| DEFAULT_REMOTE_COMMAND.to_string());
+        args.extend(remote.split_whitespace().map(str::to_string));
+        Ok(Endpoint::Command { program: "ssh".to_string(), args })
+    }
+
+    async fn open(&self) -> io::Result<Connection> {
+        match self {
+            Endpoint::Tcp(addrs) => {
+                let stream = TcpStream::connect(addrs.as_slice()).await?;
+                stream.set_nodelay(true)?;
+                Ok(Connection::new(stream))
+            }
+            #[cfg(unix)]
+            Endpoint::Unix(path) => Ok(Connection::new(tokio::net::UnixStream::connect(path).await?)),
+            #[cfg(not(unix))]
+            Endpoint::Unix(_) => Err(io::Error::new(io::ErrorKind::Unsupported, "Unix soketleri bu platformda desteklenmiyor")),
+            Endpoint::Command { program, args } => {
+                let mut child = Command::new(program)
+                    .args(args)
+                    .stdin(ProcessStdio::piped())
+                    .stdout(ProcessStdio::piped())
+                    .kill_on_drop(true)
+                    .spawn()?;
+                let stdin: ChildStdin = child.stdin.take().ok_or_else(|| io::Error::other("Komutun stdin'i alınamadı"))?;
+                let stdout = child.stdout.take().ok_or_else(|| io::Error::other("Komutun stdout'u alınamadı"))?;
+                Ok(Connection {
+                    reader: Box::new(ChildOutput { stdout, _child: child }),
+                    writer: Box::new(stdin),
+                    peer: Peer::Command(self.to_string()),
+                })
+            }
+        }
+    }
+}
+
+impl fmt::Display for Endpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Endpoint::Tcp(addrs) => match addrs.first() {
+                Some(addr) => write!(f, "{}", addr),
+                None => write!(f, "?"),
+            },
+            Endpoint::Unix(path) => write!(f, "unix:{}", path.display()),
+            Endpoint::Command { program, args } => {
+                let words: Vec<&str> = std::iter::once(program.as_str()).chain(args.iter().map(String::as_str)).collect();
+                write!(f, "{}", words.join(" "))
+            }
+        }
+    }
+}
+
+impl Connector for Endpoint {
+    fn connect(&self) -> Pin<Box<dyn Future<Output = io::Result<Connection>> + Send + '_>> {
+        Box::pin(self.open())
+    }
+}
+
+/// Metin hedefini bağlayıcıya çevirir. QUIC hedefleri sunucu kimliğini
+/// doğrulamak için anahtara ihtiyaç duyar; `tls` verilirse diğer hedeflerin
+/// bağlantıları TLS ile sarılır.
+pub fn connector(target: &str, key: &[u8; 32], tls: Option<&ClientTls>) -> Result<Box<dyn Connector>> {
+    if let Some(rest) = target.strip_prefix("quic://") {
+        if tls.is_some() {
+            anyhow::bail!("QUIC bağlantıları zaten TLS 1.3 kullanır; sertifika seçenekleri QUIC ile kullanılamaz");
+        }
+        let addrs = net::resolve_target(rest.trim_end_matches('/'))?;
+        return Ok(Box::new(QuicConnector::new(addrs, &access_key(key))?));
+    }
+    let endpoint = Box::new(Endpoint::parse(target)?);
+    match tls {
+        Some(tls) => Ok(Box::new(tls.wrap(endpoint, target))),
+        None => Ok(endpoint),
+    }
+}
+
+/// Bağlantı açılamazsa hedefi de içeren hata
+pub async fn connect(connector: &dyn Connector) -> Result<Connection> {
+    connector.connect().await
+        .with_context(|| format!("Sunucuya bağlanılamadı: {}", connector))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Error;
+    use crate::protocol::{self, Frame};
+
+    async fn echo_roundtrip(mut connection: Connection) {
+        protocol::write_frame(&mut connection.writer, &Frame::Error(Error::Io("merhaba".to_string()))).await.unwrap();
+        match protocol::read_frame(&mut connection.reader).await.unwrap() {
+            Some(Frame::Error(error)) => assert_eq!(error.message(), "merhaba"),
+            other => panic!("beklenmeyen çerçeve: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_endpoints() {
+        assert_eq!(Endpoint::parse("127.0.0.1:4000").unwrap(), Endpoint::Tcp(vec!["127.0.0.1:4000".parse().unwrap()]));
+        assert_eq!(Endpoint::parse("unix:/run/deltasafe.sock").unwrap(), Endpoint::Unix(PathBuf::from("/run/deltasafe.sock")));
+        assert!(Endpoint::parse("unix:").is_err());
+
+        let Endpoint::Command { program, args } = Endpoint::parse("ssh://yedek@nas:2222").unwrap() else { panic!() };
+        assert_eq!(program, "ssh");
+        assert_eq!(&args[..3], ["-p", "2222", "yedek@nas"]);
+        let Endpoint::Command { args, .. } = Endpoint::parse("ssh://[2001:db8::1]").unwrap() else { panic!() };
+        assert_eq!(args[0], "2001:db8::1");
+        assert!(Endpoint::parse("ssh://").is_err());
+        assert!(Endpoint::parse("ssh://@nas").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_duplex_transport() {
+        let (client, server) = tokio::io::duplex(1024);
+        let server = Connection::new(server);
+        assert_eq!(server.peer, Peer::Pipe("duplex".to_string()));
+        let echo = tokio::spawn(async move {
+            let Connection { mut reader, mut writer, .. } = server;
+            tokio::io::copy(&mut reader, &mut writer).await.unwrap();
+        });
+        echo_roundtrip(Connection::new(client)).await;
+        echo.abort();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_unix_socket_transport() {
+        let path = std::env::temp_dir().join(format!("deltasafe-transport-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let mut listener = tokio::net::UnixListener::bind(&path).unwrap();
+        let echo = tokio::spawn(async move {
+            let Connection { mut reader, mut writer, peer } = Listener::accept(&mut listener).await.unwrap();
+            assert!(matches!(peer, Peer::Unix(_)));
+            tokio::io::copy(&mut reader, &mut writer).await.unwrap();
+        });
+        let endpoint = Endpoint::parse(&format!("unix:{}", path.display())).unwrap();
+        echo_roundtrip(connect(&endpoint).await.unwrap()).await;
+        echo.abort();
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_command_transport() {
+        // `cat` gönderileni geri yazar
+        let endpoint = Endpoint::Command { program: "cat".to_string(), args: Vec::new() };
+        let connection = connect(&endpoint).await.unwrap();
+        assert_eq!(connection.peer, Peer::Command("cat".to_string()));
+        echo_roundtrip(connection).await;
+
+        let missing = Endpoint::Command { program: "deltasafe-olmayan-komut".to_string(), args: Vec::new() };
+        assert!(connect(&missing).await.is_err());
+    }
+}
@@ -0,0 +1,566 @@
+//! Şifreli, içerik adresli kasa
+//!
+//! Kasa modundaki sunucu dosya saklamaz. İstemci her dosyayı 1 MiB'lık
+//! parçalara böler, her parçayı kendi anahtarıyla şifreler ve sunucuya
+//! "blob" olarak gönderir. Blob'un kimliği şifreli verinin BLAKE3 özetidir;
+//! sunucu içeriği çözemeden her blob'u doğrulayabilir. IV, parçanın anahtarlı
+//! özetinden türetildiği için aynı içerik aynı blob'u verir ve bir kez
+//! saklanır.
+//!
+//! Hangi dosyanın hangi blob'lardan oluştuğu, yollar ve öznitelikler şifreli
+//! bir indekste durur. İndeks de blob olarak saklanır; sunucu yalnızca her
+//! gönderim için indeksin blob listesini kaydeder:
+//!
+//! - `.deltasafe/vault/blobs/<ilk 2 hex>/<kimlik>`
+//! - `.deltasafe/vault/indexes/<zaman>.json`
+//!
+//! `pull` indeksi ve blob'ları indirir, dosyaları istemcide yeniden kurar.
+//! Sunucuya yalnızca erişim anahtarı ([`crate::crypto::access_key`])
+//! verilebilir; bu anahtar istemcileri doğrulamaya yeter ama içeriği çözmeye
+//! yetmez.
+
+use std::collections::HashSet;
+use std::fs;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use anyhow::{Context, Result};
+use chrono::{DateTime, NaiveDateTime, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+use crate::crypto::{self, derive_subkey, IV_SIZE};
+use crate::entry::{is_safe_relative_path, is_safe_symlink_target};
+use crate::error::Error;
+use crate::metadata::{self, FileMetadata, Preserve};
+use crate::protocol::{self, Frame, Request, Response};
+use crate::restore::{connect, read_reply, send_request, unexpected_reply, RESPONSE_TIMEOUT};
+use crate::tls::ClientTls;
+use crate::server::{create_symlink, temp_path_for};
+use crate::snapshot::{self, SnapshotKind};
+use crate::storage::Storage;
+use crate::sync::Stream;
+use crate::versions::{is_reserved, STATE_DIR};
+
+/// `STATE_DIR` altındaki kasa klasörü
+const VAULT_DIR: &str = "vault";
+
+/// Şifrelenmeden önceki en büyük parça
+pub const BLOB_SIZE: usize = 1024 * 1024;
+
+/// Tek `MissingBlobs` veya `GetBlobs` isteğindeki en fazla kimlik
+const IDS_PER_REQUEST: usize = 1024;
+
+/// İndeks kimliğinin biçimi; sabit genişlikli olduğu için sıralanabilir
+const ID_FORMAT: &str = "%Y%m%dT%H%M%S%.6fZ";
+
+/// Blob içeriği için BLAKE3 bağlamı
+const CONTENT_CONTEXT: &str = "deltasafe 2024 vault content";
+
+/// İçerikten IV türetmek için BLAKE3 bağlamı
+const IV_CONTEXT: &str = "deltasafe 2024 vault iv";
+
+fn vault_dir() -> PathBuf {
+    Path::new(STATE_DIR).join(VAULT_DIR)
+}
+
+fn blob_path(id: &str) -> PathBuf {
+    vault_dir().join("blobs").join(&id[..2]).join(id)
+}
+
+fn indexes_dir() -> PathBuf {
+    vault_dir().join("indexes")
+}
+
+/// Hex blob kimliğini doğrular
+fn parse_blob_id(id: &str) -> Result<blake3::Hash, Error> {
+    blake3::Hash::from_hex(id).map_err(|_| Error::Protocol(format!("Geçersiz blob kimliği: {}", id)))
+}
+
+fn has_blob(storage: &dyn Storage, id: &str) -> Result<bool, Error> {
+    storage.stat(&blob_path(id))
+        .map(|stat| stat.is_some_and(|stat| !stat.is_dir))
+        .map_err(|e| Error::io("Kasa okunamadı", &e))
+}
+
+/// Veriyi depolamaya tek seferde yazar
+fn write_whole(storage: &dyn Storage, path: &Path, data: &[u8]) -> io::Result<()> {
+    let mut temp = storage.open_temp(path, data.len() as u64)?;
+    temp.write_at(0, data)?;
+    temp.commit()
+}
+
+/// Blob'u, içeriği kimliğiyle eşleşiyorsa saklar. Blob zaten varsa dokunmaz;
+/// yeni yazıldıysa `true` döner.
+pub fn store_blob(storage: &dyn Storage, id: &[u8; 32], data: &[u8]) -> Result<bool, Error> {
+    let hash = blake3::Hash::from(*id);
+    if blake3::hash(data) != hash {
+        return Err(Error::Integrity(format!("Blob içeriği kimliğiyle eşleşmiyor: {}", hash.to_hex())));
+    }
+    let hex_id = hash.to_hex();
+    if has_blob(storage, &hex_id)? {
+        return Ok(false);
+    }
+    write_whole(storage, &blob_path(&hex_id), data)
+        .map_err(|e| Error::io("Blob yazılamadı", &e))?;
+    Ok(true)
+}
+
+/// Verilen kimliklerden kasada bulunmayanları, sırayı koruyarak döner
+pub fn missing_blobs(storage: &dyn Storage, ids: &[String]) -> Result<Vec<String>, Error> {
+    let mut missing = Vec::new();
+    for id in ids {
+        let hash = parse_blob_id(id)?.to_hex();
+        if !has_blob(storage, &hash)? {
+            missing.push(hash.to_string());
+        }
+    }
+    Ok(missing)
+}
+
+/// Blob'u okur
+pub fn read_blob(storage: &dyn Storage, id: &str) -> Result<([u8; 32], Vec<u8>), Error> {
+    let hash = parse_blob_id(id)?;
+    let mut data = Vec::new();
+    storage.read(&blob_path(&hash.to_hex()))
+        .and_then(|mut reader| reader.read_to_end(&mut data))
+        .map_err(|e| Error::io(&format!("Blob bulunamadı: {}", id), &e))?;
+    Ok((*hash.as_bytes(), data))
+}
+
+/// İndeksi oluşturan blob'ların hepsi kasadaysa yeni bir indeks kaydeder ve
+/// kimliğini döner
+pub fn store_index(storage: &dyn Storage, blobs: &[String], now: DateTime<Utc>) -> Result<String, Error> {
+    if blobs.is_empty() {
+        return Err(Error::Protocol("İndeks boş olamaz".to_string()));
+    }
+    if let Some(missing) = missing_blobs(storage, blobs)?.first() {
+        return Err(Error::Protocol(format!("İndeksin blob'u kasada yok: {}", missing)));
+    }
+    let write = || -> io::Result<String> {
+        let dir = indexes_dir();
+        let mut time = now;
+        let id = loop {
+            let id = time.format(ID_FORMAT).to_string();
+            if storage.stat(&dir.join(format!("{}.json", id)))?.is_none() {
+                break id;
+            }
+            time += TimeDelta::microseconds(1);
+        };
+        write_whole(storage, &dir.join(format!("{}.json", id)), &serde_json::to_vec(blobs)?)?;
+        Ok(id)
+    };
+    write().map_err(|e| Error::io("İndeks yazılamadı", &e))
+}
+
+fn is_index_id(id: &str) -> bool {
+    NaiveDateTime::parse_from_str(id, ID_FORMAT).is_ok()
+}
+
+/// Kayıtlı indeksleri yeniden eskiye listeler
+pub fn list_indexes(storage: &dyn Storage) -> io::Result<Vec<String>> {
+    let mut ids: Vec<String> = storage.list(&indexes_dir())?
+        .into_iter()
+        .filter_map(|entry| entry.name.strip_suffix(".json").filter(|id| is_index_id(id)).map(str::to_string))
+        .collect();
+    ids.sort_unstable_by(|a, b| b.cmp(a));
+    Ok(ids)
+}
+
+/// İndeksin blob listesini döner; kimlik verilmezse en yeni indeksinkini
+pub fn read_index(storage: &dyn Storage, id: Option<&str>) -> Result<(String, Vec<String>), Error> {
+    let id = match id {
+        Some(id) if is_index_id(id) => id.to_string(),
+        Some(id) => return Err(Error::Protocol(format!("Geçersiz indeks kimliği: {}", id))),
+        None => list_indexes(storage)
+            .map_err(|e| Error::io("İndeksler okunamadı", &e))?
+            .into_iter()
+            .next()
+            .ok_or_else(|| Error::Io("Kasada henüz indeks yok".to_string()))?,
+    };
+    let mut data = Vec::new();
+    storage.read(&indexes_dir().join(format!("{}.json", id)))
+        .and_then(|mut reader| reader.read_to_end(&mut data))
+        .map_err(|e| Error::io(&format!("'{}' indeksi bulunamadı", id), &e))?;
+    let blobs = serde_json::from_slice(&data)
+        .map_err(|e| Error::Integrity(format!("'{}' indeksi bozuk: {}", id, e)))?;
+    Ok((id, blobs))
+}
+
+/// Ana anahtardan türetilen kasa anahtarları
+struct VaultKeys {
+    content: [u8; 32],
+    iv: [u8; 32],
+}
+
+impl VaultKeys {
+    fn derive(key: &[u8; 32]) -> Self {
+        Self { content: derive_subkey(key, CONTENT_CONTEXT), iv: derive_subkey(key, IV_CONTEXT) }
+    }
+
+    /// Parçayı şifreler; blob kimliğini ve şifreli veriyi döner
+    fn seal(&self, plain: &[u8]) -> ([u8; 32], Vec<u8>) {
+        let mut iv = [0u8; IV_SIZE];
+        iv.copy_from_slice(&blake3::keyed_hash(&self.iv, plain).as_bytes()[..IV_SIZE]);
+        let sealed = crypto::seal_with_iv(plain, &self.content, &iv);
+        (*blake3::hash(&sealed).as_bytes(), sealed)
+    }
+
+    /// Blob'u doğrular ve çözer
+    fn open(&self, id: &[u8; 32], sealed: &[u8]) -> Result<Vec<u8>> {
+        if blake3::hash(sealed) != blake3::Hash::from(*id) {
+            anyhow::bail!(Error::Integrity(format!("Blob bozuk: {}", hex::encode(id))));
+        }
+        crypto::open(sealed, &self.content)
+            .context("Blob çözülemedi (anahtar uyuşmuyor olabilir)")
+    }
+}
+
+/// Şifreli indeksteki bir girdi
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+struct VaultEntry {
+    path: PathBuf,
+    #[serde(flatten)]
+    kind: SnapshotKind,
+    #[serde(default)]
+    metadata: FileMetadata,
+    /// Dosya içeriğini sırayla oluşturan blob'lar
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    blobs: Vec<String>,
+}
+
+/// Şifreli indeksin içeriği; girdiler üst klasörler önce gelecek şekilde sıralıdır
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+struct VaultIndex {
+    entries: Vec<VaultEntry>,
+}
+
+impl VaultIndex {
+    /// Yolların ve hedeflerin hedef klasörde kaldığını doğrular
+    fn validate(&self) -> Result<()> {
+        for entry in &self.entries {
+            if !is_safe_relative_path(&entry.path) || is_reserved(&entry.path) {
+                anyhow::bail!(Error::Permission(format!("İndekste geçersiz yol: {}", entry.path.display())));
+            }
+            if let SnapshotKind::Symlink { target } = &entry.kind
+                && !is_safe_symlink_target(&entry.path, target)
+            {
+                anyhow::bail!(Error::Permission(format!("İndekste kökün dışını gösteren bağlantı: {}", entry.path.display())));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Gönderimin özeti
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pushed {
+    /// Sunucunun verdiği indeks kimliği
+    pub index: String,
+    pub files: usize,
+    pub bytes: u64,
+    /// Kasada olmadığı için gönderilen blob sayısı
+    pub uploaded: usize,
+}
+
+/// Geri kurmanın özeti
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pulled {
+    pub index: String,
+    pub files: usize,
+    pub bytes: u64,
+}
+
+/// Dosyanın blob kimliklerini, BLAKE3 hash'ini ve boyutunu hesaplar
+fn file_blobs(path: &Path, keys: &VaultKeys) -> io::Result<(Vec<String>, String, u64)> {
+    let mut file = fs::File::open(path)?;
+    let mut hasher = blake3::Hasher::new();
+    let mut buffer = vec![0; BLOB_SIZE];
+    let mut blobs = Vec::new();
+    let mut size = 0u64;
+    loop {
+        let read = read_full(&mut file, &mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+        blobs.push(hex::encode(keys.seal(&buffer[..read]).0));
+        size += read as u64;
+    }
+    Ok((blobs, hasher.finalize().to_hex().to_string(), size))
+}
+
+/// Dosyanın `index`. parçasını yeniden okuyup şifreler
+fn file_blob(path: &Path, index: usize, keys: &VaultKeys) -> io::Result<([u8; 32], Vec<u8>)> {
+    let mut file = fs::File::open(path)?;
+    file.seek(SeekFrom::Start((index * BLOB_SIZE) as u64))?;
+    let mut buffer = vec![0; BLOB_SIZE];
+    let read = read_full(&mut file, &mut buffer)?;
+    Ok(keys.seal(&buffer[..read]))
+}
+
+/// Tampon dolana veya dosya bitene kadar okur
+fn read_full(file: &mut fs::File, buffer: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buffer.len() {
+        match file.read(&mut buffer[filled..])? {
+            0 => break,
+            read => filled += read,
+        }
+    }
+    Ok(filled)
+}
+
+async fn send_blob(stream: &mut Stream, id: [u8; 32], data: Vec<u8>) -> Result<()> {
+    tokio::time::timeout(RESPONSE_TIMEOUT, protocol::write_frame(&mut stream.writer, &Frame::Blob { id, data })).await
+        .context("Blob gönderimi zaman aşımına uğradı")?
+        .context("Blob gönderilemedi")
+}
+
+/// Verilen blob'lardan kasada bulunmayanları sorar
+async fn ask_missing(stream: &mut Stream, ids: &[String]) -> Result<HashSet<String>> {
+    let mut missing = HashSet::new();
+    for batch in ids.chunks(IDS_PER_REQUEST) {
+        send_request(stream, Request::MissingBlobs { ids: batch.to_vec() }).await?;
+        match read_reply(&mut stream.reader).await? {
+            Frame::Response(Response::MissingBlobs { ids }) => missing.extend(ids),
+            other => anyhow::bail!(unexpected_reply(other)),
+        }
+    }
+    Ok(missing)
+}
+
+/// Blob'ları sırayla indirir, doğrular ve çözülmüş içeriklerini `sink`e verir
+async fn fetch_blobs(stream: &mut Stream, keys: &VaultKeys, ids: &[String], mut sink: impl FnMut(Vec<u8>) -> Result<()>) -> Result<()> {
+    for batch in ids.chunks(IDS_PER_REQUEST) {
+        send_request(stream, Request::GetBlobs { ids: batch.to_vec() }).await?;
+        for expected in batch {
+            let (id, data) = match read_reply(&mut stream.reader).await? {
+                Frame::Blob { id, data } => (id, data),
+                other => anyhow::bail!(unexpected_reply(other)),
+            };
+            if hex::encode(id) != *expected {
+                anyhow::bail!(Error::Protocol(format!("Sunucu istenmeyen blob gönderdi: {}", hex::encode(id))));
+            }
+            sink(keys.open(&id, &data)?)?;
+        }
+    }
+    Ok(())
+}
+
+/// `source` klasörünü şifreleyip kasaya gönderir. Kasada zaten bulunan
+/// parçalar yeniden gönderilmez.
+pub async fn push(source: &Path, target: &str, key: &[u8; 32], tls: Option<&ClientTls>) -> Result<Pushed, Error> {
+    let keys = VaultKeys::derive(key);
+    let root = source.to_path_buf();
+    let scanned = tokio::task::spawn_blocking(move || snapshot::scan(&root)).await
+        .context("Tarama görevi tamamlanamadı")?
+        .with_context(|| format!("Kaynak taranamadı: {}", source.display()))?;
+
+    let mut stream = connect(target, key, tls).await?;
+    let keys = std::sync::Arc::new(keys);
+    let mut entries = Vec::new();
+    let mut files = 0;
+    let mut bytes = 0;
+    let mut uploaded = 0;
+    let mut sent = HashSet::new();
+    for (path, fs_metadata) in scanned {
+        let full_path = source.join(&path);
+        let metadata = FileMetadata::from_fs(&fs_metadata);
+        if fs_metadata.file_type().is_symlink() {
+            let target = fs::read_link(&full_path)
+                .with_context(|| format!("Bağlantı okunamadı: {}", full_path.display()))?;
+            entries.push(VaultEntry { path, kind: SnapshotKind::Symlink { target }, metadata, blobs: Vec::new() });
+            continue;
+        }
+        if fs_metadata.is_dir() {
+            entries.push(VaultEntry { path, kind: SnapshotKind::Directory, metadata, blobs: Vec::new() });
+            continue;
+        }
+        if !fs_metadata.is_file() {
+            continue;
+        }
+
+        let (reading, task_keys) = (full_path.clone(), keys.clone());
+        let (blobs, hash, size) = tokio::task::spawn_blocking(move || file_blobs(&reading, &task_keys)).await
+            .context("Şifreleme görevi tamamlanamadı")?
+            .with_context(|| format!("Dosya okunamadı: {}", full_path.display()))?;
+
+        let missing = ask_missing(&mut stream, &blobs).await?;
+        for (index, id) in blobs.iter().enumerate() {
+            if !missing.contains(id) || !sent.insert(id.clone()) {
+                continue;
+            }
+            let (reading, task_keys) = (full_path.clone(), keys.clone());
+            let (blob_id, data) = tokio::task::spawn_blocking(move || file_blob(&reading, index, &task_keys)).await
+                .context("Şifreleme görevi tamamlanamadı")?
+                .with_context(|| format!("Dosya okunamadı: {}", full_path.display()))?;
+            if hex::encode(blob_id) != *id {
+                return Err(Error::Io(format!("'{}' gönderim sırasında değişti", path.display())));
+            }
+            send_blob(&mut stream, blob_id, data).await?;
+            uploaded += 1;
+        }
+        log::info!("[🔒] {} ({} bayt, {} parça)", path.display(), size, blobs.len());
+        files += 1;
+        bytes += size;
+        entries.push(VaultEntry { path, kind: SnapshotKind::File { hash, size }, metadata, blobs });
+    }
+
+    // İndeks de şifrelenip blob olarak gönderilir
+    let index = serde_json::to_vec(&VaultIndex { entries })
+        .context("İndeks oluşturulamadı")?;
+    let pieces: Vec<([u8; 32], Vec<u8>)> = index.chunks(BLOB_SIZE).map(|piece| keys.seal(piece)).collect();
+    let index_blobs: Vec<String> = pieces.iter().map(|(id, _)| hex::encode(id)).collect();
+    let missing = ask_missing(&mut stream, &index_blobs).await?;
+    for (id, data) in pieces {
+        if missing.contains(&hex::encode(id)) && sent.insert(hex::encode(id)) {
+            send_blob(&mut stream, id, data).await?;
+            uploaded += 1;
+        }
+    }
+    send_request(&mut stream, Request::PutIndex { blobs: index_blobs }).await?;
+    let index = match read_reply(&mut stream.reader).await? {
+        Frame::Response(Response::IndexStored { id }) => id,
+        other => return Err(unexpected_reply(other)),
+    };
+    Ok(Pushed { index, files, bytes, uploaded })
+}
+
+/// Kasadaki indeksleri yeniden eskiye listeler
+pub async fn indexes(target: &str, key: &[u8; 32], tls: Option<&ClientTls>) -> Result<Vec<String>, Error> {
+    let mut stream = connect(target, key, tls).await?;
+    send_request(&mut stream, Request::Indexes).await?;
+    match read_reply(&mut stream.reader).await? {
+        Frame::Response(Response::Indexes { ids }) => Ok(ids),
+        other => Err(unexpected_reply(other)),
+    }
+}
+
+/// İndeksi (verilmezse en yenisini) indirip dosyaları `output` klasöründe
+/// yeniden kurar. Her dosya hash'i doğrulandıktan sonra yerine taşınır.
+pub async fn pull(target: &str, key: &[u8; 32], tls: Option<&ClientTls>, index: Option<&str>, output: &Path) -> Result<Pulled, Error> {
+    let keys = VaultKeys::derive(key);
+    let mut stream = connect(target, key, tls).await?;
+    send_request(&mut stream, Request::Index { id: index.map(str::to_string) }).await?;
+    let (index, index_blobs) = match read_reply(&mut stream.reader).await? {
+        Frame::Response(Response::Index { id, blobs }) => (id, blobs),
+        other => return Err(unexpected_reply(other)),
+    };
+    let mut plain = Vec::new();
+    fetch_blobs(&mut stream, &keys, &index_blobs, |data| {
+        plain.extend_from_slice(&data);
+        Ok(())
+    }).await?;
+    let vault_index: VaultIndex = serde_json::from_slice(&plain)
+        .context("İndeks çözülemedi")?;
+    vault_index.validate()?;
+
+    fs::create_dir_all(output)
+        .with_context(|| format!("Klasör oluşturulamadı: {}", output.display()))?;
+    let mut files = 0;
+    let mut bytes = 0;
+    for entry in &vault_index.entries {
+        let full_path = output.join(&entry.path);
+        match &entry.kind {
+            SnapshotKind::Directory => fs::create_dir_all(&full_path)
+                .with_context(|| format!("Klasör oluşturulamadı: {}", full_path.display()))?,
+            SnapshotKind::Symlink { target } => {
+                if fs::symlink_metadata(&full_path).is_err() {
+                    create_symlink(target, &full_path)
+                        .with_context(|| format!("Bağlantı oluşturulamadı: {}", full_path.display()))?;
+                }
+            }
+            SnapshotKind::File { hash, size } => {
+                pull_file(&mut stream, &keys, entry, hash, &full_path).await?;
+                files += 1;
+                bytes += size;
+            }
+        }
+    }
+
+    // Klasör zamanları içleri doldurulduktan sonra uygulanır
+    for entry in vault_index.entries.iter().rev().filter(|entry| entry.kind == SnapshotKind::Directory) {
+        if let Err(e) = metadata::apply(&output.join(&entry.path), &entry.metadata, Preserve::default()) {
+            log::warn!("[⚠️] '{}' öznitelikleri uygulanamadı: {}", entry.path.display(), e);
+        }
+    }
+    Ok(Pulled { index, files, bytes })
+}
+
+/// Dosyanın blob'larını geçici dosyaya yazar, hash'i doğrulayıp yerine taşır
+async fn pull_file(stream: &mut Stream, keys: &VaultKeys, entry: &VaultEntry, hash: &str, full_path: &Path) -> Result<()> {
+    let temp_path = temp_path_for(full_path);
+    let written = async {
+        let mut file = fs::File::create(&temp_path)
+            .with_context(|| format!("Dosya oluşturulamadı: {}", temp_path.display()))?;
+        let mut hasher = blake3::Hasher::new();
+        fetch_blobs(stream, keys, &entry.blobs, |data| {
+            hasher.update(&data);
+            file.write_all(&data).context("Veri dosyaya yazılamadı")
+        }).await?;
+        file.sync_all().context("Dosya diske yazılamadı")?;
+        let calculated = hasher.finalize().to_hex();
+        if calculated.as_str() != hash {
+            anyhow::bail!(Error::Integrity(format!("'{}' hash doğrulaması başarısız! Beklenen: {}, Hesaplanan: {}", entry.path.display(), hash, calculated)));
+        }
+        Ok(())
+    }.await;
+    if let Err(e) = written {
+        let _ = fs::remove_file(&temp_path);
+        return Err(e);
+    }
+    fs::rename(&temp_path, full_path)
+        .with_context(|| format!("Dosya yerine taşınamadı: {}", full_path.display()))?;
+    if let Err(e) = metadata::apply(full_path, &entry.metadata, Preserve::default()) {
+        log::warn!("[⚠️] '{}' öznitelikleri uygulanamadı: {}", entry.path.display(), e);
+    }
+    log::info!("[🔓] {}", entry.path.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::MemoryStorage;
+
+    #[test]
+    fn test_blob_sealing_is_deterministic_and_verified() {
+        let keys = VaultKeys::derive(&[1u8; 32]);
+        let (id, sealed) = keys.seal(b"gizli veri");
+        assert_eq!(keys.seal(b"gizli veri"), (id, sealed.clone()));
+        assert_ne!(keys.seal(b"baska veri").0, id);
+        // Başka anahtar aynı içerik için başka blob üretir
+        assert_ne!(VaultKeys::derive(&[2u8; 32]).seal(b"gizli veri").0, id);
+
+        assert_eq!(keys.open(&id, &sealed).unwrap(), b"gizli veri");
+        let mut tampered = sealed.clone();
+        tampered[IV_SIZE] ^= 1;
+        assert!(keys.open(&id, &tampered).is_err());
+        assert!(VaultKeys::derive(&[2u8; 32]).open(&id, &sealed).is_err());
+    }
+
+    #[test]
+    fn test_blob_and_index_store() {
+        let storage: &dyn Storage = &MemoryStorage::new();
+        let keys = VaultKeys::derive(&[1u8; 32]);
+        let (id, sealed) = keys.seal(b"icerik");
+        let hex_id = hex::encode(id);
+
+        assert_eq!(missing_blobs(storage, std::slice::from_ref(&hex_id)).unwrap(), vec![hex_id.clone()]);
+        assert!(matches!(store_blob(storage, &[0u8; 32], &sealed), Err(Error::Integrity(_))));
+        assert!(store_blob(storage, &id, &sealed).unwrap());
+        assert!(!store_blob(storage, &id, &sealed).unwrap());
+        assert!(missing_blobs(storage, std::slice::from_ref(&hex_id)).unwrap().is_empty());
+        assert!(matches!(missing_blobs(storage, &["../x".to_string()]), Err(Error::Protocol(_))));
+        assert_eq!(read_blob(storage, &hex_id).unwrap(), (id, sealed));
+
+        let now = DateTime::parse_from_rfc3339("2024-03-31T12:00:00Z").unwrap().with_timezone(&Utc);
+        assert!(matches!(store_index(storage, &[hex::encode([9u8; 32])], now), Err(Error::Protocol(_))));
+        assert!(matches!(read_index(storage, None), Err(Error::Io(_))));
+        let first = store_index(storage, std::slice::from_ref(&hex_id), now).unwrap();
+        let second = store_index(storage, std::slice::from_ref(&hex_id), now).unwrap();
+        assert_ne!(first, second);
+        assert_eq!(list_indexes(storage).unwrap(), vec![second.clone(), first.clone()]);
+        assert_eq!(read_index(storage, None).unwrap(), (second, vec![hex_id.clone()]));
+        assert_eq!(read_index(storage, Some(&first)).unwrap().0, first);
+        assert!(read_index(storage, Some("../../etc/passwd")).is_err());
+    }
+}
@@ -0,0 +1,246 @@
+//! Sunucu tarafı dosya sürümleri
+//!
+//! Sürümleme açıkken bir dosya farklı içerikle yeniden gönderildiğinde eski
+//! içerik silinmez; paylaşım kökündeki `.deltasafe/versions` klasörüne
+//! taşınır. Her yolun sürümleri, yolun BLAKE3 özetiyle adlandırılan ayrı
+//! bir klasörde durur; sürüm kimliği içeriğin yerini yenisine bıraktığı
+//! UTC zamandır (`20240131T154500.123456Z`), böylece ad sıralaması zaman
+//! sıralamasıyla aynıdır.
+//!
+//! Saklama kuralları her arşivlemeden sonra uygulanır. Bir sürüm, kurallardan
+//! en az birine uyuyorsa tutulur:
+//!
+//! - `keep_last`: en yeni N sürüm
+//! - `keep_daily`: son N gün içinde, her günün (UTC) en yeni sürümü
+
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use chrono::{DateTime, NaiveDateTime, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+use crate::entry::is_safe_relative_path;
+use crate::error::Error;
+use crate::sync::calculate_file_hash;
+
+/// Sunucunun kendi verileri için ayırdığı, paylaşım köküne göreli klasör
+pub const STATE_DIR: &str = ".deltasafe";
+
+/// `STATE_DIR` altındaki sürüm klasörü
+const VERSIONS_DIR: &str = "versions";
+
+/// Sürüm kimliğinin biçimi; sabit genişlikli olduğu için sıralanabilir
+const ID_FORMAT: &str = "%Y%m%dT%H%M%S%.6fZ";
+
+/// Saklama kuralları; ikisi de sıfırsa sürümleme kapalıdır
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Retention {
+    /// Tutulacak en yeni sürüm sayısı
+    pub keep_last: usize,
+    /// Her günün en yeni sürümünün tutulacağı gün sayısı
+    pub keep_daily: u32,
+}
+
+impl Retention {
+    pub fn is_enabled(&self) -> bool {
+        self.keep_last > 0 || self.keep_daily > 0
+    }
+
+    /// Yeniden eskiye sıralı sürüm zamanlarından hangilerinin tutulacağını döner
+    pub fn select(&self, times: &[DateTime<Utc>], now: DateTime<Utc>) -> Vec<bool> {
+        let horizon = now - TimeDelta::days(i64::from(self.keep_daily));
+        let mut last_day = None;
+        times.iter().enumerate().map(|(i, time)| {
+            let day = time.date_naive();
+            let newest_of_day = last_day != Some(day);
+            last_day = Some(day);
+            i < self.keep_last || (self.keep_daily > 0 && *time > horizon && newest_of_day)
+        }).collect()
+    }
+}
+
+/// Bir yolun saklanan sürümü
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct VersionInfo {
+    pub id: String,
+    pub size: u64,
+}
+
+impl VersionInfo {
+    /// İçeriğin yerini yenisine bıraktığı zaman
+    pub fn time(&self) -> Option<DateTime<Utc>> {
+        parse_id(&self.id)
+    }
+}
+
+/// Yol sunucunun kendi klasörünü gösteriyorsa `true`; istemciler buraya yazamaz
+pub fn is_reserved(relative: &Path) -> bool {
+    matches!(relative.components().next(), Some(Component::Normal(name)) if name == STATE_DIR)
+}
+
+fn parse_id(id: &str) -> Option<DateTime<Utc>> {
+    NaiveDateTime::parse_from_str(id, ID_FORMAT).ok().map(|time| time.and_utc())
+}
+
+/// Yolun sürümlerinin tutulduğu klasör
+fn versions_dir(root: &Path, relative: &Path) -> PathBuf {
+    let digest = blake3::hash(relative.to_string_lossy().as_bytes());
+    root.join(STATE_DIR).join(VERSIONS_DIR).join(&digest.to_hex()[..32])
+}
+
+/// Hedefteki dosyayı, içeriği `new_hash`ten farklıysa sürüm olarak taşır ve
+/// saklama kurallarını uygular. Arşivlenen sürümün kimliğini döner.
+pub fn archive(root: &Path, relative: &Path, new_hash: &str, retention: Retention, now: DateTime<Utc>) -> io::Result<Option<String>> {
+    let current = root.join(relative);
+    match fs::symlink_metadata(&current) {
+        Ok(metadata) if metadata.is_file() => {}
+        Ok(_) => return Ok(None),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e),
+    }
+    // Aynı içeriğin yeniden gönderilmesi sürüm oluşturmaz
+    if calculate_file_hash(&current)? == new_hash {
+        return Ok(None);
+    }
+
+    let dir = versions_dir(root, relative);
+    fs::create_dir_all(&dir)?;
+    let mut time = now;
+    let id = loop {
+        let id = time.format(ID_FORMAT).to_string();
+        if !dir.join(&id).exists() {
+            break id;
+        }
+        time += TimeDelta::microseconds(1);
+    };
+    fs::rename(&current, dir.join(&id))?;
+    prune(&dir, retention, now)?;
+    Ok(Some(id))
+}
+
+/// Saklama kurallarına uymayan sürümleri siler
+fn prune(dir: &Path, retention: Retention, now: DateTime<Utc>) -> io::Result<()> {
+    let ids = version_ids(dir)?;
+    let times: Vec<DateTime<Utc>> = ids.iter().filter_map(|id| parse_id(id)).collect();
+    for (id, keep) in ids.iter().zip(retention.select(&times, now)) {
+        if !keep {
+            fs::remove_file(dir.join(id))?;
+        }
+    }
+    Ok(())
+}
+
+/// Klasördeki sürüm kimlikleri, yeniden eskiye
+fn version_ids(dir: &Path) -> io::Result<Vec<String>> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let mut ids = Vec::new();
+    for entry in entries {
+        let entry = entry?;
+        if let Some(id) = entry.file_name().to_str().filter(|id| parse_id(id).is_some())
+            && entry.file_type()?.is_file()
+        {
+            ids.push(id.to_string());
+        }
+    }
+    ids.sort_unstable_by(|a, b| b.cmp(a));
+    Ok(ids)
+}
+
+/// Yolun saklanan sürümlerini yeniden eskiye listeler
+pub fn list(root: &Path, relative: &Path) -> io::Result<Vec<VersionInfo>> {
+    let dir = versions_dir(root, relative);
+    version_ids(&dir)?.into_iter().map(|id| {
+        let size = fs::metadata(dir.join(&id))?.len();
+        Ok(VersionInfo { id, size })
+    }).collect()
+}
+
+/// Sürümün dosya yolunu döner; yol veya kimlik geçersizse ya da sürüm
+/// bulunamazsa hata mesajı döner
+pub fn locate(root: &Path, relative: &Path, id: &str) -> Result<PathBuf, Error> {
+    if !is_safe_relative_path(relative) || is_reserved(relative) {
+        return Err(Error::Permission(format!("Geçersiz dosya yolu: {}", relative.display())));
+    }
+    if parse_id(id).is_none() {
+        return Err(Error::Protocol(format!("Geçersiz sürüm kimliği: {}", id)));
+    }
+    let path = versions_dir(root, relative).join(id);
+    match fs::symlink_metadata(&path) {
+        Ok(metadata) if metadata.is_file() => Ok(path),
+        _ => Err(Error::Io(format!("'{}' için {} sürümü bulunamadı", relative.display(), id))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(text: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(text).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_retention_select() {
+        let now = at("2024-03-31T12:00:00Z");
+        // Yeniden eskiye: aynı gün iki sürüm, ardından günlük ve çok eski sürümler
+        let times = [
+            at("2024-03-31T11:00:00Z"),
+            at("2024-03-31T09:00:00Z"),
+            at("2024-03-30T18:00:00Z"),
+            at("2024-03-28T08:00:00Z"),
+            at("2024-01-01T08:00:00Z"),
+        ];
+
+        let last = Retention { keep_last: 2, keep_daily: 0 };
+        assert_eq!(last.select(&times, now), vec![true, true, false, false, false]);
+
+        let daily = Retention { keep_last: 0, keep_daily: 30 };
+        assert_eq!(daily.select(&times, now), vec![true, false, true, true, false]);
+
+        // Kurallardan birine uyan sürüm tutulur
+        let both = Retention { keep_last: 2, keep_daily: 2 };
+        assert_eq!(both.select(&times, now), vec![true, true, true, false, false]);
+        assert!(!Retention::default().is_enabled());
+    }
+
+    #[test]
+    fn test_archive_list_and_prune() {
+        let root = std::env::temp_dir().join(format!("deltasafe-versions-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("docs")).unwrap();
+        let relative = Path::new("docs/plan.txt");
+        let retention = Retention { keep_last: 2, keep_daily: 0 };
+        let now = at("2024-03-31T12:00:00Z");
+
+        // Dosya yokken ve içerik aynıyken sürüm oluşmaz
+        assert_eq!(archive(&root, relative, "x", retention, now).unwrap(), None);
+        fs::write(root.join(relative), "v1").unwrap();
+        let same = blake3::hash(b"v1").to_hex().to_string();
+        assert_eq!(archive(&root, relative, &same, retention, now).unwrap(), None);
+
+        for (i, content) in ["v1", "v2", "v3"].iter().enumerate() {
+            fs::write(root.join(relative), content).unwrap();
+            let id = archive(&root, relative, "yeni", retention, now + TimeDelta::minutes(i as i64)).unwrap();
+            assert!(id.is_some());
+            assert!(!root.join(relative).exists());
+        }
+
+        // En eski sürüm kuraldan dolayı silinir
+        let versions = list(&root, relative).unwrap();
+        assert_eq!(versions.iter().map(|v| v.id.as_str()).collect::<Vec<_>>(), vec!["20240331T120200.000000Z", "20240331T120100.000000Z"]);
+        assert_eq!(versions[0].time(), Some(now + TimeDelta::minutes(2)));
+        let newest = locate(&root, relative, &versions[0].id).unwrap();
+        assert_eq!(fs::read_to_string(newest).unwrap(), "v3");
+
+        assert!(locate(&root, relative, "20200101T000000.000000Z").is_err());
+        assert!(locate(&root, relative, "../../etc").is_err());
+        assert!(locate(&root, Path::new(".deltasafe/versions"), &versions[0].id).is_err());
+        assert!(list(&root, Path::new("yok.txt")).unwrap().is_empty());
+        assert!(is_reserved(Path::new(".deltasafe/x")) && !is_reserved(Path::new("a/.deltasafe")));
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
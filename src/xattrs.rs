@@ -0,0 +1,235 @@
+//! Genişletilmiş öznitelikler (xattr) ve POSIX ACL'ler
+//!
+//! İstemci seçilen ad alanlarındaki öznitelikleri okur ve listeyi içerikle
+//! aynı anahtarla şifreleyip `FileMetadata` içinde gönderir; etiketler ve
+//! güvenlik bağlamları böylece ağda açık gitmez. Sunucu yalnızca kendi
+//! süzgecine uyan öznitelikleri uygular ve aynı ad alanlarında kaynakta
+//! bulunmayanları kaldırır. Linux'ta POSIX ACL'ler `system.posix_acl_*`
+//! öznitelikleri olarak taşınır.
+
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+use serde::{Deserialize, Serialize};
+use crate::crypto;
+use crate::error::Error;
+
+/// `acl` kısaltmasının kapsadığı öznitelikler
+const ACL_NAMES: &[&str] = &["system.posix_acl_access", "system.posix_acl_default"];
+
+/// Şifreli listenin açılınca ulaşabileceği en büyük boyut
+const MAX_SEALED_SIZE: usize = 1024 * 1024;
+
+/// Bir genişletilmiş öznitelik
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Xattr {
+    pub name: String,
+    pub value: Vec<u8>,
+}
+
+/// Gönderilecek veya uygulanacak özniteliklerin ad alanları
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct XattrFilter {
+    /// Ad alanı önekleri; `user` hem `user` hem `user.*` ile eşleşir
+    prefixes: Vec<String>,
+    all: bool,
+}
+
+impl XattrFilter {
+    /// Hiçbir öznitelik seçilmemişse `true`
+    pub fn is_empty(&self) -> bool {
+        !self.all && self.prefixes.is_empty()
+    }
+
+    pub fn matches(&self, name: &str) -> bool {
+        self.all || self.prefixes.iter().any(|prefix| {
+            name.strip_prefix(prefix.as_str()).is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+        })
+    }
+}
+
+impl FromStr for XattrFilter {
+    type Err = String;
+
+    /// `none`, `all` veya `user`, `security`, `trusted`, `acl`, `com.apple`
+    /// gibi ad alanlarının listesini kabul eder
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let mut filter = XattrFilter::default();
+        for item in value.split(',').map(str::trim).filter(|item| !item.is_empty()) {
+            match item.to_ascii_lowercase().as_str() {
+                "none" => {}
+                "all" => filter.all = true,
+                "acl" => filter.prefixes.extend(ACL_NAMES.iter().map(|name| name.to_string())),
+                _ if item.starts_with('.') || item.ends_with('.') || item.contains(char::is_whitespace) => {
+                    return Err(format!("Geçersiz xattr ad alanı: '{}'", item));
+                }
+                _ => filter.prefixes.push(item.to_string()),
+            }
+        }
+        Ok(filter)
+    }
+}
+
+impl fmt::Display for XattrFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.all {
+            return write!(f, "all");
+        }
+        if self.is_empty() {
+            return write!(f, "none");
+        }
+        let mut names: Vec<&str> = self.prefixes.iter()
+            .map(String::as_str)
+            .filter(|prefix| !ACL_NAMES.contains(prefix))
+            .collect();
+        if ACL_NAMES.iter().all(|name| self.prefixes.iter().any(|prefix| prefix == name)) {
+            names.push("acl");
+        }
+        write!(f, "{}", names.join(","))
+    }
+}
+
+/// Yoldaki süzgece uyan öznitelikleri okur; sembolik bağlantı izlenmez
+#[cfg(unix)]
+pub fn read(path: &Path, filter: &XattrFilter) -> std::io::Result<Vec<Xattr>> {
+    let mut attrs = Vec::new();
+    for name in xattr::list(path)? {
+        let Some(name) = name.to_str().filter(|name| filter.matches(name)) else { continue };
+        // Okuma sırasında silinen öznitelik atlanır
+        if let Some(value) = xattr::get(path, name)? {
+            attrs.push(Xattr { name: name.to_string(), value });
+        }
+    }
+    attrs.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(attrs)
+}
+
+#[cfg(not(unix))]
+pub fn read(_path: &Path, _filter: &XattrFilter) -> std::io::Result<Vec<Xattr>> {
+    Ok(Vec::new())
+}
+
+/// Öznitelik listesini şifreler; `FileMetadata` içinde hex olarak gider
+pub fn seal(attrs: &[Xattr], key: &[u8; 32]) -> String {
+    let json = serde_json::to_vec(attrs).unwrap_or_default();
+    hex::encode(crypto::seal(&json, key))
+}
+
+/// [`seal`] çıktısını çözer
+pub fn open(sealed: &str, key: &[u8; 32]) -> Result<Vec<Xattr>, Error> {
+    if sealed.len() > MAX_SEALED_SIZE * 2 {
+        return Err(Error::Protocol("Şifreli öznitelik listesi çok büyük".to_string()));
+    }
+    let bytes = hex::decode(sealed)
+        .map_err(|_| Error::Protocol("Şifreli öznitelikler bozuk".to_string()))?;
+    let json = crypto::open(&bytes, key)
+        .ok_or_else(|| Error::Integrity("Öznitelikler çözülemedi (anahtar uyuşmuyor olabilir)".to_string()))?;
+    serde_json::from_slice(&json)
+        .map_err(|e| Error::Integrity(format!("Öznitelik listesi okunamadı: {}", e)))
+}
+
+/// Süzgece uyan öznitelikleri yazar ve aynı ad alanlarında listede olmayanları
+/// kaldırır. Uygulanamayanlar hata mesajı olarak döner.
+#[cfg(unix)]
+pub fn apply(path: &Path, attrs: &[Xattr], filter: &XattrFilter) -> Result<(), String> {
+    let mut errors = Vec::new();
+    if let Ok(existing) = xattr::list(path) {
+        for name in existing {
+            let Some(name) = name.to_str().filter(|name| filter.matches(name)) else { continue };
+            if !attrs.iter().any(|attr| attr.name == name)
+                && let Err(e) = xattr::remove(path, name)
+            {
+                errors.push(format!("{} kaldırılamadı: {}", name, e));
+            }
+        }
+    }
+    for attr in attrs.iter().filter(|attr| filter.matches(&attr.name)) {
+        if let Err(e) = xattr::set(path, &attr.name, &attr.value) {
+            errors.push(format!("{}: {}", attr.name, e));
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors.join(", "))
+    }
+}
+
+#[cfg(not(unix))]
+pub fn apply(_path: &Path, attrs: &[Xattr], filter: &XattrFilter) -> Result<(), String> {
+    match attrs.iter().any(|attr| filter.matches(&attr.name)) {
+        true => Err("bu platformda xattr desteklenmiyor".to_string()),
+        false => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_filter() {
+        let filter: XattrFilter = "user, acl".parse().unwrap();
+        assert!(filter.matches("user.etiket"));
+        assert!(filter.matches("system.posix_acl_access"));
+        assert!(!filter.matches("userx.etiket"));
+        assert!(!filter.matches("security.selinux"));
+        assert!(!filter.matches("system.nfs4_acl"));
+        assert_eq!(filter.to_string(), "user,acl");
+
+        let apple: XattrFilter = "com.apple".parse().unwrap();
+        assert!(apple.matches("com.apple.quarantine"));
+        assert!(!apple.matches("com.apples"));
+
+        assert!("none".parse::<XattrFilter>().unwrap().is_empty());
+        assert!("all".parse::<XattrFilter>().unwrap().matches("trusted.x"));
+        assert!(".user".parse::<XattrFilter>().is_err());
+    }
+
+    #[test]
+    fn test_seal_roundtrip() {
+        let key = [7u8; 32];
+        let attrs = vec![Xattr { name: "user.renk".to_string(), value: b"mavi".to_vec() }];
+        let sealed = seal(&attrs, &key);
+        // Ad ve değer şifreli gider
+        assert!(!sealed.contains(&hex::encode("user.renk")));
+        assert_eq!(open(&sealed, &key).unwrap(), attrs);
+        assert!(open(&sealed, &[8u8; 32]).is_err());
+        assert!(open("zz", &key).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_read_and_apply() {
+        let dir = std::env::temp_dir().join(format!("deltasafe-xattrs-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let source = dir.join("kaynak");
+        let target = dir.join("hedef");
+        std::fs::write(&source, "").unwrap();
+        std::fs::write(&target, "").unwrap();
+
+        // Dosya sistemi kullanıcı özniteliklerini desteklemiyorsa atla
+        if xattr::set(&source, "user.renk", b"mavi").is_err() {
+            std::fs::remove_dir_all(&dir).unwrap();
+            return;
+        }
+        xattr::set(&source, "user.etiket", b"tasarim").unwrap();
+        xattr::set(&target, "user.eski", b"1").unwrap();
+
+        let all = read(&source, &"user".parse().unwrap()).unwrap();
+        assert_eq!(all.iter().map(|attr| attr.name.as_str()).collect::<Vec<_>>(), vec!["user.etiket", "user.renk"]);
+        assert!(read(&source, &"security".parse().unwrap()).unwrap().is_empty());
+
+        // Listede olmayan öznitelik aynı ad alanındaysa kaldırılır
+        apply(&target, &all, &"user".parse().unwrap()).unwrap();
+        assert_eq!(read(&target, &"user".parse().unwrap()).unwrap(), all);
+
+        // Süzgeç dışındaki öznitelikler uygulanmaz ve kaldırılmaz
+        apply(&target, &[], &"user.renk".parse().unwrap()).unwrap();
+        let left = read(&target, &"user".parse().unwrap()).unwrap();
+        assert_eq!(left.iter().map(|attr| attr.name.as_str()).collect::<Vec<_>>(), vec!["user.etiket"]);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
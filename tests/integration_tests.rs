@@ -23,9 +23,9 @@ fn test_basic_sync() {
     let test_key = "0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef";
     
     // Sunucuyu başlat (arka planda)
-    let server_handle = thread::spawn(move || {
+    let _server_handle = thread::spawn(move || {
         Command::new("cargo")
-            .args(&["run", "--", "server", "--address", "127.0.0.1:12346", "--key", test_key])
+            .args(["run", "--", "server", "--address", "127.0.0.1:12346", "--key", test_key])
             .output()
             .expect("Sunucu başlatılamadı");
     });
@@ -35,7 +35,7 @@ fn test_basic_sync() {
     
     // Sync işlemini başlat
     let output = Command::new("cargo")
-        .args(&["run", "--", "sync", "--source", &source_dir, "--target", "127.0.0.1:12346", "--key", test_key])
+        .args(["run", "--", "sync", "--source", &source_dir, "--target", "127.0.0.1:12346", "--key", test_key])
         .output()
         .expect("Sync komutu çalıştırılamadı");
     
@@ -68,8 +68,37 @@ fn test_file_operations() {
     assert_eq!(hash.len(), 64);
     
     // Chunk size kontrolü
-    assert_eq!(CHUNK_SIZE, 4096);
+    assert_eq!(CHUNK_SIZE, 256 * 1024);
     
     // Temizlik
     fs::remove_file(test_file).unwrap();
-}
\ No newline at end of file
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_stdio_sync() {
+    use deltasafe::sync::{sync_files_over, SyncOptions};
+    use deltasafe::transport::Endpoint;
+
+    // `serve --stdio` süreci, tıpkı ssh üzerinden çalıştırılmış gibi boruya bağlanır
+    let test_dir = std::env::temp_dir().join(format!("deltasafe_stdio_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&test_dir);
+    let source_dir = test_dir.join("source");
+    fs::create_dir_all(&source_dir).unwrap();
+    fs::write(source_dir.join("test.txt"), "Hello, stdio!").unwrap();
+
+    let test_key = "0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef";
+    let key_bytes = deltasafe::crypto::parse_hex_key(test_key).unwrap();
+    let endpoint = Endpoint::Command {
+        program: "sh".to_string(),
+        args: vec![
+            "-c".to_string(),
+            format!("cd '{}' && exec '{}' serve --stdio --key {}", test_dir.display(), env!("CARGO_BIN_EXE_deltasafe"), test_key),
+        ],
+    };
+    sync_files_over(&source_dir.to_string_lossy(), &endpoint, &key_bytes, &SyncOptions::default()).await.unwrap();
+
+    let content = fs::read_to_string(test_dir.join("received_files/test.txt")).unwrap();
+    assert_eq!(content, "Hello, stdio!");
+    fs::remove_dir_all(&test_dir).unwrap();
+}
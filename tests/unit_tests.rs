@@ -1,6 +1,8 @@
 use std::fs;
 use std::path::Path;
 use deltasafe::sync::{calculate_file_hash, FileHeader, CHUNK_SIZE};
+use deltasafe::metadata::{FileMetadata, Timestamp};
+use deltasafe::chunk::{parse_chunk_size, ChunkSize, MAX_CHUNK_SIZE, MIN_CHUNK_SIZE};
 
 #[cfg(test)]
 mod tests {
@@ -56,6 +58,7 @@ mod tests {
             file_size: 1024,
             file_hash: "abcd1234".to_string(),
             relative_path: PathBuf::from("subdir/test.txt"),
+            metadata: FileMetadata::default(),
         };
         
         let serialized = serde_json::to_string(&header).unwrap();
@@ -68,19 +71,57 @@ mod tests {
         assert_eq!(deserialized.file_name, "test.txt");
         assert_eq!(deserialized.file_size, 1024);
         assert_eq!(deserialized.file_hash, "abcd1234");
+        assert_eq!(deserialized.metadata, FileMetadata::default());
+    }
+
+    #[test]
+    fn test_file_header_metadata() {
+        let header = FileHeader {
+            file_name: "calistir.sh".to_string(),
+            file_size: 10,
+            file_hash: "abcd".to_string(),
+            relative_path: "bin/calistir.sh".into(),
+            metadata: FileMetadata {
+                mode: Some(0o755),
+                mtime: Some(Timestamp { secs: 1_700_000_000, nanos: 5 }),
+                ..FileMetadata::default()
+            },
+        };
+        let serialized = serde_json::to_string(&header).unwrap();
+        assert!(!serialized.contains("uid"));
+        let deserialized: FileHeader = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(deserialized.metadata, header.metadata);
+
+        // Metadata'sız eski başlıklar da okunabilir
+        let old = r#"{"file_name":"a","file_size":1,"file_hash":"h","relative_path":"a"}"#;
+        let deserialized: FileHeader = serde_json::from_str(old).unwrap();
+        assert_eq!(deserialized.metadata, FileMetadata::default());
     }
 
     #[test]
     fn test_chunk_size_constant() {
         // CHUNK_SIZE sabitinin makul bir değer olduğunu kontrol et
-        const EXPECTED_CHUNK_SIZE: usize = 4096;
+        const EXPECTED_CHUNK_SIZE: usize = 256 * 1024;
         assert_eq!(CHUNK_SIZE, EXPECTED_CHUNK_SIZE);
         
         // Chunk size'ın pozitif olduğunu kontrol et
-        assert!(CHUNK_SIZE > 0);
+        const { assert!(CHUNK_SIZE > 0) };
         
-        // Chunk size'ın çok büyük olmadığını kontrol et (max 1MB)
-        assert!(CHUNK_SIZE <= 1024 * 1024);
+        // Varsayılan boyut pazarlık aralığının içinde olmalı (64KB - 4MB)
+        const { assert!(MIN_CHUNK_SIZE == 64 * 1024 && MAX_CHUNK_SIZE == 4 * 1024 * 1024) };
+        const { assert!(CHUNK_SIZE >= MIN_CHUNK_SIZE && CHUNK_SIZE <= MAX_CHUNK_SIZE) };
+    }
+
+    #[test]
+    fn test_chunk_size_parsing() {
+        for (text, expected) in [("64K", MIN_CHUNK_SIZE), ("512KB", 512 * 1024), ("2M", 2 * 1024 * 1024), ("4MB", MAX_CHUNK_SIZE)] {
+            assert_eq!(text.parse::<ChunkSize>().unwrap(), ChunkSize::Fixed(expected));
+            assert_eq!(parse_chunk_size(text).unwrap(), expected);
+        }
+        assert_eq!("AUTO".parse::<ChunkSize>().unwrap(), ChunkSize::Auto);
+        assert_eq!(ChunkSize::default(), ChunkSize::Auto);
+        assert!(parse_chunk_size("32K").is_err());
+        assert!(parse_chunk_size("5M").is_err());
     }
 
     #[test]